@@ -0,0 +1,99 @@
+//! Load time and memory of the embedded text dictionaries, parsed with
+//! `Dict::load_lines`, against the precompiled blobs of the split build.
+//!
+//! ```bash
+//! cargo bench --bench load
+//! ```
+use simplecc::Dict;
+use simplecc_wasm::blob;
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Instant;
+
+const RUNS: usize = 10;
+
+/// Tracks the number of live heap bytes.
+struct Counting;
+
+static LIVE: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc(layout);
+        if !ptr.is_null() {
+            LIVE.fetch_add(layout.size(), Ordering::Relaxed);
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+        LIVE.fetch_sub(layout.size(), Ordering::Relaxed);
+    }
+}
+
+#[global_allocator]
+static GLOBAL: Counting = Counting;
+
+/// Median load time in milliseconds and heap bytes retained by the result.
+fn measure<T>(load: impl Fn() -> T) -> (f64, usize) {
+    let mut times = Vec::with_capacity(RUNS);
+    let mut retained = 0;
+    for _ in 0..RUNS {
+        let before = LIVE.load(Ordering::Relaxed);
+        let start = Instant::now();
+        let value = load();
+        times.push(start.elapsed().as_secs_f64() * 1000.0);
+        retained = LIVE.load(Ordering::Relaxed) - before;
+        drop(value);
+    }
+    times.sort_by(f64::total_cmp);
+    (times[RUNS / 2], retained)
+}
+
+fn kib(bytes: usize) -> f64 {
+    bytes as f64 / 1024.0
+}
+
+fn main() {
+    let dir = concat!(env!("CARGO_MANIFEST_DIR"), "/OpenCC/data/dictionary");
+    println!(
+        "{:<6} {:>10} {:>10} | {:>12} {:>12} | {:>12} {:>12}",
+        "dict", "text KiB", "blob KiB", "lines ms", "lines KiB", "blob ms", "blob KiB"
+    );
+    for (name, chain) in blob::CHAINS {
+        let sources: Vec<Vec<String>> = chain
+            .iter()
+            .map(|files| {
+                files
+                    .iter()
+                    .map(|file| {
+                        std::fs::read_to_string(format!("{dir}/{file}.txt"))
+                            .expect("dictionary source")
+                    })
+                    .collect()
+            })
+            .collect();
+        let text_len: usize = sources.iter().flatten().map(String::len).sum();
+        let bytes = blob::compile(name).expect("built-in dictionary");
+
+        let (lines_ms, lines_mem) = measure(|| {
+            sources
+                .iter()
+                .map(|texts| Dict::load_lines(texts.iter().flat_map(|t| t.lines())))
+                .reduce(Dict::chain)
+        });
+        let (blob_ms, blob_mem) = measure(|| blob::decode(&bytes).expect("valid blob"));
+
+        println!(
+            "{:<6} {:>10.0} {:>10.0} | {:>12.2} {:>12.0} | {:>12.2} {:>12.0}",
+            name,
+            kib(text_len),
+            kib(bytes.len()),
+            lines_ms,
+            kib(lines_mem),
+            blob_ms,
+            kib(blob_mem)
+        );
+    }
+}
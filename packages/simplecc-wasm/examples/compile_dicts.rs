@@ -0,0 +1,22 @@
+//! Compile every built-in dictionary into a blob for the split build.
+//!
+//! ```bash
+//! cargo run --release --example compile_dicts -- pkg/web-split/dicts
+//! ```
+use simplecc_wasm::blob;
+use std::path::PathBuf;
+
+fn main() {
+    let out = PathBuf::from(
+        std::env::args()
+            .nth(1)
+            .unwrap_or_else(|| "pkg/dicts".to_string()),
+    );
+    std::fs::create_dir_all(&out).expect("failed to create output directory");
+    for (name, _) in blob::CHAINS {
+        let bytes = blob::compile(name).expect("built-in dictionary");
+        let path = out.join(format!("{name}.sccb"));
+        std::fs::write(&path, &bytes).expect("failed to write blob");
+        println!("{} ({} KiB)", path.display(), bytes.len() / 1024);
+    }
+}
@@ -0,0 +1,429 @@
+//! Precompiled dictionary blobs.
+//!
+//! The split build ships without the OpenCC text sources. Each built-in
+//! dictionary is instead compiled ahead of time into a blob holding one
+//! double-array trie per link of its chain, fetched by the host only when
+//! needed and handed over with `load_dict_blob()`.
+//!
+//! Blob layout, all integers little-endian:
+//!
+//! ```text
+//! "SCCB" version:u16 links:u16
+//! per link:
+//!   max_len:u32
+//!   alphabet_len:u32 alphabet:[u32]     sorted chars, code = index + 1
+//!   units:u32 base:[i32] check:[i32]
+//!   values:u32 ends:[u32] bytes_len:u32 bytes:[u8]
+//! ```
+use crate::offsets::Table;
+pub use crate::dicts::CHAINS;
+use std::collections::HashMap;
+use std::convert::{TryFrom, TryInto};
+use std::sync::{Arc, LazyLock, Mutex};
+
+const MAGIC: &[u8; 4] = b"SCCB";
+const VERSION: u16 = 1;
+
+/// Marks a free unit in `DoubleArray::check`.
+const FREE: i32 = -1;
+/// Transition code of the end-of-key marker leading to a value.
+const TERMINAL: u32 = 0;
+
+/// A double-array trie mapping phrases to their replacement.
+///
+/// The children of node `s` live at `base[s] + code`, each recording its
+/// parent in `check`. The end-of-key child of a node stores the index of its
+/// value as `-(index + 1)` in `base`.
+#[derive(Debug, Clone, Default)]
+pub struct DoubleArray {
+    alphabet: Vec<char>,
+    base: Vec<i32>,
+    check: Vec<i32>,
+    /// End offset in `values` of each value, the first one starting at 0.
+    ends: Vec<u32>,
+    values: String,
+}
+
+impl DoubleArray {
+    /// Build a trie from unique phrases.
+    pub fn build<'a>(phrases: impl Iterator<Item = (&'a str, &'a str)>) -> Self {
+        let mut entries: Vec<(Vec<char>, &str)> =
+            phrases.map(|(k, v)| (k.chars().collect(), v)).collect();
+        entries.sort_unstable();
+
+        let mut alphabet: Vec<char> = entries.iter().flat_map(|(k, _)| k.clone()).collect();
+        alphabet.sort_unstable();
+        alphabet.dedup();
+
+        let keys: Vec<Vec<u32>> = entries
+            .iter()
+            .map(|(k, _)| {
+                k.iter()
+                    .map(|c| alphabet.binary_search(c).unwrap() as u32 + 1)
+                    .collect()
+            })
+            .collect();
+
+        let mut trie = DoubleArray {
+            alphabet,
+            base: vec![0],
+            check: vec![-2],
+            ..Default::default()
+        };
+        for (_, value) in &entries {
+            trie.values.push_str(value);
+            trie.ends.push(trie.values.len() as u32);
+        }
+        if !keys.is_empty() {
+            let mut first_free = 1;
+            trie.insert(0, &keys, 0, 0, &mut first_free);
+        }
+        while trie.check.last() == Some(&FREE) {
+            trie.check.pop();
+            trie.base.pop();
+        }
+        trie
+    }
+
+    /// Place the children of node `s`, shared by `keys` at `depth`. `offset`
+    /// is the index of `keys[0]` in the whole sorted key list.
+    fn insert(
+        &mut self,
+        s: usize,
+        keys: &[Vec<u32>],
+        offset: usize,
+        depth: usize,
+        first_free: &mut usize,
+    ) {
+        // Runs of keys sharing the next code; shorter keys sort first, so an
+        // end-of-key marker, if any, comes first.
+        let mut children: Vec<(u32, usize, usize)> = Vec::new();
+        for (i, key) in keys.iter().enumerate() {
+            let code = key.get(depth).copied().unwrap_or(TERMINAL);
+            match children.last_mut() {
+                Some((last, _, end)) if *last == code => *end = i + 1,
+                _ => children.push((code, i, i + 1)),
+            }
+        }
+
+        let first = children[0].0 as usize;
+        let mut b = first_free.saturating_sub(first).max(1);
+        loop {
+            let needed = b + children.last().unwrap().0 as usize + 1;
+            if needed > self.check.len() {
+                self.check.resize(needed, FREE);
+                self.base.resize(needed, 0);
+            }
+            if children
+                .iter()
+                .all(|(code, _, _)| self.check[b + *code as usize] == FREE)
+            {
+                break;
+            }
+            b += 1;
+        }
+
+        self.base[s] = b as i32;
+        for (code, _, _) in &children {
+            self.check[b + *code as usize] = s as i32;
+        }
+        while *first_free < self.check.len() && self.check[*first_free] != FREE {
+            *first_free += 1;
+        }
+
+        for (code, start, end) in children {
+            let t = b + code as usize;
+            if code == TERMINAL {
+                self.base[t] = -((offset + start) as i32 + 1);
+            } else {
+                self.insert(t, &keys[start..end], offset + start, depth + 1, first_free);
+            }
+        }
+    }
+
+    fn child(&self, s: usize, code: u32) -> Option<usize> {
+        if self.base[s] <= 0 {
+            return None;
+        }
+        let t = self.base[s] as usize + code as usize;
+        (self.check.get(t) == Some(&(s as i32))).then_some(t)
+    }
+
+    fn value(&self, index: usize) -> &str {
+        let start = match index {
+            0 => 0,
+            _ => self.ends[index - 1] as usize,
+        };
+        &self.values[start..self.ends[index] as usize]
+    }
+
+    /// Longest phrase at the start of `text`, as its length in chars and its
+    /// replacement, looking at no more than `max_len` chars.
+    pub fn longest_match(&self, text: &str, max_len: usize) -> Option<(usize, &str)> {
+        let mut s = 0;
+        let mut best = None;
+        for (n, c) in text.chars().take(max_len).enumerate() {
+            let Ok(code) = self.alphabet.binary_search(&c) else {
+                break;
+            };
+            let Some(t) = self.child(s, code as u32 + 1) else {
+                break;
+            };
+            s = t;
+            if let Some(end) = self.child(s, TERMINAL) {
+                best = Some((n + 1, self.value((-self.base[end] - 1) as usize)));
+            }
+        }
+        best
+    }
+
+    fn encode(&self, out: &mut Vec<u8>) {
+        put_u32(out, self.alphabet.len() as u32);
+        for c in &self.alphabet {
+            put_u32(out, *c as u32);
+        }
+        put_u32(out, self.base.len() as u32);
+        for v in self.base.iter().chain(&self.check) {
+            out.extend(v.to_le_bytes());
+        }
+        put_u32(out, self.ends.len() as u32);
+        for end in &self.ends {
+            put_u32(out, *end);
+        }
+        put_u32(out, self.values.len() as u32);
+        out.extend(self.values.as_bytes());
+    }
+
+    /// Whether unit `t`, if it is the end-of-key child of its parent, stores
+    /// a value index rather than a base.
+    fn is_valid_terminal(&self, t: usize) -> bool {
+        let Ok(parent) = usize::try_from(self.check[t]) else {
+            return true;
+        };
+        match self.base.get(parent) {
+            Some(&b) if b > 0 && b as usize == t => self.base[t] < 0,
+            _ => true,
+        }
+    }
+
+    fn decode(reader: &mut Reader) -> Result<Self, String> {
+        let alphabet = reader
+            .u32s()?
+            .into_iter()
+            .map(|c| char::from_u32(c).ok_or("invalid char in alphabet"))
+            .collect::<Result<Vec<_>, _>>()?;
+        let units = reader.u32()? as usize;
+        let base = reader.i32s(units)?;
+        let check = reader.i32s(units)?;
+        let ends = reader.u32s()?;
+        let len = reader.u32()? as usize;
+        let values = String::from_utf8(reader.bytes(len)?.to_vec())
+            .map_err(|_| "invalid UTF-8 in values")?;
+
+        let trie = DoubleArray {
+            alphabet,
+            base,
+            check,
+            ends,
+            values,
+        };
+        let valid = trie.check.first() == Some(&-2)
+            && trie.ends.windows(2).all(|w| w[0] <= w[1])
+            && trie.ends.last().map_or(0, |e| *e as usize) <= trie.values.len()
+            && trie
+                .ends
+                .iter()
+                .all(|e| trie.values.is_char_boundary(*e as usize))
+            && trie
+                .check
+                .iter()
+                .zip(&trie.base)
+                .all(|(check, base)| *check == FREE || *base >= -(trie.ends.len() as i32))
+            && (0..trie.check.len()).all(|t| trie.is_valid_terminal(t));
+        if valid {
+            Ok(trie)
+        } else {
+            Err("corrupted dictionary blob".to_string())
+        }
+    }
+}
+
+fn put_u32(out: &mut Vec<u8>, v: u32) {
+    out.extend(v.to_le_bytes());
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn bytes(&mut self, len: usize) -> Result<&'a [u8], String> {
+        if len > self.bytes.len() {
+            return Err("truncated dictionary blob".to_string());
+        }
+        let (head, tail) = self.bytes.split_at(len);
+        self.bytes = tail;
+        Ok(head)
+    }
+
+    fn u16(&mut self) -> Result<u16, String> {
+        Ok(u16::from_le_bytes(self.bytes(2)?.try_into().unwrap()))
+    }
+
+    fn u32(&mut self) -> Result<u32, String> {
+        Ok(u32::from_le_bytes(self.bytes(4)?.try_into().unwrap()))
+    }
+
+    fn u32s(&mut self) -> Result<Vec<u32>, String> {
+        let len = self.u32()? as usize;
+        let bytes = self.bytes(len.checked_mul(4).ok_or("truncated dictionary blob")?)?;
+        Ok(bytes
+            .chunks_exact(4)
+            .map(|b| u32::from_le_bytes(b.try_into().unwrap()))
+            .collect())
+    }
+
+    fn i32s(&mut self, len: usize) -> Result<Vec<i32>, String> {
+        let bytes = self.bytes(len.checked_mul(4).ok_or("truncated dictionary blob")?)?;
+        Ok(bytes
+            .chunks_exact(4)
+            .map(|b| i32::from_le_bytes(b.try_into().unwrap()))
+            .collect())
+    }
+}
+
+/// Serialize the links of a conversion chain.
+pub fn encode(tables: &[Arc<Table>]) -> Vec<u8> {
+    let mut out = Vec::new();
+    out.extend(MAGIC);
+    out.extend(VERSION.to_le_bytes());
+    out.extend((tables.len() as u16).to_le_bytes());
+    for table in tables {
+        put_u32(&mut out, table.max_len() as u32);
+        table.to_double_array().encode(&mut out);
+    }
+    out
+}
+
+/// Deserialize the links of a conversion chain.
+pub fn decode(bytes: &[u8]) -> Result<Vec<Arc<Table>>, String> {
+    let mut reader = Reader { bytes };
+    if reader.bytes(4)? != MAGIC {
+        return Err("not a dictionary blob".to_string());
+    }
+    let version = reader.u16()?;
+    if version != VERSION {
+        return Err(format!("unsupported dictionary blob version {version}"));
+    }
+    let links = reader.u16()?;
+    let mut tables = Vec::with_capacity(links as usize);
+    for _ in 0..links {
+        let max_len = reader.u32()? as usize;
+        let trie = DoubleArray::decode(&mut reader)?;
+        tables.push(Arc::new(Table::from_double_array(trie, max_len)));
+    }
+    if !reader.bytes.is_empty() {
+        return Err("trailing data in dictionary blob".to_string());
+    }
+    Ok(tables)
+}
+
+/// Compile a built-in dictionary from its embedded sources.
+#[cfg(feature = "embed-dicts")]
+pub fn compile(name: &str) -> Option<Vec<u8>> {
+    crate::dicts::tables(name).map(|tables| encode(&tables))
+}
+
+static LOADED: LazyLock<Mutex<HashMap<String, Vec<Arc<Table>>>>> =
+    LazyLock::new(Default::default);
+
+/// Register the blob of a built-in dictionary.
+pub fn load(name: &str, bytes: &[u8]) -> Result<(), String> {
+    let (_, chain) = CHAINS
+        .iter()
+        .find(|(n, _)| *n == name)
+        .ok_or_else(|| format!("`{name}` is not a built-in dictionary"))?;
+    let tables = decode(bytes)?;
+    if tables.len() != chain.len() {
+        return Err(format!(
+            "blob has {} links, `{name}` needs {}",
+            tables.len(),
+            chain.len()
+        ));
+    }
+    LOADED.lock().unwrap().insert(name.to_string(), tables);
+    Ok(())
+}
+
+/// Tables of a built-in dictionary loaded from a blob, if any.
+pub fn loaded(name: &str) -> Option<Vec<Arc<Table>>> {
+    LOADED.lock().unwrap().get(name).cloned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(feature = "embed-dicts")]
+    use crate::offsets::{convert, Converter};
+
+    #[test]
+    fn test_longest_match() {
+        let trie = DoubleArray::build(
+            [("打印", "列印"), ("打印机", "印表機"), ("机", "機"), ("😀", "🙂")]
+                .iter()
+                .copied(),
+        );
+        assert_eq!(trie.longest_match("打印机器", 8), Some((3, "印表機")));
+        assert_eq!(trie.longest_match("打印机器", 2), Some((2, "列印")));
+        assert_eq!(trie.longest_match("打", 8), None);
+        assert_eq!(trie.longest_match("😀!", 8), Some((1, "🙂")));
+        assert_eq!(trie.longest_match("器", 8), None);
+        assert_eq!(DoubleArray::build(std::iter::empty()).longest_match("a", 1), None);
+    }
+
+    #[test]
+    fn test_rejects_terminal_without_value() {
+        let trie = DoubleArray::build([("打印", "列印")].iter().copied());
+        let mut bytes = Vec::new();
+        trie.encode(&mut bytes);
+        assert!(DoubleArray::decode(&mut Reader { bytes: &bytes }).is_ok());
+
+        let code = |c| trie.alphabet.binary_search(&c).unwrap() as u32 + 1;
+        let s = trie.child(trie.child(0, code('打')).unwrap(), code('印')).unwrap();
+        let mut corrupted = trie.clone();
+        corrupted.base[trie.child(s, TERMINAL).unwrap()] = 0;
+        let mut bytes = Vec::new();
+        corrupted.encode(&mut bytes);
+        assert!(DoubleArray::decode(&mut Reader { bytes: &bytes }).is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "embed-dicts")]
+    fn test_blob_roundtrip_matches_sources() {
+        for name in ["s2twp", "tw2sp"] {
+            let blob = compile(name).unwrap();
+            let from_blob = Converter::new(decode(&blob).unwrap());
+            let from_sources = crate::offsets::converter(name).unwrap();
+            for case in [
+                include_str!("../OpenCC/test/testcases/s2twp.in"),
+                include_str!("../OpenCC/test/testcases/tw2sp.in"),
+            ] {
+                let (a, b) = (convert(&from_blob, case), convert(&from_sources, case));
+                assert_eq!(a.text, b.text);
+                assert_eq!(a.mapping, b.mapping);
+            }
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "embed-dicts")]
+    fn test_rejects_bad_blobs() {
+        let blob = compile("s2t").unwrap();
+        assert!(decode(&blob[..blob.len() - 1]).is_err());
+        assert!(decode(b"nope").is_err());
+        assert!(load("s2tw", &blob).is_err());
+        assert!(load("nope", &blob).is_err());
+        load("s2t", &blob).unwrap();
+        assert!(loaded("s2t").is_some());
+    }
+}
@@ -0,0 +1,388 @@
+//! Dictionaries loaded at runtime and the converters built from them.
+//!
+//! User dictionaries use the same plain-text format as the OpenCC sources in
+//! `OpenCC/data/dictionary`: one entry per line, the key and its candidates
+//! separated by a tab, candidates separated by spaces. Only the first
+//! candidate is used for conversion.
+use crate::dicts;
+use crate::offsets::Table;
+use simplecc::Dict;
+use std::collections::HashMap;
+use std::sync::{Arc, LazyLock, Mutex, OnceLock};
+use wasm_bindgen::prelude::*;
+
+struct UserDict {
+    dict: Dict,
+    entries: usize,
+    /// Validated lines, kept to build the offset-tracking table on demand.
+    source: String,
+    table: OnceLock<Arc<Table>>,
+}
+
+impl UserDict {
+    fn table(&self) -> Arc<Table> {
+        self.table
+            .get_or_init(|| Arc::new(Table::load_lines(self.source.lines())))
+            .clone()
+    }
+}
+
+struct Converter {
+    /// `None` when the base dictionary is only available as a precompiled
+    /// blob, in which case conversion goes through the offset tables.
+    dict: Option<Dict>,
+    base: String,
+    before: Vec<String>,
+    after: Vec<String>,
+}
+
+impl Converter {
+    fn uses(&self, dict: &str) -> bool {
+        self.before
+            .iter()
+            .chain(&self.after)
+            .any(|name| name == dict)
+    }
+}
+
+static USER_DICTS: LazyLock<Mutex<HashMap<String, UserDict>>> = LazyLock::new(Default::default);
+static CONVERTERS: LazyLock<Mutex<HashMap<String, Converter>>> = LazyLock::new(Default::default);
+
+/// Description of a dictionary or converter usable by `simplecc()`.
+#[wasm_bindgen(getter_with_clone)]
+#[derive(Debug, Clone)]
+pub struct DictInfo {
+    pub name: String,
+    /// One of `builtin`, `user` or `converter`.
+    pub kind: String,
+    pub description: String,
+    /// Number of entries, known only for user dictionaries.
+    pub entries: Option<u32>,
+    /// Whether it can be used now. Built-in dictionaries of the split build
+    /// need their blob loaded first.
+    pub available: bool,
+}
+
+/// Validate OpenCC text-format lines, skipping blank ones.
+pub fn parse_lines(text: &str) -> Result<Vec<&str>, String> {
+    let mut lines = Vec::new();
+    for (no, line) in text.lines().enumerate() {
+        let line = line.trim_end_matches('\r');
+        if line.trim().is_empty() {
+            continue;
+        }
+        match line.split_once('\t') {
+            Some((key, values)) if !key.is_empty() && !values.trim().is_empty() => lines.push(line),
+            _ => {
+                return Err(format!(
+                    "line {}: expected `<phrase>\\t<replacement>`",
+                    no + 1
+                ))
+            }
+        }
+    }
+    Ok(lines)
+}
+
+fn check_name(name: &str) -> Result<(), String> {
+    if name.is_empty() || name.contains('.') {
+        return Err(format!("invalid dictionary name `{name}`"));
+    }
+    if dicts::builtin(name).is_some() {
+        return Err(format!("`{name}` is a built-in dictionary"));
+    }
+    Ok(())
+}
+
+/// Load (or replace) a user dictionary and return its number of entries.
+///
+/// Converters already chaining a dictionary of the same name are rebuilt.
+pub fn load_dict(name: &str, text: &str) -> Result<usize, String> {
+    check_name(name)?;
+    if CONVERTERS.lock().unwrap().contains_key(name) {
+        return Err(format!("`{name}` is already used by a converter"));
+    }
+    let lines = parse_lines(text)?;
+    let entries = lines.len();
+    let source = lines.join("\n");
+    let dict = Dict::load_lines(lines.into_iter());
+
+    // Rebuild the converters chaining it first, so that nothing is replaced
+    // unless all of them succeed.
+    let mut converters = CONVERTERS.lock().unwrap();
+    let rebuilt = converters
+        .iter()
+        .filter(|(_, c)| c.uses(name))
+        .map(|(n, c)| {
+            let replacing = Some((name, &dict));
+            Ok((n.clone(), build(&c.base, &c.before, &c.after, replacing)?))
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+    USER_DICTS.lock().unwrap().insert(
+        name.to_string(),
+        UserDict {
+            dict,
+            entries,
+            source,
+            table: OnceLock::new(),
+        },
+    );
+    for (n, dict) in rebuilt {
+        if let Some(converter) = converters.get_mut(&n) {
+            converter.dict = dict;
+        }
+    }
+    Ok(entries)
+}
+
+/// Remove a user dictionary that no converter depends on.
+pub fn unload_dict(name: &str) -> Result<(), String> {
+    let converters = CONVERTERS.lock().unwrap();
+    let mut users: Vec<&str> = converters
+        .iter()
+        .filter(|(_, c)| c.uses(name))
+        .map(|(n, _)| n.as_str())
+        .collect();
+    if !users.is_empty() {
+        users.sort_unstable();
+        return Err(format!("`{name}` is used by {}", users.join(", ")));
+    }
+    match USER_DICTS.lock().unwrap().remove(name) {
+        Some(_) => Ok(()),
+        None => Err(format!("dictionary `{name}` is not loaded")),
+    }
+}
+
+/// Chain `before`, `base` and `after`, taking the user dictionary named in
+/// `replacing` from there rather than from the loaded ones.
+fn build(
+    base: &str,
+    before: &[String],
+    after: &[String],
+    replacing: Option<(&str, &Dict)>,
+) -> Result<Option<Dict>, String> {
+    let user_dicts = USER_DICTS.lock().unwrap();
+    let user = |name: &String| match replacing {
+        Some((replaced, dict)) if replaced == name => Ok(dict.clone()),
+        _ => user_dicts
+            .get(name)
+            .map(|d| d.dict.clone())
+            .ok_or_else(|| format!("dictionary `{name}` is not loaded")),
+    };
+    let base = match dicts::builtin(base) {
+        Some(dict) => dict.clone(),
+        None if dicts::tables(base).is_some() => {
+            for name in before.iter().chain(after) {
+                user(name)?;
+            }
+            return Ok(None);
+        }
+        None => return Err(format!("`{base}` is not an available built-in dictionary")),
+    };
+
+    let mut dict: Option<Dict> = None;
+    for name in before {
+        let next = user(name)?;
+        dict = Some(match dict {
+            Some(dict) => dict.chain(next),
+            None => next,
+        });
+    }
+    let mut dict = match dict {
+        Some(dict) => dict.chain(base),
+        None => base,
+    };
+    for name in after {
+        dict = dict.chain(user(name)?);
+    }
+    Ok(Some(dict))
+}
+
+/// Build a converter that runs the `before` user dictionaries, then the
+/// built-in `base`, then the `after` user dictionaries, and cache it as `name`.
+pub fn create_converter(
+    name: &str,
+    base: &str,
+    before: Vec<String>,
+    after: Vec<String>,
+) -> Result<(), String> {
+    check_name(name)?;
+    if USER_DICTS.lock().unwrap().contains_key(name) {
+        return Err(format!("`{name}` is already used by a user dictionary"));
+    }
+    let dict = build(base, &before, &after, None)?;
+    CONVERTERS.lock().unwrap().insert(
+        name.to_string(),
+        Converter {
+            dict,
+            base: base.to_string(),
+            before,
+            after,
+        },
+    );
+    Ok(())
+}
+
+/// Drop a cached converter, returning whether it existed.
+pub fn remove_converter(name: &str) -> bool {
+    CONVERTERS.lock().unwrap().remove(name).is_some()
+}
+
+/// Run `f` with the converter cached as `name`, if any and if it was built
+/// on an embedded dictionary.
+pub fn with_converter<R>(name: &str, f: impl FnOnce(&Dict) -> R) -> Option<R> {
+    CONVERTERS
+        .lock()
+        .unwrap()
+        .get(name)
+        .and_then(|c| c.dict.as_ref())
+        .map(f)
+}
+
+/// Offset-tracking tables for the converter cached as `name`, if any.
+pub fn tables(name: &str) -> Option<Vec<Arc<Table>>> {
+    let converters = CONVERTERS.lock().unwrap();
+    let converter = converters.get(name)?;
+    let user_dicts = USER_DICTS.lock().unwrap();
+    let user = |names: &[String]| -> Option<Vec<Arc<Table>>> {
+        names
+            .iter()
+            .map(|name| user_dicts.get(name).map(UserDict::table))
+            .collect()
+    };
+    let mut tables = user(&converter.before)?;
+    tables.extend(dicts::tables(&converter.base)?);
+    tables.extend(user(&converter.after)?);
+    Some(tables)
+}
+
+/// Describe every built-in dictionary, user dictionary and converter.
+pub fn list() -> Vec<DictInfo> {
+    let mut infos: Vec<DictInfo> = dicts::BUILTINS
+        .iter()
+        .map(|(name, description)| DictInfo {
+            name: name.to_string(),
+            kind: "builtin".to_string(),
+            description: description.to_string(),
+            entries: None,
+            available: dicts::is_available(name),
+        })
+        .collect();
+
+    let mut user: Vec<DictInfo> = USER_DICTS
+        .lock()
+        .unwrap()
+        .iter()
+        .map(|(name, d)| DictInfo {
+            name: name.clone(),
+            kind: "user".to_string(),
+            description: "User dictionary".to_string(),
+            entries: Some(d.entries as u32),
+            available: true,
+        })
+        .collect();
+    user.sort_by(|a, b| a.name.cmp(&b.name));
+
+    let mut converters: Vec<DictInfo> = CONVERTERS
+        .lock()
+        .unwrap()
+        .iter()
+        .map(|(name, c)| DictInfo {
+            name: name.clone(),
+            kind: "converter".to_string(),
+            description: c
+                .before
+                .iter()
+                .chain(std::iter::once(&c.base))
+                .chain(&c.after)
+                .map(String::as_str)
+                .collect::<Vec<_>>()
+                .join(" → "),
+            entries: None,
+            available: true,
+        })
+        .collect();
+    converters.sort_by(|a, b| a.name.cmp(&b.name));
+
+    infos.extend(user);
+    infos.extend(converters);
+    infos
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(feature = "embed-dicts")]
+    fn convert(name: &str, text: &str) -> String {
+        with_converter(name, |dict| dict.replace_all(text)).unwrap()
+    }
+
+    #[test]
+    fn test_parse_lines() {
+        assert_eq!(
+            parse_lines("a\tb\n\n c\td e\r\n").unwrap(),
+            vec!["a\tb", " c\td e"]
+        );
+        assert!(parse_lines("a\tb\nno-tab")
+            .unwrap_err()
+            .starts_with("line 2"));
+        assert!(parse_lines("\tb").is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "embed-dicts")]
+    fn test_chain_before_and_after() {
+        load_dict("test-before", "发财\t發大財").unwrap();
+        load_dict("test-after", "發財\t大賺").unwrap();
+        create_converter("test-s2t-before", "s2t", vec!["test-before".into()], vec![]).unwrap();
+        create_converter("test-s2t-after", "s2t", vec![], vec!["test-after".into()]).unwrap();
+
+        assert_eq!(convert("test-s2t-before", "发财了"), "發大財了");
+        assert_eq!(convert("test-s2t-after", "发财了"), "大賺了");
+
+        load_dict("test-after", "發財\t暴富").unwrap();
+        assert_eq!(convert("test-s2t-after", "发财了"), "暴富了");
+        assert!(unload_dict("test-after").is_err());
+        assert!(remove_converter("test-s2t-after"));
+        unload_dict("test-after").unwrap();
+    }
+
+    #[test]
+    #[cfg(feature = "embed-dicts")]
+    fn test_name_conflicts() {
+        assert!(load_dict("s2t", "a\tb").is_err());
+        assert!(create_converter("test-missing", "s2t", vec!["nope".into()], vec![]).is_err());
+        assert!(create_converter("test-bad-base", "nope", vec![], vec![]).is_err());
+        assert!(create_converter("test-bad-base", "s2t", vec![], vec!["nope".into()]).is_err());
+        load_dict("test-conflict", "a\tb").unwrap();
+        assert!(create_converter("test-conflict", "s2t", vec![], vec![]).is_err());
+    }
+
+    #[test]
+    fn test_list() {
+        load_dict("test-list", "a\tb\nc\td").unwrap();
+        let infos = list();
+        assert_eq!(infos[0].name, "s2t");
+        let info = infos.iter().find(|i| i.name == "test-list").unwrap();
+        assert_eq!(info.kind, "user");
+        assert_eq!(info.entries, Some(2));
+    }
+
+    #[test]
+    #[cfg(feature = "embed-dicts")]
+    fn test_offset_tables_follow_chain() {
+        load_dict("test-offsets", "發財\t大賺").unwrap();
+        create_converter(
+            "test-s2t-offsets",
+            "s2t",
+            vec![],
+            vec!["test-offsets".into()],
+        )
+        .unwrap();
+        let converter = crate::offsets::converter("test-s2t-offsets").unwrap();
+        let conversion = crate::offsets::convert(&converter, "发财了");
+        assert_eq!(conversion.text, convert("test-s2t-offsets", "发财了"));
+        assert_eq!(conversion.mapping, vec![0, 2, 0, 2, 2, 3, 2, 3]);
+    }
+}
@@ -1,45 +1,89 @@
 //! A set of built-in OpenCC dictionaries.
 //!
 //! Turn on `builtin_dicts` feature to enable them.
+//!
+//! Without the `embed-dicts` feature the text sources are left out of the
+//! build and dictionaries only become available once their precompiled blob
+//! is loaded, see `blob`.
+use crate::blob;
+use crate::offsets::Table;
+#[cfg(feature = "embed-dicts")]
 use simplecc::Dict;
-use std::sync::LazyLock;
+#[cfg(feature = "embed-dicts")]
+use std::collections::HashMap;
+use std::sync::Arc;
+#[cfg(feature = "embed-dicts")]
+use std::sync::{LazyLock, Mutex};
 
-macro_rules! builtin_dicts {
-    ( $x:expr ) => {
-        {
-            let lines = include_str!(
-                concat!("../OpenCC/data/dictionary/", $x, ".txt")).lines();
-            Dict::load_lines(lines)
+#[cfg(feature = "embed-dicts")]
+macro_rules! dictionary_sources {
+    ( $( $x:literal ),+ ) => {
+        /// Raw text of a dictionary file in `OpenCC/data/dictionary`.
+        pub fn source(file: &str) -> Option<&'static str> {
+            match file {
+                $(
+                    $x => Some(include_str!(
+                        concat!("../OpenCC/data/dictionary/", $x, ".txt"))),
+                )+
+                _ => None,
+            }
         }
     };
-    ( $x:expr $(, $y:expr )+ ) => {
-        {
-            let lines = include_str!(
-                concat!("../OpenCC/data/dictionary/", $x, ".txt")).lines();
-            let mut lines = Box::new(lines) as Box<dyn Iterator<Item=&'static str>>;
-            $(
-                let text = include_str!(
-                    concat!("../OpenCC/data/dictionary/", $y, ".txt"));
-                lines = Box::new(lines.chain(text.lines()));
-            )+
-            Dict::load_lines(lines)
-        }
+}
+
+#[cfg(feature = "embed-dicts")]
+dictionary_sources!(
+    "STCharacters",
+    "STPhrases",
+    "TSCharacters",
+    "TSPhrases",
+    "TWVariants",
+    "TWVariantsRev",
+    "TWVariantsRevPhrases",
+    "TWPhrasesIT",
+    "TWPhrasesName",
+    "TWPhrasesOther",
+    "TWPhrasesRev",
+    "HKVariants",
+    "HKVariantsRev",
+    "HKVariantsRevPhrases"
+);
+
+/// Lines of the given dictionary files, in order.
+#[cfg(feature = "embed-dicts")]
+fn lines(files: &'static [&'static str]) -> impl Iterator<Item = &'static str> {
+    files.iter().flat_map(|file| {
+        source(file)
+            .expect("unknown built-in dictionary file")
+            .lines()
+    })
+}
+
+#[cfg(feature = "embed-dicts")]
+macro_rules! builtin_dicts {
+    ( $( $x:expr ),+ ) => {
+        Dict::load_lines(lines(&[ $( $x ),+ ]))
     };
 }
 
 /// Simplified Chinese to Traditional Chinese
+#[cfg(feature = "embed-dicts")]
 pub static S2T: LazyLock<Dict> = LazyLock::new(|| builtin_dicts!("STCharacters", "STPhrases"));
 
 /// Traditional Chinese to Simplified Chinese
+#[cfg(feature = "embed-dicts")]
 pub static T2S: LazyLock<Dict> = LazyLock::new(|| builtin_dicts!("TSCharacters", "TSPhrases"));
 
 /// Simplified Chinese to Traditional Chinese (Taiwan Standard)
+#[cfg(feature = "embed-dicts")]
 pub static S2TW: LazyLock<Dict> = LazyLock::new(|| S2T.clone().chain(builtin_dicts!("TWVariants")));
 
 /// Simplified Chinese to Traditional Chinese (Hong Kong Standard)
+#[cfg(feature = "embed-dicts")]
 pub static S2HK: LazyLock<Dict> = LazyLock::new(|| S2T.clone().chain(builtin_dicts!("HKVariants")));
 
 /// Simplified Chinese to Traditional Chinese (Taiwan Standard) with
+#[cfg(feature = "embed-dicts")]
 pub static S2TWP: LazyLock<Dict> = LazyLock::new(|| {
     S2T.clone().chain(builtin_dicts!(
         "TWVariants",
@@ -49,16 +93,155 @@ pub static S2TWP: LazyLock<Dict> = LazyLock::new(|| {
     ))
 });
 
+#[cfg(feature = "embed-dicts")]
 pub static HK2S: LazyLock<Dict> =
     LazyLock::new(|| builtin_dicts!("HKVariantsRev", "HKVariantsRevPhrases").chain(T2S.clone()));
 
+#[cfg(feature = "embed-dicts")]
 pub static TW2S: LazyLock<Dict> =
     LazyLock::new(|| builtin_dicts!("TWVariantsRev", "TWVariantsRevPhrases").chain(T2S.clone()));
 
+#[cfg(feature = "embed-dicts")]
 pub static TW2SP: LazyLock<Dict> =
     LazyLock::new(|| builtin_dicts!("TWPhrasesRev", "TWVariantsRev").chain(T2S.clone()));
 
-#[cfg(test)]
+/// Names and descriptions of the built-in dictionaries, in the order they
+/// are reported by `list_dicts()`.
+pub const BUILTINS: &[(&str, &str)] = &[
+    ("s2t", "Simplified Chinese to Traditional Chinese"),
+    ("t2s", "Traditional Chinese to Simplified Chinese"),
+    (
+        "s2tw",
+        "Simplified Chinese to Traditional Chinese (Taiwan Standard)",
+    ),
+    (
+        "s2hk",
+        "Simplified Chinese to Traditional Chinese (Hong Kong Standard)",
+    ),
+    (
+        "s2twp",
+        "Simplified Chinese to Traditional Chinese (Taiwan Standard) with Taiwanese idiom",
+    ),
+    (
+        "hk2s",
+        "Traditional Chinese (Hong Kong Standard) to Simplified Chinese",
+    ),
+    (
+        "tw2s",
+        "Traditional Chinese (Taiwan Standard) to Simplified Chinese",
+    ),
+    (
+        "tw2sp",
+        "Traditional Chinese (Taiwan Standard) to Simplified Chinese with Mainland Chinese idiom",
+    ),
+];
+
+/// Dictionary files loaded into each link of the built-in chains.
+pub const CHAINS: &[(&str, &[&[&str]])] = &[
+    ("s2t", &[&["STCharacters", "STPhrases"]]),
+    ("t2s", &[&["TSCharacters", "TSPhrases"]]),
+    ("s2tw", &[&["STCharacters", "STPhrases"], &["TWVariants"]]),
+    ("s2hk", &[&["STCharacters", "STPhrases"], &["HKVariants"]]),
+    (
+        "s2twp",
+        &[
+            &["STCharacters", "STPhrases"],
+            &[
+                "TWVariants",
+                "TWPhrasesIT",
+                "TWPhrasesName",
+                "TWPhrasesOther",
+            ],
+        ],
+    ),
+    (
+        "hk2s",
+        &[
+            &["HKVariantsRev", "HKVariantsRevPhrases"],
+            &["TSCharacters", "TSPhrases"],
+        ],
+    ),
+    (
+        "tw2s",
+        &[
+            &["TWVariantsRev", "TWVariantsRevPhrases"],
+            &["TSCharacters", "TSPhrases"],
+        ],
+    ),
+    (
+        "tw2sp",
+        &[
+            &["TWPhrasesRev", "TWVariantsRev"],
+            &["TSCharacters", "TSPhrases"],
+        ],
+    ),
+];
+
+#[cfg(feature = "embed-dicts")]
+static TABLES: LazyLock<Mutex<HashMap<&'static [&'static str], Arc<Table>>>> =
+    LazyLock::new(Default::default);
+
+/// Offset-tracking tables for a built-in dictionary, one per link of its chain.
+///
+/// A loaded blob takes precedence; otherwise tables are built from the
+/// embedded sources on first use and shared between chains.
+pub fn tables(name: &str) -> Option<Vec<Arc<Table>>> {
+    if let Some(tables) = blob::loaded(name) {
+        return Some(tables);
+    }
+    embedded_tables(name)
+}
+
+#[cfg(not(feature = "embed-dicts"))]
+fn embedded_tables(_name: &str) -> Option<Vec<Arc<Table>>> {
+    None
+}
+
+#[cfg(feature = "embed-dicts")]
+fn embedded_tables(name: &str) -> Option<Vec<Arc<Table>>> {
+    let (_, chain) = CHAINS.iter().find(|(n, _)| *n == name)?;
+    let mut cache = TABLES.lock().unwrap();
+    let tables = chain
+        .iter()
+        .map(|files| {
+            cache
+                .entry(*files)
+                .or_insert_with(|| Arc::new(Table::load_lines(lines(files))))
+                .clone()
+        })
+        .collect();
+    Some(tables)
+}
+
+/// Whether a built-in dictionary can be used right away.
+pub fn is_available(name: &str) -> bool {
+    cfg!(feature = "embed-dicts") || blob::loaded(name).is_some()
+}
+
+/// Look up a built-in dictionary by name.
+#[cfg(not(feature = "embed-dicts"))]
+pub fn builtin(_name: &str) -> Option<&'static simplecc::Dict> {
+    None
+}
+
+/// Look up a built-in dictionary by name.
+#[cfg(feature = "embed-dicts")]
+pub fn builtin(name: &str) -> Option<&'static Dict> {
+    let dict = match name {
+        "s2t" => &*S2T,
+        "t2s" => &*T2S,
+        "s2tw" => &*S2TW,
+        "s2hk" => &*S2HK,
+        "s2twp" => &*S2TWP,
+        "hk2s" => &*HK2S,
+        "tw2s" => &*TW2S,
+        "tw2sp" => &*TW2SP,
+        _ => return None,
+    };
+    Some(dict)
+}
+
+#[cfg(all(test, feature = "embed-dicts"))]
 mod tests {
     use super::*;
 
@@ -1,24 +1,75 @@
-use simplecc::Dict;
 use wasm_bindgen::prelude::*;
 
+pub mod blob;
+mod custom;
 mod dicts;
+mod offsets;
+
+pub use custom::DictInfo;
+pub use offsets::{Conversion, StreamConverter};
 
 #[wasm_bindgen]
 pub fn simplecc(text: &str, name: &str) -> Result<String, JsValue> {
-    let fname = name.split(".").nth(0);
-    let dict: &Dict = match fname {
-        None => return Err(JsValue::from_str("dict name is not supported")),
-        Some(fname) => match fname {
-            "s2t" => &*dicts::S2T,
-            "t2s" => &*dicts::T2S,
-            "s2tw" => &*dicts::S2TW,
-            "s2hk" => &*dicts::S2HK,
-            "s2twp" => &*dicts::S2TWP,
-            "hk2s" => &*dicts::HK2S,
-            "tw2s" => &*dicts::TW2S,
-            "tw2sp" => &*dicts::TW2SP,
-            _ => return Err(JsValue::from_str("dict name is not supported")),
-        },
-    };
-    Ok(dict.replace_all(text))
+    let fname = name.split('.').next().unwrap_or_default();
+    match dicts::builtin(fname) {
+        Some(dict) => Ok(dict.replace_all(text)),
+        None => custom::with_converter(fname, |dict| dict.replace_all(text))
+            .or_else(|| offsets::converter(fname).map(|c| c.replace_all(text)))
+            .ok_or_else(|| JsValue::from_str("dict name is not supported")),
+    }
+}
+
+/// Convert `text` and report which input range produced each output range.
+#[wasm_bindgen]
+pub fn simplecc_with_offsets(text: &str, name: &str) -> Result<Conversion, JsValue> {
+    let fname = name.split('.').next().unwrap_or_default();
+    offsets::converter(fname)
+        .map(|converter| offsets::convert(&converter, text))
+        .ok_or_else(|| JsValue::from_str("dict name is not supported"))
+}
+
+/// Load the precompiled blob of a built-in dictionary, as produced by
+/// `cargo run --example compile_dicts`. Needed before use in the split build.
+#[wasm_bindgen]
+pub fn load_dict_blob(name: &str, blob: &[u8]) -> Result<(), JsValue> {
+    blob::load(name, blob).map_err(|e| JsValue::from_str(&e))
+}
+
+/// Load a user dictionary in OpenCC text format, replacing any previous
+/// dictionary of the same name. Returns the number of entries.
+#[wasm_bindgen]
+pub fn load_dict(name: &str, text: &str) -> Result<u32, JsValue> {
+    custom::load_dict(name, text)
+        .map(|entries| entries as u32)
+        .map_err(|e| JsValue::from_str(&e))
+}
+
+/// Remove a user dictionary that is not used by any converter.
+#[wasm_bindgen]
+pub fn unload_dict(name: &str) -> Result<(), JsValue> {
+    custom::unload_dict(name).map_err(|e| JsValue::from_str(&e))
+}
+
+/// Chain user dictionaries before and/or after a built-in dictionary and
+/// cache the result as `name`, usable with `simplecc(text, name)`.
+#[wasm_bindgen]
+pub fn create_converter(
+    name: &str,
+    base: &str,
+    before: Vec<String>,
+    after: Vec<String>,
+) -> Result<(), JsValue> {
+    custom::create_converter(name, base, before, after).map_err(|e| JsValue::from_str(&e))
+}
+
+/// Drop a cached converter. Returns `false` if there was none.
+#[wasm_bindgen]
+pub fn remove_converter(name: &str) -> bool {
+    custom::remove_converter(name)
+}
+
+/// List the built-in dictionaries, loaded user dictionaries and converters.
+#[wasm_bindgen]
+pub fn list_dicts() -> Vec<DictInfo> {
+    custom::list()
 }
@@ -0,0 +1,460 @@
+//! Offset-preserving conversion.
+//!
+//! `simplecc::Dict` only hands back the converted string, so this module
+//! re-implements its forward maximum matching over the same dictionary
+//! sources and records which input range produced which output range.
+//! Offsets handed to JavaScript are in UTF-16 code units, the unit used by
+//! DOM ranges and EPUB CFIs.
+use crate::blob::DoubleArray;
+use crate::{custom, dicts};
+use std::collections::HashMap;
+use std::sync::Arc;
+use wasm_bindgen::prelude::*;
+
+/// One link of a conversion chain: a phrase table matched longest-first.
+#[derive(Debug, Default)]
+pub struct Table {
+    phrases: Phrases,
+    /// Length in chars of the longest phrase.
+    max_len: usize,
+}
+
+#[derive(Debug)]
+enum Phrases {
+    /// Built from text sources at runtime.
+    Map(HashMap<Box<str>, Box<str>>),
+    /// Loaded from a precompiled blob.
+    Trie(DoubleArray),
+}
+
+impl Default for Phrases {
+    fn default() -> Self {
+        Phrases::Map(HashMap::new())
+    }
+}
+
+impl Table {
+    /// Load OpenCC text-format lines. As with `simplecc::Dict`, only the
+    /// first candidate of an entry is used and earlier entries win.
+    pub fn load_lines<'a>(lines: impl Iterator<Item = &'a str>) -> Self {
+        let mut phrases = HashMap::new();
+        let mut max_len = 0;
+        for line in lines {
+            let Some((key, values)) = line.split_once('\t') else {
+                continue;
+            };
+            let Some(value) = values.split(' ').next() else {
+                continue;
+            };
+            if key.is_empty() {
+                continue;
+            }
+            max_len = max_len.max(key.chars().count());
+            phrases
+                .entry(Box::from(key))
+                .or_insert_with(|| Box::from(value));
+        }
+        Table {
+            phrases: Phrases::Map(phrases),
+            max_len,
+        }
+    }
+
+    pub fn from_double_array(trie: DoubleArray, max_len: usize) -> Self {
+        Table {
+            phrases: Phrases::Trie(trie),
+            max_len,
+        }
+    }
+
+    pub fn max_len(&self) -> usize {
+        self.max_len
+    }
+
+    pub fn to_double_array(&self) -> DoubleArray {
+        match &self.phrases {
+            Phrases::Map(map) => DoubleArray::build(map.iter().map(|(k, v)| (&**k, &**v))),
+            Phrases::Trie(trie) => trie.clone(),
+        }
+    }
+
+    /// Convert `text`, marking as stable the leading matches that cannot
+    /// change once more text is appended after its first `stable_len` chars.
+    /// `None` means the text is complete and every match is stable.
+    fn convert(&self, text: &str, stable_len: Option<usize>) -> Pass {
+        let bounds: Vec<usize> = text
+            .char_indices()
+            .map(|(i, _)| i)
+            .chain(std::iter::once(text.len()))
+            .collect();
+        let len = bounds.len() - 1;
+        let lookahead = self.max_len.max(1);
+
+        let mut pass = Pass::default();
+        let mut out_len = 0;
+        let mut i = 0;
+        while i < len {
+            let matched = match &self.phrases {
+                Phrases::Map(map) => (1..=self.max_len.min(len - i)).rev().find_map(|n| {
+                    map.get(&text[bounds[i]..bounds[i + n]])
+                        .map(|value| (n, &**value))
+                }),
+                Phrases::Trie(trie) => trie.longest_match(&text[bounds[i]..], self.max_len),
+            };
+            let (n, value) = matched.unwrap_or((1, &text[bounds[i]..bounds[i + 1]]));
+            let value_len = value.chars().count();
+            pass.text.push_str(value);
+            pass.segments.push(Segment {
+                in_start: i,
+                in_end: i + n,
+                out_start: out_len,
+                out_end: out_len + value_len,
+            });
+            if stable_len.is_none_or(|stable_len| i + lookahead <= stable_len) {
+                pass.stable += 1;
+            }
+            out_len += value_len;
+            i += n;
+        }
+        pass
+    }
+}
+
+#[derive(Debug, Default)]
+struct Pass {
+    text: String,
+    segments: Vec<Segment>,
+    /// Number of leading segments that are final.
+    stable: usize,
+}
+
+/// Maps an input range to the output range it was converted to, in chars.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Segment {
+    in_start: usize,
+    in_end: usize,
+    out_start: usize,
+    out_end: usize,
+}
+
+/// Chain two passes, keeping only the boundaries both of them agree on.
+///
+/// A phrase of the second pass may straddle several segments of the first,
+/// in which case they are merged into a single coarser segment.
+fn compose(first: Pass, second: Pass) -> Pass {
+    let in_len = first.segments.last().map_or(0, |s| s.in_end);
+    let out_len = second.segments.last().map_or(0, |s| s.out_end);
+    let (a, b) = (&first.segments, &second.segments);
+
+    let mut segments = Vec::new();
+    let mut stable = 0;
+    let (mut in_start, mut out_start) = (0, 0);
+    let (mut i, mut j) = (0, 0);
+    while i < a.len() && j < b.len() {
+        let (a_end, b_end) = (a[i].out_end, b[j].in_end);
+        if a_end <= b_end {
+            i += 1;
+        }
+        if b_end <= a_end {
+            j += 1;
+        }
+        if a_end == b_end {
+            let segment = Segment {
+                in_start,
+                in_end: a[i - 1].in_end,
+                out_start,
+                out_end: b[j - 1].out_end,
+            };
+            if i <= first.stable && j <= second.stable {
+                stable += 1;
+            }
+            (in_start, out_start) = (segment.in_end, segment.out_end);
+            segments.push(segment);
+        }
+    }
+    if in_start < in_len || out_start < out_len {
+        segments.push(Segment {
+            in_start,
+            in_end: in_len,
+            out_start,
+            out_end: out_len,
+        });
+        if a.len() <= first.stable && b.len() <= second.stable {
+            stable += 1;
+        }
+    }
+
+    Pass {
+        text: second.text,
+        segments,
+        stable,
+    }
+}
+
+/// A conversion chain with offset tracking.
+#[derive(Clone)]
+pub struct Converter {
+    tables: Vec<Arc<Table>>,
+}
+
+impl Converter {
+    pub fn new(tables: Vec<Arc<Table>>) -> Self {
+        Converter { tables }
+    }
+
+    /// Run every link of the chain over `text`. When `complete` is false the
+    /// text may still be continued, and trailing matches that could change
+    /// are left out of `Pass::stable`.
+    fn run(&self, text: &str, complete: bool) -> Pass {
+        let mut stable_len = (!complete).then(|| text.chars().count());
+        let mut result: Option<Pass> = None;
+        for table in &self.tables {
+            let input = result.as_ref().map_or(text, |pass| &pass.text);
+            let pass = table.convert(input, stable_len);
+            stable_len =
+                stable_len.map(|_| pass.segments[..pass.stable].last().map_or(0, |s| s.out_end));
+            result = Some(match result {
+                Some(result) => compose(result, pass),
+                None => pass,
+            });
+        }
+        result.unwrap_or_else(|| Table::default().convert(text, stable_len))
+    }
+
+    /// Convert a complete text without tracking offsets.
+    pub fn replace_all(&self, text: &str) -> String {
+        self.run(text, true).text
+    }
+}
+
+/// Resolve a built-in dictionary or cached converter by name.
+pub fn converter(name: &str) -> Option<Converter> {
+    dicts::tables(name)
+        .or_else(|| custom::tables(name))
+        .map(Converter::new)
+}
+
+/// Converted text with its offset mapping.
+#[wasm_bindgen(getter_with_clone)]
+#[derive(Debug, Clone, Default)]
+pub struct Conversion {
+    pub text: String,
+    /// Flat `[inStart, inEnd, outStart, outEnd, ...]` quadruples in UTF-16
+    /// code units, covering input and output without gaps.
+    pub mapping: Vec<u32>,
+}
+
+/// UTF-16 offset of every char boundary in `text`.
+fn utf16_bounds(text: &str) -> Vec<usize> {
+    let mut bounds = Vec::with_capacity(text.len() + 1);
+    let mut offset = 0;
+    bounds.push(offset);
+    for c in text.chars() {
+        offset += c.len_utf16();
+        bounds.push(offset);
+    }
+    bounds
+}
+
+/// Convert a complete text.
+pub fn convert(converter: &Converter, text: &str) -> Conversion {
+    let pass = converter.run(text, true);
+    let (in_bounds, out_bounds) = (utf16_bounds(text), utf16_bounds(&pass.text));
+    let mut mapping = Vec::with_capacity(pass.segments.len() * 4);
+    for s in &pass.segments {
+        mapping.extend([
+            in_bounds[s.in_start] as u32,
+            in_bounds[s.in_end] as u32,
+            out_bounds[s.out_start] as u32,
+            out_bounds[s.out_end] as u32,
+        ]);
+    }
+    Conversion {
+        text: pass.text,
+        mapping,
+    }
+}
+
+/// Incremental converter for text split across several chunks, such as the
+/// text nodes of a DOM tree.
+///
+/// Text that could still be part of a phrase continuing in the next chunk is
+/// held back until it is known. Offsets in the returned mappings count from
+/// the start of the stream.
+#[wasm_bindgen]
+pub struct StreamConverter {
+    converter: Converter,
+    pending: String,
+    in_offset: u32,
+    out_offset: u32,
+}
+
+#[wasm_bindgen]
+impl StreamConverter {
+    #[wasm_bindgen(constructor)]
+    pub fn new(name: &str) -> Result<StreamConverter, JsValue> {
+        let name = name.split('.').next().unwrap_or_default();
+        converter(name)
+            .map(StreamConverter::from_converter)
+            .ok_or_else(|| JsValue::from_str("dict name is not supported"))
+    }
+
+    /// Feed the next chunk and return whatever output is now final.
+    pub fn push(&mut self, chunk: &str) -> Conversion {
+        self.pending.push_str(chunk);
+        self.drain(false)
+    }
+
+    /// Flush all held-back text. The converter can be reused afterwards.
+    pub fn finish(&mut self) -> Conversion {
+        self.drain(true)
+    }
+
+    /// Length in UTF-16 code units of the input held back so far.
+    #[wasm_bindgen(getter)]
+    pub fn pending(&self) -> u32 {
+        self.pending.encode_utf16().count() as u32
+    }
+}
+
+impl StreamConverter {
+    pub fn from_converter(converter: Converter) -> Self {
+        StreamConverter {
+            converter,
+            pending: String::new(),
+            in_offset: 0,
+            out_offset: 0,
+        }
+    }
+
+    fn drain(&mut self, complete: bool) -> Conversion {
+        let pass = self.converter.run(&self.pending, complete);
+        let Some(last) = pass.segments[..pass.stable].last() else {
+            return Conversion::default();
+        };
+
+        let in_bounds = utf16_bounds(&self.pending);
+        let out_bounds = utf16_bounds(&pass.text);
+        let mut mapping = Vec::with_capacity(pass.stable * 4);
+        for s in &pass.segments[..pass.stable] {
+            mapping.extend([
+                self.in_offset + in_bounds[s.in_start] as u32,
+                self.in_offset + in_bounds[s.in_end] as u32,
+                self.out_offset + out_bounds[s.out_start] as u32,
+                self.out_offset + out_bounds[s.out_end] as u32,
+            ]);
+        }
+
+        let in_end = self
+            .pending
+            .char_indices()
+            .nth(last.in_end)
+            .map_or(self.pending.len(), |(i, _)| i);
+        let out_end = pass
+            .text
+            .char_indices()
+            .nth(last.out_end)
+            .map_or(pass.text.len(), |(i, _)| i);
+        self.in_offset += in_bounds[last.in_end] as u32;
+        self.out_offset += out_bounds[last.out_end] as u32;
+        self.pending.drain(..in_end);
+
+        let mut text = pass.text;
+        text.truncate(out_end);
+        Conversion { text, mapping }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn table(text: &str) -> Arc<Table> {
+        Arc::new(Table::load_lines(text.lines()))
+    }
+
+    fn quads(mapping: &[u32]) -> Vec<[u32; 4]> {
+        mapping
+            .chunks(4)
+            .map(|c| [c[0], c[1], c[2], c[3]])
+            .collect()
+    }
+
+    #[test]
+    #[cfg(feature = "embed-dicts")]
+    fn test_builtin_text_matches_opencc() {
+        macro_rules! test {
+            ( $name:expr ) => {
+                let input = include_str!(concat!("../OpenCC/test/testcases/", $name, ".in"));
+                let ans = include_str!(concat!("../OpenCC/test/testcases/", $name, ".ans"));
+                let converter = converter($name).unwrap();
+                let conversion = convert(&converter, input);
+                assert_eq!(ans, conversion.text);
+
+                let quads = quads(&conversion.mapping);
+                let mut prev = [0, 0, 0, 0];
+                for q in &quads {
+                    assert_eq!((q[0], q[2]), (prev[1], prev[3]));
+                    prev = *q;
+                }
+                assert_eq!(prev[1] as usize, input.encode_utf16().count());
+                assert_eq!(prev[3] as usize, ans.encode_utf16().count());
+            };
+        }
+        test!("s2t");
+        test!("t2s");
+        test!("s2tw");
+        test!("s2hk");
+        test!("s2twp");
+        test!("hk2s");
+        test!("tw2s");
+        test!("tw2sp");
+    }
+
+    #[test]
+    fn test_phrase_mapping() {
+        let converter = Converter::new(vec![table("软件\t軟體\n件\t件")]);
+        let conversion = convert(&converter, "a软件😀");
+        assert_eq!(conversion.text, "a軟體😀");
+        assert_eq!(
+            quads(&conversion.mapping),
+            vec![[0, 1, 0, 1], [1, 3, 1, 3], [3, 5, 3, 5]]
+        );
+    }
+
+    #[test]
+    fn test_chain_merges_straddling_phrases() {
+        // The second link matches across two segments of the first one.
+        let converter = Converter::new(vec![table("a\txy\nb\tz"), table("yz\tW")]);
+        let conversion = convert(&converter, "abc");
+        assert_eq!(conversion.text, "xWc");
+        assert_eq!(quads(&conversion.mapping), vec![[0, 2, 0, 2], [2, 3, 2, 3]]);
+    }
+
+    #[test]
+    #[cfg(feature = "embed-dicts")]
+    fn test_stream_keeps_phrases_across_chunks() {
+        let converter = converter("s2twp").unwrap();
+        let text = "我们用打印机打印了一份计算机软件的说明书。";
+        let whole = convert(&converter, text);
+
+        for split in 1..text.chars().count() {
+            let cut = text.char_indices().nth(split).unwrap().0;
+            let mut stream = StreamConverter::from_converter(converter.clone());
+            let mut output = String::new();
+            let mut mapping = Vec::new();
+            for chunk in [&text[..cut], &text[cut..]] {
+                let c = stream.push(chunk);
+                output.push_str(&c.text);
+                mapping.extend(c.mapping);
+            }
+            let c = stream.finish();
+            output.push_str(&c.text);
+            mapping.extend(c.mapping);
+
+            assert_eq!(output, whole.text, "split at {split}");
+            assert_eq!(mapping, whole.mapping, "split at {split}");
+            assert_eq!(stream.pending(), 0);
+        }
+    }
+}
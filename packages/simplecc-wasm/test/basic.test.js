@@ -1,5 +1,12 @@
 import { describe, test, expect } from "vitest";
-import { simplecc } from "..";
+import {
+  simplecc,
+  simplecc_with_offsets,
+  StreamConverter,
+  load_dict,
+  create_converter,
+  list_dicts,
+} from "..";
 
 describe.concurrent("suite", () => {
   test("basic s2t and t2s", () => {
@@ -30,4 +37,23 @@ describe.concurrent("suite", () => {
     expect(simplecc("印表機", "tw2sp")).toBe("打印机");
     expect(simplecc("雷射", "tw2sp")).toBe("激光");
   });
+
+  test("custom dictionary chained before s2t", () => {
+    expect(load_dict("test-physics", "激光\t雷射")).toBe(1);
+    create_converter("s2t-physics", "s2t", ["test-physics"], []);
+    expect(simplecc("激光", "s2t-physics")).toBe("雷射");
+    const names = list_dicts().map((info) => info.name);
+    expect(names).toContain("test-physics");
+    expect(names).toContain("s2t-physics");
+  });
+
+  test("offset mapping and streaming", () => {
+    const { text, mapping } = simplecc_with_offsets("用打印机", "s2twp");
+    expect(text).toBe("用印表機");
+    expect(Array.from(mapping)).toEqual([0, 1, 0, 1, 1, 4, 1, 4]);
+
+    const stream = new StreamConverter("s2twp");
+    const out = [stream.push("用打印"), stream.push("机"), stream.finish()];
+    expect(out.map((c) => c.text).join("")).toBe("用印表機");
+  });
 });
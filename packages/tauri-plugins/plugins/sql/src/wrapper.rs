@@ -34,7 +34,8 @@ pub enum DbPool {
 }
 
 // public methods
-/* impl DbPool {
+#[allow(unreachable_patterns)]
+impl DbPool {
     /// Get the inner Sqlite Pool. Returns None for MySql and Postgres pools.
     #[cfg(feature = "sqlite")]
     pub fn sqlite(&self) -> Option<&Pool<Sqlite>> {
@@ -61,7 +62,7 @@ pub enum DbPool {
             _ => None,
         }
     }
-} */
+}
 
 // private methods
 impl DbPool {
@@ -7,6 +7,7 @@ const COMMANDS: &[&str] = &[
     "set_system_ui_visibility",
     "get_status_bar_height",
     "get_sys_fonts_list",
+    "load_font_file",
     "intercept_keys",
     "lock_screen_orientation",
     "iap_is_available",
@@ -65,7 +65,18 @@ pub(crate) async fn get_status_bar_height<R: Runtime>(
 pub(crate) async fn get_sys_fonts_list<R: Runtime>(
     app: AppHandle<R>,
 ) -> Result<GetSysFontsListResponse> {
-    app.native_bridge().get_sys_fonts_list()
+    // Enumerating and reading every installed font blocks for a while
+    tauri::async_runtime::spawn_blocking(move || app.native_bridge().get_sys_fonts_list())
+        .await
+        .map_err(|e| crate::Error::NativeBridgeError(e.to_string()))?
+}
+
+#[command]
+pub(crate) async fn load_font_file<R: Runtime>(
+    app: AppHandle<R>,
+    payload: LoadFontFileRequest,
+) -> Result<LoadFontFileResponse> {
+    app.native_bridge().load_font_file(payload)
 }
 
 #[command]
@@ -1,18 +1,47 @@
 use serde::de::DeserializeOwned;
-use std::collections::HashMap;
-use tauri::{plugin::PluginApi, AppHandle, Runtime};
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+use std::sync::Mutex;
+use tauri::{plugin::PluginApi, AppHandle, Manager, Runtime, Theme};
 
+#[cfg(target_os = "linux")]
+use crate::platform::linux;
+
+use crate::fonts;
 use crate::models::*;
 
 pub fn init<R: Runtime, C: DeserializeOwned>(
     app: &AppHandle<R>,
     _api: PluginApi<R, C>,
 ) -> crate::Result<NativeBridge<R>> {
-    Ok(NativeBridge(app.clone()))
+    #[cfg(target_os = "linux")]
+    {
+        use tauri::Emitter;
+        let app = app.clone();
+        linux::watch_color_scheme(move |color_scheme| {
+            let payload = GetSystemColorSchemeResponse {
+                color_scheme: color_scheme.to_string(),
+            };
+            let _ = app.emit(COLOR_SCHEME_EVENT, payload);
+        });
+    }
+    Ok(NativeBridge(
+        app.clone(),
+        Mutex::new(None),
+        fonts::Cache::default(),
+    ))
 }
 
+/// Emitted with a [`GetSystemColorSchemeResponse`] when the desktop switches between
+/// light and dark.
+pub const COLOR_SCHEME_EVENT: &str = "system-color-scheme-changed";
+
 /// Access to the native-bridge APIs.
-pub struct NativeBridge<R: Runtime>(AppHandle<R>);
+///
+/// The second field keeps the backlight level from before the app first changed it,
+/// so that a negative brightness can hand control back to the system. The third one
+/// keeps the faces of the system fonts, which take a while to read.
+pub struct NativeBridge<R: Runtime>(AppHandle<R>, Mutex<Option<u32>>, fonts::Cache);
 
 impl<R: Runtime> NativeBridge<R> {
     pub fn auth_with_safari(&self, _payload: AuthRequest) -> crate::Result<AuthResponse> {
@@ -50,8 +79,18 @@ impl<R: Runtime> NativeBridge<R> {
     }
 
     pub fn get_sys_fonts_list(&self) -> crate::Result<GetSysFontsListResponse> {
-        let font_collection = font_enumeration::Collection::new().unwrap();
+        let font_collection = match font_enumeration::Collection::new() {
+            Ok(collection) => collection,
+            Err(err) => {
+                return Ok(GetSysFontsListResponse {
+                    fonts: HashMap::new(),
+                    faces: Vec::new(),
+                    error: Some(format!("Failed to enumerate system fonts: {err}")),
+                })
+            }
+        };
         let mut fonts = HashMap::new();
+        let mut by_path: BTreeMap<&Path, Vec<&font_enumeration::Font>> = BTreeMap::new();
         for font in font_collection.all() {
             if cfg!(target_os = "windows") {
                 // FIXME: temporarily disable font name with style for windows
@@ -59,8 +98,45 @@ impl<R: Runtime> NativeBridge<R> {
             } else {
                 fonts.insert(font.font_name.clone(), font.family_name.clone());
             }
+            by_path.entry(font.path.as_path()).or_default().push(font);
+        }
+
+        // Read each file once, even when a collection holds several of the faces.
+        let mut faces = Vec::new();
+        for (path, entries) in by_path {
+            let described = self.2.faces(path);
+            for font in entries {
+                let face = described
+                    .iter()
+                    .find(|face| face.name == font.font_name)
+                    .or_else(|| {
+                        described
+                            .iter()
+                            .find(|face| face.family == font.family_name)
+                    })
+                    .or(described.first());
+                if let Some(face) = face {
+                    faces.push(FontInfo {
+                        name: font.font_name.clone(),
+                        family: font.family_name.clone(),
+                        ..face.clone()
+                    });
+                }
+            }
         }
-        Ok(GetSysFontsListResponse { fonts, error: None })
+        Ok(GetSysFontsListResponse {
+            fonts,
+            faces,
+            error: None,
+        })
+    }
+
+    pub fn load_font_file(
+        &self,
+        payload: LoadFontFileRequest,
+    ) -> crate::Result<LoadFontFileResponse> {
+        let faces = fonts::load_font_file(Path::new(&payload.path))?;
+        Ok(LoadFontFileResponse { faces })
     }
 
     pub fn intercept_keys(&self, _payload: InterceptKeysRequest) -> crate::Result<()> {
@@ -104,17 +180,84 @@ impl<R: Runtime> NativeBridge<R> {
     }
 
     pub fn get_system_color_scheme(&self) -> crate::Result<GetSystemColorSchemeResponse> {
-        Err(crate::Error::UnsupportedPlatformError)
+        #[cfg(target_os = "linux")]
+        let color_scheme = linux::color_scheme();
+        #[cfg(not(target_os = "linux"))]
+        let color_scheme = None;
+
+        let color_scheme = color_scheme.unwrap_or_else(|| {
+            let window = self.0.webview_windows().into_values().next();
+            match window.and_then(|window| window.theme().ok()) {
+                Some(Theme::Dark) => "dark",
+                _ => "light",
+            }
+        });
+        Ok(GetSystemColorSchemeResponse {
+            color_scheme: color_scheme.to_string(),
+        })
     }
 
     pub fn get_safe_area_insets(&self) -> crate::Result<GetSafeAreaInsetsResponse> {
-        Err(crate::Error::UnsupportedPlatformError)
+        // Desktop windows have no notches or system bars drawn over the content.
+        Ok(GetSafeAreaInsetsResponse {
+            top: 0.0,
+            bottom: 0.0,
+            left: 0.0,
+            right: 0.0,
+        })
     }
 
+    #[cfg(target_os = "linux")]
+    pub fn get_screen_brightness(&self) -> crate::Result<GetScreenBrightnessResponse> {
+        // Desktop monitors without a backlight device report -1, like an unset override.
+        let brightness = match linux::Backlight::find() {
+            Some(backlight) => backlight.get()?,
+            None => -1.0,
+        };
+        Ok(GetScreenBrightnessResponse { brightness })
+    }
+
+    #[cfg(not(target_os = "linux"))]
     pub fn get_screen_brightness(&self) -> crate::Result<GetScreenBrightnessResponse> {
         Err(crate::Error::UnsupportedPlatformError)
     }
 
+    #[cfg(target_os = "linux")]
+    pub fn set_screen_brightness(
+        &self,
+        payload: SetScreenBrightnessRequest,
+    ) -> crate::Result<SetScreenBrightnessResponse> {
+        let Some(backlight) = linux::Backlight::find() else {
+            return Ok(SetScreenBrightnessResponse {
+                success: false,
+                error: Some("No backlight device found".to_string()),
+            });
+        };
+        let mut original = self.1.lock().unwrap();
+        let result = if payload.brightness < 0.0 {
+            match original.take() {
+                Some(raw) => backlight.set_raw(raw),
+                None => Ok(()),
+            }
+        } else {
+            if original.is_none() {
+                *original = backlight.raw().ok();
+            }
+            backlight.set(payload.brightness)
+        };
+        Ok(match result {
+            Ok(()) => SetScreenBrightnessResponse {
+                success: true,
+                error: None,
+            },
+            Err(err) => SetScreenBrightnessResponse {
+                success: false,
+                error: Some(err.to_string()),
+            },
+        })
+    }
+
+    #[cfg(not(target_os = "linux"))]
     pub fn set_screen_brightness(
         &self,
         _payload: SetScreenBrightnessRequest,
@@ -122,6 +265,21 @@ impl<R: Runtime> NativeBridge<R> {
         Err(crate::Error::UnsupportedPlatformError)
     }
 
+    /// Puts the backlight back to the level it had before the app first changed it,
+    /// for when the app exits.
+    #[cfg(target_os = "linux")]
+    pub fn restore_screen_brightness(&self) {
+        let Some(raw) = self.1.lock().unwrap().take() else {
+            return;
+        };
+        if let Some(Err(err)) = linux::Backlight::find().map(|backlight| backlight.set_raw(raw)) {
+            log::warn!("Failed to restore the screen brightness: {err}");
+        }
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    pub fn restore_screen_brightness(&self) {}
+
     pub fn get_external_sdcard_path(&self) -> crate::Result<GetExternalSDCardPathResponse> {
         Err(crate::Error::UnsupportedPlatformError)
     }
@@ -0,0 +1,206 @@
+//! Font metadata read from the font files themselves, so that every platform reports
+//! styles, widths and script coverage the same way.
+
+use std::fs::File;
+use std::path::Path;
+#[cfg(desktop)]
+use std::{collections::HashMap, path::PathBuf, sync::Mutex, time::SystemTime};
+
+use memmap2::Mmap;
+use ttf_parser::{name_id, Face, Style};
+
+use crate::models::FontInfo;
+
+/// Scripts worth offering in the font picker, as ISO 15924 codes, each with a few
+/// characters a font must map to count as covering it.
+const SCRIPTS: &[(&str, &[char])] = &[
+    ("Latn", &['A', 'z', 'é']),
+    ("Grek", &['Α', 'ω']),
+    ("Cyrl", &['Ж', 'я']),
+    ("Arab", &['ا', 'ب', 'ي']),
+    ("Hebr", &['א', 'ש']),
+    ("Deva", &['क', 'ि', 'ं']),
+    ("Beng", &['ক', 'ি']),
+    ("Guru", &['ਕ', 'ਿ']),
+    ("Gujr", &['ક', 'િ']),
+    ("Orya", &['କ', 'ି']),
+    ("Taml", &['க', 'ி']),
+    ("Telu", &['క', 'ి']),
+    ("Knda", &['ಕ', 'ಿ']),
+    ("Mlym", &['ക', 'ി']),
+    ("Thai", &['ก', 'า']),
+    ("Hani", &['中', '文', '字']),
+    ("Hira", &['あ', 'の']),
+    ("Kana", &['ア', 'ン']),
+    ("Hang", &['한', '글']),
+];
+
+/// Maps the OS/2 width class (1 to 9) to a CSS `font-stretch` percentage.
+fn stretch_percent(width_class: u16) -> f32 {
+    const PERCENTS: [f32; 9] = [50.0, 62.5, 75.0, 87.5, 100.0, 112.5, 125.0, 150.0, 200.0];
+    PERCENTS[usize::from(width_class.clamp(1, 9)) - 1]
+}
+
+fn name(face: &Face, ids: &[u16]) -> Option<String> {
+    ids.iter().find_map(|id| {
+        face.names()
+            .into_iter()
+            .filter(|name| name.name_id == *id && name.is_unicode())
+            .find_map(|name| name.to_string())
+            .filter(|name| !name.trim().is_empty())
+    })
+}
+
+fn scripts(face: &Face) -> Vec<String> {
+    SCRIPTS
+        .iter()
+        .filter(|(_, samples)| samples.iter().all(|c| face.glyph_index(*c).is_some()))
+        .map(|(script, _)| script.to_string())
+        .collect()
+}
+
+fn is_monospaced(face: &Face) -> bool {
+    if face.is_monospaced() {
+        return true;
+    }
+    // Plenty of coding fonts never set the `post` flag, so compare a narrow and a
+    // wide glyph as well.
+    let advance = |c| {
+        face.glyph_index(c)
+            .and_then(|id| face.glyph_hor_advance(id))
+    };
+    matches!((advance('i'), advance('M')), (Some(i), Some(m)) if i == m && i > 0)
+}
+
+/// Describes face `index` of a font file, or `None` when it can't be parsed.
+pub fn describe(data: &[u8], index: u32, path: &Path) -> Option<FontInfo> {
+    let face = Face::parse(data, index).ok()?;
+    let family = name(&face, &[name_id::TYPOGRAPHIC_FAMILY, name_id::FAMILY])?;
+    let style = name(&face, &[name_id::TYPOGRAPHIC_SUBFAMILY, name_id::SUBFAMILY])
+        .unwrap_or_else(|| "Regular".to_string());
+    let full_name =
+        name(&face, &[name_id::FULL_NAME]).unwrap_or_else(|| format!("{family} {style}"));
+    Some(FontInfo {
+        name: full_name,
+        family,
+        style,
+        weight: face.weight().to_number(),
+        stretch: stretch_percent(face.width().to_number()),
+        italic: matches!(face.style(), Style::Italic | Style::Oblique),
+        monospace: is_monospaced(&face),
+        scripts: scripts(&face),
+        path: path.to_string_lossy().into_owned(),
+    })
+}
+
+/// Describes every face in a font file; collections (`.ttc`) hold more than one.
+pub fn load_font_file(path: &Path) -> crate::Result<Vec<FontInfo>> {
+    let file = File::open(path)?;
+    // SAFETY: the map is only read while the faces are described and is dropped right
+    // after; installed fonts are not rewritten in place while that happens.
+    let data = unsafe { Mmap::map(&file)? };
+    let count = ttf_parser::fonts_in_collection(&data).unwrap_or(1);
+    let faces = (0..count)
+        .filter_map(|index| describe(&data, index, path))
+        .collect::<Vec<_>>();
+    if faces.is_empty() {
+        return Err(crate::Error::NativeBridgeError(format!(
+            "Not a supported font file: {}",
+            path.display()
+        )));
+    }
+    Ok(faces)
+}
+
+/// Faces of the system fonts read so far, by path, along with the modification time
+/// of the file they were read from.
+#[cfg(desktop)]
+#[derive(Default)]
+pub struct Cache(Mutex<HashMap<PathBuf, (SystemTime, Vec<FontInfo>)>>);
+
+#[cfg(desktop)]
+impl Cache {
+    /// Like [`load_font_file`], but only reads files that are new or have changed
+    /// since. Files that can't be parsed have no faces.
+    pub fn faces(&self, path: &Path) -> Vec<FontInfo> {
+        let Ok(modified) = path.metadata().and_then(|meta| meta.modified()) else {
+            return Vec::new();
+        };
+        if let Some((mtime, faces)) = self.0.lock().unwrap().get(path) {
+            if *mtime == modified {
+                return faces.clone();
+            }
+        }
+        let faces = load_font_file(path).unwrap_or_default();
+        self.0
+            .lock()
+            .unwrap()
+            .insert(path.to_path_buf(), (modified, faces.clone()));
+        faces
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn fixture(name: &str) -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("../../../packages/foliate-js/vendor/pdfjs/standard_fonts")
+            .join(name)
+    }
+
+    #[test]
+    fn describes_a_font_file() {
+        let faces = load_font_file(&fixture("LiberationSans-BoldItalic.ttf")).unwrap();
+        assert_eq!(faces.len(), 1);
+        let face = &faces[0];
+        assert_eq!(face.family, "Liberation Sans");
+        assert_eq!(face.style, "Bold Italic");
+        assert_eq!(face.weight, 700);
+        assert_eq!(face.stretch, 100.0);
+        assert!(face.italic);
+        assert!(!face.monospace);
+        assert!(face.scripts.iter().any(|script| script == "Latn"));
+        assert!(!face.scripts.iter().any(|script| script == "Deva"));
+    }
+
+    #[test]
+    fn rejects_files_that_are_not_fonts() {
+        assert!(load_font_file(&fixture("FoxitDingbats.pfb")).is_err());
+        assert!(load_font_file(&fixture("missing.ttf")).is_err());
+    }
+
+    #[test]
+    #[cfg(desktop)]
+    fn caches_faces_until_the_file_changes() {
+        use std::fs;
+
+        let dir = std::env::temp_dir().join(format!("native-bridge-fonts-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("font.ttf");
+        fs::copy(fixture("LiberationSans-BoldItalic.ttf"), &path).unwrap();
+
+        let cache = Cache::default();
+        assert_eq!(cache.faces(&path)[0].family, "Liberation Sans");
+        let mtime = fs::metadata(&path).unwrap().modified().unwrap();
+        cache.0.lock().unwrap().get_mut(&path).unwrap().1[0].family = "Cached".to_string();
+        assert_eq!(cache.faces(&path)[0].family, "Cached");
+
+        let file = File::options().write(true).open(&path).unwrap();
+        file.set_modified(mtime + std::time::Duration::from_secs(1))
+            .unwrap();
+        assert_eq!(cache.faces(&path)[0].family, "Liberation Sans");
+        assert!(cache.faces(&dir.join("missing.ttf")).is_empty());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn maps_width_classes_to_css_stretch() {
+        assert_eq!(stretch_percent(5), 100.0);
+        assert_eq!(stretch_percent(1), 50.0);
+        assert_eq!(stretch_percent(0), 50.0);
+        assert_eq!(stretch_percent(12), 200.0);
+    }
+}
@@ -13,6 +13,7 @@ mod mobile;
 
 mod commands;
 mod error;
+mod fonts;
 mod models;
 mod platform;
 
@@ -53,7 +54,7 @@ impl<R: Runtime> Default for DirectoryCallbackState<R> {
 
 /// Initializes the plugin.
 pub fn init<R: Runtime>() -> TauriPlugin<R> {
-    Builder::new("native-bridge")
+    let builder = Builder::new("native-bridge")
         .invoke_handler(tauri::generate_handler![
             commands::auth_with_safari,
             commands::auth_with_custom_tab,
@@ -63,6 +64,7 @@ pub fn init<R: Runtime>() -> TauriPlugin<R> {
             commands::set_system_ui_visibility,
             commands::get_status_bar_height,
             commands::get_sys_fonts_list,
+            commands::load_font_file,
             commands::intercept_keys,
             commands::lock_screen_orientation,
             commands::iap_is_available,
@@ -87,8 +89,14 @@ pub fn init<R: Runtime>() -> TauriPlugin<R> {
             app.manage(native_bridge);
             app.manage(DirectoryCallbackState::<R>::default());
             Ok(())
-        })
-        .build()
+        });
+    #[cfg(desktop)]
+    let builder = builder.on_event(|app, event| {
+        if let tauri::RunEvent::Exit = event {
+            app.native_bridge().restore_screen_brightness();
+        }
+    });
+    builder.build()
 }
 
 pub fn register_select_directory_callback<R: Runtime>(
@@ -92,6 +92,14 @@ impl<R: Runtime> NativeBridge<R> {
             .run_mobile_plugin("get_sys_fonts_list", ())
             .map_err(Into::into)
     }
+
+    pub fn load_font_file(
+        &self,
+        payload: LoadFontFileRequest,
+    ) -> crate::Result<LoadFontFileResponse> {
+        let faces = crate::fonts::load_font_file(std::path::Path::new(&payload.path))?;
+        Ok(LoadFontFileResponse { faces })
+    }
 }
 
 impl<R: Runtime> NativeBridge<R> {
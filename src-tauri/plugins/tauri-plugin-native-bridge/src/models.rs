@@ -71,9 +71,37 @@ pub struct GetStatusBarHeightResponse {
 #[serde(rename_all = "camelCase")]
 pub struct GetSysFontsListResponse {
     pub fonts: HashMap<String, String>,
+    #[serde(default)]
+    pub faces: Vec<FontInfo>,
     pub error: Option<String>,
 }
 
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FontInfo {
+    pub name: String,
+    pub family: String,
+    pub style: String,
+    pub weight: u16,
+    pub stretch: f32, // CSS font-stretch percentage
+    pub italic: bool,
+    pub monospace: bool,
+    pub scripts: Vec<String>, // ISO 15924 codes, e.g. "Latn", "Deva", "Hani"
+    pub path: String,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LoadFontFileRequest {
+    pub path: String,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LoadFontFileResponse {
+    pub faces: Vec<FontInfo>,
+}
+
 #[derive(Debug, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct InterceptKeysRequest {
@@ -0,0 +1,238 @@
+//! Display settings on Linux: the color scheme from the XDG desktop portal and the
+//! screen brightness from the kernel's backlight class, set through logind when the
+//! sysfs file is not writable by the user.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use zbus::blocking::{Connection, Proxy};
+use zbus::zvariant::{OwnedValue, Value};
+
+const BACKLIGHT_DIR: &str = "/sys/class/backlight";
+
+const PORTAL_DESTINATION: &str = "org.freedesktop.portal.Desktop";
+const PORTAL_PATH: &str = "/org/freedesktop/portal/desktop";
+const PORTAL_SETTINGS: &str = "org.freedesktop.portal.Settings";
+const APPEARANCE_NAMESPACE: &str = "org.freedesktop.appearance";
+const COLOR_SCHEME_KEY: &str = "color-scheme";
+
+fn dbus_error(err: zbus::Error) -> crate::Error {
+    crate::Error::NativeBridgeError(format!("D-Bus: {err}"))
+}
+
+/// Maps the portal's `color-scheme` value (0 no preference, 1 dark, 2 light).
+fn parse_portal_scheme(value: &Value) -> Option<&'static str> {
+    match value {
+        Value::U32(1) => Some("dark"),
+        Value::U32(2) => Some("light"),
+        // The deprecated `Read` method wraps the value in one more variant.
+        Value::Value(inner) => parse_portal_scheme(inner),
+        _ => None,
+    }
+}
+
+/// Maps a GNOME `color-scheme` setting or a `GTK_THEME` name.
+fn parse_theme_name(name: &str) -> Option<&'static str> {
+    let name = name.trim().trim_matches('\'').to_ascii_lowercase();
+    if name.is_empty() {
+        None
+    } else if name.contains("dark") {
+        Some("dark")
+    } else if name.contains("light") {
+        Some("light")
+    } else {
+        None
+    }
+}
+
+fn portal_color_scheme(conn: &Connection) -> zbus::Result<Option<&'static str>> {
+    let proxy = Proxy::new(conn, PORTAL_DESTINATION, PORTAL_PATH, PORTAL_SETTINGS)?;
+    let setting = (APPEARANCE_NAMESPACE, COLOR_SCHEME_KEY);
+    let value: OwnedValue = match proxy.call("ReadOne", &setting) {
+        Ok(value) => value,
+        // Portals older than version 2 only implement `Read`.
+        Err(_) => proxy.call("Read", &setting)?,
+    };
+    Ok(parse_portal_scheme(&value))
+}
+
+fn gsettings_color_scheme() -> Option<&'static str> {
+    let output = Command::new("gsettings")
+        .args(["get", "org.gnome.desktop.interface", COLOR_SCHEME_KEY])
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    // `default` means no preference, which falls through to the next source.
+    parse_theme_name(&String::from_utf8_lossy(&output.stdout))
+}
+
+/// The desktop's preferred color scheme, `"light"` or `"dark"`, or `None` when neither
+/// the portal, GNOME settings nor `GTK_THEME` express a preference.
+pub fn color_scheme() -> Option<&'static str> {
+    let portal = Connection::session()
+        .ok()
+        .and_then(|conn| portal_color_scheme(&conn).ok().flatten());
+    portal.or_else(gsettings_color_scheme).or_else(|| {
+        std::env::var("GTK_THEME")
+            .ok()
+            .as_deref()
+            .and_then(parse_theme_name)
+    })
+}
+
+/// Calls `on_change` on a background thread whenever the portal reports a new color
+/// scheme. Does nothing when the session bus or the portal is unavailable.
+pub fn watch_color_scheme(on_change: impl Fn(&'static str) + Send + 'static) {
+    std::thread::spawn(move || {
+        let watch = || -> zbus::Result<()> {
+            let conn = Connection::session()?;
+            let proxy = Proxy::new(&conn, PORTAL_DESTINATION, PORTAL_PATH, PORTAL_SETTINGS)?;
+            for message in proxy.receive_signal("SettingChanged")? {
+                let (namespace, key, value): (String, String, OwnedValue) =
+                    message.body().deserialize()?;
+                if namespace != APPEARANCE_NAMESPACE || key != COLOR_SCHEME_KEY {
+                    continue;
+                }
+                if let Some(scheme) = parse_portal_scheme(&value) {
+                    on_change(scheme);
+                }
+            }
+            Ok(())
+        };
+        if let Err(err) = watch() {
+            log::warn!("Failed to watch the system color scheme: {err}");
+        }
+    });
+}
+
+/// Firmware interfaces map to the panel most directly, raw registers least.
+fn backlight_rank(kind: &str) -> u8 {
+    match kind.trim() {
+        "firmware" => 0,
+        "platform" => 1,
+        "raw" => 2,
+        _ => 3,
+    }
+}
+
+fn pick_backlight(devices: &[(String, String)]) -> Option<&str> {
+    devices
+        .iter()
+        .min_by_key(|(name, kind)| (backlight_rank(kind), name.as_str()))
+        .map(|(name, _)| name.as_str())
+}
+
+fn to_raw(brightness: f64, max: u32) -> u32 {
+    // Never write zero: on most panels it switches the backlight off entirely.
+    ((brightness.clamp(0.0, 1.0) * max as f64).round() as u32).clamp(1, max.max(1))
+}
+
+fn read_u32(path: &Path) -> std::io::Result<u32> {
+    fs::read_to_string(path)?
+        .trim()
+        .parse()
+        .map_err(|err| std::io::Error::new(std::io::ErrorKind::InvalidData, err))
+}
+
+/// A backlight device under `/sys/class/backlight`.
+pub struct Backlight {
+    name: String,
+    dir: PathBuf,
+    max: u32,
+}
+
+impl Backlight {
+    /// The preferred backlight device, if the machine has one.
+    pub fn find() -> Option<Self> {
+        let devices = fs::read_dir(BACKLIGHT_DIR)
+            .ok()?
+            .flatten()
+            .map(|entry| {
+                let kind = fs::read_to_string(entry.path().join("type")).unwrap_or_default();
+                (entry.file_name().to_string_lossy().into_owned(), kind)
+            })
+            .collect::<Vec<_>>();
+        let name = pick_backlight(&devices)?.to_string();
+        let dir = Path::new(BACKLIGHT_DIR).join(&name);
+        let max = read_u32(&dir.join("max_brightness"))
+            .ok()
+            .filter(|max| *max > 0)?;
+        Some(Self { name, dir, max })
+    }
+
+    pub fn raw(&self) -> crate::Result<u32> {
+        Ok(read_u32(&self.dir.join("brightness"))?)
+    }
+
+    /// The current brightness between 0.0 and 1.0.
+    pub fn get(&self) -> crate::Result<f64> {
+        Ok(self.raw()? as f64 / self.max as f64)
+    }
+
+    /// Sets the brightness between 0.0 and 1.0.
+    pub fn set(&self, brightness: f64) -> crate::Result<()> {
+        self.set_raw(to_raw(brightness, self.max))
+    }
+
+    pub fn set_raw(&self, value: u32) -> crate::Result<()> {
+        let value = value.min(self.max);
+        if fs::write(self.dir.join("brightness"), value.to_string()).is_ok() {
+            return Ok(());
+        }
+        // Unprivileged users usually can't write to sysfs, but logind lets the
+        // active session change its own backlight.
+        let conn = Connection::system().map_err(dbus_error)?;
+        conn.call_method(
+            Some("org.freedesktop.login1"),
+            "/org/freedesktop/login1/session/auto",
+            Some("org.freedesktop.login1.Session"),
+            "SetBrightness",
+            &("backlight", self.name.as_str(), value),
+        )
+        .map_err(dbus_error)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_portal_schemes() {
+        assert_eq!(parse_portal_scheme(&Value::U32(1)), Some("dark"));
+        assert_eq!(parse_portal_scheme(&Value::U32(2)), Some("light"));
+        assert_eq!(parse_portal_scheme(&Value::U32(0)), None);
+        let nested = Value::Value(Box::new(Value::U32(1)));
+        assert_eq!(parse_portal_scheme(&nested), Some("dark"));
+    }
+
+    #[test]
+    fn parses_theme_names() {
+        assert_eq!(parse_theme_name("'prefer-dark'\n"), Some("dark"));
+        assert_eq!(parse_theme_name("'prefer-light'"), Some("light"));
+        assert_eq!(parse_theme_name("'default'"), None);
+        assert_eq!(parse_theme_name("Adwaita:dark"), Some("dark"));
+        assert_eq!(parse_theme_name(""), None);
+    }
+
+    #[test]
+    fn prefers_firmware_backlights() {
+        let devices = vec![
+            ("intel_backlight".to_string(), "raw\n".to_string()),
+            ("acpi_video0".to_string(), "firmware\n".to_string()),
+        ];
+        assert_eq!(pick_backlight(&devices), Some("acpi_video0"));
+        assert_eq!(pick_backlight(&[]), None);
+    }
+
+    #[test]
+    fn scales_to_raw_without_turning_off() {
+        assert_eq!(to_raw(0.5, 1000), 500);
+        assert_eq!(to_raw(1.5, 255), 255);
+        assert_eq!(to_raw(0.0, 255), 1);
+    }
+}
@@ -54,10 +54,17 @@ data class TTSMessageEvent(
     val mark: String? = null
 )
 
+@InvokeArg
+class SpeechMark {
+    var offset: Int = 0
+    var name: String = ""
+}
+
 @InvokeArg
 class SpeakArgs(
     val text: String? = "",
-    val preload: Boolean? = false
+    val preload: Boolean? = false,
+    val marks: Array<SpeechMark>? = null
 )
 
 @InvokeArg
@@ -125,6 +132,8 @@ class NativeTTSPlugin(private val activity: Activity) : Plugin(activity) {
     
     private val eventChannels = ConcurrentHashMap<String, Channel<TTSMessageEvent>>()
     private val speakingJobs = ConcurrentHashMap<String, Job>()
+    // SSML marks not reached yet, in text order
+    private val pendingMarks = ConcurrentHashMap<String, ArrayDeque<SpeechMark>>()
     private val coroutineScope = CoroutineScope(Dispatchers.Main + SupervisorJob())
 
     @Command
@@ -177,12 +186,14 @@ class NativeTTSPlugin(private val activity: Activity) : Plugin(activity) {
                 utteranceId?.let { id ->
                     isSpeaking.set(true)
                     sendEvent(id, TTSMessageEvent("boundary", "start"))
+                    sendMarksUpTo(id, 0)
                 }
             }
             
             override fun onDone(utteranceId: String?) {
                 utteranceId?.let { id ->
                     isSpeaking.set(false)
+                    sendMarksUpTo(id, Int.MAX_VALUE)
                     sendEvent(id, TTSMessageEvent("end"))
                     closeEventChannel(id)
                 }
@@ -207,6 +218,7 @@ class NativeTTSPlugin(private val activity: Activity) : Plugin(activity) {
             
             override fun onRangeStart(utteranceId: String?, start: Int, end: Int, frame: Int) {
                 utteranceId?.let { id ->
+                    sendMarksUpTo(id, start)
                     sendEvent(id, TTSMessageEvent("boundary", "range", "pos:$start-$end"))
                 }
             }
@@ -229,6 +241,9 @@ class NativeTTSPlugin(private val activity: Activity) : Plugin(activity) {
             try {
                 val eventChannel = Channel<TTSMessageEvent>(Channel.UNLIMITED)
                 eventChannels[utteranceId] = eventChannel
+                args.marks?.takeIf { it.isNotEmpty() }?.let { marks ->
+                    pendingMarks[utteranceId] = ArrayDeque(marks.sortedBy { it.offset })
+                }
                 
                 val speakJob = launch {
                     speakText(text, utteranceId, args.preload ?: false)
@@ -297,6 +312,17 @@ class NativeTTSPlugin(private val activity: Activity) : Plugin(activity) {
         }
     }
     
+    private fun sendMarksUpTo(utteranceId: String, offset: Int) {
+        val marks = pendingMarks[utteranceId] ?: return
+        while (marks.isNotEmpty() && marks.first().offset <= offset) {
+            val mark = marks.removeFirst()
+            sendEvent(utteranceId, TTSMessageEvent("boundary", "mark", mark.name))
+        }
+        if (marks.isEmpty()) {
+            pendingMarks.remove(utteranceId)
+        }
+    }
+
     private fun sendEvent(utteranceId: String, event: TTSMessageEvent) {
         coroutineScope.launch {
             eventChannels[utteranceId]?.trySend(event)
@@ -307,6 +333,7 @@ class NativeTTSPlugin(private val activity: Activity) : Plugin(activity) {
         coroutineScope.launch {
             eventChannels[utteranceId]?.close()
             eventChannels.remove(utteranceId)
+            pendingMarks.remove(utteranceId)
             speakingJobs[utteranceId]?.cancel()
             speakingJobs.remove(utteranceId)
         }
@@ -346,6 +373,7 @@ class NativeTTSPlugin(private val activity: Activity) : Plugin(activity) {
                 eventChannels.values.forEach { it.close() }
                 speakingJobs.clear()
                 eventChannels.clear()
+                pendingMarks.clear()
                 
                 invoke.resolve()
             } else {
@@ -355,7 +383,20 @@ class NativeTTSPlugin(private val activity: Activity) : Plugin(activity) {
             invoke.reject("Exception while stopping: ${e.message}")
         }
     }
-    
+
+    // Called from Rust, so the playback queue reaches the webview's listeners
+    @Command
+    fun emit_event(invoke: Invoke) {
+        val args = invoke.getArgs()
+        val event = args.getString("event")
+        if (event.isNullOrEmpty()) {
+            invoke.reject("Missing event name")
+            return
+        }
+        trigger(event, args.getJSObject("payload") ?: JSObject())
+        invoke.resolve()
+    }
+
     @Command
     fun set_rate(invoke: Invoke) {
         val args = invoke.parseArgs(SetRateArgs::class.java)
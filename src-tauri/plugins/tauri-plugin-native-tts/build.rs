@@ -8,6 +8,14 @@ const COMMANDS: &[&str] = &[
     "set_pitch",
     "set_voice",
     "get_all_voices",
+    "get_lexicon",
+    "set_lexicon",
+    "enqueue",
+    "clear_queue",
+    "set_sleep_timer",
+    "get_queue_state",
+    "get_resume_position",
+    "synthesize_to_file",
     "set_media_session_active",
     "update_media_session_state",
     "update_media_session_metadata",
@@ -0,0 +1,153 @@
+//! Playback for the engines that synthesize audio themselves. Events are
+//! queued in the audio stream, so they fire as playback reaches them.
+
+use rodio::buffer::SamplesBuffer;
+use rodio::source::EmptyCallback;
+use rodio::{OutputStream, OutputStreamHandle, Sink};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::{self, Sender};
+use std::sync::{Arc, Mutex};
+
+use crate::models::TTSMessageEvent;
+
+pub type EventSink = Arc<dyn Fn(String, TTSMessageEvent) + Send + Sync>;
+
+pub fn error(context: &str, err: impl std::fmt::Display) -> crate::Error {
+    crate::Error::NativeTTSError(format!("{context}: {err}"))
+}
+
+pub fn event(code: &str, message: Option<&str>, mark: Option<String>) -> TTSMessageEvent {
+    TTSMessageEvent {
+        code: code.to_string(),
+        message: message.map(str::to_string),
+        mark,
+    }
+}
+
+/// The audio device. The output stream cannot move between threads, so it
+/// lives on its own thread for as long as the player exists.
+struct Player {
+    handle: OutputStreamHandle,
+    sink: Sink,
+    _keep_alive: Sender<()>,
+}
+
+impl Player {
+    fn open() -> crate::Result<Self> {
+        let (ready_tx, ready_rx) = mpsc::channel();
+        let (keep_alive, closed) = mpsc::channel::<()>();
+        std::thread::Builder::new()
+            .name("tts-audio".into())
+            .spawn(move || match OutputStream::try_default() {
+                Ok((_stream, handle)) => {
+                    let _ = ready_tx.send(Ok(handle));
+                    let _ = closed.recv();
+                }
+                Err(err) => {
+                    let _ = ready_tx.send(Err(error("No audio output device", err)));
+                }
+            })?;
+        let handle = ready_rx
+            .recv()
+            .map_err(|e| error("Audio thread failed", e))??;
+        let sink = Sink::try_new(&handle).map_err(|e| error("Failed to open audio", e))?;
+        Ok(Self {
+            handle,
+            sink,
+            _keep_alive: keep_alive,
+        })
+    }
+
+    fn clear(&mut self) -> crate::Result<()> {
+        self.sink.stop();
+        self.sink = Sink::try_new(&self.handle).map_err(|e| error("Failed to open audio", e))?;
+        Ok(())
+    }
+}
+
+/// An audio queue that `stop` empties. Utterances remember the generation
+/// they were queued in, and stop queueing once it has moved on.
+pub struct Output {
+    player: Mutex<Option<Player>>,
+    generation: AtomicU64,
+    on_event: EventSink,
+}
+
+impl Output {
+    pub fn new(on_event: EventSink) -> Self {
+        Self {
+            player: Mutex::new(None),
+            generation: AtomicU64::new(0),
+            on_event,
+        }
+    }
+
+    /// Open the audio device on first use.
+    pub fn open(&self) -> crate::Result<()> {
+        let mut player = self.player.lock().unwrap();
+        if player.is_none() {
+            *player = Some(Player::open()?);
+        }
+        Ok(())
+    }
+
+    pub fn generation(&self) -> u64 {
+        self.generation.load(Ordering::SeqCst)
+    }
+
+    pub fn cancelled(&self, generation: u64) -> bool {
+        self.generation() != generation
+    }
+
+    /// Report an event now.
+    pub fn emit(&self, utterance_id: &str, event: TTSMessageEvent) {
+        (self.on_event)(utterance_id.to_string(), event)
+    }
+
+    /// Queue a callback in the audio stream, fired when playback reaches it.
+    pub fn at_playback(&self, sink: &Sink, utterance_id: &str, event: TTSMessageEvent) {
+        let on_event = self.on_event.clone();
+        let utterance_id = utterance_id.to_string();
+        sink.append(EmptyCallback::<f32>::new(Box::new(move || {
+            on_event(utterance_id.clone(), event.clone())
+        })));
+    }
+
+    /// Queue audio unless the utterance was cancelled.
+    pub fn queue(&self, generation: u64, f: impl FnOnce(&Sink)) -> bool {
+        match self.player.lock().unwrap().as_ref() {
+            Some(player) if !self.cancelled(generation) => {
+                f(&player.sink);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    pub fn silence(sink: &Sink, millis: u32) {
+        const RATE: u32 = 16_000;
+        let silence = vec![0.0f32; (millis * RATE / 1000) as usize];
+        sink.append(SamplesBuffer::new(1, RATE, silence));
+    }
+
+    pub fn pause(&self) {
+        if let Some(player) = self.player.lock().unwrap().as_ref() {
+            player.sink.pause();
+        }
+    }
+
+    pub fn resume(&self) {
+        if let Some(player) = self.player.lock().unwrap().as_ref() {
+            player.sink.play();
+        }
+    }
+
+    /// Drop queued and playing audio without further events.
+    pub fn stop(&self) -> crate::Result<()> {
+        self.generation.fetch_add(1, Ordering::SeqCst);
+        match self.player.lock().unwrap().as_mut() {
+            Some(player) => player.clear(),
+            None => Ok(()),
+        }
+    }
+}
@@ -1,12 +1,26 @@
+use tauri::ipc::Channel;
 use tauri::{command, AppHandle, Runtime};
 
 use crate::models::*;
+use crate::queue;
 use crate::NativeTtsExt;
 use crate::Result;
 
+/// Runs `f` on the blocking thread pool. The engines talk to Speech Dispatcher's
+/// socket, spawn espeak-ng and connect to Edge synchronously, which must not stall
+/// the async runtime.
+async fn blocking<R: Runtime, T: Send + 'static>(
+    app: AppHandle<R>,
+    f: impl FnOnce(&AppHandle<R>) -> Result<T> + Send + 'static,
+) -> Result<T> {
+    tauri::async_runtime::spawn_blocking(move || f(&app))
+        .await
+        .map_err(|e| crate::Error::NativeTTSError(e.to_string()))?
+}
+
 #[command]
 pub(crate) async fn init<R: Runtime>(app: AppHandle<R>) -> Result<InitResponse> {
-    app.native_tts().init()
+    blocking(app, |app| app.native_tts().init()).await
 }
 
 #[command]
@@ -14,42 +28,112 @@ pub(crate) async fn speak<R: Runtime>(
     app: AppHandle<R>,
     payload: SpeakArgs,
 ) -> Result<SpeakResponse> {
-    app.native_tts().speak(payload)
+    blocking(app, move |app| app.native_tts().speak(payload)).await
 }
 
 #[command]
 pub(crate) async fn pause<R: Runtime>(app: AppHandle<R>) -> Result<()> {
-    app.native_tts().pause()
+    blocking(app, |app| app.native_tts().pause()).await
 }
 
 #[command]
 pub(crate) async fn resume<R: Runtime>(app: AppHandle<R>) -> Result<()> {
-    app.native_tts().resume()
+    blocking(app, |app| app.native_tts().resume()).await
 }
 
 #[command]
 pub(crate) async fn stop<R: Runtime>(app: AppHandle<R>) -> Result<()> {
-    app.native_tts().stop()
+    blocking(app, |app| {
+        queue::clear(app);
+        app.native_tts().stop()
+    })
+    .await
 }
 
 #[command]
 pub(crate) async fn set_rate<R: Runtime>(app: AppHandle<R>, payload: SetRateArgs) -> Result<()> {
-    app.native_tts().set_rate(payload)
+    blocking(app, move |app| app.native_tts().set_rate(payload)).await
 }
 
 #[command]
 pub(crate) async fn set_pitch<R: Runtime>(app: AppHandle<R>, payload: SetPitchArgs) -> Result<()> {
-    app.native_tts().set_pitch(payload)
+    blocking(app, move |app| app.native_tts().set_pitch(payload)).await
 }
 
 #[command]
 pub(crate) async fn set_voice<R: Runtime>(app: AppHandle<R>, payload: SetVoiceArgs) -> Result<()> {
-    app.native_tts().set_voice(payload)
+    blocking(app, move |app| app.native_tts().set_voice(payload)).await
 }
 
 #[command]
 pub(crate) async fn get_all_voices<R: Runtime>(app: AppHandle<R>) -> Result<GetVoicesResponse> {
-    app.native_tts().get_all_voices()
+    blocking(app, |app| app.native_tts().get_all_voices()).await
+}
+
+#[command]
+pub(crate) async fn get_lexicon<R: Runtime>(
+    app: AppHandle<R>,
+    payload: GetLexiconArgs,
+) -> Result<GetLexiconResponse> {
+    blocking(app, move |app| app.native_tts().get_lexicon(payload)).await
+}
+
+#[command]
+pub(crate) async fn set_lexicon<R: Runtime>(
+    app: AppHandle<R>,
+    payload: SetLexiconArgs,
+) -> Result<()> {
+    blocking(app, move |app| app.native_tts().set_lexicon(payload)).await
+}
+
+#[command]
+pub(crate) async fn enqueue<R: Runtime>(
+    app: AppHandle<R>,
+    payload: EnqueueArgs,
+) -> Result<EnqueueResponse> {
+    blocking(app, move |app| queue::enqueue(app, payload)).await
+}
+
+#[command]
+pub(crate) async fn clear_queue<R: Runtime>(app: AppHandle<R>) -> Result<()> {
+    queue::clear(&app);
+    Ok(())
+}
+
+#[command]
+pub(crate) async fn set_sleep_timer<R: Runtime>(
+    app: AppHandle<R>,
+    payload: SetSleepTimerArgs,
+) -> Result<()> {
+    queue::set_sleep_timer(&app, payload)
+}
+
+#[command]
+pub(crate) async fn get_queue_state<R: Runtime>(
+    app: AppHandle<R>,
+) -> Result<GetQueueStateResponse> {
+    Ok(queue::get_queue_state(&app))
+}
+
+#[command]
+pub(crate) async fn get_resume_position<R: Runtime>(
+    app: AppHandle<R>,
+    payload: GetResumePositionArgs,
+) -> Result<GetResumePositionResponse> {
+    Ok(queue::get_resume_position(&app, payload))
+}
+
+#[command]
+pub(crate) async fn synthesize_to_file<R: Runtime>(
+    app: AppHandle<R>,
+    payload: SynthesizeToFileArgs,
+    on_progress: Channel<SynthesisProgress>,
+) -> Result<SynthesizeToFileResponse> {
+    // Rendering a book takes minutes.
+    blocking(app, move |app| {
+        app.native_tts().synthesize_to_file(payload, on_progress)
+    })
+    .await
 }
 
 #[command]
@@ -57,7 +141,10 @@ pub(crate) async fn set_media_session_active<R: Runtime>(
     app: AppHandle<R>,
     payload: SetMediaSessionActiveRequest,
 ) -> Result<()> {
-    app.native_tts().set_media_session_active(payload)
+    blocking(app, move |app| {
+        app.native_tts().set_media_session_active(payload)
+    })
+    .await
 }
 
 #[command]
@@ -65,7 +152,10 @@ pub(crate) async fn update_media_session_state<R: Runtime>(
     app: AppHandle<R>,
     payload: UpdateMediaSessionStateRequest,
 ) -> Result<()> {
-    app.native_tts().update_media_session_state(payload)
+    blocking(app, move |app| {
+        app.native_tts().update_media_session_state(payload)
+    })
+    .await
 }
 
 #[command]
@@ -73,5 +163,30 @@ pub(crate) async fn update_media_session_metadata<R: Runtime>(
     app: AppHandle<R>,
     payload: UpdateMediaSessionMetadataRequest,
 ) -> Result<()> {
-    app.native_tts().update_media_session_metadata(payload)
+    blocking(app, move |app| {
+        app.native_tts().update_media_session_metadata(payload)
+    })
+    .await
+}
+
+#[cfg(desktop)]
+#[command]
+pub(crate) async fn register_listener<R: Runtime>(
+    app: AppHandle<R>,
+    event: String,
+    handler: Channel<serde_json::Value>,
+) -> Result<()> {
+    app.native_tts().register_listener(event, handler);
+    Ok(())
+}
+
+#[cfg(desktop)]
+#[command]
+pub(crate) async fn remove_listener<R: Runtime>(
+    app: AppHandle<R>,
+    event: String,
+    channel_id: u32,
+) -> Result<()> {
+    app.native_tts().remove_listener(event, channel_id);
+    Ok(())
 }
@@ -1,58 +1,710 @@
 use serde::de::DeserializeOwned;
-use tauri::{plugin::PluginApi, AppHandle, Runtime};
+use std::collections::HashSet;
+use std::ops::Range;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+#[cfg(target_os = "linux")]
+use tauri::Manager;
+use tauri::{ipc::Channel, plugin::PluginApi, AppHandle, Runtime};
+use tauri_plugin_fs::FsExt;
 
+#[cfg(feature = "audio")]
+use crate::audio::{self, Output};
+#[cfg(feature = "edge")]
+use crate::edge::{self, Edge};
+use crate::export::{self, Artwork, Pcm, Tags};
 use crate::models::*;
+#[cfg(target_os = "linux")]
+use crate::mpris;
+use crate::normalize::Normalizer;
+#[cfg(feature = "piper")]
+use crate::piper::{self, Piper};
+use crate::queue::{self, Player};
+use crate::ssip::{self, Address, Event};
+use crate::ssml::Document;
+use crate::Dirs;
+
+const EVENT_NAME: &str = "tts_events";
+
+/// Pause between chapters rendered into one file.
+const CHAPTER_SILENCE: f32 = 1.5;
 
 pub fn init<R: Runtime, C: DeserializeOwned>(
     app: &AppHandle<R>,
     _api: PluginApi<R, C>,
+    dirs: &Dirs,
 ) -> crate::Result<NativeTts<R>> {
-    Ok(NativeTts(app.clone()))
+    let data_dir = &dirs.data_dir;
+    let shared = Arc::new(Shared::default());
+    let _ = shared.on_settled.set({
+        let app = app.clone();
+        // Off the SSIP and audio threads, as the queue speaks the next item.
+        Box::new(move |utterance_id: &str, code: &str| {
+            let (app, utterance_id, code) =
+                (app.clone(), utterance_id.to_string(), code.to_string());
+            std::thread::spawn(move || queue::on_event(&app, &utterance_id, &code));
+        })
+    });
+    #[cfg(feature = "audio")]
+    let output = {
+        let shared = shared.clone();
+        let on_event: audio::EventSink =
+            Arc::new(move |utterance_id, event| shared.send(utterance_id, event));
+        Arc::new(Output::new(on_event))
+    };
+    Ok(NativeTts {
+        app: app.clone(),
+        client: Mutex::new(None),
+        settings: Mutex::new(Settings::default()),
+        shared,
+        #[cfg(feature = "piper")]
+        piper: Piper::new(data_dir.join("voices"), output.clone()),
+        #[cfg(feature = "edge")]
+        edge: Edge::new(dirs.cache_dir.join("edge-tts"), output.clone()),
+        #[cfg(feature = "audio")]
+        output,
+        normalizer: Normalizer::new(data_dir.join("lexicons")),
+        player: Player::new(data_dir.join("tts-positions.json")),
+        engine: Mutex::new(Engine::SpeechDispatcher),
+        #[cfg(target_os = "linux")]
+        mpris: Mutex::new(None),
+        #[cfg(target_os = "linux")]
+        cover_dir: dirs.cache_dir.join("media-session"),
+    })
+}
+
+/// Speech settings, reapplied whenever the connection is re-established.
+#[derive(Default)]
+struct Settings {
+    rate: i32,
+    pitch: i32,
+    /// Output module and voice name.
+    voice: Option<(String, String)>,
+}
+
+/// The engine of the selected voice.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Engine {
+    SpeechDispatcher,
+    #[cfg(feature = "piper")]
+    Piper,
+    #[cfg(feature = "edge")]
+    Edge,
+}
+
+impl Engine {
+    /// Whether speech can be rendered to files. Speech Dispatcher only speaks to the
+    /// sound card.
+    fn renders(self) -> bool {
+        self != Engine::SpeechDispatcher
+    }
+}
+
+/// Called with the utterance id and the code when an utterance ends or fails.
+type SettledSink = Box<dyn Fn(&str, &str) + Send + Sync>;
+
+/// State shared with the SSIP reader and the audio threads.
+#[derive(Default)]
+struct Shared {
+    listeners: Mutex<Vec<(String, Channel<serde_json::Value>)>>,
+    /// Messages that are queued or speaking.
+    in_flight: Mutex<HashSet<u64>>,
+    on_settled: OnceLock<SettledSink>,
+}
+
+impl Shared {
+    fn send(&self, utterance_id: String, event: TTSMessageEvent) {
+        let code = event.code.clone();
+        let payload = TTSEventPayload {
+            utterance_id,
+            event,
+        };
+        if let Ok(payload) = serde_json::to_value(&payload) {
+            self.send_to(EVENT_NAME, payload);
+        }
+        if code == "end" || code == "error" {
+            if let Some(on_settled) = self.on_settled.get() {
+                on_settled(&payload.utterance_id, &code);
+            }
+        }
+    }
+
+    fn send_to(&self, name: &str, payload: serde_json::Value) {
+        for (event, channel) in self.listeners.lock().unwrap().iter() {
+            if event == name {
+                let _ = channel.send(payload.clone());
+            }
+        }
+    }
+
+    fn emit(&self, utterance_id: u64, code: &str, message: Option<String>, mark: Option<String>) {
+        let event = TTSMessageEvent {
+            code: code.to_string(),
+            message,
+            mark,
+        };
+        self.send(utterance_id.to_string(), event);
+    }
+
+    /// Map Speech Dispatcher notifications to the events of the mobile plugins.
+    fn on_event(&self, event: Event) {
+        match event {
+            Event::Begin(id) => self.emit(id, "boundary", Some("start".into()), None),
+            Event::IndexMark(id, mark) => {
+                self.emit(id, "boundary", Some("mark".into()), Some(mark))
+            }
+            Event::End(id) => {
+                self.in_flight.lock().unwrap().remove(&id);
+                self.emit(id, "end", None, None);
+            }
+            // Like `stop` on Android, cancelling ends the utterance silently.
+            Event::Cancel(id) => {
+                self.in_flight.lock().unwrap().remove(&id);
+            }
+            Event::Pause(_) | Event::Resume(_) => {}
+            Event::Closed => {
+                let ids: Vec<u64> = self.in_flight.lock().unwrap().drain().collect();
+                for id in ids {
+                    let message = "Speech Dispatcher connection lost".to_string();
+                    self.emit(id, "error", Some(message), None);
+                }
+            }
+        }
+    }
 }
 
 /// Access to the native-tts APIs.
-pub struct NativeTts<R: Runtime>(AppHandle<R>);
+///
+/// On desktop, speech goes through Speech Dispatcher, which is available on
+/// most Linux distributions, through Piper models installed in
+/// `$APPDATA/voices`, or through the Edge read-aloud service, cached in
+/// `$APPCACHE/edge-tts`, depending on the selected voice and on the `piper` and
+/// `edge` features. On Linux, the media session is an MPRIS player.
+pub struct NativeTts<R: Runtime> {
+    app: AppHandle<R>,
+    /// The connection, and whether it is still open.
+    client: Mutex<Option<(Arc<ssip::Client>, Arc<AtomicBool>)>>,
+    settings: Mutex<Settings>,
+    shared: Arc<Shared>,
+    /// Playback of Piper and Edge speech.
+    #[cfg(feature = "audio")]
+    output: Arc<Output>,
+    #[cfg(feature = "piper")]
+    piper: Piper,
+    #[cfg(feature = "edge")]
+    edge: Edge,
+    normalizer: Normalizer,
+    player: Player,
+    engine: Mutex<Engine>,
+    /// The media session, while the reader has one active.
+    #[cfg(target_os = "linux")]
+    mpris: Mutex<Option<mpris::Mpris>>,
+    /// Where the cover of the media session is kept.
+    #[cfg(target_os = "linux")]
+    cover_dir: PathBuf,
+}
+
+/// SSIP takes rate and pitch from -100 to 100 around the voice's default;
+/// one doubling of the multiplier is 50 steps.
+fn ssip_scale(value: f32) -> i32 {
+    if value <= 0.0 {
+        return -100;
+    }
+    ((value.log2() * 50.0).round() as i32).clamp(-100, 100)
+}
+
+/// A file name without characters that some file systems reject.
+fn file_name(name: &str) -> String {
+    let name: String = name
+        .chars()
+        .map(|c| match c {
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '_',
+            c if c.is_control() => '_',
+            c => c,
+        })
+        .take(120)
+        .collect();
+    let name = name.trim().trim_end_matches('.');
+    if name.is_empty() {
+        "speech".to_string()
+    } else {
+        name.to_string()
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn dbus_error(err: zbus::Error) -> crate::Error {
+    crate::Error::NativeTTSError(format!("Media session: {err}"))
+}
+
+fn connect(shared: &Arc<Shared>, open: &Arc<AtomicBool>) -> crate::Result<ssip::Client> {
+    let address = Address::from_env().ok_or_else(|| {
+        crate::Error::NativeTTSError("No Speech Dispatcher address configured".into())
+    })?;
+    let on_event = {
+        let shared = shared.clone();
+        let open = open.clone();
+        move |event: Event| {
+            if event == Event::Closed {
+                open.store(false, Ordering::SeqCst);
+            }
+            shared.on_event(event)
+        }
+    };
+    match ssip::Client::connect(&address, on_event.clone()) {
+        Err(crate::Error::Io(_)) => {
+            // Like libspeechd, start the per-user server on demand.
+            let spawned = std::process::Command::new("speech-dispatcher")
+                .arg("--spawn")
+                .status()
+                .is_ok_and(|status| status.success());
+            if !spawned {
+                return Err(crate::Error::NativeTTSError(
+                    "Speech Dispatcher is not running".into(),
+                ));
+            }
+            ssip::Client::connect(&address, on_event)
+        }
+        result => result,
+    }
+}
 
 impl<R: Runtime> NativeTts<R> {
+    fn client(&self) -> crate::Result<Arc<ssip::Client>> {
+        let mut slot = self.client.lock().unwrap();
+        if let Some((client, open)) = slot.as_ref() {
+            if open.load(Ordering::SeqCst) {
+                return Ok(client.clone());
+            }
+        }
+        *slot = None;
+        let open = Arc::new(AtomicBool::new(true));
+        let client = Arc::new(connect(&self.shared, &open)?);
+        let settings = self.settings.lock().unwrap();
+        client.set_rate(settings.rate)?;
+        client.set_pitch(settings.pitch)?;
+        if let Some((module, voice)) = &settings.voice {
+            client.set_output_module(module)?;
+            client.set_voice(voice)?;
+        }
+        *slot = Some((client.clone(), open));
+        Ok(client)
+    }
+
+    pub fn register_listener(&self, event: String, handler: Channel<serde_json::Value>) {
+        self.shared.listeners.lock().unwrap().push((event, handler));
+    }
+
+    pub fn remove_listener(&self, event: String, channel_id: u32) {
+        self.shared
+            .listeners
+            .lock()
+            .unwrap()
+            .retain(|(name, channel)| !(*name == event && channel.id() == channel_id));
+    }
+
+    pub(crate) fn player(&self) -> &Player {
+        &self.player
+    }
+
+    pub(crate) fn send_queue_event(&self, payload: serde_json::Value) {
+        self.shared.send_to(queue::EVENT_NAME, payload);
+    }
+
+    fn engine(&self) -> Engine {
+        *self.engine.lock().unwrap()
+    }
+
+    /// The player of the selected engine's speech, unless Speech Dispatcher plays it.
+    #[cfg(feature = "audio")]
+    fn output(&self) -> Option<&Output> {
+        (self.engine() != Engine::SpeechDispatcher).then_some(&*self.output)
+    }
+
+    /// Select the engine of a new voice, silencing the previous one.
+    fn switch_to(&self, engine: Engine) -> crate::Result<()> {
+        #[cfg(feature = "audio")]
+        if self.engine() != engine {
+            if let Some(output) = self.output() {
+                output.stop()?;
+            }
+        }
+        *self.engine.lock().unwrap() = engine;
+        Ok(())
+    }
+
     pub fn init(&self) -> crate::Result<InitResponse> {
-        Err(crate::Error::UnsupportedPlatformError)
+        // Edge voices need nothing installed.
+        Ok(InitResponse { success: true })
     }
-    pub fn speak(&self, _args: SpeakArgs) -> crate::Result<SpeakResponse> {
-        Err(crate::Error::UnsupportedPlatformError)
+    pub fn speak(&self, args: SpeakArgs) -> crate::Result<SpeakResponse> {
+        let mut document = if args.ssml {
+            Document::parse(&args.text)?
+        } else {
+            Document::with_marks(&args.text, &args.marks)
+        };
+        self.normalizer
+            .document(&mut document, args.lang.as_deref());
+        if args.preload {
+            // Only Edge speech takes long enough to start to be worth fetching early.
+            #[cfg(feature = "edge")]
+            if self.engine() == Engine::Edge {
+                self.edge.prefetch(&document)?;
+            }
+            return Ok(SpeakResponse {
+                utterance_id: String::new(),
+            });
+        }
+        match self.engine() {
+            #[cfg(feature = "piper")]
+            Engine::Piper => {
+                return Ok(SpeakResponse {
+                    utterance_id: self.piper.speak(document)?,
+                })
+            }
+            #[cfg(feature = "edge")]
+            Engine::Edge => {
+                return Ok(SpeakResponse {
+                    utterance_id: self.edge.speak(document)?,
+                })
+            }
+            Engine::SpeechDispatcher => {}
+        }
+        let client = self.client()?;
+        client.set_ssml_mode(args.ssml)?;
+        let id = if args.ssml {
+            client.speak(&document.to_ssml())?
+        } else {
+            client.speak(&document.plain_text().0)?
+        };
+        self.shared.in_flight.lock().unwrap().insert(id);
+        Ok(SpeakResponse {
+            utterance_id: id.to_string(),
+        })
     }
     pub fn pause(&self) -> crate::Result<()> {
-        Err(crate::Error::UnsupportedPlatformError)
+        #[cfg(feature = "audio")]
+        if let Some(output) = self.output() {
+            output.pause();
+            return Ok(());
+        }
+        self.client()?.pause()
     }
     pub fn resume(&self) -> crate::Result<()> {
-        Err(crate::Error::UnsupportedPlatformError)
+        #[cfg(feature = "audio")]
+        if let Some(output) = self.output() {
+            output.resume();
+            return Ok(());
+        }
+        self.client()?.resume()
     }
     pub fn stop(&self) -> crate::Result<()> {
-        Err(crate::Error::UnsupportedPlatformError)
+        #[cfg(feature = "audio")]
+        if let Some(output) = self.output() {
+            return output.stop();
+        }
+        self.client()?.cancel()
     }
-    pub fn set_rate(&self, _args: SetRateArgs) -> crate::Result<()> {
-        Err(crate::Error::UnsupportedPlatformError)
+    pub fn set_rate(&self, args: SetRateArgs) -> crate::Result<()> {
+        #[cfg(feature = "piper")]
+        self.piper.set_rate(args.rate);
+        #[cfg(feature = "edge")]
+        self.edge.set_rate(args.rate);
+        let rate = ssip_scale(args.rate);
+        self.settings.lock().unwrap().rate = rate;
+        if self.engine() != Engine::SpeechDispatcher {
+            return Ok(());
+        }
+        self.client()?.set_rate(rate)
     }
-    pub fn set_pitch(&self, _args: SetPitchArgs) -> crate::Result<()> {
-        Err(crate::Error::UnsupportedPlatformError)
+    pub fn set_pitch(&self, args: SetPitchArgs) -> crate::Result<()> {
+        // Piper voices have no pitch control.
+        #[cfg(feature = "edge")]
+        self.edge.set_pitch(args.pitch);
+        let pitch = ssip_scale(args.pitch);
+        self.settings.lock().unwrap().pitch = pitch;
+        if self.engine() != Engine::SpeechDispatcher {
+            return Ok(());
+        }
+        self.client()?.set_pitch(pitch)
     }
-    pub fn set_voice(&self, _args: SetVoiceArgs) -> crate::Result<()> {
-        Err(crate::Error::UnsupportedPlatformError)
+    pub fn set_voice(&self, args: SetVoiceArgs) -> crate::Result<()> {
+        #[cfg(feature = "piper")]
+        if let Some(key) = args.voice.strip_prefix(piper::VOICE_PREFIX) {
+            self.piper.set_voice(key)?;
+            return self.switch_to(Engine::Piper);
+        }
+        #[cfg(feature = "edge")]
+        if let Some(name) = args.voice.strip_prefix(edge::VOICE_PREFIX) {
+            self.edge.set_voice(name)?;
+            return self.switch_to(Engine::Edge);
+        }
+        let (module, voice) = args.voice.split_once(':').ok_or_else(|| {
+            crate::Error::NativeTTSError(format!("Invalid voice id: {}", args.voice))
+        })?;
+        let client = self.client()?;
+        client.set_output_module(module)?;
+        client.set_voice(voice)?;
+        self.settings.lock().unwrap().voice = Some((module.to_string(), voice.to_string()));
+        self.switch_to(Engine::SpeechDispatcher)
+    }
+    fn speech_dispatcher_voices(&self) -> crate::Result<Vec<TTSVoice>> {
+        let client = self.client()?;
+        let current = client.output_module()?;
+        let mut voices = Vec::new();
+        for module in client.list_output_modules()? {
+            if client.set_output_module(&module).is_err() {
+                continue;
+            }
+            for voice in client.list_voices().unwrap_or_default() {
+                voices.push(TTSVoice {
+                    id: format!("{module}:{}", voice.name),
+                    name: voice.name,
+                    lang: voice.language,
+                    disabled: false,
+                });
+            }
+        }
+        client.set_output_module(&current)?;
+        if let Some((_, voice)) = &self.settings.lock().unwrap().voice {
+            client.set_voice(voice)?;
+        }
+        Ok(voices)
     }
     pub fn get_all_voices(&self) -> crate::Result<GetVoicesResponse> {
-        Err(crate::Error::UnsupportedPlatformError)
+        let mut voices = Vec::new();
+        #[cfg(feature = "piper")]
+        voices.extend(self.piper.voices());
+        // Without Speech Dispatcher, the other engines still speak.
+        if let Ok(more) = self.speech_dispatcher_voices() {
+            voices.extend(more);
+        }
+        #[cfg(feature = "edge")]
+        voices.extend(Edge::voices());
+        Ok(GetVoicesResponse { voices })
+    }
+    pub fn get_lexicon(&self, args: GetLexiconArgs) -> crate::Result<GetLexiconResponse> {
+        Ok(GetLexiconResponse {
+            pls: self.normalizer.lexicons().read(args.lang.as_deref())?,
+        })
+    }
+    pub fn set_lexicon(&self, args: SetLexiconArgs) -> crate::Result<()> {
+        self.normalizer
+            .lexicons()
+            .write(args.lang.as_deref(), &args.pls)
+    }
+    /// Synthesize a document into memory with the selected voice, reporting the
+    /// fraction done.
+    #[cfg_attr(not(any(feature = "piper", feature = "edge")), allow(unused_variables))]
+    fn render(&self, document: &Document, on_progress: impl FnMut(f32)) -> crate::Result<Pcm> {
+        match self.engine() {
+            #[cfg(feature = "piper")]
+            Engine::Piper => self.piper.render(document, on_progress),
+            #[cfg(feature = "edge")]
+            Engine::Edge => self.edge.render(document, on_progress),
+            Engine::SpeechDispatcher => Err(crate::Error::NativeTTSError(
+                "Speech Dispatcher voices cannot be rendered to files".into(),
+            )),
+        }
+    }
+    /// Render chapters through the selected Piper or Edge voice. Speech
+    /// Dispatcher only speaks to the sound card, so its voices cannot be
+    /// rendered.
+    pub fn synthesize_to_file(
+        &self,
+        args: SynthesizeToFileArgs,
+        on_progress: Channel<SynthesisProgress>,
+    ) -> crate::Result<SynthesizeToFileResponse> {
+        if !self.engine().renders() {
+            return Err(crate::Error::NativeTTSError(
+                "Only Piper and Edge voices can be rendered to files".into(),
+            ));
+        }
+        let dir = PathBuf::from(&args.dir);
+        // Only folders the webview may write to.
+        let allowed = self.app.try_fs_scope();
+        if !allowed.is_some_and(|scope| scope.is_allowed(&dir)) {
+            return Err(crate::Error::NativeTTSError(
+                "Permission denied: Path not in filesystem scope".into(),
+            ));
+        }
+        std::fs::create_dir_all(&dir)?;
+        let metadata = args.metadata.unwrap_or(UpdateMediaSessionMetadataRequest {
+            title: None,
+            artist: None,
+            album: None,
+            artwork: None,
+        });
+        let artwork = metadata.artwork.as_deref().and_then(Artwork::load);
+        let base = args
+            .file_name
+            .as_deref()
+            .or(metadata.title.as_deref())
+            .unwrap_or("speech")
+            .to_string();
+
+        let count = args.chapters.len();
+        let groups: Vec<Range<usize>> = if args.split_chapters {
+            (0..count).map(|i| i..i + 1).collect()
+        } else {
+            std::iter::once(0..count).collect()
+        };
+        // Progress is weighted by the length of the chapters.
+        let total = args
+            .chapters
+            .iter()
+            .map(|chapter| chapter.text.len())
+            .sum::<usize>()
+            .max(1) as f32;
+        let mut done = 0;
+        let mut files = Vec::new();
+        for (track, group) in groups.iter().enumerate() {
+            let mut pcm = Pcm::default();
+            for i in group.clone() {
+                let chapter = &args.chapters[i];
+                let mut document = if chapter.ssml {
+                    Document::parse(&chapter.text)?
+                } else {
+                    Document::with_marks(&chapter.text, &chapter.marks)
+                };
+                self.normalizer
+                    .document(&mut document, args.lang.as_deref());
+                let start = done as f32 / total;
+                let weight = chapter.text.len() as f32 / total;
+                let rendered = self.render(&document, |fraction| {
+                    let _ = on_progress.send(SynthesisProgress {
+                        chapter: i,
+                        chapters: count,
+                        progress: start + weight * fraction,
+                        file: None,
+                    });
+                })?;
+                if !pcm.samples.is_empty() {
+                    pcm.append_silence(CHAPTER_SILENCE);
+                }
+                pcm.append(&rendered.samples, rendered.sample_rate);
+                done += chapter.text.len();
+            }
+
+            let (name, tags) = if args.split_chapters {
+                let title = args.chapters[group.start].title.clone();
+                let name = format!("{:02} {}", track + 1, title.as_deref().unwrap_or(&base));
+                let tags = Tags {
+                    title: title.or_else(|| metadata.title.clone()),
+                    artist: metadata.artist.clone(),
+                    album: metadata.album.clone().or_else(|| metadata.title.clone()),
+                    track: Some((track as u32 + 1, groups.len() as u32)),
+                    artwork: artwork.clone(),
+                };
+                (name, tags)
+            } else {
+                let tags = Tags {
+                    title: metadata.title.clone(),
+                    artist: metadata.artist.clone(),
+                    album: metadata.album.clone(),
+                    track: None,
+                    artwork: artwork.clone(),
+                };
+                (base.clone(), tags)
+            };
+            let path = dir.join(format!("{}.{}", file_name(&name), args.format.extension()));
+            export::write(args.format, &path, &pcm, &tags)?;
+            let path = path.to_string_lossy().into_owned();
+            let _ = on_progress.send(SynthesisProgress {
+                chapter: group.end.saturating_sub(1),
+                chapters: count,
+                progress: done as f32 / total,
+                file: Some(path.clone()),
+            });
+            files.push(path);
+        }
+        Ok(SynthesizeToFileResponse { files })
+    }
+    #[cfg(target_os = "linux")]
+    pub fn set_media_session_active(
+        &self,
+        payload: SetMediaSessionActiveRequest,
+    ) -> crate::Result<()> {
+        let mut slot = self.mpris.lock().unwrap();
+        if !payload.active {
+            *slot = None;
+            return Ok(());
+        }
+        if slot.is_none() {
+            let shared = self.shared.clone();
+            let on_action: mpris::ActionSink =
+                Arc::new(move |event| shared.send_to(event, serde_json::json!({})));
+            let session = mpris::Mpris::start(&self.app.package_info().name, on_action)
+                .map_err(dbus_error)?;
+            *slot = Some(session);
+        }
+        Ok(())
+    }
+    #[cfg(target_os = "linux")]
+    pub fn update_media_session_state(
+        &self,
+        payload: UpdateMediaSessionStateRequest,
+    ) -> crate::Result<()> {
+        match self.mpris.lock().unwrap().as_ref() {
+            Some(session) => session
+                .set_playback(payload.playing, payload.position, payload.duration)
+                .map_err(dbus_error),
+            None => Ok(()),
+        }
+    }
+    #[cfg(target_os = "linux")]
+    pub fn update_media_session_metadata(
+        &self,
+        payload: UpdateMediaSessionMetadataRequest,
+    ) -> crate::Result<()> {
+        let art_url = match payload.artwork.as_deref() {
+            Some(artwork) if artwork.starts_with("data:") => self.save_cover(artwork),
+            artwork => artwork.map(str::to_string),
+        };
+        let metadata = mpris::Metadata {
+            title: payload.title,
+            artist: payload.artist,
+            album: payload.album,
+            art_url,
+        };
+        match self.mpris.lock().unwrap().as_ref() {
+            Some(session) => session.set_metadata(metadata).map_err(dbus_error),
+            None => Ok(()),
+        }
+    }
+    /// Write a data URL cover to the cache, replacing the previous one, and
+    /// return its `file://` URI.
+    #[cfg(target_os = "linux")]
+    fn save_cover(&self, data_url: &str) -> Option<String> {
+        use std::hash::{Hash, Hasher};
+
+        let artwork = Artwork::load(data_url)?;
+        let dir = &self.cover_dir;
+        let _ = std::fs::remove_dir_all(dir);
+        std::fs::create_dir_all(dir).ok()?;
+        // A new name for each cover, as shells cache artwork by URI.
+        let mut hasher = std::collections::hash_map::DefaultHasher::new();
+        artwork.data.hash(&mut hasher);
+        let path = dir.join(format!("{:016x}.{}", hasher.finish(), artwork.extension()));
+        std::fs::write(&path, &artwork.data).ok()?;
+        Some(format!("file://{}", path.display()))
     }
+    #[cfg(not(target_os = "linux"))]
     pub fn set_media_session_active(
         &self,
         _payload: SetMediaSessionActiveRequest,
     ) -> crate::Result<()> {
         Err(crate::Error::UnsupportedPlatformError)
     }
+    #[cfg(not(target_os = "linux"))]
     pub fn update_media_session_state(
         &self,
         _payload: UpdateMediaSessionStateRequest,
     ) -> crate::Result<()> {
         Err(crate::Error::UnsupportedPlatformError)
     }
+    #[cfg(not(target_os = "linux"))]
     pub fn update_media_session_metadata(
         &self,
         _payload: UpdateMediaSessionMetadataRequest,
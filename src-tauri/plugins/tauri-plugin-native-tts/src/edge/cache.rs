@@ -0,0 +1,180 @@
+//! Synthesized speech on disk, so pages read again play without the
+//! network. Each entry is an MP3 file and the JSON of its word boundaries,
+//! named after a hash of what was synthesized. The least recently used
+//! entries go once the cache outgrows its limit.
+
+use sha2::{Digest, Sha256};
+use std::fs::{self, File};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use super::protocol::{Request, WordBoundary};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Entry {
+    pub audio: Vec<u8>,
+    pub boundaries: Vec<WordBoundary>,
+}
+
+pub struct Cache {
+    dir: PathBuf,
+    /// Bytes kept on disk.
+    limit: u64,
+}
+
+impl Cache {
+    pub fn new(dir: PathBuf, limit: u64) -> Self {
+        Self { dir, limit }
+    }
+
+    pub fn key(request: &Request) -> String {
+        let mut hasher = Sha256::new();
+        for part in [
+            request.voice.as_str(),
+            request.lang.as_str(),
+            &request.rate.to_string(),
+            &request.pitch.to_string(),
+            request.text.as_str(),
+        ] {
+            hasher.update(part.as_bytes());
+            hasher.update([0]);
+        }
+        hasher
+            .finalize()
+            .iter()
+            .map(|b| format!("{b:02x}"))
+            .collect()
+    }
+
+    fn paths(&self, key: &str) -> (PathBuf, PathBuf) {
+        (
+            self.dir.join(format!("{key}.mp3")),
+            self.dir.join(format!("{key}.json")),
+        )
+    }
+
+    pub fn get(&self, key: &str) -> Option<Entry> {
+        let (audio_path, boundaries_path) = self.paths(key);
+        let audio = fs::read(&audio_path).ok()?;
+        let boundaries = serde_json::from_slice(&fs::read(boundaries_path).ok()?).ok()?;
+        // The modification time orders entries by last use.
+        if let Ok(file) = File::options().append(true).open(&audio_path) {
+            let _ = file.set_modified(SystemTime::now());
+        }
+        Some(Entry { audio, boundaries })
+    }
+
+    pub fn put(&self, key: &str, entry: &Entry) -> io::Result<()> {
+        fs::create_dir_all(&self.dir)?;
+        let (audio_path, boundaries_path) = self.paths(key);
+        // The audio goes last: an entry without it is never read.
+        write(&boundaries_path, &serde_json::to_vec(&entry.boundaries)?)?;
+        write(&audio_path, &entry.audio)?;
+        self.evict()
+    }
+
+    fn evict(&self) -> io::Result<()> {
+        let mut entries = Vec::new();
+        let mut total = 0;
+        for file in fs::read_dir(&self.dir)? {
+            let path = file?.path();
+            let Ok(metadata) = path.metadata() else {
+                continue;
+            };
+            total += metadata.len();
+            if path.extension().is_some_and(|ext| ext == "mp3") {
+                let used = metadata.modified().unwrap_or(UNIX_EPOCH);
+                entries.push((used, path));
+            }
+        }
+        entries.sort();
+        for (_, audio_path) in entries {
+            if total <= self.limit {
+                break;
+            }
+            let boundaries_path = audio_path.with_extension("json");
+            for path in [audio_path, boundaries_path] {
+                if let Ok(metadata) = path.metadata() {
+                    if fs::remove_file(&path).is_ok() {
+                        total = total.saturating_sub(metadata.len());
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Write through a temporary file, so readers never see half an entry.
+fn write(path: &Path, data: &[u8]) -> io::Result<()> {
+    let tmp = path.with_extension("tmp");
+    fs::write(&tmp, data)?;
+    fs::rename(tmp, path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn request(text: &str) -> Request {
+        Request {
+            text: text.into(),
+            voice: "en-US-AriaNeural".into(),
+            lang: "en-US".into(),
+            rate: 1.0,
+            pitch: 1.0,
+        }
+    }
+
+    fn entry(size: usize) -> Entry {
+        Entry {
+            audio: vec![0xff; size],
+            boundaries: vec![WordBoundary {
+                offset: 0,
+                duration: 10,
+                text: "Hi".into(),
+            }],
+        }
+    }
+
+    #[test]
+    fn keys() {
+        let key = Cache::key(&request("Hi"));
+        assert_eq!(key.len(), 64);
+        assert_eq!(key, Cache::key(&request("Hi")));
+        assert_ne!(key, Cache::key(&request("Hi.")));
+        let faster = Request {
+            rate: 1.5,
+            ..request("Hi")
+        };
+        assert_ne!(key, Cache::key(&faster));
+    }
+
+    #[test]
+    fn evicts_least_recently_used() {
+        let dir = std::env::temp_dir().join(format!("edge-tts-cache-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let cache = Cache::new(dir.clone(), 2500);
+
+        cache.put("a", &entry(1000)).unwrap();
+        cache.put("b", &entry(1000)).unwrap();
+        assert_eq!(cache.get("a"), Some(entry(1000)));
+        // Make "b" the older entry, whatever the resolution of the clock.
+        let old = SystemTime::now() - Duration::from_secs(60);
+        File::options()
+            .append(true)
+            .open(dir.join("b.mp3"))
+            .unwrap()
+            .set_modified(old)
+            .unwrap();
+
+        cache.put("c", &entry(1000)).unwrap();
+        assert!(cache.get("b").is_none());
+        assert!(cache.get("a").is_some());
+        assert!(cache.get("c").is_some());
+        assert!(cache.get("missing").is_none());
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,558 @@
+//! Online neural voices from the read-aloud service of Microsoft Edge.
+//! Speech is cached on disk and fetched a few sentences ahead of playback,
+//! which follows the word boundaries the service reports.
+
+mod cache;
+mod protocol;
+mod voices;
+
+use rodio::buffer::SamplesBuffer;
+use rodio::{Decoder, Sink, Source};
+use std::collections::HashSet;
+use std::io::Cursor;
+use std::net::TcpStream;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::{self, Sender};
+use std::sync::{Arc, Condvar, Mutex};
+use std::time::Duration;
+use tungstenite::client::IntoClientRequest;
+use tungstenite::http::HeaderValue;
+use tungstenite::Message;
+
+use crate::audio::{error, event, Output};
+use crate::export::Pcm;
+use crate::models::{TTSMessageEvent, TTSVoice};
+use crate::ssml::{Document, Item, Span};
+use cache::{Cache, Entry};
+use protocol::Request;
+
+/// Prefix of the ids of Edge voices in `get_all_voices`.
+pub const VOICE_PREFIX: &str = "edgetts_";
+
+const HOST: &str = "speech.platform.bing.com";
+/// Spans fetched ahead of the one playing.
+const PREFETCH: usize = 3;
+const CACHE_LIMIT: u64 = 256 * 1024 * 1024;
+const TIMEOUT: Duration = Duration::from_secs(30);
+/// Of the service's output format.
+const SAMPLE_RATE: u32 = 24_000;
+
+fn failed(err: tungstenite::Error) -> crate::Error {
+    error("Edge TTS failed", err)
+}
+
+/// Synthesize over a new connection, as the service closes idle ones.
+fn download(request: &Request) -> crate::Result<Entry> {
+    let connection_id = protocol::random_id();
+    let mut handshake = protocol::url(&connection_id)
+        .into_client_request()
+        .map_err(failed)?;
+    for (name, value) in protocol::headers() {
+        let value = HeaderValue::from_str(&value).map_err(|e| error("Invalid header", e))?;
+        handshake.headers_mut().insert(name, value);
+    }
+    let stream = TcpStream::connect((HOST, 443))?;
+    stream.set_read_timeout(Some(TIMEOUT))?;
+    stream.set_write_timeout(Some(TIMEOUT))?;
+    let (mut socket, _) = tungstenite::client_tls(handshake, stream)
+        .map_err(|e| error("Failed to connect to Edge TTS", e))?;
+    socket
+        .send(Message::text(protocol::config_message()))
+        .map_err(failed)?;
+    socket
+        .send(Message::text(protocol::ssml_message(
+            &connection_id,
+            request,
+        )))
+        .map_err(failed)?;
+
+    let mut entry = Entry {
+        audio: Vec::new(),
+        boundaries: Vec::new(),
+    };
+    loop {
+        match socket.read().map_err(failed)? {
+            Message::Text(text) => {
+                let (headers, body) = protocol::parse_text(text.as_str());
+                match headers.get("Path").copied() {
+                    Some("audio.metadata") => {
+                        entry.boundaries.extend(protocol::word_boundaries(body))
+                    }
+                    Some("turn.end") => break,
+                    _ => {}
+                }
+            }
+            Message::Binary(data) => {
+                if let Some((headers, audio)) = protocol::parse_binary(&data) {
+                    if headers.get("Path").copied() == Some("audio") {
+                        entry.audio.extend_from_slice(audio);
+                    }
+                }
+            }
+            Message::Close(_) => {
+                return Err(crate::Error::NativeTTSError(
+                    "Edge TTS closed the connection".into(),
+                ))
+            }
+            _ => {}
+        }
+    }
+    let _ = socket.close(None);
+    if entry.audio.is_empty() {
+        return Err(crate::Error::NativeTTSError(
+            "Edge TTS returned no audio".into(),
+        ));
+    }
+    Ok(entry)
+}
+
+/// Mono samples and their rate.
+fn decode(audio: Vec<u8>) -> crate::Result<(Vec<f32>, u32)> {
+    let decoder =
+        Decoder::new(Cursor::new(audio)).map_err(|e| error("Failed to decode speech", e))?;
+    let rate = decoder.sample_rate();
+    let channels = decoder.channels().max(1) as usize;
+    let samples: Vec<f32> = decoder.convert_samples().collect();
+    if channels == 1 {
+        return Ok((samples, rate));
+    }
+    let mono = samples
+        .chunks(channels)
+        .map(|frame| frame.iter().sum::<f32>() / frame.len() as f32)
+        .collect();
+    Ok((mono, rate))
+}
+
+/// Fetches through the cache, downloading each request once even when
+/// playback and prefetching ask for it together.
+struct Fetcher {
+    cache: Cache,
+    pending: Mutex<HashSet<String>>,
+    done: Condvar,
+}
+
+impl Fetcher {
+    fn fetch(&self, request: &Request) -> crate::Result<Entry> {
+        let key = Cache::key(request);
+        {
+            let mut pending = self.pending.lock().unwrap();
+            while pending.contains(&key) {
+                pending = self.done.wait(pending).unwrap();
+            }
+            if let Some(entry) = self.cache.get(&key) {
+                return Ok(entry);
+            }
+            pending.insert(key.clone());
+        }
+        let result = download(request);
+        if let Ok(entry) = &result {
+            let _ = self.cache.put(&key, entry);
+        }
+        self.pending.lock().unwrap().remove(&key);
+        self.done.notify_all();
+        result
+    }
+}
+
+/// The locale of a voice in the table.
+fn locale(voice: &str) -> Option<&'static str> {
+    voices::VOICES
+        .iter()
+        .find(|(_, names)| names.contains(&voice))
+        .map(|(locale, _)| *locale)
+}
+
+fn primary(tag: &str) -> String {
+    tag.split(['-', '_'])
+        .next()
+        .unwrap_or_default()
+        .to_lowercase()
+}
+
+/// The voice and locale for text in `lang`: the current voice if it speaks
+/// the language, else the first voice of the closest locale.
+fn voice_for(lang: Option<&str>, voice: &str) -> (String, String) {
+    let current = locale(voice).unwrap_or_default();
+    let Some(lang) = lang.filter(|lang| primary(lang) != primary(current)) else {
+        return (voice.to_string(), current.to_string());
+    };
+    voices::VOICES
+        .iter()
+        .find(|(locale, _)| locale.eq_ignore_ascii_case(lang))
+        .or_else(|| {
+            voices::VOICES
+                .iter()
+                .find(|(locale, _)| primary(locale) == primary(lang))
+        })
+        .map_or_else(
+            || (voice.to_string(), current.to_string()),
+            |(locale, names)| (names[0].to_string(), locale.to_string()),
+        )
+}
+
+/// What is spoken in a document: the voice, rate and pitch of each span.
+#[derive(Clone)]
+struct Prosody {
+    voice: String,
+    rate: f32,
+    pitch: f32,
+}
+
+impl Prosody {
+    fn request(&self, span: &Span) -> Request {
+        let (voice, lang) = voice_for(span.lang.as_deref(), &self.voice);
+        Request {
+            text: span.text.clone(),
+            voice,
+            lang,
+            rate: self.rate * span.rate,
+            pitch: self.pitch * span.pitch,
+        }
+    }
+
+    fn requests(&self, document: &Document) -> Vec<Request> {
+        document
+            .items
+            .iter()
+            .filter_map(|item| match item {
+                Item::Text(span) => Some(self.request(span)),
+                _ => None,
+            })
+            .collect()
+    }
+}
+
+struct Job {
+    utterance_id: String,
+    items: Vec<Item>,
+    prosody: Prosody,
+    generation: u64,
+}
+
+/// Fetches and queues jobs in order on a worker thread.
+struct Speaker {
+    fetcher: Arc<Fetcher>,
+    prefetch: Sender<Request>,
+    output: Arc<Output>,
+}
+
+impl Speaker {
+    fn queue(&self, job: &Job, f: impl FnOnce(&Sink)) -> bool {
+        self.output.queue(job.generation, f)
+    }
+
+    fn at_playback(&self, sink: &Sink, utterance_id: &str, event: TTSMessageEvent) {
+        self.output.at_playback(sink, utterance_id, event)
+    }
+
+    /// Speak a span starting at UTF-16 `offset` of the document's text.
+    fn speak_span(
+        &self,
+        job: &Job,
+        span: &Span,
+        request: &Request,
+        offset: usize,
+    ) -> crate::Result<bool> {
+        let entry = self.fetcher.fetch(request)?;
+        if self.output.cancelled(job.generation) {
+            return Ok(false);
+        }
+        let (audio, rate) = decode(entry.audio)?;
+
+        let utf16 = |byte: usize| offset + span.text[..byte].encode_utf16().count();
+        let pos = |start: usize, end: usize| format!("pos:{}-{}", utf16(start), utf16(end));
+        // Words, located in the text in order, and where they start playing.
+        let mut words = Vec::new();
+        let mut cursor = 0;
+        for boundary in &entry.boundaries {
+            let Some(start) = span.text[cursor..].find(boundary.text.as_str()) else {
+                continue;
+            };
+            let start = cursor + start;
+            cursor = start + boundary.text.len();
+            let sample = (boundary.offset as u128 * rate as u128 / 10_000_000) as usize;
+            words.push((pos(start, cursor), sample.min(audio.len())));
+        }
+
+        Ok(self.queue(job, |sink| {
+            let whole = pos(0, span.text.len());
+            self.at_playback(
+                sink,
+                &job.utterance_id,
+                event("boundary", Some("sentence"), Some(whole)),
+            );
+            let first = words.first().map_or(audio.len(), |(_, sample)| *sample);
+            sink.append(SamplesBuffer::new(1, rate, audio[..first].to_vec()));
+            for (i, (pos, start)) in words.iter().enumerate() {
+                let end = words.get(i + 1).map_or(audio.len(), |(_, sample)| *sample);
+                self.at_playback(
+                    sink,
+                    &job.utterance_id,
+                    event("boundary", Some("range"), Some(pos.clone())),
+                );
+                let chunk = audio[*start..end.max(*start)].to_vec();
+                sink.append(SamplesBuffer::new(1, rate, chunk));
+            }
+        }))
+    }
+
+    fn run(&self, job: Job) -> crate::Result<()> {
+        let start = event("boundary", Some("start"), None);
+        if !self.queue(&job, |sink| {
+            self.at_playback(sink, &job.utterance_id, start)
+        }) {
+            return Ok(());
+        }
+        let requests = job.prosody.requests(&Document {
+            items: job.items.clone(),
+        });
+        let mut offset = 0;
+        let mut span_index = 0;
+        for item in &job.items {
+            let queued = match item {
+                Item::Text(span) => {
+                    for request in requests.iter().skip(span_index + 1).take(PREFETCH) {
+                        let _ = self.prefetch.send(request.clone());
+                    }
+                    let queued = self.speak_span(&job, span, &requests[span_index], offset)?;
+                    offset += span.text.encode_utf16().count();
+                    span_index += 1;
+                    queued
+                }
+                Item::Mark(name) => self.queue(&job, |sink| {
+                    let mark = event("boundary", Some("mark"), Some(name.clone()));
+                    self.at_playback(sink, &job.utterance_id, mark)
+                }),
+                Item::Break(millis) => self.queue(&job, |sink| Output::silence(sink, *millis)),
+            };
+            if !queued {
+                return Ok(());
+            }
+        }
+        let end = event("end", None, None);
+        self.queue(&job, |sink| self.at_playback(sink, &job.utterance_id, end));
+        Ok(())
+    }
+}
+
+/// The voices of the Edge read-aloud service.
+pub struct Edge {
+    prosody: Mutex<Prosody>,
+    fetcher: Arc<Fetcher>,
+    output: Arc<Output>,
+    next_id: AtomicU64,
+    jobs: Mutex<Option<Sender<Job>>>,
+    prefetch: Mutex<Option<Sender<Request>>>,
+}
+
+impl Edge {
+    pub fn new(cache_dir: PathBuf, output: Arc<Output>) -> Self {
+        Self {
+            prosody: Mutex::new(Prosody {
+                voice: String::new(),
+                rate: 1.0,
+                pitch: 1.0,
+            }),
+            fetcher: Arc::new(Fetcher {
+                cache: Cache::new(cache_dir, CACHE_LIMIT),
+                pending: Mutex::new(HashSet::new()),
+                done: Condvar::new(),
+            }),
+            output,
+            next_id: AtomicU64::new(0),
+            jobs: Mutex::new(None),
+            prefetch: Mutex::new(None),
+        }
+    }
+
+    pub fn voices() -> Vec<TTSVoice> {
+        voices::VOICES
+            .iter()
+            .flat_map(|(locale, names)| {
+                names.iter().map(|name| TTSVoice {
+                    id: format!("{VOICE_PREFIX}{name}"),
+                    name: name.to_string(),
+                    lang: locale.to_string(),
+                    disabled: false,
+                })
+            })
+            .collect()
+    }
+
+    pub fn set_voice(&self, name: &str) -> crate::Result<()> {
+        if locale(name).is_none() {
+            return Err(crate::Error::NativeTTSError(format!(
+                "Unknown Edge voice {name}"
+            )));
+        }
+        self.prosody.lock().unwrap().voice = name.to_string();
+        Ok(())
+    }
+
+    pub fn set_rate(&self, rate: f32) {
+        self.prosody.lock().unwrap().rate = rate;
+    }
+
+    pub fn set_pitch(&self, pitch: f32) {
+        self.prosody.lock().unwrap().pitch = pitch;
+    }
+
+    fn prosody(&self) -> crate::Result<Prosody> {
+        let prosody = self.prosody.lock().unwrap().clone();
+        if prosody.voice.is_empty() {
+            return Err(crate::Error::NativeTTSError(
+                "No Edge voice selected".into(),
+            ));
+        }
+        Ok(prosody)
+    }
+
+    fn prefetcher(&self) -> crate::Result<Sender<Request>> {
+        let mut prefetch = self.prefetch.lock().unwrap();
+        if let Some(sender) = prefetch.as_ref() {
+            return Ok(sender.clone());
+        }
+        let (sender, receiver) = mpsc::channel::<Request>();
+        let fetcher = self.fetcher.clone();
+        std::thread::Builder::new()
+            .name("edge-tts-prefetch".into())
+            .spawn(move || {
+                for request in receiver {
+                    // Playback reports its own failures.
+                    let _ = fetcher.fetch(&request);
+                }
+            })?;
+        *prefetch = Some(sender.clone());
+        Ok(sender)
+    }
+
+    fn jobs(&self) -> crate::Result<Sender<Job>> {
+        let mut jobs = self.jobs.lock().unwrap();
+        if let Some(sender) = jobs.as_ref() {
+            return Ok(sender.clone());
+        }
+        let (sender, receiver) = mpsc::channel::<Job>();
+        let speaker = Speaker {
+            fetcher: self.fetcher.clone(),
+            prefetch: self.prefetcher()?,
+            output: self.output.clone(),
+        };
+        std::thread::Builder::new()
+            .name("edge-tts".into())
+            .spawn(move || {
+                for job in receiver {
+                    let utterance_id = job.utterance_id.clone();
+                    if let Err(err) = speaker.run(job) {
+                        let message = err.to_string();
+                        let error = event("error", Some(&message), None);
+                        speaker.output.emit(&utterance_id, error);
+                    }
+                }
+            })?;
+        *jobs = Some(sender.clone());
+        Ok(sender)
+    }
+
+    pub fn speak(&self, document: Document) -> crate::Result<String> {
+        let prosody = self.prosody()?;
+        self.output.open()?;
+        let utterance_id = format!("edge-{}", self.next_id.fetch_add(1, Ordering::SeqCst));
+        let job = Job {
+            utterance_id: utterance_id.clone(),
+            items: document.items,
+            prosody,
+            generation: self.output.generation(),
+        };
+        self.jobs()?
+            .send(job)
+            .map_err(|e| error("Edge TTS worker stopped", e))?;
+        Ok(utterance_id)
+    }
+
+    /// Synthesize a document into memory with the current voice, rate and
+    /// pitch, reporting the fraction of its spans done.
+    pub fn render(
+        &self,
+        document: &Document,
+        mut on_progress: impl FnMut(f32),
+    ) -> crate::Result<Pcm> {
+        let prosody = self.prosody()?;
+        let total = prosody.requests(document).len();
+        let mut pcm = Pcm {
+            samples: Vec::new(),
+            sample_rate: SAMPLE_RATE,
+        };
+        let mut done = 0;
+        for item in &document.items {
+            match item {
+                Item::Text(span) => {
+                    let entry = self.fetcher.fetch(&prosody.request(span))?;
+                    let (audio, rate) = decode(entry.audio)?;
+                    pcm.append(&audio, rate);
+                    done += 1;
+                    on_progress(done as f32 / total as f32);
+                }
+                Item::Break(millis) => pcm.append_silence(*millis as f32 / 1000.0),
+                Item::Mark(_) => {}
+            }
+        }
+        Ok(pcm)
+    }
+
+    /// Fetch a document into the cache in the background, to be spoken
+    /// soon.
+    pub fn prefetch(&self, document: &Document) -> crate::Result<()> {
+        let prefetch = self.prefetcher()?;
+        for request in self.prosody()?.requests(document) {
+            prefetch
+                .send(request)
+                .map_err(|e| error("Edge TTS worker stopped", e))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn voices_for_languages() {
+        let voice = "en-US-AriaNeural";
+        assert_eq!(locale(voice), Some("en-US"));
+        assert_eq!(locale("en-US-Nobody"), None);
+        let pick = |lang| voice_for(lang, voice);
+        assert_eq!(pick(None), (voice.into(), "en-US".into()));
+        assert_eq!(pick(Some("en-GB")), (voice.into(), "en-US".into()));
+        assert_eq!(
+            pick(Some("fr-FR")),
+            ("fr-FR-DeniseNeural".into(), "fr-FR".into())
+        );
+        assert_eq!(pick(Some("ja")).1, "ja-JP");
+        assert_eq!(pick(Some("tlh")), (voice.into(), "en-US".into()));
+        assert!(Edge::voices()
+            .iter()
+            .any(|voice| voice.id == "edgetts_en-US-AriaNeural" && voice.lang == "en-US"));
+    }
+
+    #[test]
+    fn requests_follow_spans() {
+        let prosody = Prosody {
+            voice: "de-DE-KatjaNeural".into(),
+            rate: 1.2,
+            pitch: 1.0,
+        };
+        let document = Document::parse(
+            "<speak xml:lang='de-DE'>Hallo <mark name='0'/><prosody rate='2'>\
+             <lang xml:lang='en-US'>Hello</lang></prosody></speak>",
+        )
+        .unwrap();
+        let requests = prosody.requests(&document);
+        assert_eq!(requests.len(), 2);
+        assert_eq!(requests[0].voice, "de-DE-KatjaNeural");
+        assert_eq!(requests[1].voice, "en-US-AnaNeural");
+        assert_eq!(requests[1].lang, "en-US");
+        assert!((requests[1].rate - 2.4).abs() < 1e-6);
+    }
+}
@@ -0,0 +1,303 @@
+//! The read-aloud protocol of Microsoft Edge: SSML goes out over a
+//! websocket, and MP3 frames and word boundaries come back until
+//! `turn.end`.
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const URL: &str = "wss://speech.platform.bing.com/consumer/speech/synthesize/readaloud/edge/v1";
+const TRUSTED_CLIENT_TOKEN: &str = "6A5AA1D4EAFF4E9FB37E23D68491D6F4";
+const CHROMIUM_FULL_VERSION: &str = "143.0.3650.75";
+const CHROMIUM_MAJOR_VERSION: &str = "143";
+/// Seconds from the Windows epoch, 1601-01-01, to the Unix one.
+const WIN_EPOCH_OFFSET: u64 = 11_644_473_600;
+
+const CONFIG: &str = r#"{"context":{"synthesis":{"audio":{"metadataoptions":{"sentenceBoundaryEnabled":false,"wordBoundaryEnabled":true},"outputFormat":"audio-24khz-48kbitrate-mono-mp3"}}}}"#;
+
+/// A spoken word, timed in ticks of 100 ns from the start of the audio.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WordBoundary {
+    pub offset: u64,
+    pub duration: u64,
+    pub text: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Request {
+    pub text: String,
+    pub voice: String,
+    pub lang: String,
+    pub rate: f32,
+    pub pitch: f32,
+}
+
+fn unix_time() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs())
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+/// 32 hex digits that differ on every call.
+pub fn random_id() -> String {
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_nanos());
+    let seed = format!(
+        "{nanos}:{}:{}:{:?}",
+        COUNTER.fetch_add(1, Ordering::SeqCst),
+        std::process::id(),
+        std::thread::current().id()
+    );
+    hex(&Sha256::digest(seed)[..16])
+}
+
+/// The `Sec-MS-GEC` token: a hash of the Windows file time, rounded down to
+/// five minutes, and the client token.
+pub fn sec_ms_gec(unix_secs: u64) -> String {
+    let mut secs = unix_secs + WIN_EPOCH_OFFSET;
+    secs -= secs % 300;
+    let ticks = secs as u128 * 10_000_000;
+    hex(&Sha256::digest(format!("{ticks}{TRUSTED_CLIENT_TOKEN}"))).to_uppercase()
+}
+
+pub fn url(connection_id: &str) -> String {
+    format!(
+        "{URL}?ConnectionId={connection_id}&TrustedClientToken={TRUSTED_CLIENT_TOKEN}\
+         &Sec-MS-GEC={}&Sec-MS-GEC-Version=1-{CHROMIUM_FULL_VERSION}",
+        sec_ms_gec(unix_time())
+    )
+}
+
+/// Headers of the websocket handshake, as the browser extension sends them.
+pub fn headers() -> Vec<(&'static str, String)> {
+    vec![
+        (
+            "User-Agent",
+            format!(
+                "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 \
+                 (KHTML, like Gecko) Chrome/{CHROMIUM_MAJOR_VERSION}.0.0.0 Safari/537.36 \
+                 Edg/{CHROMIUM_MAJOR_VERSION}.0.0.0"
+            ),
+        ),
+        ("Accept-Encoding", "gzip, deflate, br, zstd".into()),
+        ("Accept-Language", "en-US,en;q=0.9".into()),
+        ("Pragma", "no-cache".into()),
+        ("Cache-Control", "no-cache".into()),
+        (
+            "Origin",
+            "chrome-extension://jdiccldimpdaibmpdkjnbmckianbfold".into(),
+        ),
+        ("Cookie", format!("muid={};", random_id().to_uppercase())),
+    ]
+}
+
+/// A date as JavaScript's `Date.toString` formats it in UTC.
+pub fn timestamp(unix_secs: u64) -> String {
+    const DAYS: [&str; 7] = ["Sun", "Mon", "Tue", "Wed", "Thu", "Fri", "Sat"];
+    const MONTHS: [&str; 12] = [
+        "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+    ];
+    let days = (unix_secs / 86_400) as i64;
+    let secs = unix_secs % 86_400;
+    // Civil date from days since 1970-01-01, after Howard Hinnant.
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!(
+        "{} {} {day:02} {year} {:02}:{:02}:{:02} GMT+0000 (Coordinated Universal Time)",
+        DAYS[(days + 4).rem_euclid(7) as usize],
+        MONTHS[(month - 1) as usize],
+        secs / 3600,
+        secs % 3600 / 60,
+        secs % 60,
+    )
+}
+
+pub fn config_message() -> String {
+    format!(
+        "X-Timestamp:{}\r\nContent-Type:application/json; charset=utf-8\r\n\
+         Path:speech.config\r\n\r\n{CONFIG}",
+        timestamp(unix_time())
+    )
+}
+
+/// Prosody is relative to the voice: rate in percent, pitch in Hz.
+pub fn ssml(request: &Request) -> String {
+    let rate = ((request.rate - 1.0) * 100.0).round() as i32;
+    let pitch = ((request.pitch - 1.0) * 50.0).round() as i32;
+    format!(
+        "<speak version='1.0' xmlns='http://www.w3.org/2001/10/synthesis' xml:lang='{}'>\
+         <voice name='{}'><prosody pitch='{pitch:+}Hz' rate='{rate:+}%' volume='+0%'>{}\
+         </prosody></voice></speak>",
+        crate::ssml::escape(&request.lang),
+        crate::ssml::escape(&request.voice),
+        crate::ssml::escape(&request.text),
+    )
+}
+
+pub fn ssml_message(request_id: &str, request: &Request) -> String {
+    format!(
+        "X-RequestId:{request_id}\r\nContent-Type:application/ssml+xml\r\n\
+         X-Timestamp:{}Z\r\nPath:ssml\r\n\r\n{}",
+        timestamp(unix_time()),
+        ssml(request)
+    )
+}
+
+fn parse_headers(head: &str) -> HashMap<&str, &str> {
+    head.lines()
+        .filter_map(|line| line.split_once(':'))
+        .map(|(key, value)| (key.trim(), value.trim()))
+        .collect()
+}
+
+/// Headers and body of a text message.
+pub fn parse_text(message: &str) -> (HashMap<&str, &str>, &str) {
+    let (head, body) = message.split_once("\r\n\r\n").unwrap_or((message, ""));
+    (parse_headers(head), body)
+}
+
+/// Headers and payload of a binary message, whose headers are prefixed
+/// with their big-endian length.
+pub fn parse_binary(message: &[u8]) -> Option<(HashMap<&str, &str>, &[u8])> {
+    let len = u16::from_be_bytes([*message.first()?, *message.get(1)?]) as usize;
+    let head = std::str::from_utf8(message.get(2..2 + len)?).ok()?;
+    Some((parse_headers(head), &message[2 + len..]))
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct Metadata {
+    metadata: Vec<MetadataItem>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct MetadataItem {
+    #[serde(rename = "Type")]
+    kind: String,
+    data: MetadataData,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct MetadataData {
+    offset: u64,
+    duration: u64,
+    #[serde(rename = "text")]
+    text: MetadataText,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct MetadataText {
+    text: String,
+}
+
+/// The word boundaries of an `audio.metadata` message.
+pub fn word_boundaries(body: &str) -> Vec<WordBoundary> {
+    let Ok(metadata) = serde_json::from_str::<Metadata>(body) else {
+        return Vec::new();
+    };
+    metadata
+        .metadata
+        .into_iter()
+        .filter(|item| item.kind == "WordBoundary")
+        .map(|item| WordBoundary {
+            offset: item.data.offset,
+            duration: item.data.duration,
+            text: item.data.text.text,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tokens_and_dates() {
+        assert_eq!(
+            timestamp(0),
+            "Thu Jan 01 1970 00:00:00 GMT+0000 (Coordinated Universal Time)"
+        );
+        assert_eq!(
+            timestamp(1_792_318_245),
+            "Sun Oct 18 2026 10:10:45 GMT+0000 (Coordinated Universal Time)"
+        );
+        // The token changes every five minutes.
+        assert_eq!(sec_ms_gec(1_792_318_200), sec_ms_gec(1_792_318_499));
+        assert_ne!(sec_ms_gec(1_792_318_200), sec_ms_gec(1_792_318_500));
+        assert_eq!(
+            sec_ms_gec(1_792_318_245),
+            "1B2752DBB6DCA65728557569919F5C288F541390957A6E748B4E7C3E3BA1E620"
+        );
+        assert_ne!(random_id(), random_id());
+    }
+
+    #[test]
+    fn requests() {
+        let request = Request {
+            text: "Tom & Jerry".into(),
+            voice: "en-US-AriaNeural".into(),
+            lang: "en-US".into(),
+            rate: 1.5,
+            pitch: 0.8,
+        };
+        assert_eq!(
+            ssml(&request),
+            "<speak version='1.0' xmlns='http://www.w3.org/2001/10/synthesis' \
+             xml:lang='en-US'><voice name='en-US-AriaNeural'><prosody pitch='-10Hz' \
+             rate='+50%' volume='+0%'>Tom &amp; Jerry</prosody></voice></speak>"
+        );
+        let message = ssml_message("abc", &request);
+        let (headers, body) = parse_text(&message);
+        assert_eq!(headers["X-RequestId"], "abc");
+        assert_eq!(headers["Path"], "ssml");
+        assert!(body.starts_with("<speak"));
+    }
+
+    #[test]
+    fn responses() {
+        let (headers, body) = parse_text(
+            "X-RequestId:abc\r\nContent-Type:application/json\r\nPath:audio.metadata\r\n\r\n\
+             {\"Metadata\":[{\"Type\":\"WordBoundary\",\"Data\":{\"Offset\":1000000,\
+             \"Duration\":3375000,\"text\":{\"Text\":\"Hello\",\"Length\":5,\
+             \"BoundaryType\":\"WordBoundary\"}}},{\"Type\":\"SessionEnd\",\"Data\":\
+             {\"Offset\":0,\"Duration\":0,\"text\":{\"Text\":\"\"}}}]}",
+        );
+        assert_eq!(headers["Path"], "audio.metadata");
+        assert_eq!(
+            word_boundaries(body),
+            vec![WordBoundary {
+                offset: 1_000_000,
+                duration: 3_375_000,
+                text: "Hello".into(),
+            }]
+        );
+        assert!(word_boundaries("not json").is_empty());
+
+        let head = b"X-RequestId:abc\r\nContent-Type:audio/mpeg\r\nPath:audio\r\n";
+        let mut message = (head.len() as u16).to_be_bytes().to_vec();
+        message.extend_from_slice(head);
+        message.extend_from_slice(&[0xff, 0xf3]);
+        let (headers, audio) = parse_binary(&message).unwrap();
+        assert_eq!(headers["Path"], "audio");
+        assert_eq!(audio, [0xff, 0xf3]);
+        assert!(parse_binary(&[0, 9, b'x']).is_none());
+    }
+}
@@ -0,0 +1,196 @@
+//! Writing synthesized speech to audio files with tags.
+
+#[cfg(feature = "mp3")]
+mod mp3;
+#[cfg(feature = "opus")]
+mod opus;
+mod wav;
+
+use base64::Engine;
+use std::path::Path;
+
+use crate::models::AudioFormat;
+
+/// Mono samples from -1.0 to 1.0.
+#[derive(Debug, Default)]
+pub struct Pcm {
+    pub samples: Vec<f32>,
+    pub sample_rate: u32,
+}
+
+impl Pcm {
+    pub fn append(&mut self, samples: &[f32], sample_rate: u32) {
+        if self.sample_rate == 0 {
+            self.sample_rate = sample_rate;
+        }
+        if sample_rate == self.sample_rate {
+            self.samples.extend_from_slice(samples);
+        } else {
+            self.samples
+                .extend(resample(samples, sample_rate, self.sample_rate));
+        }
+    }
+
+    pub fn append_silence(&mut self, seconds: f32) {
+        let len = (self.sample_rate as f32 * seconds) as usize;
+        self.samples.resize(self.samples.len() + len, 0.0);
+    }
+
+    fn to_i16(&self) -> Vec<i16> {
+        self.samples
+            .iter()
+            .map(|s| (s.clamp(-1.0, 1.0) * i16::MAX as f32) as i16)
+            .collect()
+    }
+}
+
+/// Linear interpolation, which is enough for speech.
+pub fn resample(samples: &[f32], from: u32, to: u32) -> Vec<f32> {
+    if from == to || samples.is_empty() {
+        return samples.to_vec();
+    }
+    let len = (samples.len() as u64 * to as u64 / from as u64) as usize;
+    let step = from as f64 / to as f64;
+    (0..len)
+        .map(|i| {
+            let pos = i as f64 * step;
+            let index = pos as usize;
+            let frac = (pos - index as f64) as f32;
+            let a = samples[index.min(samples.len() - 1)];
+            let b = samples[(index + 1).min(samples.len() - 1)];
+            a + (b - a) * frac
+        })
+        .collect()
+}
+
+/// Embedded by the MP3 and Opus encoders, and saved for the MPRIS media session.
+#[derive(Debug, Clone)]
+#[cfg_attr(
+    not(any(target_os = "linux", feature = "mp3", feature = "opus")),
+    allow(dead_code)
+)]
+pub struct Artwork {
+    pub mime_type: String,
+    pub data: Vec<u8>,
+}
+
+impl Artwork {
+    /// Artwork from a `data:` URL, as the reader passes covers, or a file.
+    pub fn load(source: &str) -> Option<Self> {
+        if let Some(url) = source.strip_prefix("data:") {
+            let (header, data) = url.split_once(',')?;
+            let mime_type = header.strip_suffix(";base64")?;
+            let data = base64::engine::general_purpose::STANDARD
+                .decode(data.trim())
+                .ok()?;
+            return Some(Self {
+                mime_type: mime_type.to_string(),
+                data,
+            });
+        }
+        let path = Path::new(source);
+        let mime_type = match path.extension()?.to_str()?.to_lowercase().as_str() {
+            "jpg" | "jpeg" => "image/jpeg",
+            "png" => "image/png",
+            "webp" => "image/webp",
+            _ => return None,
+        };
+        Some(Self {
+            mime_type: mime_type.to_string(),
+            data: std::fs::read(path).ok()?,
+        })
+    }
+
+    #[cfg_attr(not(any(target_os = "linux", feature = "opus")), allow(dead_code))]
+    pub fn extension(&self) -> &str {
+        match self.mime_type.as_str() {
+            "image/jpeg" => "jpg",
+            "image/png" => "png",
+            "image/webp" => "webp",
+            _ => "img",
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct Tags {
+    pub title: Option<String>,
+    pub artist: Option<String>,
+    pub album: Option<String>,
+    pub track: Option<(u32, u32)>,
+    /// WAV files have no artwork.
+    #[cfg_attr(not(any(feature = "mp3", feature = "opus")), allow(dead_code))]
+    pub artwork: Option<Artwork>,
+}
+
+pub fn write(format: AudioFormat, path: &Path, pcm: &Pcm, tags: &Tags) -> crate::Result<()> {
+    let bytes = match format {
+        AudioFormat::Wav => wav::encode(pcm, tags),
+        #[cfg(feature = "mp3")]
+        AudioFormat::Mp3 => mp3::encode(pcm, tags)?,
+        #[cfg(feature = "opus")]
+        AudioFormat::Opus => opus::encode(pcm, tags)?,
+        #[allow(unreachable_patterns)]
+        format => {
+            return Err(crate::Error::NativeTTSError(format!(
+                "This build cannot write {} files",
+                format.extension()
+            )))
+        }
+    };
+    std::fs::write(path, bytes)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Tags of the first of three chapters, with a tiny PNG cover.
+    pub fn tags() -> Tags {
+        Tags {
+            title: Some("Chapter 1".into()),
+            artist: Some("Jane Doe".into()),
+            album: Some("The Book".into()),
+            track: Some((1, 3)),
+            artwork: Artwork::load("data:image/png;base64,iVBORw0KGgo="),
+        }
+    }
+
+    /// A quarter second of a 440 Hz tone.
+    #[cfg(any(feature = "mp3", feature = "opus"))]
+    pub fn tone(sample_rate: u32) -> Pcm {
+        let samples = (0..sample_rate / 4)
+            .map(|i| (i as f32 * 440.0 * std::f32::consts::TAU / sample_rate as f32).sin() * 0.5)
+            .collect();
+        Pcm {
+            samples,
+            sample_rate,
+        }
+    }
+
+    #[test]
+    fn test_resample() {
+        assert_eq!(resample(&[0.0, 1.0], 1, 2), [0.0, 0.5, 1.0, 1.0]);
+        assert_eq!(resample(&[0.0, 0.5, 1.0, 0.5], 2, 1), [0.0, 1.0]);
+        let mut pcm = Pcm::default();
+        pcm.append(&[0.25; 4], 8_000);
+        pcm.append(&[0.5; 4], 16_000);
+        pcm.append_silence(0.001);
+        assert_eq!(pcm.sample_rate, 8_000);
+        assert_eq!(
+            pcm.samples,
+            [0.25, 0.25, 0.25, 0.25, 0.5, 0.5, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0]
+        );
+    }
+
+    #[test]
+    fn test_load_artwork() {
+        let artwork = tags().artwork.unwrap();
+        assert_eq!(artwork.mime_type, "image/png");
+        assert_eq!(artwork.data, b"\x89PNG\r\n\x1a\n");
+        assert_eq!(artwork.extension(), "png");
+        assert!(Artwork::load("data:image/png,raw").is_none());
+        assert!(Artwork::load("/covers/cover.gif").is_none());
+    }
+}
@@ -0,0 +1,92 @@
+use id3::frame::{Picture, PictureType};
+use id3::{Tag, TagLike, Version};
+use mp3lame_encoder::{Bitrate, Builder, FlushNoGap, MonoPcm, Quality};
+
+use super::{Pcm, Tags};
+
+fn error(err: impl std::fmt::Debug) -> crate::Error {
+    crate::Error::NativeTTSError(format!("Failed to encode MP3: {err:?}"))
+}
+
+fn id3_tag(tags: &Tags) -> Tag {
+    let mut tag = Tag::new();
+    if let Some(title) = &tags.title {
+        tag.set_title(title);
+    }
+    if let Some(artist) = &tags.artist {
+        tag.set_artist(artist);
+    }
+    if let Some(album) = &tags.album {
+        tag.set_album(album);
+    }
+    if let Some((track, total)) = tags.track {
+        tag.set_track(track);
+        tag.set_total_tracks(total);
+    }
+    tag.set_genre("Audiobook");
+    if let Some(artwork) = &tags.artwork {
+        tag.add_frame(Picture {
+            mime_type: artwork.mime_type.clone(),
+            picture_type: PictureType::CoverFront,
+            description: String::new(),
+            data: artwork.data.clone(),
+        });
+    }
+    tag
+}
+
+/// Speech at 64 kbit/s, with an ID3v2.4 tag in front.
+pub fn encode(pcm: &Pcm, tags: &Tags) -> crate::Result<Vec<u8>> {
+    let mut builder = Builder::new().ok_or_else(|| error("no encoder"))?;
+    builder.set_num_channels(1).map_err(error)?;
+    builder.set_sample_rate(pcm.sample_rate).map_err(error)?;
+    builder.set_brate(Bitrate::Kbps64).map_err(error)?;
+    builder.set_quality(Quality::Good).map_err(error)?;
+    let mut encoder = builder.build().map_err(error)?;
+
+    let mut audio = Vec::new();
+    let samples = pcm.to_i16();
+    encoder
+        .encode_to_vec(MonoPcm(&samples), &mut audio)
+        .map_err(error)?;
+    encoder
+        .flush_to_vec::<FlushNoGap>(&mut audio)
+        .map_err(error)?;
+
+    let mut out = Vec::new();
+    id3_tag(tags)
+        .write_to(&mut out, Version::Id3v24)
+        .map_err(error)?;
+    out.extend_from_slice(&audio);
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::tests::{tags, tone};
+    use super::*;
+
+    #[test]
+    fn test_encode() {
+        let mp3 = encode(&tone(22_050), &tags()).unwrap();
+        let tag = Tag::read_from2(std::io::Cursor::new(&mp3)).unwrap();
+        assert_eq!(tag.title(), Some("Chapter 1"));
+        assert_eq!(tag.artist(), Some("Jane Doe"));
+        assert_eq!(tag.album(), Some("The Book"));
+        assert_eq!((tag.track(), tag.total_tracks()), (Some(1), Some(3)));
+        assert_eq!(tag.genre(), Some("Audiobook"));
+        let picture = tag.pictures().next().unwrap();
+        assert_eq!(picture.mime_type, "image/png");
+        assert_eq!(picture.picture_type, PictureType::CoverFront);
+
+        // The audio follows the tag, whose size is syncsafe, and starts
+        // with the sync word of an MPEG audio frame.
+        assert_eq!(&mp3[..4], b"ID3\x04");
+        let size = mp3[6..10]
+            .iter()
+            .fold(0usize, |size, byte| size << 7 | usize::from(*byte));
+        let audio = &mp3[10 + size..];
+        assert_eq!(audio[0], 0xff);
+        assert_eq!(audio[1] & 0xe0, 0xe0);
+    }
+}
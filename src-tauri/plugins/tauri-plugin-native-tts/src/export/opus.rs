@@ -0,0 +1,171 @@
+//! Opus in Ogg (RFC 7845), tagged with Vorbis comments.
+
+use base64::Engine;
+use ogg::writing::{PacketWriteEndInfo, PacketWriter};
+use opus::{Application, Bitrate, Channels, Encoder};
+
+use super::{resample, Artwork, Pcm, Tags};
+
+/// Opus always decodes at 48 kHz.
+const RATE: u32 = 48_000;
+/// 20 ms frames.
+const FRAME: usize = 960;
+const SERIAL: u32 = 0x4858_4936;
+
+fn error(err: opus::Error) -> crate::Error {
+    crate::Error::NativeTTSError(format!("Failed to encode Opus: {err}"))
+}
+
+fn head(pre_skip: u16, input_rate: u32) -> Vec<u8> {
+    let mut head = b"OpusHead".to_vec();
+    head.push(1); // version
+    head.push(1); // channels
+    head.extend_from_slice(&pre_skip.to_le_bytes());
+    head.extend_from_slice(&input_rate.to_le_bytes());
+    head.extend_from_slice(&0i16.to_le_bytes()); // output gain
+    head.push(0); // mono/stereo mapping
+    head
+}
+
+/// A FLAC picture block, the format of `METADATA_BLOCK_PICTURE`.
+fn picture(artwork: &Artwork) -> Vec<u8> {
+    let mut block = Vec::new();
+    block.extend_from_slice(&3u32.to_be_bytes()); // front cover
+    block.extend_from_slice(&(artwork.mime_type.len() as u32).to_be_bytes());
+    block.extend_from_slice(artwork.mime_type.as_bytes());
+    block.extend_from_slice(&0u32.to_be_bytes()); // description
+    block.extend_from_slice(&[0; 16]); // dimensions, depth, colors: unknown
+    block.extend_from_slice(&(artwork.data.len() as u32).to_be_bytes());
+    block.extend_from_slice(&artwork.data);
+    block
+}
+
+fn comments(tags: &Tags) -> Vec<u8> {
+    let mut comments = Vec::new();
+    let mut add = |key: &str, value: &str| {
+        if !value.is_empty() {
+            comments.push(format!("{key}={value}"));
+        }
+    };
+    add("TITLE", tags.title.as_deref().unwrap_or_default());
+    add("ARTIST", tags.artist.as_deref().unwrap_or_default());
+    add("ALBUM", tags.album.as_deref().unwrap_or_default());
+    if let Some((track, total)) = tags.track {
+        add("TRACKNUMBER", &track.to_string());
+        add("TRACKTOTAL", &total.to_string());
+    }
+    add("GENRE", "Audiobook");
+    if let Some(artwork) = &tags.artwork {
+        let block = base64::engine::general_purpose::STANDARD.encode(picture(artwork));
+        add("METADATA_BLOCK_PICTURE", &block);
+    }
+
+    let vendor = "HackXIndia26";
+    let mut packet = b"OpusTags".to_vec();
+    packet.extend_from_slice(&(vendor.len() as u32).to_le_bytes());
+    packet.extend_from_slice(vendor.as_bytes());
+    packet.extend_from_slice(&(comments.len() as u32).to_le_bytes());
+    for comment in comments {
+        packet.extend_from_slice(&(comment.len() as u32).to_le_bytes());
+        packet.extend_from_slice(comment.as_bytes());
+    }
+    packet
+}
+
+/// Speech at 24 kbit/s.
+pub fn encode(pcm: &Pcm, tags: &Tags) -> crate::Result<Vec<u8>> {
+    let samples = resample(&pcm.samples, pcm.sample_rate, RATE);
+    let mut encoder = Encoder::new(RATE, Channels::Mono, Application::Voip).map_err(error)?;
+    encoder.set_bitrate(Bitrate::Bits(24_000)).map_err(error)?;
+    let pre_skip = encoder.get_lookahead().map_err(error)?.max(0) as u64;
+
+    let mut out = Vec::new();
+    let mut writer = PacketWriter::new(&mut out);
+    writer.write_packet(
+        head(pre_skip as u16, pcm.sample_rate),
+        SERIAL,
+        PacketWriteEndInfo::EndPage,
+        0,
+    )?;
+    writer.write_packet(comments(tags), SERIAL, PacketWriteEndInfo::EndPage, 0)?;
+
+    let frames = samples.len().div_ceil(FRAME).max(1);
+    let mut packet = vec![0u8; 4000];
+    let mut frame = vec![0f32; FRAME];
+    for i in 0..frames {
+        let chunk = &samples[(i * FRAME).min(samples.len())..((i + 1) * FRAME).min(samples.len())];
+        frame[..chunk.len()].copy_from_slice(chunk);
+        frame[chunk.len()..].fill(0.0);
+        let len = encoder.encode_float(&frame, &mut packet).map_err(error)?;
+        let last = i + 1 == frames;
+        // The final granule position trims the padding of the last frame.
+        let granule = if last {
+            pre_skip + samples.len() as u64
+        } else {
+            pre_skip + ((i + 1) * FRAME) as u64
+        };
+        let end = if last {
+            PacketWriteEndInfo::EndStream
+        } else {
+            PacketWriteEndInfo::NormalPacket
+        };
+        writer.write_packet(packet[..len].to_vec(), SERIAL, end, granule)?;
+    }
+    drop(writer);
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::tests::{tags, tone};
+    use super::*;
+    use ogg::reading::PacketReader;
+
+    #[test]
+    fn test_encode() {
+        let pcm = tone(24_000);
+        let opus = encode(&pcm, &tags()).unwrap();
+        let mut reader = PacketReader::new(std::io::Cursor::new(opus));
+        let mut packets = Vec::new();
+        while let Some(packet) = reader.read_packet().unwrap() {
+            packets.push(packet);
+        }
+
+        let head = &packets[0].data;
+        assert_eq!(&head[..8], b"OpusHead");
+        assert_eq!(head[9], 1); // mono
+        let pre_skip = u16::from_le_bytes([head[10], head[11]]) as u64;
+        assert_eq!(u32::from_le_bytes(head[12..16].try_into().unwrap()), 24_000);
+
+        let comments = String::from_utf8_lossy(&packets[1].data);
+        assert!(comments.starts_with("OpusTags"));
+        for comment in [
+            "TITLE=Chapter 1",
+            "ARTIST=Jane Doe",
+            "ALBUM=The Book",
+            "TRACKNUMBER=1",
+            "TRACKTOTAL=3",
+            "GENRE=Audiobook",
+            "METADATA_BLOCK_PICTURE=",
+        ] {
+            assert!(comments.contains(comment), "missing {comment}");
+        }
+
+        // A quarter second at 48 kHz is 12000 samples, in 13 frames of 20 ms.
+        let audio = &packets[2..];
+        assert_eq!(audio.len(), 13);
+        let last = audio.last().unwrap();
+        assert!(last.last_in_stream());
+        assert_eq!(last.absgp_page(), pre_skip + 12_000);
+    }
+
+    #[test]
+    fn test_picture() {
+        let block = picture(&tags().artwork.unwrap());
+        assert_eq!(&block[..4], 3u32.to_be_bytes());
+        assert_eq!(&block[4..8], 9u32.to_be_bytes());
+        assert_eq!(&block[8..17], b"image/png");
+        assert_eq!(&block[37..41], 8u32.to_be_bytes());
+        assert_eq!(&block[41..], b"\x89PNG\r\n\x1a\n");
+    }
+}
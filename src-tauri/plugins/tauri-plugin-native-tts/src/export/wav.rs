@@ -0,0 +1,115 @@
+//! 16-bit PCM WAV with a `LIST`/`INFO` chunk for tags, which is what
+//! players read from WAV files.
+
+use super::{Pcm, Tags};
+
+fn chunk(out: &mut Vec<u8>, id: &[u8; 4], data: &[u8]) {
+    out.extend_from_slice(id);
+    out.extend_from_slice(&(data.len() as u32).to_le_bytes());
+    out.extend_from_slice(data);
+    if data.len() % 2 == 1 {
+        out.push(0);
+    }
+}
+
+fn info(tags: &Tags) -> Vec<u8> {
+    let track = tags.track.map(|(track, _)| track.to_string());
+    let fields = [
+        (b"INAM", tags.title.as_deref()),
+        (b"IART", tags.artist.as_deref()),
+        (b"IPRD", tags.album.as_deref()),
+        (b"ITRK", track.as_deref()),
+        (b"ISFT", Some("HackXIndia26")),
+    ];
+    let mut list = b"INFO".to_vec();
+    for (id, value) in fields {
+        if let Some(value) = value.filter(|v| !v.is_empty()) {
+            let mut data = value.as_bytes().to_vec();
+            data.push(0);
+            chunk(&mut list, id, &data);
+        }
+    }
+    list
+}
+
+pub fn encode(pcm: &Pcm, tags: &Tags) -> Vec<u8> {
+    let samples = pcm.to_i16();
+    let mut format = Vec::with_capacity(16);
+    format.extend_from_slice(&1u16.to_le_bytes()); // PCM
+    format.extend_from_slice(&1u16.to_le_bytes()); // mono
+    format.extend_from_slice(&pcm.sample_rate.to_le_bytes());
+    format.extend_from_slice(&(pcm.sample_rate * 2).to_le_bytes());
+    format.extend_from_slice(&2u16.to_le_bytes());
+    format.extend_from_slice(&16u16.to_le_bytes());
+    let data: Vec<u8> = samples.iter().flat_map(|s| s.to_le_bytes()).collect();
+
+    let mut body = b"WAVE".to_vec();
+    chunk(&mut body, b"fmt ", &format);
+    chunk(&mut body, b"LIST", &info(tags));
+    chunk(&mut body, b"data", &data);
+    let mut out = Vec::with_capacity(body.len() + 8);
+    chunk(&mut out, b"RIFF", &body);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::tests::tags;
+    use super::*;
+
+    fn u32_at(bytes: &[u8], at: usize) -> u32 {
+        u32::from_le_bytes(bytes[at..at + 4].try_into().unwrap())
+    }
+
+    /// The chunks of a RIFF body by id.
+    fn chunks(mut body: &[u8]) -> Vec<(&[u8], &[u8])> {
+        let mut chunks = Vec::new();
+        while body.len() >= 8 {
+            let len = u32_at(body, 4) as usize;
+            chunks.push((&body[..4], &body[8..8 + len]));
+            body = &body[(8 + len + len % 2).min(body.len())..];
+        }
+        chunks
+    }
+
+    #[test]
+    fn test_encode() {
+        let pcm = Pcm {
+            samples: vec![0.0, 0.5, -1.0, 2.0],
+            sample_rate: 22_050,
+        };
+        let wav = encode(&pcm, &tags());
+        assert_eq!(&wav[..4], b"RIFF");
+        assert_eq!(u32_at(&wav, 4) as usize, wav.len() - 8);
+        assert_eq!(&wav[8..12], b"WAVE");
+
+        let body = chunks(&wav[12..]);
+        let ids: Vec<&[u8]> = body.iter().map(|(id, _)| *id).collect();
+        assert_eq!(ids, [b"fmt ", b"LIST", b"data"]);
+        let format = body[0].1;
+        assert_eq!(&format[..4], [1, 0, 1, 0]); // PCM, mono
+        assert_eq!(u32_at(format, 4), 22_050);
+        assert_eq!(u32_at(format, 8), 44_100);
+        assert_eq!(&format[12..], [2, 0, 16, 0]);
+        let samples: Vec<i16> = body[2]
+            .1
+            .chunks(2)
+            .map(|s| i16::from_le_bytes([s[0], s[1]]))
+            .collect();
+        assert_eq!(samples, [0, 16383, -32767, 32767]);
+
+        let info = body[1].1;
+        assert_eq!(&info[..4], b"INFO");
+        let fields: Vec<(&[u8], &[u8])> = chunks(&info[4..]);
+        assert_eq!(
+            fields,
+            [
+                (&b"INAM"[..], &b"Chapter 1\0"[..]),
+                (b"IART", b"Jane Doe\0"),
+                (b"IPRD", b"The Book\0"),
+                (b"ITRK", b"1\0"),
+                (b"ISFT", b"HackXIndia26\0"),
+            ]
+        );
+    }
+}
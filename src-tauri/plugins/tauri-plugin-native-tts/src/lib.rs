@@ -1,18 +1,34 @@
+use std::path::PathBuf;
 use tauri::{
     plugin::{Builder, TauriPlugin},
-    Manager, Runtime,
+    AppHandle, Manager, Runtime,
 };
 
 pub use models::*;
 
+#[cfg(all(desktop, feature = "audio"))]
+mod audio;
 #[cfg(desktop)]
 mod desktop;
+#[cfg(all(desktop, feature = "edge"))]
+mod edge;
+#[cfg(desktop)]
+mod export;
 #[cfg(mobile)]
 mod mobile;
+#[cfg(target_os = "linux")]
+mod mpris;
+#[cfg(all(desktop, feature = "piper"))]
+mod piper;
+#[cfg(desktop)]
+mod ssip;
 
 mod commands;
 mod error;
 mod models;
+mod normalize;
+mod queue;
+mod ssml;
 
 pub use error::{Error, Result};
 
@@ -32,8 +48,36 @@ impl<R: Runtime, T: Manager<R>> crate::NativeTtsExt<R> for T {
     }
 }
 
+/// Where the plugin keeps voices, lexicons, reading positions and caches.
+#[derive(Debug, Clone)]
+pub struct Dirs {
+    pub data_dir: PathBuf,
+    pub cache_dir: PathBuf,
+}
+
+impl Dirs {
+    /// The app data and cache dirs.
+    fn resolve<R: Runtime>(app: &AppHandle<R>) -> Result<Self> {
+        let path_error = |e: tauri::Error| Error::NativeTTSError(e.to_string());
+        Ok(Self {
+            data_dir: app.path().app_data_dir().map_err(path_error)?,
+            cache_dir: app.path().app_cache_dir().map_err(path_error)?,
+        })
+    }
+}
+
 /// Initializes the plugin.
 pub fn init<R: Runtime>() -> TauriPlugin<R> {
+    build(None)
+}
+
+/// Initializes the plugin with its files in `dirs` instead of the app dirs, e.g. for
+/// portable installs.
+pub fn init_with_dirs<R: Runtime>(dirs: Dirs) -> TauriPlugin<R> {
+    build(Some(dirs))
+}
+
+fn build<R: Runtime>(dirs: Option<Dirs>) -> TauriPlugin<R> {
     Builder::new("native-tts")
         .invoke_handler(tauri::generate_handler![
             commands::init,
@@ -45,15 +89,31 @@ pub fn init<R: Runtime>() -> TauriPlugin<R> {
             commands::set_pitch,
             commands::set_voice,
             commands::get_all_voices,
+            commands::get_lexicon,
+            commands::set_lexicon,
+            commands::enqueue,
+            commands::clear_queue,
+            commands::set_sleep_timer,
+            commands::get_queue_state,
+            commands::get_resume_position,
+            commands::synthesize_to_file,
             commands::set_media_session_active,
             commands::update_media_session_state,
             commands::update_media_session_metadata,
+            #[cfg(desktop)]
+            commands::register_listener,
+            #[cfg(desktop)]
+            commands::remove_listener,
         ])
-        .setup(|app, api| {
+        .setup(move |app, api| {
+            let dirs = match dirs {
+                Some(dirs) => dirs,
+                None => Dirs::resolve(app)?,
+            };
             #[cfg(mobile)]
-            let native_tts = mobile::init(app, api)?;
+            let native_tts = mobile::init(app, api, &dirs)?;
             #[cfg(desktop)]
-            let native_tts = desktop::init(app, api)?;
+            let native_tts = desktop::init(app, api, &dirs)?;
             app.manage(native_tts);
             Ok(())
         })
@@ -1,28 +1,94 @@
-use serde::de::DeserializeOwned;
+use serde::{de::DeserializeOwned, Serialize};
 use tauri::{
+    ipc::{Channel, InvokeResponseBody},
     plugin::{PluginApi, PluginHandle},
     AppHandle, Runtime,
 };
 
 use crate::models::*;
+use crate::normalize::Normalizer;
+use crate::queue::{self, Player};
+use crate::ssml::Document;
+use crate::Dirs;
 
 #[cfg(target_os = "ios")]
 tauri::ios_plugin_binding!(init_plugin_native_tts);
 
 // initializes the Kotlin or Swift plugin classes
 pub fn init<R: Runtime, C: DeserializeOwned>(
-    _app: &AppHandle<R>,
+    app: &AppHandle<R>,
     api: PluginApi<R, C>,
+    dirs: &Dirs,
 ) -> crate::Result<NativeTts<R>> {
     #[cfg(target_os = "android")]
     let handle = api.register_android_plugin("com.hackxindia26.native_tts", "NativeTTSPlugin")?;
     #[cfg(target_os = "ios")]
     let handle = api.register_ios_plugin(init_plugin_native_tts)?;
-    Ok(NativeTts(handle))
+    let data_dir = &dirs.data_dir;
+    follow_utterances(app, &handle)?;
+    Ok(NativeTts(
+        handle,
+        Normalizer::new(data_dir.join("lexicons")),
+        Player::new(data_dir.join("tts-positions.json")),
+    ))
+}
+
+#[derive(Serialize)]
+struct RegisterListener {
+    event: &'static str,
+    handler: Channel,
+}
+
+#[derive(Serialize)]
+struct EmitEvent {
+    event: &'static str,
+    payload: serde_json::Value,
+}
+
+/// Listen to the platform's utterance events, as the webview does, for the
+/// queue to move on when one ends.
+fn follow_utterances<R: Runtime>(
+    app: &AppHandle<R>,
+    handle: &PluginHandle<R>,
+) -> crate::Result<()> {
+    let app = app.clone();
+    let handler = Channel::new(move |body| {
+        let InvokeResponseBody::Json(json) = body else {
+            return Ok(());
+        };
+        if let Ok(payload) = serde_json::from_str::<TTSEventPayload>(&json) {
+            let app = app.clone();
+            std::thread::spawn(move || {
+                queue::on_event(&app, &payload.utterance_id, &payload.event.code)
+            });
+        }
+        Ok(())
+    });
+    let listener = RegisterListener {
+        event: "tts_events",
+        handler,
+    };
+    handle.run_mobile_plugin::<()>("registerListener", listener)?;
+    Ok(())
 }
 
 /// Access to the native-tts APIs.
-pub struct NativeTts<R: Runtime>(PluginHandle<R>);
+pub struct NativeTts<R: Runtime>(PluginHandle<R>, Normalizer, Player);
+
+impl<R: Runtime> NativeTts<R> {
+    pub(crate) fn player(&self) -> &Player {
+        &self.2
+    }
+
+    /// Pass a queue event to the webview's listeners on the platform plugin.
+    pub(crate) fn send_queue_event(&self, payload: serde_json::Value) {
+        let event = EmitEvent {
+            event: queue::EVENT_NAME,
+            payload,
+        };
+        let _ = self.0.run_mobile_plugin::<()>("emit_event", event);
+    }
+}
 
 impl<R: Runtime> NativeTts<R> {
     pub fn init(&self) -> crate::Result<InitResponse> {
@@ -31,7 +97,22 @@ impl<R: Runtime> NativeTts<R> {
 }
 
 impl<R: Runtime> NativeTts<R> {
-    pub fn speak(&self, payload: SpeakArgs) -> crate::Result<SpeakResponse> {
+    pub fn speak(&self, mut payload: SpeakArgs) -> crate::Result<SpeakResponse> {
+        // The platform engines take plain text and report the marks from
+        // the positions they reach.
+        let mut document = if payload.ssml {
+            Document::parse(&payload.text)?
+        } else {
+            Document::with_marks(&payload.text, &payload.marks)
+        };
+        self.1.document(&mut document, payload.lang.as_deref());
+        let (text, marks) = document.plain_text();
+        payload = SpeakArgs {
+            text,
+            marks,
+            ssml: false,
+            ..payload
+        };
         self.0
             .run_mobile_plugin("speak", payload)
             .map_err(Into::into)
@@ -88,6 +169,34 @@ impl<R: Runtime> NativeTts<R> {
     }
 }
 
+impl<R: Runtime> NativeTts<R> {
+    pub fn get_lexicon(&self, payload: GetLexiconArgs) -> crate::Result<GetLexiconResponse> {
+        Ok(GetLexiconResponse {
+            pls: self.1.lexicons().read(payload.lang.as_deref())?,
+        })
+    }
+}
+
+impl<R: Runtime> NativeTts<R> {
+    pub fn set_lexicon(&self, payload: SetLexiconArgs) -> crate::Result<()> {
+        self.1
+            .lexicons()
+            .write(payload.lang.as_deref(), &payload.pls)
+    }
+}
+
+impl<R: Runtime> NativeTts<R> {
+    /// The platform engines only speak; Android's file synthesis is not
+    /// wired up yet.
+    pub fn synthesize_to_file(
+        &self,
+        _payload: SynthesizeToFileArgs,
+        _on_progress: Channel<SynthesisProgress>,
+    ) -> crate::Result<SynthesizeToFileResponse> {
+        Err(crate::Error::UnsupportedPlatformError)
+    }
+}
+
 impl<R: Runtime> NativeTts<R> {
     pub fn set_media_session_active(
         &self,
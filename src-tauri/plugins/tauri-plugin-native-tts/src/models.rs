@@ -18,18 +18,44 @@ pub struct TTSMessageEvent {
     pub mark: Option<String>,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TTSEventPayload {
+    pub utterance_id: String,
+    #[serde(flatten)]
+    pub event: TTSMessageEvent,
+}
+
 #[derive(Debug, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct InitResponse {
     pub success: bool,
 }
 
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SpeechMark {
+    /// Position in the text, in UTF-16 code units.
+    pub offset: usize,
+    pub name: String,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct SpeakArgs {
     pub text: String,
     #[serde(default)]
     pub preload: bool,
+    /// Whether `text` is an SSML document.
+    #[serde(default)]
+    pub ssml: bool,
+    /// Marks in plain `text`, reported as the engine reaches them.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub marks: Vec<SpeechMark>,
+    /// Language of the text, for reading its numbers and abbreviations.
+    /// SSML `xml:lang` takes precedence.
+    #[serde(default, skip_serializing)]
+    pub lang: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -62,6 +88,27 @@ pub struct GetVoicesResponse {
     pub voices: Vec<TTSVoice>,
 }
 
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GetLexiconArgs {
+    /// Language of the lexicon; without one, the lexicon for all text.
+    pub lang: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GetLexiconResponse {
+    /// The lexicon as a W3C PLS document.
+    pub pls: String,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SetLexiconArgs {
+    pub lang: Option<String>,
+    pub pls: String,
+}
+
 #[derive(Debug, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct SetMediaSessionActiveRequest {
@@ -89,3 +136,165 @@ pub struct UpdateMediaSessionMetadataRequest {
     pub album: Option<String>,
     pub artwork: Option<String>,
 }
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AudioFormat {
+    Wav,
+    Opus,
+    Mp3,
+}
+
+impl AudioFormat {
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Wav => "wav",
+            Self::Opus => "opus",
+            Self::Mp3 => "mp3",
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SynthesisChapter {
+    pub title: Option<String>,
+    pub text: String,
+    /// Whether `text` is an SSML document.
+    #[serde(default)]
+    pub ssml: bool,
+    /// Marks in plain `text`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub marks: Vec<SpeechMark>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SynthesizeToFileArgs {
+    pub chapters: Vec<SynthesisChapter>,
+    pub format: AudioFormat,
+    /// Directory to write to, created if missing. It must be in the
+    /// filesystem scope of the webview.
+    pub dir: String,
+    /// File name without extension; defaults to the metadata title.
+    pub file_name: Option<String>,
+    /// Write one numbered file per chapter instead of a single file.
+    #[serde(default)]
+    pub split_chapters: bool,
+    /// Tags for the files. With split chapters, chapter titles become the
+    /// track titles and the metadata title the album, unless one is set.
+    pub metadata: Option<UpdateMediaSessionMetadataRequest>,
+    /// Language of the chapters, as for speaking.
+    #[serde(default)]
+    pub lang: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SynthesisProgress {
+    /// Index of the chapter being synthesized.
+    pub chapter: usize,
+    pub chapters: usize,
+    /// Fraction of the whole job done, from 0 to 1.
+    pub progress: f32,
+    /// A file that was just written.
+    pub file: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SynthesizeToFileResponse {
+    pub files: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QueueItem {
+    /// Chosen by the caller and reported back in queue events.
+    pub id: String,
+    pub text: String,
+    /// Whether `text` is an SSML document.
+    #[serde(default)]
+    pub ssml: bool,
+    /// Language of the text, as for speaking.
+    #[serde(default)]
+    pub lang: Option<String>,
+    /// Items of one chapter share it; the end-of-chapter sleep timer stops
+    /// playback where it changes.
+    #[serde(default)]
+    pub chapter: Option<String>,
+    /// Where the item starts in the book, saved as the resume position
+    /// when it starts.
+    #[serde(default)]
+    pub position: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EnqueueMode {
+    /// Add the items after the pending ones, skipping those already queued.
+    #[default]
+    Append,
+    /// Replace the pending items, letting the current one finish.
+    Next,
+    /// Stop the current item and start the new ones.
+    Replace,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EnqueueArgs {
+    /// The book the items come from, to save their resume positions under.
+    #[serde(default)]
+    pub book: Option<String>,
+    pub items: Vec<QueueItem>,
+    #[serde(default)]
+    pub mode: EnqueueMode,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EnqueueResponse {
+    /// The utterance of the item that started, or of the one already
+    /// speaking the first appended item.
+    pub utterance_id: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SetSleepTimerArgs {
+    /// Stop after this many minutes. With neither this nor `end_of_chapter`,
+    /// the timer is off.
+    #[serde(default)]
+    pub minutes: Option<f64>,
+    /// Stop at the end of the chapter instead.
+    #[serde(default)]
+    pub end_of_chapter: bool,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GetQueueStateResponse {
+    pub book: Option<String>,
+    /// Id of the item speaking.
+    pub current: Option<String>,
+    pub utterance_id: Option<String>,
+    /// Ids of the items after it.
+    pub pending: Vec<String>,
+    /// When the sleep timer stops playback, in milliseconds since the Unix epoch.
+    pub sleep_at: Option<u64>,
+    pub sleep_at_end_of_chapter: bool,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GetResumePositionArgs {
+    pub book: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GetResumePositionResponse {
+    /// The position of the last item started from the book.
+    pub position: Option<serde_json::Value>,
+}
@@ -0,0 +1,296 @@
+//! An MPRIS2 player on the session bus, so media keys and the media
+//! controls of GNOME and KDE can drive read-aloud.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use zbus::blocking::connection::Builder;
+use zbus::blocking::Connection;
+use zbus::fdo;
+use zbus::interface;
+use zbus::zvariant::{ObjectPath, Value};
+
+const PATH: &str = "/org/mpris/MediaPlayer2";
+const TRACK_ID: &str = "/org/hackxindia26/track/0";
+
+/// Receives the names of the `media-session-*` events to forward.
+pub type ActionSink = Arc<dyn Fn(&str) + Send + Sync>;
+
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Metadata {
+    pub title: Option<String>,
+    pub artist: Option<String>,
+    pub album: Option<String>,
+    /// A URI, as MPRIS clients do not read data URLs.
+    pub art_url: Option<String>,
+}
+
+struct Root {
+    identity: String,
+}
+
+#[interface(name = "org.mpris.MediaPlayer2")]
+impl Root {
+    fn raise(&self) {}
+
+    fn quit(&self) {}
+
+    #[zbus(property)]
+    fn can_quit(&self) -> bool {
+        false
+    }
+
+    #[zbus(property)]
+    fn can_raise(&self) -> bool {
+        false
+    }
+
+    #[zbus(property)]
+    fn has_track_list(&self) -> bool {
+        false
+    }
+
+    #[zbus(property)]
+    fn identity(&self) -> String {
+        self.identity.clone()
+    }
+
+    #[zbus(property)]
+    fn desktop_entry(&self) -> String {
+        self.identity.clone()
+    }
+
+    #[zbus(property)]
+    fn supported_uri_schemes(&self) -> Vec<String> {
+        Vec::new()
+    }
+
+    #[zbus(property)]
+    fn supported_mime_types(&self) -> Vec<String> {
+        Vec::new()
+    }
+}
+
+struct Player {
+    playing: bool,
+    /// Microseconds, as MPRIS counts time.
+    position: i64,
+    length: Option<i64>,
+    metadata: Metadata,
+    on_action: ActionSink,
+}
+
+#[interface(name = "org.mpris.MediaPlayer2.Player")]
+impl Player {
+    fn next(&self) {
+        (self.on_action)("media-session-next");
+    }
+
+    fn previous(&self) {
+        (self.on_action)("media-session-previous");
+    }
+
+    fn pause(&self) {
+        if self.playing {
+            (self.on_action)("media-session-pause");
+        }
+    }
+
+    fn play_pause(&self) {
+        if self.playing {
+            (self.on_action)("media-session-pause");
+        } else {
+            (self.on_action)("media-session-play");
+        }
+    }
+
+    fn stop(&self) {
+        (self.on_action)("media-session-stop");
+    }
+
+    fn play(&self) {
+        if !self.playing {
+            (self.on_action)("media-session-play");
+        }
+    }
+
+    fn seek(&self, _offset: i64) -> fdo::Result<()> {
+        Err(fdo::Error::NotSupported("Read-aloud cannot seek".into()))
+    }
+
+    fn set_position(&self, _track_id: ObjectPath<'_>, _position: i64) -> fdo::Result<()> {
+        Err(fdo::Error::NotSupported("Read-aloud cannot seek".into()))
+    }
+
+    fn open_uri(&self, _uri: &str) -> fdo::Result<()> {
+        Err(fdo::Error::NotSupported("Read-aloud opens no URIs".into()))
+    }
+
+    #[zbus(property)]
+    fn playback_status(&self) -> &str {
+        if self.playing {
+            "Playing"
+        } else {
+            "Paused"
+        }
+    }
+
+    #[zbus(property)]
+    fn rate(&self) -> f64 {
+        1.0
+    }
+
+    #[zbus(property)]
+    fn set_rate(&mut self, _rate: f64) {}
+
+    #[zbus(property)]
+    fn minimum_rate(&self) -> f64 {
+        1.0
+    }
+
+    #[zbus(property)]
+    fn maximum_rate(&self) -> f64 {
+        1.0
+    }
+
+    #[zbus(property)]
+    fn volume(&self) -> f64 {
+        1.0
+    }
+
+    #[zbus(property)]
+    fn set_volume(&mut self, _volume: f64) {}
+
+    #[zbus(property)]
+    fn metadata(&self) -> HashMap<&'static str, Value<'static>> {
+        let mut map = HashMap::new();
+        map.insert(
+            "mpris:trackid",
+            Value::from(ObjectPath::from_static_str_unchecked(TRACK_ID)),
+        );
+        if let Some(length) = self.length {
+            map.insert("mpris:length", Value::from(length));
+        }
+        let metadata = &self.metadata;
+        if let Some(title) = &metadata.title {
+            map.insert("xesam:title", Value::from(title.clone()));
+        }
+        if let Some(artist) = &metadata.artist {
+            map.insert("xesam:artist", Value::from(vec![artist.clone()]));
+        }
+        if let Some(album) = &metadata.album {
+            map.insert("xesam:album", Value::from(album.clone()));
+        }
+        if let Some(art_url) = &metadata.art_url {
+            map.insert("mpris:artUrl", Value::from(art_url.clone()));
+        }
+        map
+    }
+
+    #[zbus(property(emits_changed_signal = "false"))]
+    fn position(&self) -> i64 {
+        self.position
+    }
+
+    #[zbus(property)]
+    fn can_go_next(&self) -> bool {
+        true
+    }
+
+    #[zbus(property)]
+    fn can_go_previous(&self) -> bool {
+        true
+    }
+
+    #[zbus(property)]
+    fn can_play(&self) -> bool {
+        true
+    }
+
+    #[zbus(property)]
+    fn can_pause(&self) -> bool {
+        true
+    }
+
+    #[zbus(property)]
+    fn can_seek(&self) -> bool {
+        false
+    }
+
+    #[zbus(property(emits_changed_signal = "const"))]
+    fn can_control(&self) -> bool {
+        true
+    }
+}
+
+/// The player, registered for as long as this lives.
+pub struct Mpris {
+    connection: Connection,
+}
+
+impl Mpris {
+    /// Register as `org.mpris.MediaPlayer2.<app>.instance<pid>`, the name
+    /// the spec reserves for players that run more than once.
+    pub fn start(app: &str, on_action: ActionSink) -> zbus::Result<Self> {
+        let app: String = app
+            .chars()
+            .filter(|c| c.is_ascii_alphanumeric() || *c == '_')
+            .collect();
+        let name = format!(
+            "org.mpris.MediaPlayer2.{app}.instance{}",
+            std::process::id()
+        );
+        let player = Player {
+            playing: false,
+            position: 0,
+            length: None,
+            metadata: Metadata::default(),
+            on_action,
+        };
+        let connection = Builder::session()?
+            .name(name)?
+            .serve_at(PATH, Root { identity: app })?
+            .serve_at(PATH, player)?
+            .build()?;
+        Ok(Self { connection })
+    }
+
+    pub fn set_playback(
+        &self,
+        playing: bool,
+        position_ms: Option<f64>,
+        duration_ms: Option<f64>,
+    ) -> zbus::Result<()> {
+        let player = self
+            .connection
+            .object_server()
+            .interface::<_, Player>(PATH)?;
+        let mut state = player.get_mut();
+        if let Some(position) = position_ms {
+            state.position = (position * 1000.0) as i64;
+        }
+        let length = duration_ms.map(|duration| (duration * 1000.0) as i64);
+        let emitter = player.signal_emitter();
+        if state.length != length {
+            state.length = length;
+            zbus::block_on(state.metadata_changed(emitter))?;
+        }
+        if state.playing != playing {
+            state.playing = playing;
+            zbus::block_on(state.playback_status_changed(emitter))?;
+        }
+        Ok(())
+    }
+
+    pub fn set_metadata(&self, metadata: Metadata) -> zbus::Result<()> {
+        let player = self
+            .connection
+            .object_server()
+            .interface::<_, Player>(PATH)?;
+        let mut state = player.get_mut();
+        if state.metadata != metadata {
+            state.metadata = metadata;
+            zbus::block_on(state.metadata_changed(player.signal_emitter()))?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,297 @@
+//! Pronunciation lexicons in the W3C PLS format, one file per language in
+//! `$APPDATA/lexicons`. Every engine takes text, so lexemes apply through
+//! their `<alias>`; those with only a `<phoneme>` are skipped.
+
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+
+use crate::ssml::{attr, tokens, Token};
+
+/// What a new lexicon file starts as.
+const TEMPLATE: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<lexicon version="1.0" xmlns="http://www.w3.org/2005/01/pronunciation-lexicon" alphabet="ipa">
+</lexicon>
+"#;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Lexeme {
+    pub graphemes: Vec<String>,
+    pub alias: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Lexicon {
+    /// The `xml:lang` of the lexicon; without one, it applies to any text.
+    pub lang: Option<String>,
+    pub lexemes: Vec<Lexeme>,
+}
+
+fn invalid(message: &str) -> crate::Error {
+    crate::Error::NativeTTSError(format!("Invalid lexicon: {message}"))
+}
+
+fn primary(tag: &str) -> String {
+    tag.split(['-', '_'])
+        .next()
+        .unwrap_or_default()
+        .to_lowercase()
+}
+
+impl Lexicon {
+    pub fn parse(pls: &str) -> crate::Result<Self> {
+        let mut lexicon = Lexicon::default();
+        let mut seen_root = false;
+        let mut lexeme: Option<(Vec<String>, Option<String>)> = None;
+        let mut element = "";
+        for token in tokens(pls)? {
+            match token {
+                Token::Open { name, attrs, empty } => {
+                    match name {
+                        "lexicon" => {
+                            seen_root = true;
+                            lexicon.lang = attr(&attrs, "xml:lang").map(str::to_string);
+                        }
+                        "lexeme" => lexeme = Some((Vec::new(), None)),
+                        _ => {}
+                    }
+                    if !empty {
+                        element = name;
+                    }
+                }
+                Token::Close(name) => {
+                    if name == "lexeme" {
+                        if let Some((graphemes, Some(alias))) = lexeme.take() {
+                            if !graphemes.is_empty() {
+                                lexicon.lexemes.push(Lexeme { graphemes, alias });
+                            }
+                        }
+                    }
+                    element = "";
+                }
+                Token::Text(text) => {
+                    let text = text.trim();
+                    let Some((graphemes, alias)) = lexeme.as_mut() else {
+                        continue;
+                    };
+                    match element {
+                        "grapheme" if !text.is_empty() => graphemes.push(text.to_string()),
+                        "alias" => *alias = Some(text.to_string()),
+                        _ => {}
+                    }
+                }
+            }
+        }
+        if !seen_root {
+            return Err(invalid("no <lexicon> element"));
+        }
+        Ok(lexicon)
+    }
+
+    pub fn applies_to(&self, lang: Option<&str>) -> bool {
+        match (&self.lang, lang) {
+            (None, _) => true,
+            (Some(own), Some(lang)) => primary(own) == primary(lang),
+            (Some(_), None) => false,
+        }
+    }
+}
+
+/// Replace whole words that match a grapheme by its alias. Longer
+/// graphemes win over their prefixes.
+pub fn apply(lexicons: &[Lexicon], lang: Option<&str>, text: &str) -> String {
+    let mut entries: Vec<(&str, &str)> = lexicons
+        .iter()
+        .filter(|lexicon| lexicon.applies_to(lang))
+        .flat_map(|lexicon| &lexicon.lexemes)
+        .flat_map(|lexeme| {
+            lexeme
+                .graphemes
+                .iter()
+                .map(|grapheme| (grapheme.as_str(), lexeme.alias.as_str()))
+        })
+        .collect();
+    if entries.is_empty() {
+        return text.to_string();
+    }
+    entries.sort_by_key(|(grapheme, _)| std::cmp::Reverse(grapheme.len()));
+
+    let is_word = |c: char| c.is_alphanumeric();
+    let mut out = String::with_capacity(text.len());
+    let mut i = 0;
+    'scan: while let Some(c) = text[i..].chars().next() {
+        if !text[..i].chars().next_back().is_some_and(is_word) {
+            for (grapheme, alias) in &entries {
+                let end = i + grapheme.len();
+                if text[i..].starts_with(grapheme)
+                    && !text[end..].chars().next().is_some_and(is_word)
+                {
+                    out.push_str(alias);
+                    i = end;
+                    continue 'scan;
+                }
+            }
+        }
+        out.push(c);
+        i += c.len_utf8();
+    }
+    out
+}
+
+/// Lexicon files and when they were last modified.
+type Files = Vec<(PathBuf, SystemTime)>;
+
+/// The lexicon files, reparsed when they change.
+pub struct Lexicons {
+    dir: PathBuf,
+    loaded: Mutex<(Files, Arc<Vec<Lexicon>>)>,
+}
+
+impl Lexicons {
+    pub fn new(dir: PathBuf) -> Self {
+        Self {
+            dir,
+            loaded: Mutex::new((Vec::new(), Arc::new(Vec::new()))),
+        }
+    }
+
+    fn files(&self) -> Files {
+        let mut files: Vec<_> = std::fs::read_dir(&self.dir)
+            .into_iter()
+            .flatten()
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == "pls"))
+            .filter_map(|path| {
+                let modified = path.metadata().and_then(|m| m.modified()).ok()?;
+                Some((path, modified))
+            })
+            .collect();
+        files.sort();
+        files
+    }
+
+    /// The valid lexicons; broken files are ignored until fixed.
+    pub fn get(&self) -> Arc<Vec<Lexicon>> {
+        let files = self.files();
+        let mut loaded = self.loaded.lock().unwrap();
+        if loaded.0 != files {
+            let lexicons = files
+                .iter()
+                .filter_map(|(path, _)| std::fs::read_to_string(path).ok())
+                .filter_map(|pls| Lexicon::parse(&pls).ok())
+                .collect();
+            *loaded = (files, Arc::new(lexicons));
+        }
+        loaded.1.clone()
+    }
+
+    fn path(&self, lang: Option<&str>) -> PathBuf {
+        let name: String = lang
+            .unwrap_or("default")
+            .chars()
+            .filter(|c| c.is_ascii_alphanumeric() || *c == '-')
+            .collect();
+        self.dir.join(format!("{name}.pls"))
+    }
+
+    /// The lexicon of a language, or of all when `lang` is `None`.
+    pub fn read(&self, lang: Option<&str>) -> crate::Result<String> {
+        match std::fs::read_to_string(self.path(lang)) {
+            Ok(pls) => Ok(pls),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(template(lang)),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    pub fn write(&self, lang: Option<&str>, pls: &str) -> crate::Result<()> {
+        Lexicon::parse(pls)?;
+        std::fs::create_dir_all(&self.dir)?;
+        write(&self.path(lang), pls)
+    }
+}
+
+fn template(lang: Option<&str>) -> String {
+    match lang {
+        Some(lang) => TEMPLATE.replacen(
+            "alphabet=\"ipa\"",
+            &format!(
+                "alphabet=\"ipa\" xml:lang=\"{}\"",
+                crate::ssml::escape(lang)
+            ),
+            1,
+        ),
+        None => TEMPLATE.to_string(),
+    }
+}
+
+fn write(path: &Path, pls: &str) -> crate::Result<()> {
+    let tmp = path.with_extension("tmp");
+    std::fs::write(&tmp, pls)?;
+    std::fs::rename(tmp, path)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PLS: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+        <lexicon version="1.0" xmlns="http://www.w3.org/2005/01/pronunciation-lexicon"
+                 alphabet="ipa" xml:lang="en-IN">
+          <!-- Names the voices stumble on -->
+          <lexeme>
+            <grapheme>HackXIndia</grapheme>
+            <grapheme>HXI</grapheme>
+            <alias>Hack X India</alias>
+          </lexeme>
+          <lexeme>
+            <grapheme>Hack</grapheme>
+            <alias>Hak</alias>
+          </lexeme>
+          <lexeme>
+            <grapheme>tomato</grapheme>
+            <phoneme>təˈmɑːtoʊ</phoneme>
+          </lexeme>
+          <lexeme><grapheme>C++</grapheme><alias>C plus plus</alias></lexeme>
+        </lexicon>"#;
+
+    #[test]
+    fn parses_aliases() {
+        let lexicon = Lexicon::parse(PLS).unwrap();
+        assert_eq!(lexicon.lang.as_deref(), Some("en-IN"));
+        assert_eq!(lexicon.lexemes.len(), 3);
+        assert_eq!(lexicon.lexemes[0].graphemes, ["HackXIndia", "HXI"]);
+        assert!(Lexicon::parse("<speak>Hi</speak>").is_err());
+        assert!(Lexicon::parse("<lexicon").is_err());
+    }
+
+    #[test]
+    fn replaces_whole_words() {
+        let lexicons = [Lexicon::parse(PLS).unwrap()];
+        let apply = |lang, text| apply(&lexicons, lang, text);
+        assert_eq!(
+            apply(Some("en-US"), "HackXIndia (HXI) loves C++, Hack, Hacker."),
+            "Hack X India (Hack X India) loves C plus plus, Hak, Hacker."
+        );
+        assert_eq!(apply(Some("fr"), "HXI"), "HXI");
+        assert_eq!(apply(None, "HXI"), "HXI");
+    }
+
+    #[test]
+    fn files() {
+        let dir = std::env::temp_dir().join(format!("tts-lexicons-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let lexicons = Lexicons::new(dir.clone());
+        assert!(lexicons.get().is_empty());
+        assert!(lexicons
+            .read(Some("hi"))
+            .unwrap()
+            .contains("xml:lang=\"hi\""));
+        assert!(lexicons.write(Some("en"), "not a lexicon").is_err());
+        lexicons.write(Some("en"), PLS).unwrap();
+        assert_eq!(lexicons.read(Some("en")).unwrap(), PLS);
+        assert_eq!(lexicons.get().len(), 1);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,869 @@
+//! Text as the voices should say it. The reader's pronunciation lexicons
+//! apply first; then what voices misread in print is written out: currency
+//! amounts, dates, ordinals, numbers grouped in lakhs and crores, Roman
+//! numerals, chemical formulas, units and abbreviations.
+
+mod lexicon;
+mod numbers;
+mod words;
+
+use std::borrow::Cow;
+use std::path::PathBuf;
+
+use crate::ssml::{Document, Item};
+use words::Abbreviation;
+
+pub use lexicon::Lexicons;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Language {
+    English,
+    German,
+    French,
+    Spanish,
+    Hindi,
+    Other,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Locale {
+    pub language: Language,
+    /// Numeric dates put the month first, as in the US.
+    pub month_first: bool,
+    /// Large numbers are named in lakhs and crores.
+    pub indian: bool,
+}
+
+impl Locale {
+    /// The locale of a BCP 47 tag like `en-IN`.
+    pub fn new(tag: &str) -> Self {
+        let mut subtags = tag.split(['-', '_']);
+        let language = match subtags.next().unwrap_or_default().to_lowercase().as_str() {
+            "en" => Language::English,
+            "de" => Language::German,
+            "fr" => Language::French,
+            "es" => Language::Spanish,
+            "hi" => Language::Hindi,
+            _ => Language::Other,
+        };
+        let region = subtags
+            .find(|subtag| subtag.len() == 2)
+            .map(str::to_uppercase);
+        Self {
+            language,
+            month_first: language == Language::English && region.as_deref() == Some("US"),
+            indian: language == Language::Hindi || region.as_deref() == Some("IN"),
+        }
+    }
+
+    fn decimal_separator(&self) -> char {
+        match self.language {
+            Language::German | Language::French | Language::Spanish => ',',
+            _ => '.',
+        }
+    }
+}
+
+pub struct Normalizer {
+    lexicons: Lexicons,
+}
+
+impl Normalizer {
+    pub fn new(lexicons_dir: PathBuf) -> Self {
+        Self {
+            lexicons: Lexicons::new(lexicons_dir),
+        }
+    }
+
+    pub fn lexicons(&self) -> &Lexicons {
+        &self.lexicons
+    }
+
+    /// Normalize the text of `document`, in the language of each span or
+    /// else `lang`, the voice's. Marks stay where they were.
+    pub fn document(&self, document: &mut Document, lang: Option<&str>) {
+        let lexicons = self.lexicons.get();
+        for item in &mut document.items {
+            if let Item::Text(span) = item {
+                let lang = match (span.lang.as_deref(), lang) {
+                    // The voice adds its region to a span in its language.
+                    (Some(own), Some(voice))
+                        if Locale::new(own).language == Locale::new(voice).language =>
+                    {
+                        Some(voice)
+                    }
+                    (own, voice) => own.or(voice),
+                };
+                let text = lexicon::apply(&lexicons, lang, &span.text);
+                span.text = normalize(&text, Locale::new(lang.unwrap_or_default()));
+            }
+        }
+    }
+}
+
+/// `text` with what its voices would misread written out in words.
+pub fn normalize(text: &str, locale: Locale) -> String {
+    if locale.language == Language::Other {
+        return text.to_string();
+    }
+    let text = phrases(text, locale.language);
+    let (leading, tokens) = tokenize(&text);
+    let mut out = String::with_capacity(text.len());
+    out.push_str(leading);
+    let mut i = 0;
+    while i < tokens.len() {
+        let token = &tokens[i];
+        out.push_str(token.lead);
+        match rewrite(&tokens, i, locale) {
+            Some(rewrite) => {
+                out.push_str(&rewrite.text);
+                out.push_str(&rewrite.trail);
+                i += rewrite.consumed;
+            }
+            None => {
+                out.push_str(token.core);
+                out.push_str(token.trail);
+                i += 1;
+            }
+        }
+        out.push_str(tokens[i - 1].space);
+    }
+    out
+}
+
+/// Opening and closing punctuation around a word.
+const LEAD: &[char] = &['(', '[', '"', '\'', '“', '‘', '«', '¿', '¡'];
+const TRAIL: &[char] = &[
+    ')', ']', '"', '\'', '”', '’', '»', ',', ';', ':', '!', '?', '.', '…',
+];
+
+/// A word between whitespace, split from its punctuation.
+struct Token<'a> {
+    lead: &'a str,
+    core: &'a str,
+    trail: &'a str,
+    /// The whitespace after it.
+    space: &'a str,
+}
+
+impl Token<'_> {
+    fn keep(&self, text: String) -> Rewrite {
+        Rewrite {
+            text,
+            consumed: 1,
+            trail: self.trail.to_string(),
+        }
+    }
+
+    fn starts_with_digit(&self) -> bool {
+        self.core.starts_with(|c: char| c.is_ascii_digit())
+    }
+}
+
+/// What replaces `consumed` tokens, from the core of the first to the
+/// trailing punctuation of the last.
+struct Rewrite {
+    text: String,
+    consumed: usize,
+    trail: String,
+}
+
+fn tokenize(text: &str) -> (&str, Vec<Token<'_>>) {
+    let start = text.len() - text.trim_start().len();
+    let mut rest = &text[start..];
+    let mut tokens = Vec::new();
+    while !rest.is_empty() {
+        let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+        let (word, after) = rest.split_at(end);
+        let (space, after) = after.split_at(after.len() - after.trim_start().len());
+        rest = after;
+        let core_start = word.len() - word.trim_start_matches(LEAD).len();
+        let core_end = word.trim_end_matches(TRAIL).len().max(core_start);
+        tokens.push(Token {
+            lead: &word[..core_start],
+            core: &word[core_start..core_end],
+            trail: &word[core_end..],
+            space,
+        });
+    }
+    (&text[..start], tokens)
+}
+
+fn rewrite(tokens: &[Token], i: usize, locale: Locale) -> Option<Rewrite> {
+    let token = &tokens[i];
+    if token.core.is_empty() {
+        return None;
+    }
+    currency(tokens, i, locale)
+        .or_else(|| ordinal(tokens, i, locale))
+        .or_else(|| date(tokens, i, locale))
+        .or_else(|| roman(tokens, i, locale))
+        .or_else(|| unit(tokens, i, locale))
+        .or_else(|| formula(token.core, locale).map(|text| token.keep(text)))
+        .or_else(|| grouped(token.core, locale).map(|text| token.keep(text)))
+        .or_else(|| abbreviation(tokens, i, locale))
+}
+
+/// Abbreviations of several words, like `p. ex.`, which tokens would split.
+fn phrases(text: &str, language: Language) -> Cow<'_, str> {
+    let mut text = Cow::Borrowed(text);
+    for (short, long, _) in words::abbreviations(language) {
+        if !short.contains(' ') || !text.contains(short) {
+            continue;
+        }
+        let mut out = String::with_capacity(text.len());
+        let mut rest = text.as_ref();
+        while let Some(at) = rest.find(short) {
+            let (before, after) = rest.split_at(at);
+            out.push_str(before);
+            let starts_word = !out.chars().next_back().is_some_and(char::is_alphanumeric);
+            out.push_str(if starts_word { long } else { short });
+            rest = &after[short.len()..];
+        }
+        out.push_str(rest);
+        text = Cow::Owned(out);
+    }
+    text
+}
+
+/// A written number: its integer part, the digits after the decimal
+/// separator, and whether it was grouped in lakhs and crores.
+struct Amount {
+    integer: u64,
+    fraction: String,
+    indian: bool,
+}
+
+impl Amount {
+    fn is_one(&self) -> bool {
+        self.integer == 1 && self.fraction.is_empty()
+    }
+}
+
+fn amount(text: &str, locale: Locale) -> Option<Amount> {
+    let decimal = locale.decimal_separator();
+    let group = if decimal == ',' { '.' } else { ',' };
+    let (integer, fraction) = match text.split_once(decimal) {
+        Some((_, "")) => return None,
+        Some((integer, fraction)) => (integer, fraction),
+        None => (text, ""),
+    };
+    if !fraction.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    let groups: Vec<&str> = integer.split(group).collect();
+    if !groups
+        .iter()
+        .all(|g| !g.is_empty() && g.chars().all(|c| c.is_ascii_digit()))
+    {
+        return None;
+    }
+    let (first, rest) = groups.split_first()?;
+    let indian = match rest.split_last() {
+        None => false,
+        Some((last, _)) if last.len() != 3 || first.len() > 3 => return None,
+        Some((_, middle)) if middle.iter().all(|g| g.len() == 3) => false,
+        Some((_, middle)) if middle.iter().all(|g| g.len() == 2) && first.len() <= 2 => true,
+        Some(_) => return None,
+    };
+    let digits = groups.concat();
+    if digits.len() > 18 {
+        return None;
+    }
+    Some(Amount {
+        integer: digits.parse().ok()?,
+        fraction: fraction.to_string(),
+        indian,
+    })
+}
+
+/// `n` in words where the voices need them, else in plain digits.
+fn cardinal(n: u64, locale: Locale, indian: bool) -> String {
+    numbers::cardinal(n, locale.language, indian || locale.indian).unwrap_or_else(|| n.to_string())
+}
+
+/// A number with its decimals read digit by digit, as in `2.5 crore`.
+fn decimal(amount: &Amount, locale: Locale) -> String {
+    let integer = cardinal(amount.integer, locale, amount.indian);
+    if amount.fraction.is_empty() {
+        return integer;
+    }
+    let point = match locale.language {
+        Language::English => "point",
+        Language::German => "Komma",
+        Language::French => "virgule",
+        Language::Spanish => "coma",
+        Language::Hindi => "दशमलव",
+        Language::Other => return format!("{}.{}", amount.integer, amount.fraction),
+    };
+    let digits: Vec<String> = amount
+        .fraction
+        .chars()
+        .filter_map(|c| c.to_digit(10))
+        .map(|d| cardinal(d.into(), locale, false))
+        .collect();
+    format!("{integer} {point} {}", digits.join(" "))
+}
+
+/// A cardinal agreeing with the noun after it: `eine Rupie`, `une livre`,
+/// `un dólar`.
+fn before_noun(words: String, language: Language, feminine: bool) -> String {
+    match language {
+        Language::German if words == "eins" => if feminine { "eine" } else { "ein" }.to_string(),
+        Language::French if feminine => match words.strip_suffix("un") {
+            Some(head) => format!("{head}une"),
+            None => words,
+        },
+        Language::Spanish => numbers::apocope(&words, feminine),
+        _ => words,
+    }
+}
+
+/// `₹500`, `Rs. 1,50,000/-`, `$3 million`, `12,50 €`.
+fn currency(tokens: &[Token], i: usize, locale: Locale) -> Option<Rewrite> {
+    let token = &tokens[i];
+    let next = tokens.get(i + 1).filter(|next| next.lead.is_empty());
+    let (currency, number, mut consumed) = match words::currency_prefix(token.core) {
+        Some((currency, "")) if matches!(token.trail, "" | ".") => (currency, next?.core, 2),
+        Some((_, "")) => return None,
+        Some((currency, rest)) => (currency, rest, 1),
+        None => {
+            let next = next.filter(|_| token.trail.is_empty())?;
+            (words::currency(next.core)?, token.core, 2)
+        }
+    };
+    let names = words::currency_names(currency, locale.language)?;
+    let amount = amount(number.strip_suffix("/-").unwrap_or(number), locale)?;
+    let language = locale.language;
+
+    let last = &tokens[i + consumed - 1];
+    let scale = tokens
+        .get(i + consumed)
+        .filter(|scale| last.trail.is_empty() && scale.lead.is_empty())
+        .filter(|scale| words::is_scale(scale.core, language));
+    let text = if let Some(scale) = scale {
+        consumed += 1;
+        format!("{} {} {}", decimal(&amount, locale), scale.core, names.many)
+    } else {
+        let minor = match amount.fraction.len() {
+            0 => 0,
+            1 => amount.fraction.parse::<u64>().ok()? * 10,
+            2 => amount.fraction.parse().ok()?,
+            _ => return None,
+        };
+        let major = format!(
+            "{} {}",
+            before_noun(
+                cardinal(amount.integer, locale, amount.indian),
+                language,
+                names.feminine
+            ),
+            if amount.integer == 1 {
+                names.one
+            } else {
+                names.many
+            }
+        );
+        let minor_words = format!(
+            "{} {}",
+            before_noun(cardinal(minor, locale, false), language, false),
+            if minor == 1 {
+                names.minor_one
+            } else {
+                names.minor_many
+            }
+        );
+        let and = match language {
+            Language::English => " and ",
+            Language::German => " und ",
+            Language::French => " et ",
+            Language::Spanish => " con ",
+            Language::Hindi | Language::Other => " ",
+        };
+        match (amount.integer, minor) {
+            (_, 0) => major,
+            (0, _) => minor_words,
+            _ => format!("{major}{and}{minor_words}"),
+        }
+    };
+    Some(Rewrite {
+        text,
+        trail: tokens[i + consumed - 1].trail.to_string(),
+        consumed,
+    })
+}
+
+/// German ordinals after `am`, `vom` and the like decline: `am ersten Mai`.
+fn declined(ordinal: String, tokens: &[Token], i: usize, locale: Locale) -> String {
+    let dative = i > 0
+        && matches!(
+            tokens[i - 1].core.to_lowercase().as_str(),
+            "am" | "vom" | "zum" | "im" | "dem" | "den" | "bis"
+        );
+    match ordinal.strip_suffix('r') {
+        Some(stem) if locale.language == Language::German && dative => format!("{stem}n"),
+        _ => ordinal,
+    }
+}
+
+/// `1st`, `1er`, `2e`, `1º`, `1ª`, German `1.` before a month, and French
+/// centuries and monarchs like `XIXe` and `Ier`.
+fn ordinal(tokens: &[Token], i: usize, locale: Locale) -> Option<Rewrite> {
+    let token = &tokens[i];
+    let language = locale.language;
+    let split = token
+        .core
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(token.core.len());
+    let (digits, suffix) = token.core.split_at(split);
+    if digits.is_empty() {
+        if language != Language::French {
+            return None;
+        }
+        let (numeral, feminine) = ["ère", "re", "er", "ème", "e"]
+            .iter()
+            .find_map(|suffix| Some((token.core.strip_suffix(suffix)?, suffix.ends_with("re"))))?;
+        if !numeral.chars().all(|c| matches!(c, 'I' | 'V' | 'X')) {
+            return None;
+        }
+        let n = roman_value(numeral)?;
+        return Some(token.keep(numbers::ordinal(n, language, feminine)?));
+    }
+    let n: u64 = digits.parse().ok().filter(|n| *n > 0)?;
+    let feminine = match (language, suffix.to_lowercase().as_str()) {
+        (Language::English, "st" | "nd" | "rd" | "th") => false,
+        (Language::French, "er" | "e" | "ème" | "è") => false,
+        (Language::French, "re" | "ère") => true,
+        (Language::Spanish, "º" | ".º" | "°") => false,
+        (Language::Spanish, "ª" | ".ª") => true,
+        (Language::German, "") => {
+            let trail = token.trail.strip_prefix('.')?;
+            let months = words::months(language)?;
+            if !months.contains(&tokens.get(i + 1)?.core) {
+                return None;
+            }
+            let ordinal = declined(numbers::ordinal(n, language, false)?, tokens, i, locale);
+            return Some(Rewrite {
+                text: ordinal,
+                consumed: 1,
+                trail: trail.to_string(),
+            });
+        }
+        _ => return None,
+    };
+    Some(token.keep(numbers::ordinal(n, language, feminine)?))
+}
+
+/// `2024-01-26`, and `26/01/2024` or `26.01.2024` in day, month, year
+/// order, or month first where that is the custom.
+fn date(tokens: &[Token], i: usize, locale: Locale) -> Option<Rewrite> {
+    let token = &tokens[i];
+    let months = words::months(locale.language)?;
+    let separator = token.core.chars().find(|c| matches!(c, '-' | '/' | '.'))?;
+    let parts: Vec<&str> = token.core.split(separator).collect();
+    if parts.len() != 3
+        || !parts
+            .iter()
+            .all(|p| (1..=4).contains(&p.len()) && p.chars().all(|c| c.is_ascii_digit()))
+    {
+        return None;
+    }
+    let number = |i: usize| parts[i].parse::<u64>().ok();
+    let (year, mut month, mut day) = if parts[0].len() == 4 && separator == '-' {
+        (parts[0], number(1)?, number(2)?)
+    } else if parts[2].len() == 4 && parts[0].len() <= 2 && parts[1].len() <= 2 {
+        if locale.month_first {
+            (parts[2], number(0)?, number(1)?)
+        } else {
+            (parts[2], number(1)?, number(0)?)
+        }
+    } else {
+        return None;
+    };
+    if month > 12 && day <= 12 {
+        std::mem::swap(&mut month, &mut day);
+    }
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+        return None;
+    }
+    let month = months[month as usize - 1];
+    let language = locale.language;
+    let text = match language {
+        Language::English => {
+            let day = numbers::ordinal(day, language, false)?;
+            if locale.month_first {
+                format!("{month} {day}, {year}")
+            } else {
+                format!("{day} {month} {year}")
+            }
+        }
+        Language::German => {
+            let day = declined(numbers::ordinal(day, language, false)?, tokens, i, locale);
+            format!("{day} {month} {year}")
+        }
+        Language::French => {
+            let day = match day {
+                1 => "premier".to_string(),
+                _ => cardinal(day, locale, false),
+            };
+            format!("{day} {month} {year}")
+        }
+        Language::Spanish => {
+            let day = match day {
+                1 => "primero".to_string(),
+                _ => cardinal(day, locale, false),
+            };
+            format!("{day} de {month} de {year}")
+        }
+        Language::Hindi => format!("{} {month} {year}", cardinal(day, locale, false)),
+        Language::Other => return None,
+    };
+    Some(token.keep(text))
+}
+
+const NUMERALS: [(u64, &str); 13] = [
+    (1000, "M"),
+    (900, "CM"),
+    (500, "D"),
+    (400, "CD"),
+    (100, "C"),
+    (90, "XC"),
+    (50, "L"),
+    (40, "XL"),
+    (10, "X"),
+    (9, "IX"),
+    (5, "V"),
+    (4, "IV"),
+    (1, "I"),
+];
+
+/// The value of a Roman numeral written the standard way.
+fn roman_value(numeral: &str) -> Option<u64> {
+    if numeral.is_empty() || numeral.len() > 15 {
+        return None;
+    }
+    let mut rest = numeral;
+    let mut value = 0;
+    for (n, letters) in NUMERALS {
+        while let Some(after) = rest.strip_prefix(letters) {
+            rest = after;
+            value += n;
+        }
+    }
+    // Only the standard spelling of the value counts, so not `IIII`.
+    let mut standard = String::new();
+    let mut left = value;
+    for (n, letters) in NUMERALS {
+        while left >= n {
+            standard.push_str(letters);
+            left -= n;
+        }
+    }
+    (rest.is_empty() && standard == numeral).then_some(value)
+}
+
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+/// `Chapter IV`, and monarchs and popes like `Henry VIII`.
+fn roman(tokens: &[Token], i: usize, locale: Locale) -> Option<Rewrite> {
+    let token = &tokens[i];
+    let prev = tokens[..i]
+        .last()
+        .filter(|prev| prev.trail.is_empty() && token.lead.is_empty())?;
+    let value = roman_value(token.core)?;
+    let language = locale.language;
+    if words::is_roman_keyword(&prev.core.to_lowercase(), language) {
+        return Some(token.keep(cardinal(value, locale, false)));
+    }
+    let mut name = prev.core.chars();
+    let is_name = name.next().is_some_and(char::is_uppercase)
+        && name.clone().next().is_some()
+        && name.all(char::is_lowercase);
+    // Single letters are too often initials or words.
+    if !is_name || token.core.len() < 2 || !token.core.chars().all(|c| matches!(c, 'I' | 'V' | 'X'))
+    {
+        return None;
+    }
+    let text = match language {
+        Language::English => format!(
+            "the {}",
+            capitalize(&numbers::ordinal(value, language, false)?)
+        ),
+        // The period after `XIV.` marks the ordinal; it only ends the sentence
+        // too when nothing or a capital follows.
+        Language::German => {
+            let ordinal = numbers::ordinal(value, language, false)?;
+            let text = format!("der {}", capitalize(ordinal.strip_suffix('r')?));
+            let ends_sentence = tokens
+                .get(i + 1)
+                .map_or(true, |next| next.core.starts_with(char::is_uppercase));
+            return Some(match token.trail.strip_prefix('.') {
+                Some(trail) if !ends_sentence => Rewrite {
+                    text,
+                    consumed: 1,
+                    trail: trail.to_string(),
+                },
+                _ => token.keep(text),
+            });
+        }
+        Language::French if value == 1 => "premier".to_string(),
+        Language::French => cardinal(value, locale, false),
+        Language::Spanish if value <= 10 => numbers::ordinal(value, language, false)?,
+        Language::Spanish => cardinal(value, locale, false),
+        Language::Hindi | Language::Other => return None,
+    };
+    Some(token.keep(text))
+}
+
+/// A unit after a number, as in `5 km` or `37°C`. The number stays.
+fn unit(tokens: &[Token], i: usize, locale: Locale) -> Option<Rewrite> {
+    let token = &tokens[i];
+    let name = |amount: Amount, symbol| {
+        let (one, many) = words::unit(symbol, locale.language)?;
+        Some(if amount.is_one() { one } else { many })
+    };
+    if let Some(prev) = tokens[..i]
+        .last()
+        .filter(|prev| prev.trail.is_empty() && token.lead.is_empty())
+    {
+        if let Some(name) = amount(prev.core, locale).and_then(|amount| name(amount, token.core)) {
+            return Some(token.keep(name.to_string()));
+        }
+    }
+    let split = token
+        .core
+        .find(|c: char| !(c.is_ascii_digit() || c == ',' || c == '.'))
+        .filter(|split| *split > 0)?;
+    let (number, symbol) = token.core.split_at(split);
+    let name = name(amount(number, locale)?, symbol)?;
+    Some(token.keep(format!("{number} {name}")))
+}
+
+/// A chemical formula like `H2SO4` or `CO₂`, as its elements and counts.
+fn formula(core: &str, locale: Locale) -> Option<String> {
+    let core: String = core
+        .chars()
+        .map(|c| match c {
+            '₀'..='₉' => char::from_digit(c as u32 - '₀' as u32, 10).unwrap_or(c),
+            c => c,
+        })
+        .collect();
+    let mut parts = Vec::new();
+    let mut has_count = false;
+    let mut chars = core.chars().peekable();
+    while let Some(c) = chars.next() {
+        if !c.is_ascii_uppercase() {
+            return None;
+        }
+        let mut symbol = c.to_string();
+        if let Some(lower) = chars.next_if(char::is_ascii_lowercase) {
+            symbol.push(lower);
+        }
+        if !words::ELEMENTS.contains(&symbol.as_str()) {
+            return None;
+        }
+        // Letter by letter, so that `Na` is not read as a word.
+        parts.extend(symbol.chars().map(|c| c.to_ascii_uppercase().to_string()));
+        let mut count = String::new();
+        while let Some(digit) = chars.next_if(char::is_ascii_digit) {
+            count.push(digit);
+        }
+        if !count.is_empty() {
+            has_count = true;
+            parts.push(cardinal(count.parse().ok()?, locale, false));
+        }
+    }
+    has_count.then(|| parts.join(" "))
+}
+
+/// Numbers grouped in lakhs and crores, as in `1,50,000`.
+fn grouped(core: &str, locale: Locale) -> Option<String> {
+    if !matches!(locale.language, Language::English | Language::Hindi) {
+        return None;
+    }
+    let amount = amount(core, locale).filter(|a| a.indian && a.fraction.is_empty())?;
+    Some(cardinal(amount.integer, locale, true))
+}
+
+fn abbreviation(tokens: &[Token], i: usize, locale: Locale) -> Option<Rewrite> {
+    let token = &tokens[i];
+    let next = tokens.get(i + 1);
+    let dotted = token
+        .trail
+        .strip_prefix('.')
+        .map(|trail| (format!("{}.", token.core), trail));
+    for (form, trail) in dotted
+        .into_iter()
+        .chain([(token.core.to_string(), token.trail)])
+    {
+        for &(short, long, kind) in words::abbreviations(locale.language) {
+            let long = if form == short {
+                long.to_string()
+            } else if short.starts_with(char::is_lowercase) && form == capitalize(short) {
+                capitalize(long)
+            } else {
+                continue;
+            };
+            let trail = match kind {
+                Abbreviation::BeforeNumber if !next.is_some_and(Token::starts_with_digit) => {
+                    continue
+                }
+                Abbreviation::BeforeNumber => trail.to_string(),
+                Abbreviation::Title => trail.strip_prefix('.').unwrap_or(trail).to_string(),
+                // The dot of `etc.` also ends the sentence before a capital.
+                Abbreviation::Plain => {
+                    let ends_sentence =
+                        next.map_or(true, |next| next.core.starts_with(char::is_uppercase));
+                    if form.ends_with('.') && ends_sentence && trail.is_empty() {
+                        format!(".{trail}")
+                    } else {
+                        trail.to_string()
+                    }
+                }
+            };
+            return Some(Rewrite {
+                text: long,
+                consumed: 1,
+                trail,
+            });
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn say(text: &str, tag: &str) -> String {
+        normalize(text, Locale::new(tag))
+    }
+
+    #[test]
+    fn locales() {
+        let us = Locale::new("en-US");
+        assert!(us.month_first && !us.indian);
+        let india = Locale::new("en_IN");
+        assert!(!india.month_first && india.indian);
+        assert_eq!(Locale::new("hi-IN").language, Language::Hindi);
+        assert_eq!(Locale::new("ja").language, Language::Other);
+        assert_eq!(say("$5 on 1/2/2024", "ja"), "$5 on 1/2/2024");
+    }
+
+    #[test]
+    fn english() {
+        assert_eq!(
+            say(
+                "Dr. Smith paid $1,250.50 on 2024-01-26, e.g. for H2SO4.",
+                "en-US"
+            ),
+            "Doctor Smith paid one thousand two hundred fifty dollars and fifty cents \
+             on January twenty-sixth, 2024, for example for H two S O four."
+        );
+        assert_eq!(
+            say("Chapter IV: Henry VIII and World War II.", "en-GB"),
+            "Chapter four: Henry the Eighth and World War two."
+        );
+        assert_eq!(
+            say(
+                "It was 37°C, 1 km and then 12 km, cats, dogs, etc. Then CO₂.",
+                "en"
+            ),
+            "It was 37 degrees Celsius, 1 kilometer and then 12 kilometers, cats, dogs, \
+             et cetera. Then C O two."
+        );
+        assert_eq!(
+            say("See p. 4 and the 2nd No. I said no.", "en"),
+            "See page 4 and the second No. I said no."
+        );
+        assert_eq!(say("(£0.05)", "en"), "(five pence)");
+        assert_eq!(say("I am a Dr.", "en"), "I am a Doctor");
+    }
+
+    #[test]
+    fn indian_english() {
+        assert_eq!(
+            say("Smt. Sharma paid ₹1,50,000/- w.e.f. 1st April.", "en-IN"),
+            "Shrimati Sharma paid one lakh fifty thousand rupees with effect from first April."
+        );
+        assert_eq!(
+            say(
+                "Rs. 2.5 crore for the Hon'ble Court; 12,00,000 voters on 26/01/2024",
+                "en-IN"
+            ),
+            "two point five crore rupees for the Honourable Court; \
+             twelve lakh voters on twenty-sixth January 2024"
+        );
+        assert_eq!(
+            say("₹1 and Rs.10.50", "en-IN"),
+            "one rupee and ten rupees and fifty paise"
+        );
+    }
+
+    #[test]
+    fn german() {
+        assert_eq!(
+            say("Am 1. Mai kostet es 12,50 €, z.B. bei Nr. 5.", "de-DE"),
+            "Am ersten Mai kostet es zwölf Euro und fünfzig Cent, zum Beispiel bei Nummer 5."
+        );
+        assert_eq!(
+            say("Ludwig XIV. starb am 01.09.1715 in Kapitel III", "de"),
+            "Ludwig der Vierzehnte starb am ersten September 1715 in Kapitel drei"
+        );
+        assert_eq!(say("₹1 und 3 kg", "de"), "eine Rupie und 3 Kilogramm");
+    }
+
+    #[test]
+    fn french() {
+        assert_eq!(
+            say("Le 1er mai, M. Dupont a payé 21 € au XIXe siècle.", "fr-FR"),
+            "Le premier mai, Monsieur Dupont a payé vingt et un euros au dix-neuvième siècle."
+        );
+        assert_eq!(
+            say("Louis XIV est né le 05/09/1638, p. ex. la 1re fois.", "fr"),
+            "Louis quatorze est né le cinq septembre 1638, par exemple la première fois."
+        );
+        assert_eq!(say("₹21", "fr"), "vingt et une roupies");
+        assert_eq!(say("Ce livre", "fr"), "Ce livre");
+    }
+
+    #[test]
+    fn spanish() {
+        assert_eq!(
+            say(
+                "El Sr. García pagó 21 € el 1/5/2024, p. ej. Felipe VI.",
+                "es-ES"
+            ),
+            "El Señor García pagó veintiún euros el primero de mayo de 2024, \
+             por ejemplo Felipe sexto."
+        );
+        assert_eq!(
+            say("₹1 en el capítulo XII, la 3ª vez", "es"),
+            "una rupia en el capítulo doce, la tercera vez"
+        );
+    }
+
+    #[test]
+    fn hindi() {
+        assert_eq!(
+            say("डॉ. शर्मा ने 26/01/2024 को ₹1,50,000 दिए", "hi-IN"),
+            "डॉक्टर शर्मा ने छब्बीस जनवरी 2024 को एक लाख पचास हज़ार रुपये दिए"
+        );
+        assert_eq!(say("₹2.5 करोड़", "hi"), "दो दशमलव पाँच करोड़ रुपये");
+    }
+
+    #[test]
+    fn roman_numerals() {
+        assert_eq!(roman_value("XIV"), Some(14));
+        assert_eq!(roman_value("MCMXCIX"), Some(1999));
+        assert_eq!(roman_value("IIII"), None);
+        assert_eq!(roman_value("IC"), None);
+        assert_eq!(roman_value("Mix"), None);
+    }
+}
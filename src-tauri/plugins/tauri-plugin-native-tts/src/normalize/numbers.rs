@@ -0,0 +1,688 @@
+//! Numbers as words, for the languages whose voices need them spelled out.
+
+use super::Language;
+
+const EN_ONES: [&str; 20] = [
+    "zero",
+    "one",
+    "two",
+    "three",
+    "four",
+    "five",
+    "six",
+    "seven",
+    "eight",
+    "nine",
+    "ten",
+    "eleven",
+    "twelve",
+    "thirteen",
+    "fourteen",
+    "fifteen",
+    "sixteen",
+    "seventeen",
+    "eighteen",
+    "nineteen",
+];
+const EN_TENS: [&str; 10] = [
+    "", "", "twenty", "thirty", "forty", "fifty", "sixty", "seventy", "eighty", "ninety",
+];
+
+const DE_ONES: [&str; 20] = [
+    "null",
+    "eins",
+    "zwei",
+    "drei",
+    "vier",
+    "fünf",
+    "sechs",
+    "sieben",
+    "acht",
+    "neun",
+    "zehn",
+    "elf",
+    "zwölf",
+    "dreizehn",
+    "vierzehn",
+    "fünfzehn",
+    "sechzehn",
+    "siebzehn",
+    "achtzehn",
+    "neunzehn",
+];
+const DE_TENS: [&str; 10] = [
+    "", "", "zwanzig", "dreißig", "vierzig", "fünfzig", "sechzig", "siebzig", "achtzig", "neunzig",
+];
+
+const FR_ONES: [&str; 17] = [
+    "zéro", "un", "deux", "trois", "quatre", "cinq", "six", "sept", "huit", "neuf", "dix", "onze",
+    "douze", "treize", "quatorze", "quinze", "seize",
+];
+const FR_TENS: [&str; 7] = [
+    "",
+    "dix",
+    "vingt",
+    "trente",
+    "quarante",
+    "cinquante",
+    "soixante",
+];
+
+const ES_ONES: [&str; 30] = [
+    "cero",
+    "uno",
+    "dos",
+    "tres",
+    "cuatro",
+    "cinco",
+    "seis",
+    "siete",
+    "ocho",
+    "nueve",
+    "diez",
+    "once",
+    "doce",
+    "trece",
+    "catorce",
+    "quince",
+    "dieciséis",
+    "diecisiete",
+    "dieciocho",
+    "diecinueve",
+    "veinte",
+    "veintiuno",
+    "veintidós",
+    "veintitrés",
+    "veinticuatro",
+    "veinticinco",
+    "veintiséis",
+    "veintisiete",
+    "veintiocho",
+    "veintinueve",
+];
+const ES_TENS: [&str; 10] = [
+    "",
+    "",
+    "",
+    "treinta",
+    "cuarenta",
+    "cincuenta",
+    "sesenta",
+    "setenta",
+    "ochenta",
+    "noventa",
+];
+const ES_HUNDREDS: [&str; 10] = [
+    "",
+    "ciento",
+    "doscientos",
+    "trescientos",
+    "cuatrocientos",
+    "quinientos",
+    "seiscientos",
+    "setecientos",
+    "ochocientos",
+    "novecientos",
+];
+const ES_ORDINALS: [&str; 11] = [
+    "", "primero", "segundo", "tercero", "cuarto", "quinto", "sexto", "séptimo", "octavo",
+    "noveno", "décimo",
+];
+
+/// Hindi has a word of its own for every number below a hundred.
+const HI_BELOW_100: [&str; 100] = [
+    "शून्य",
+    "एक",
+    "दो",
+    "तीन",
+    "चार",
+    "पाँच",
+    "छह",
+    "सात",
+    "आठ",
+    "नौ",
+    "दस",
+    "ग्यारह",
+    "बारह",
+    "तेरह",
+    "चौदह",
+    "पंद्रह",
+    "सोलह",
+    "सत्रह",
+    "अठारह",
+    "उन्नीस",
+    "बीस",
+    "इक्कीस",
+    "बाईस",
+    "तेईस",
+    "चौबीस",
+    "पच्चीस",
+    "छब्बीस",
+    "सत्ताईस",
+    "अट्ठाईस",
+    "उनतीस",
+    "तीस",
+    "इकतीस",
+    "बत्तीस",
+    "तैंतीस",
+    "चौंतीस",
+    "पैंतीस",
+    "छत्तीस",
+    "सैंतीस",
+    "अड़तीस",
+    "उनतालीस",
+    "चालीस",
+    "इकतालीस",
+    "बयालीस",
+    "तैंतालीस",
+    "चवालीस",
+    "पैंतालीस",
+    "छियालीस",
+    "सैंतालीस",
+    "अड़तालीस",
+    "उनचास",
+    "पचास",
+    "इक्यावन",
+    "बावन",
+    "तिरपन",
+    "चौवन",
+    "पचपन",
+    "छप्पन",
+    "सत्तावन",
+    "अट्ठावन",
+    "उनसठ",
+    "साठ",
+    "इकसठ",
+    "बासठ",
+    "तिरसठ",
+    "चौंसठ",
+    "पैंसठ",
+    "छियासठ",
+    "सड़सठ",
+    "अड़सठ",
+    "उनहत्तर",
+    "सत्तर",
+    "इकहत्तर",
+    "बहत्तर",
+    "तिहत्तर",
+    "चौहत्तर",
+    "पचहत्तर",
+    "छिहत्तर",
+    "सतहत्तर",
+    "अठहत्तर",
+    "उनासी",
+    "अस्सी",
+    "इक्यासी",
+    "बयासी",
+    "तिरासी",
+    "चौरासी",
+    "पचासी",
+    "छियासी",
+    "सत्तासी",
+    "अट्ठासी",
+    "नवासी",
+    "नब्बे",
+    "इक्यानवे",
+    "बानवे",
+    "तिरानवे",
+    "चौरानवे",
+    "पचानवे",
+    "छियानवे",
+    "सत्तानवे",
+    "अट्ठानवे",
+    "निन्यानवे",
+];
+
+/// Split `n` by descending scales, spelling each multiple with `words`.
+fn scaled(
+    n: u64,
+    scales: &[(u64, &str, &str)],
+    small: impl Fn(u64) -> String,
+    words: impl Fn(u64, &str, &str) -> String,
+) -> String {
+    let mut parts = Vec::new();
+    let mut rest = n;
+    for &(scale, one, many) in scales {
+        if rest >= scale {
+            parts.push(words(rest / scale, one, many));
+            rest %= scale;
+        }
+    }
+    if rest > 0 || parts.is_empty() {
+        parts.push(small(rest));
+    }
+    parts.join(" ")
+}
+
+fn en_below_1000(n: u64) -> String {
+    let (hundreds, rest) = (n / 100, (n % 100) as usize);
+    let tens = if rest < 20 {
+        EN_ONES[rest].to_string()
+    } else if rest % 10 == 0 {
+        EN_TENS[rest / 10].to_string()
+    } else {
+        format!("{}-{}", EN_TENS[rest / 10], EN_ONES[rest % 10])
+    };
+    match (hundreds, rest) {
+        (0, _) => tens,
+        (h, 0) => format!("{} hundred", EN_ONES[h as usize]),
+        (h, _) => format!("{} hundred {tens}", EN_ONES[h as usize]),
+    }
+}
+
+/// Indian English counts in lakhs (10⁵) and crores (10⁷).
+fn en(n: u64, indian: bool) -> String {
+    let scales: &[(u64, &str, &str)] = if indian {
+        &[
+            (10_000_000, "crore", "crore"),
+            (100_000, "lakh", "lakh"),
+            (1_000, "thousand", "thousand"),
+        ]
+    } else {
+        &[
+            (1_000_000_000_000_000_000, "quintillion", "quintillion"),
+            (1_000_000_000_000_000, "quadrillion", "quadrillion"),
+            (1_000_000_000_000, "trillion", "trillion"),
+            (1_000_000_000, "billion", "billion"),
+            (1_000_000, "million", "million"),
+            (1_000, "thousand", "thousand"),
+        ]
+    };
+    scaled(n, scales, en_below_1000, |count, name, _| {
+        format!("{} {name}", en(count, indian))
+    })
+}
+
+fn en_ordinal(n: u64) -> String {
+    let cardinal = en(n, false);
+    let split = cardinal.rfind([' ', '-']).map_or(0, |i| i + 1);
+    let (head, last) = cardinal.split_at(split);
+    let last = match last {
+        "one" => "first".to_string(),
+        "two" => "second".to_string(),
+        "three" => "third".to_string(),
+        "five" => "fifth".to_string(),
+        "eight" => "eighth".to_string(),
+        "nine" => "ninth".to_string(),
+        "twelve" => "twelfth".to_string(),
+        word => match word.strip_suffix('y') {
+            Some(stem) => format!("{stem}ieth"),
+            None => format!("{word}th"),
+        },
+    };
+    format!("{head}{last}")
+}
+
+/// Below a million, German numbers are one word. `eins` is `ein` before
+/// what it multiplies.
+fn de_below_million(n: u64, prefix: bool) -> String {
+    let below_100 = |n: u64, prefix: bool| {
+        let n = n as usize;
+        match n {
+            1 if prefix => "ein".to_string(),
+            0..=19 => DE_ONES[n].to_string(),
+            _ if n % 10 == 0 => DE_TENS[n / 10].to_string(),
+            _ => {
+                let ones = if n % 10 == 1 { "ein" } else { DE_ONES[n % 10] };
+                format!("{ones}und{}", DE_TENS[n / 10])
+            }
+        }
+    };
+    let below_1000 = |n: u64, prefix: bool| {
+        let (hundreds, rest) = (n / 100, n % 100);
+        let mut out = String::new();
+        if hundreds > 0 {
+            out.push_str(&below_100(hundreds, true));
+            out.push_str("hundert");
+        }
+        if rest > 0 || hundreds == 0 {
+            out.push_str(&below_100(rest, prefix));
+        }
+        out
+    };
+    let (thousands, rest) = (n / 1000, n % 1000);
+    let mut out = String::new();
+    if thousands > 0 {
+        out.push_str(&below_1000(thousands, true));
+        out.push_str("tausend");
+    }
+    if rest > 0 || thousands == 0 {
+        out.push_str(&below_1000(rest, prefix));
+    }
+    out
+}
+
+fn de(n: u64) -> String {
+    scaled(
+        n,
+        &[
+            (1_000_000_000_000, "Billion", "Billionen"),
+            (1_000_000_000, "Milliarde", "Milliarden"),
+            (1_000_000, "Million", "Millionen"),
+        ],
+        |n| de_below_million(n, false),
+        |count, one, many| match count {
+            1 => format!("eine {one}"),
+            _ => format!("{} {many}", de(count)),
+        },
+    )
+}
+
+/// The masculine ordinal, as in `der erste Mai`: `erster`.
+fn de_ordinal(n: u64) -> String {
+    let small = n % 100;
+    if (1..20).contains(&small) {
+        let head = if n > small {
+            de(n - small)
+        } else {
+            String::new()
+        };
+        let last = match small {
+            1 => "erster".to_string(),
+            3 => "dritter".to_string(),
+            7 => "siebter".to_string(),
+            8 => "achter".to_string(),
+            _ => format!("{}ter", DE_ONES[small as usize]),
+        };
+        format!("{head}{last}")
+    } else {
+        format!("{}ster", de(n))
+    }
+}
+
+fn fr_below_100(n: u64) -> String {
+    let n = n as usize;
+    match n {
+        0..=16 => FR_ONES[n].to_string(),
+        17..=19 => format!("dix-{}", FR_ONES[n - 10]),
+        20..=69 if n % 10 == 0 => FR_TENS[n / 10].to_string(),
+        20..=69 if n % 10 == 1 => format!("{} et un", FR_TENS[n / 10]),
+        20..=69 => format!("{}-{}", FR_TENS[n / 10], FR_ONES[n % 10]),
+        71 => "soixante et onze".to_string(),
+        70..=79 => format!("soixante-{}", fr_below_100(n as u64 - 60)),
+        80 => "quatre-vingts".to_string(),
+        _ => format!("quatre-vingt-{}", fr_below_100(n as u64 - 80)),
+    }
+}
+
+/// `vingts` and `cents` lose their s when more follows.
+fn fr_singular(words: &str) -> String {
+    match words
+        .strip_suffix("vingts")
+        .or_else(|| words.strip_suffix("cents"))
+    {
+        Some(head) => format!("{head}{}", &words[head.len()..words.len() - 1]),
+        None => words.to_string(),
+    }
+}
+
+fn fr_below_1000(n: u64) -> String {
+    let (hundreds, rest) = (n / 100, n % 100);
+    let hundred = match hundreds {
+        0 => return fr_below_100(rest),
+        1 => "cent".to_string(),
+        h if rest == 0 => format!("{} cents", FR_ONES[h as usize]),
+        h => format!("{} cent", FR_ONES[h as usize]),
+    };
+    match rest {
+        0 => hundred,
+        _ => format!("{hundred} {}", fr_below_100(rest)),
+    }
+}
+
+fn fr(n: u64) -> String {
+    scaled(
+        n,
+        &[
+            (1_000_000_000, "milliard", "milliards"),
+            (1_000_000, "million", "millions"),
+            (1_000, "mille", "mille"),
+        ],
+        fr_below_1000,
+        |count, one, many| match (count, one) {
+            (1, "mille") => "mille".to_string(),
+            (_, "mille") => format!("{} mille", fr_singular(&fr(count))),
+            (1, _) => format!("un {one}"),
+            _ => format!("{} {many}", fr(count)),
+        },
+    )
+}
+
+fn fr_ordinal(n: u64, feminine: bool) -> String {
+    if n == 1 {
+        return if feminine { "première" } else { "premier" }.to_string();
+    }
+    let cardinal = fr_singular(&fr(n));
+    if let Some(head) = cardinal.strip_suffix("cinq") {
+        format!("{head}cinquième")
+    } else if let Some(head) = cardinal.strip_suffix("neuf") {
+        format!("{head}neuvième")
+    } else if let Some(head) = cardinal.strip_suffix('e') {
+        format!("{head}ième")
+    } else {
+        format!("{cardinal}ième")
+    }
+}
+
+fn es_below_1000(n: u64) -> String {
+    let (hundreds, rest) = (n / 100, (n % 100) as usize);
+    let tens = match rest {
+        0..=29 => ES_ONES[rest].to_string(),
+        _ if rest % 10 == 0 => ES_TENS[rest / 10].to_string(),
+        _ => format!("{} y {}", ES_TENS[rest / 10], ES_ONES[rest % 10]),
+    };
+    match (hundreds, rest) {
+        (0, _) => tens,
+        (1, 0) => "cien".to_string(),
+        (h, 0) => ES_HUNDREDS[h as usize].to_string(),
+        (h, _) => format!("{} {tens}", ES_HUNDREDS[h as usize]),
+    }
+}
+
+fn es(n: u64) -> String {
+    scaled(
+        n,
+        &[
+            (1_000_000_000_000, "billón", "billones"),
+            (1_000_000, "millón", "millones"),
+            (1_000, "mil", "mil"),
+        ],
+        es_below_1000,
+        |count, one, many| match (count, one) {
+            (1, "mil") => "mil".to_string(),
+            (1, _) => format!("un {one}"),
+            _ => format!("{} {many}", apocope(&es(count), false)),
+        },
+    )
+}
+
+fn hi_below_1000(n: u64) -> String {
+    let (hundreds, rest) = (n / 100, (n % 100) as usize);
+    match (hundreds, rest) {
+        (0, _) => HI_BELOW_100[rest].to_string(),
+        (h, 0) => format!("{} सौ", HI_BELOW_100[h as usize]),
+        (h, _) => format!("{} सौ {}", HI_BELOW_100[h as usize], HI_BELOW_100[rest]),
+    }
+}
+
+/// Hindi always counts in lakhs and crores.
+fn hi(n: u64) -> String {
+    scaled(
+        n,
+        &[
+            (10_000_000, "करोड़", "करोड़"),
+            (100_000, "लाख", "लाख"),
+            (1_000, "हज़ार", "हज़ार"),
+        ],
+        hi_below_1000,
+        |count, name, _| format!("{} {name}", hi(count)),
+    )
+}
+
+/// `पहला`, `दूसरा`, then the cardinal with `वाँ`; `ी` for the feminine.
+fn hi_ordinal(n: u64, feminine: bool) -> String {
+    let masculine = match n {
+        1 => "पहला".to_string(),
+        2 => "दूसरा".to_string(),
+        3 => "तीसरा".to_string(),
+        4 => "चौथा".to_string(),
+        6 => "छठा".to_string(),
+        _ => format!("{}वाँ", hi(n)),
+    };
+    match masculine.strip_suffix('ा') {
+        Some(stem) if feminine => format!("{stem}ी"),
+        _ => match masculine.strip_suffix("वाँ") {
+            Some(stem) if feminine => format!("{stem}वीं"),
+            _ => masculine,
+        },
+    }
+}
+
+/// Spanish `uno` before a noun: `un` or `una`, `veintiún` or `veintiuna`.
+pub fn apocope(words: &str, feminine: bool) -> String {
+    let (head, replacement) = if let Some(head) = words.strip_suffix("veintiuno") {
+        (head, if feminine { "veintiuna" } else { "veintiún" })
+    } else if let Some(head) = words.strip_suffix("uno") {
+        (head, if feminine { "una" } else { "un" })
+    } else {
+        return words.to_string();
+    };
+    format!("{head}{replacement}")
+}
+
+/// `n` in words, or `None` where the voices read digits well enough.
+pub fn cardinal(n: u64, language: Language, indian: bool) -> Option<String> {
+    match language {
+        Language::English => Some(en(n, indian)),
+        Language::German => Some(de(n)),
+        Language::French => Some(fr(n)),
+        Language::Spanish => Some(es(n)),
+        Language::Hindi => Some(hi(n)),
+        Language::Other => None,
+    }
+}
+
+/// The ordinal of `n`, masculine unless `feminine`. Spanish ordinals past
+/// ten are cardinals, as they are usually read.
+pub fn ordinal(n: u64, language: Language, feminine: bool) -> Option<String> {
+    match language {
+        Language::English => Some(en_ordinal(n)),
+        Language::German => Some(de_ordinal(n)),
+        Language::French => Some(fr_ordinal(n, feminine)),
+        Language::Spanish => Some(match ES_ORDINALS.get(n as usize) {
+            Some(word) if n > 0 && feminine => format!("{}a", word.trim_end_matches('o')),
+            Some(word) if n > 0 => word.to_string(),
+            _ => es(n),
+        }),
+        Language::Hindi => Some(hi_ordinal(n, feminine)),
+        Language::Other => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn english() {
+        let words = |n| cardinal(n, Language::English, false).unwrap();
+        assert_eq!(words(0), "zero");
+        assert_eq!(words(42), "forty-two");
+        assert_eq!(words(100), "one hundred");
+        assert_eq!(words(2024), "two thousand twenty-four");
+        assert_eq!(
+            words(1_234_567),
+            "one million two hundred thirty-four thousand five hundred sixty-seven"
+        );
+        let indian = |n| cardinal(n, Language::English, true).unwrap();
+        assert_eq!(indian(150_000), "one lakh fifty thousand");
+        assert_eq!(
+            indian(1_234_567_890),
+            "one hundred twenty-three crore forty-five lakh sixty-seven thousand eight hundred ninety"
+        );
+        let nth = |n| ordinal(n, Language::English, false).unwrap();
+        assert_eq!(nth(1), "first");
+        assert_eq!(nth(12), "twelfth");
+        assert_eq!(nth(20), "twentieth");
+        assert_eq!(nth(23), "twenty-third");
+        assert_eq!(nth(101), "one hundred first");
+    }
+
+    #[test]
+    fn german() {
+        let words = |n| cardinal(n, Language::German, false).unwrap();
+        assert_eq!(words(1), "eins");
+        assert_eq!(words(21), "einundzwanzig");
+        assert_eq!(words(101), "einhunderteins");
+        assert_eq!(words(2024), "zweitausendvierundzwanzig");
+        assert_eq!(words(1_001_000), "eine Million eintausend");
+        assert_eq!(words(3_000_000), "drei Millionen");
+        let nth = |n| ordinal(n, Language::German, false).unwrap();
+        assert_eq!(nth(1), "erster");
+        assert_eq!(nth(3), "dritter");
+        assert_eq!(nth(19), "neunzehnter");
+        assert_eq!(nth(26), "sechsundzwanzigster");
+        assert_eq!(nth(101), "einhunderterster");
+    }
+
+    #[test]
+    fn french() {
+        let words = |n| cardinal(n, Language::French, false).unwrap();
+        assert_eq!(words(17), "dix-sept");
+        assert_eq!(words(21), "vingt et un");
+        assert_eq!(words(71), "soixante et onze");
+        assert_eq!(words(80), "quatre-vingts");
+        assert_eq!(words(99), "quatre-vingt-dix-neuf");
+        assert_eq!(words(200), "deux cents");
+        assert_eq!(words(201), "deux cent un");
+        assert_eq!(words(80_000), "quatre-vingt mille");
+        assert_eq!(words(3_000), "trois mille");
+        assert_eq!(words(2_000_000), "deux millions");
+        let nth = |n, feminine| ordinal(n, Language::French, feminine).unwrap();
+        assert_eq!(nth(1, true), "première");
+        assert_eq!(nth(2, false), "deuxième");
+        assert_eq!(nth(5, false), "cinquième");
+        assert_eq!(nth(9, false), "neuvième");
+        assert_eq!(nth(11, false), "onzième");
+        assert_eq!(nth(21, false), "vingt et unième");
+        assert_eq!(nth(80, false), "quatre-vingtième");
+    }
+
+    #[test]
+    fn spanish() {
+        let words = |n| cardinal(n, Language::Spanish, false).unwrap();
+        assert_eq!(words(21), "veintiuno");
+        assert_eq!(words(45), "cuarenta y cinco");
+        assert_eq!(words(100), "cien");
+        assert_eq!(words(115), "ciento quince");
+        assert_eq!(words(500), "quinientos");
+        assert_eq!(words(1000), "mil");
+        assert_eq!(words(21_000), "veintiún mil");
+        assert_eq!(words(2_000_000), "dos millones");
+        assert_eq!(apocope("treinta y uno", true), "treinta y una");
+        let nth = |n, feminine| ordinal(n, Language::Spanish, feminine).unwrap();
+        assert_eq!(nth(1, false), "primero");
+        assert_eq!(nth(3, true), "tercera");
+        assert_eq!(nth(13, false), "trece");
+        assert!(cardinal(5, Language::Other, true).is_none());
+    }
+
+    #[test]
+    fn hindi() {
+        let words = |n| cardinal(n, Language::Hindi, false).unwrap();
+        assert_eq!(words(0), "शून्य");
+        assert_eq!(words(26), "छब्बीस");
+        assert_eq!(words(100), "एक सौ");
+        assert_eq!(words(2024), "दो हज़ार चौबीस");
+        assert_eq!(words(150_000), "एक लाख पचास हज़ार");
+        assert_eq!(
+            words(1_234_567_890),
+            "एक सौ तेईस करोड़ पैंतालीस लाख सड़सठ हज़ार आठ सौ नब्बे"
+        );
+        let nth = |n, feminine| ordinal(n, Language::Hindi, feminine).unwrap();
+        assert_eq!(nth(1, false), "पहला");
+        assert_eq!(nth(3, true), "तीसरी");
+        assert_eq!(nth(5, false), "पाँचवाँ");
+        assert_eq!(nth(5, true), "पाँचवीं");
+    }
+}
@@ -0,0 +1,439 @@
+//! What each language abbreviates, and its names for months, currencies,
+//! units and the things numbered in Roman numerals.
+
+use super::Language;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Abbreviation {
+    /// Like `etc.`, whose dot may also end the sentence.
+    Plain,
+    /// Like `Dr.`, which precedes a name.
+    Title,
+    /// Like `No.`, which is only short for a word before a number.
+    BeforeNumber,
+}
+
+use Abbreviation::{BeforeNumber, Plain, Title};
+
+type Abbreviations = &'static [(&'static str, &'static str, Abbreviation)];
+
+const EN_ABBREVIATIONS: Abbreviations = &[
+    ("Mr.", "Mister", Title),
+    ("Mrs.", "Missus", Title),
+    ("Ms.", "Miz", Title),
+    ("Dr.", "Doctor", Title),
+    ("Prof.", "Professor", Title),
+    ("St.", "Saint", Title),
+    ("Lt.", "Lieutenant", Title),
+    ("Col.", "Colonel", Title),
+    ("Gen.", "General", Title),
+    ("Capt.", "Captain", Title),
+    ("Sgt.", "Sergeant", Title),
+    ("Rev.", "Reverend", Title),
+    ("Hon.", "Honourable", Title),
+    ("Hon'ble", "Honourable", Title),
+    ("Smt.", "Shrimati", Title),
+    ("Sh.", "Shri", Title),
+    ("Kum.", "Kumari", Title),
+    ("Jr.", "Junior", Plain),
+    ("Sr.", "Senior", Plain),
+    ("e.g.", "for example", Plain),
+    ("i.e.", "that is", Plain),
+    ("etc.", "et cetera", Plain),
+    ("vs.", "versus", Plain),
+    ("viz.", "namely", Plain),
+    ("approx.", "approximately", Plain),
+    ("w.r.t.", "with respect to", Plain),
+    ("w.e.f.", "with effect from", Plain),
+    ("Govt.", "Government", Plain),
+    ("Dept.", "Department", Plain),
+    ("Pvt.", "Private", Plain),
+    ("Ltd.", "Limited", Plain),
+    ("Distt.", "District", Plain),
+    ("Addl.", "Additional", Plain),
+    ("Asst.", "Assistant", Plain),
+    ("No.", "number", BeforeNumber),
+    ("Nos.", "numbers", BeforeNumber),
+    ("p.", "page", BeforeNumber),
+    ("pp.", "pages", BeforeNumber),
+];
+
+const DE_ABBREVIATIONS: Abbreviations = &[
+    ("Dr.", "Doktor", Title),
+    ("Prof.", "Professor", Title),
+    ("Hr.", "Herr", Title),
+    ("Fr.", "Frau", Title),
+    ("St.", "Sankt", Title),
+    ("z.B.", "zum Beispiel", Plain),
+    ("d.h.", "das heißt", Plain),
+    ("u.a.", "unter anderem", Plain),
+    ("usw.", "und so weiter", Plain),
+    ("bzw.", "beziehungsweise", Plain),
+    ("ca.", "circa", Plain),
+    ("evtl.", "eventuell", Plain),
+    ("ggf.", "gegebenenfalls", Plain),
+    ("inkl.", "inklusive", Plain),
+    ("vgl.", "vergleiche", Plain),
+    ("Str.", "Straße", Plain),
+    ("Mio.", "Millionen", Plain),
+    ("Mrd.", "Milliarden", Plain),
+    ("Nr.", "Nummer", BeforeNumber),
+    ("S.", "Seite", BeforeNumber),
+];
+
+const FR_ABBREVIATIONS: Abbreviations = &[
+    ("M.", "Monsieur", Title),
+    ("MM.", "Messieurs", Title),
+    ("Mme", "Madame", Title),
+    ("Mmes", "Mesdames", Title),
+    ("Mlle", "Mademoiselle", Title),
+    ("Dr", "Docteur", Title),
+    ("Pr", "Professeur", Title),
+    ("St", "Saint", Title),
+    ("Ste", "Sainte", Title),
+    ("p. ex.", "par exemple", Plain),
+    ("etc.", "et cetera", Plain),
+    ("cf.", "confer", Plain),
+    ("env.", "environ", Plain),
+    ("n°", "numéro", BeforeNumber),
+    ("p.", "page", BeforeNumber),
+];
+
+const ES_ABBREVIATIONS: Abbreviations = &[
+    ("Sr.", "Señor", Title),
+    ("Sra.", "Señora", Title),
+    ("Srta.", "Señorita", Title),
+    ("Dr.", "Doctor", Title),
+    ("Dra.", "Doctora", Title),
+    ("Ud.", "usted", Plain),
+    ("Uds.", "ustedes", Plain),
+    ("p. ej.", "por ejemplo", Plain),
+    ("EE. UU.", "Estados Unidos", Plain),
+    ("etc.", "etcétera", Plain),
+    ("aprox.", "aproximadamente", Plain),
+    ("núm.", "número", BeforeNumber),
+    ("pág.", "página", BeforeNumber),
+];
+
+const HI_ABBREVIATIONS: Abbreviations = &[
+    ("डॉ.", "डॉक्टर", Title),
+    ("प्रो.", "प्रोफ़ेसर", Title),
+    ("कि.मी.", "किलोमीटर", Plain),
+];
+
+pub fn abbreviations(language: Language) -> Abbreviations {
+    match language {
+        Language::English => EN_ABBREVIATIONS,
+        Language::German => DE_ABBREVIATIONS,
+        Language::French => FR_ABBREVIATIONS,
+        Language::Spanish => ES_ABBREVIATIONS,
+        Language::Hindi => HI_ABBREVIATIONS,
+        Language::Other => &[],
+    }
+}
+
+pub fn months(language: Language) -> Option<[&'static str; 12]> {
+    Some(match language {
+        Language::English => [
+            "January",
+            "February",
+            "March",
+            "April",
+            "May",
+            "June",
+            "July",
+            "August",
+            "September",
+            "October",
+            "November",
+            "December",
+        ],
+        Language::German => [
+            "Januar",
+            "Februar",
+            "März",
+            "April",
+            "Mai",
+            "Juni",
+            "Juli",
+            "August",
+            "September",
+            "Oktober",
+            "November",
+            "Dezember",
+        ],
+        Language::French => [
+            "janvier",
+            "février",
+            "mars",
+            "avril",
+            "mai",
+            "juin",
+            "juillet",
+            "août",
+            "septembre",
+            "octobre",
+            "novembre",
+            "décembre",
+        ],
+        Language::Spanish => [
+            "enero",
+            "febrero",
+            "marzo",
+            "abril",
+            "mayo",
+            "junio",
+            "julio",
+            "agosto",
+            "septiembre",
+            "octubre",
+            "noviembre",
+            "diciembre",
+        ],
+        Language::Hindi => [
+            "जनवरी",
+            "फ़रवरी",
+            "मार्च",
+            "अप्रैल",
+            "मई",
+            "जून",
+            "जुलाई",
+            "अगस्त",
+            "सितंबर",
+            "अक्टूबर",
+            "नवंबर",
+            "दिसंबर",
+        ],
+        Language::Other => return None,
+    })
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Currency {
+    Rupee,
+    Dollar,
+    Euro,
+    Pound,
+}
+
+/// Symbols and codes, longest first so that `Rs.` wins over `Rs`.
+const CURRENCY_SYMBOLS: &[(&str, Currency)] = &[
+    ("US$", Currency::Dollar),
+    ("INR", Currency::Rupee),
+    ("EUR", Currency::Euro),
+    ("GBP", Currency::Pound),
+    ("USD", Currency::Dollar),
+    ("Rs.", Currency::Rupee),
+    ("रु.", Currency::Rupee),
+    ("Rs", Currency::Rupee),
+    ("₹", Currency::Rupee),
+    ("$", Currency::Dollar),
+    ("€", Currency::Euro),
+    ("£", Currency::Pound),
+];
+
+/// The currency a word starts with, and the rest of the word.
+pub fn currency_prefix(word: &str) -> Option<(Currency, &str)> {
+    CURRENCY_SYMBOLS
+        .iter()
+        .find_map(|(symbol, currency)| Some((*currency, word.strip_prefix(symbol)?)))
+}
+
+pub fn currency(word: &str) -> Option<Currency> {
+    currency_prefix(word)
+        .filter(|(_, rest)| rest.is_empty())
+        .map(|(currency, _)| currency)
+}
+
+/// Names of a currency and its hundredth.
+pub struct CurrencyNames {
+    pub one: &'static str,
+    pub many: &'static str,
+    pub minor_one: &'static str,
+    pub minor_many: &'static str,
+    /// The grammatical gender of the main unit, for `una rupia`.
+    pub feminine: bool,
+}
+
+const fn names(
+    one: &'static str,
+    many: &'static str,
+    minor_one: &'static str,
+    minor_many: &'static str,
+    feminine: bool,
+) -> CurrencyNames {
+    CurrencyNames {
+        one,
+        many,
+        minor_one,
+        minor_many,
+        feminine,
+    }
+}
+
+pub fn currency_names(currency: Currency, language: Language) -> Option<CurrencyNames> {
+    use Currency::*;
+    Some(match (language, currency) {
+        (Language::English, Rupee) => names("rupee", "rupees", "paisa", "paise", false),
+        (Language::English, Dollar) => names("dollar", "dollars", "cent", "cents", false),
+        (Language::English, Euro) => names("euro", "euros", "cent", "cents", false),
+        (Language::English, Pound) => names("pound", "pounds", "penny", "pence", false),
+        (Language::German, Rupee) => names("Rupie", "Rupien", "Paisa", "Paise", true),
+        (Language::German, Dollar) => names("Dollar", "Dollar", "Cent", "Cent", false),
+        (Language::German, Euro) => names("Euro", "Euro", "Cent", "Cent", false),
+        (Language::German, Pound) => names("Pfund", "Pfund", "Penny", "Pence", false),
+        (Language::French, Rupee) => names("roupie", "roupies", "paisa", "paise", true),
+        (Language::French, Dollar) => names("dollar", "dollars", "cent", "cents", false),
+        (Language::French, Euro) => names("euro", "euros", "centime", "centimes", false),
+        (Language::French, Pound) => names("livre", "livres", "penny", "pence", true),
+        (Language::Spanish, Rupee) => names("rupia", "rupias", "paisa", "paisas", true),
+        (Language::Spanish, Dollar) => names("dólar", "dólares", "centavo", "centavos", false),
+        (Language::Spanish, Euro) => names("euro", "euros", "céntimo", "céntimos", false),
+        (Language::Spanish, Pound) => names("libra", "libras", "penique", "peniques", true),
+        (Language::Hindi, Rupee) => names("रुपया", "रुपये", "पैसा", "पैसे", false),
+        (Language::Hindi, Dollar) => names("डॉलर", "डॉलर", "सेंट", "सेंट", false),
+        (Language::Hindi, Euro) => names("यूरो", "यूरो", "सेंट", "सेंट", false),
+        (Language::Hindi, Pound) => names("पाउंड", "पाउंड", "पेंस", "पेंस", false),
+        (Language::Other, _) => return None,
+    })
+}
+
+/// Words that multiply an amount, as in `₹5 lakh`.
+pub fn is_scale(word: &str, language: Language) -> bool {
+    let scales: &[&str] = match language {
+        Language::English => &[
+            "thousand", "lakh", "lakhs", "crore", "crores", "million", "billion", "trillion",
+        ],
+        Language::Hindi => &["हज़ार", "हजार", "लाख", "करोड़"],
+        _ => &[],
+    };
+    scales.contains(&word)
+}
+
+type Units = &'static [(&'static str, &'static str, &'static str)];
+
+const EN_UNITS: Units = &[
+    ("km", "kilometer", "kilometers"),
+    ("kg", "kilogram", "kilograms"),
+    ("cm", "centimeter", "centimeters"),
+    ("mm", "millimeter", "millimeters"),
+    ("mg", "milligram", "milligrams"),
+    ("ml", "milliliter", "milliliters"),
+    ("km/h", "kilometer per hour", "kilometers per hour"),
+    ("°C", "degree Celsius", "degrees Celsius"),
+    ("°F", "degree Fahrenheit", "degrees Fahrenheit"),
+];
+
+const DE_UNITS: Units = &[
+    ("km", "Kilometer", "Kilometer"),
+    ("kg", "Kilogramm", "Kilogramm"),
+    ("cm", "Zentimeter", "Zentimeter"),
+    ("mm", "Millimeter", "Millimeter"),
+    ("mg", "Milligramm", "Milligramm"),
+    ("ml", "Milliliter", "Milliliter"),
+    ("km/h", "Kilometer pro Stunde", "Kilometer pro Stunde"),
+    ("°C", "Grad Celsius", "Grad Celsius"),
+    ("°F", "Grad Fahrenheit", "Grad Fahrenheit"),
+];
+
+const FR_UNITS: Units = &[
+    ("km", "kilomètre", "kilomètres"),
+    ("kg", "kilogramme", "kilogrammes"),
+    ("cm", "centimètre", "centimètres"),
+    ("mm", "millimètre", "millimètres"),
+    ("mg", "milligramme", "milligrammes"),
+    ("ml", "millilitre", "millilitres"),
+    ("km/h", "kilomètre heure", "kilomètres heure"),
+    ("°C", "degré Celsius", "degrés Celsius"),
+    ("°F", "degré Fahrenheit", "degrés Fahrenheit"),
+];
+
+const ES_UNITS: Units = &[
+    ("km", "kilómetro", "kilómetros"),
+    ("kg", "kilogramo", "kilogramos"),
+    ("cm", "centímetro", "centímetros"),
+    ("mm", "milímetro", "milímetros"),
+    ("mg", "miligramo", "miligramos"),
+    ("ml", "mililitro", "mililitros"),
+    ("km/h", "kilómetro por hora", "kilómetros por hora"),
+    ("°C", "grado Celsius", "grados Celsius"),
+    ("°F", "grado Fahrenheit", "grados Fahrenheit"),
+];
+
+const HI_UNITS: Units = &[
+    ("km", "किलोमीटर", "किलोमीटर"),
+    ("kg", "किलोग्राम", "किलोग्राम"),
+    ("cm", "सेंटीमीटर", "सेंटीमीटर"),
+    ("mm", "मिलीमीटर", "मिलीमीटर"),
+    ("mg", "मिलीग्राम", "मिलीग्राम"),
+    ("ml", "मिलीलीटर", "मिलीलीटर"),
+    ("km/h", "किलोमीटर प्रति घंटा", "किलोमीटर प्रति घंटा"),
+    ("°C", "डिग्री सेल्सियस", "डिग्री सेल्सियस"),
+    ("°F", "डिग्री फ़ारेनहाइट", "डिग्री फ़ारेनहाइट"),
+];
+
+/// Singular and plural of a unit.
+pub fn unit(symbol: &str, language: Language) -> Option<(&'static str, &'static str)> {
+    let units = match language {
+        Language::English => EN_UNITS,
+        Language::German => DE_UNITS,
+        Language::French => FR_UNITS,
+        Language::Spanish => ES_UNITS,
+        Language::Hindi => HI_UNITS,
+        Language::Other => return None,
+    };
+    units
+        .iter()
+        .find(|(short, _, _)| *short == symbol)
+        .map(|(_, one, many)| (*one, *many))
+}
+
+/// Lowercase words after which a Roman numeral is a plain number, as in
+/// `Chapter IV`.
+pub fn is_roman_keyword(word: &str, language: Language) -> bool {
+    let keywords: &[&str] = match language {
+        Language::English => &[
+            "chapter", "part", "book", "volume", "vol", "act", "scene", "section", "phase",
+            "stage", "class", "type", "article", "schedule", "appendix", "war",
+        ],
+        Language::German => &[
+            "kapitel",
+            "teil",
+            "band",
+            "buch",
+            "akt",
+            "szene",
+            "abschnitt",
+            "artikel",
+        ],
+        Language::French => &[
+            "chapitre", "tome", "partie", "livre", "acte", "scène", "volume", "section", "article",
+        ],
+        Language::Spanish => &[
+            "capítulo",
+            "tomo",
+            "parte",
+            "libro",
+            "acto",
+            "escena",
+            "volumen",
+            "sección",
+            "artículo",
+            "siglo",
+        ],
+        Language::Hindi | Language::Other => &[],
+    };
+    keywords.contains(&word)
+}
+
+/// Symbols of the chemical elements.
+pub const ELEMENTS: &[&str] = &[
+    "H", "He", "Li", "Be", "B", "C", "N", "O", "F", "Ne", "Na", "Mg", "Al", "Si", "P", "S", "Cl",
+    "Ar", "K", "Ca", "Sc", "Ti", "V", "Cr", "Mn", "Fe", "Co", "Ni", "Cu", "Zn", "Ga", "Ge", "As",
+    "Se", "Br", "Kr", "Rb", "Sr", "Y", "Zr", "Nb", "Mo", "Tc", "Ru", "Rh", "Pd", "Ag", "Cd", "In",
+    "Sn", "Sb", "Te", "I", "Xe", "Cs", "Ba", "La", "Ce", "Pr", "Nd", "Pm", "Sm", "Eu", "Gd", "Tb",
+    "Dy", "Ho", "Er", "Tm", "Yb", "Lu", "Hf", "Ta", "W", "Re", "Os", "Ir", "Pt", "Au", "Hg", "Tl",
+    "Pb", "Bi", "Po", "At", "Rn", "Fr", "Ra", "Ac", "Th", "Pa", "U", "Np", "Pu", "Am", "Cm", "Bk",
+    "Cf", "Es", "Fm", "Md", "No", "Lr", "Rf", "Db", "Sg", "Bh", "Hs", "Mt", "Ds", "Rg", "Cn", "Nh",
+    "Fl", "Mc", "Lv", "Ts", "Og",
+];
@@ -0,0 +1,413 @@
+//! Offline neural voices: Piper models run on the CPU with ONNX Runtime,
+//! streamed sentence by sentence to the default audio device.
+
+mod phonemize;
+mod text;
+mod voice;
+
+use ort::session::{builder::GraphOptimizationLevel, Session};
+use ort::value::Tensor;
+use rodio::buffer::SamplesBuffer;
+use rodio::Sink;
+use std::collections::HashMap;
+use std::ops::Range;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::{self, Sender};
+use std::sync::{Arc, Mutex};
+
+use crate::audio::{error, event, Output};
+use crate::export::Pcm;
+use crate::models::{TTSMessageEvent, TTSVoice};
+use crate::ssml::{Document, Item, Span};
+
+use voice::PhonemeType;
+pub use voice::Voice;
+
+/// Prefix of the ids of Piper voices in `get_all_voices`.
+pub const VOICE_PREFIX: &str = "piper:";
+
+/// Pause after each sentence, as Piper does.
+const SENTENCE_SILENCE: f32 = 0.2;
+
+type Inputs = (Tensor<i64>, Tensor<i64>, Tensor<f32>, Tensor<i64>);
+
+/// Model inputs: phoneme ids, their count, the inference scales, and the
+/// speaker of multi-speaker models.
+fn tensors(ids: Vec<i64>, scales: Vec<f32>) -> ort::Result<Inputs> {
+    let len = ids.len();
+    Ok((
+        Tensor::from_array(([1, len], ids))?,
+        Tensor::from_array(([1], vec![len as i64]))?,
+        Tensor::from_array(([3], scales))?,
+        Tensor::from_array(([1], vec![0i64]))?,
+    ))
+}
+
+/// The voice of a document and those for its `xml:lang` switches.
+struct Voices {
+    default: Arc<Voice>,
+    by_lang: HashMap<String, Arc<Voice>>,
+}
+
+impl Voices {
+    fn get(&self, lang: Option<&String>) -> &Arc<Voice> {
+        lang.and_then(|lang| self.by_lang.get(lang))
+            .unwrap_or(&self.default)
+    }
+}
+
+struct Job {
+    utterance_id: String,
+    items: Vec<Item>,
+    voices: Voices,
+    rate: f32,
+    generation: u64,
+}
+
+/// The last used model, kept loaded and shared by playback and rendering.
+#[derive(Clone, Default)]
+struct Model(Arc<Mutex<Option<(PathBuf, Session)>>>);
+
+impl Model {
+    fn infer(&self, voice: &Voice, ids: Vec<i64>, rate: f32) -> crate::Result<Vec<f32>> {
+        let length_scale = voice.inference.length_scale / rate.clamp(0.2, 5.0);
+        let scales = vec![
+            voice.inference.noise_scale,
+            length_scale,
+            voice.inference.noise_w,
+        ];
+        let (input, lengths, scales, speaker) =
+            tensors(ids, scales).map_err(|e| error("Invalid input", e))?;
+        let mut loaded = self.0.lock().unwrap();
+        if loaded.as_ref().map(|(path, _)| path.as_path()) != Some(voice.model.as_path()) {
+            *loaded = None;
+            let threads = std::thread::available_parallelism().map_or(1, |n| n.get());
+            let session = Session::builder()
+                .and_then(|b| b.with_optimization_level(GraphOptimizationLevel::Level3))
+                .and_then(|b| b.with_intra_threads(threads))
+                .and_then(|b| b.commit_from_file(&voice.model))
+                .map_err(|e| error("Failed to load voice model", e))?;
+            *loaded = Some((voice.model.clone(), session));
+        }
+        let session = &mut loaded.as_mut().unwrap().1;
+        let outputs = if voice.multi_speaker {
+            session.run(ort::inputs![
+                "input" => input,
+                "input_lengths" => lengths,
+                "scales" => scales,
+                "sid" => speaker,
+            ])
+        } else {
+            session.run(ort::inputs![
+                "input" => input,
+                "input_lengths" => lengths,
+                "scales" => scales,
+            ])
+        }
+        .map_err(|e| error("Synthesis failed", e))?;
+        let (_, audio) = outputs["output"]
+            .try_extract_tensor::<f32>()
+            .map_err(|e| error("Synthesis failed", e))?;
+        // Piper scales each sentence to full volume.
+        let peak = audio.iter().fold(0.01f32, |peak, s| peak.max(s.abs()));
+        Ok(audio.iter().map(|s| s / peak).collect())
+    }
+}
+
+/// Synthesizes jobs in order on a worker thread.
+struct Synthesizer {
+    model: Model,
+    output: Arc<Output>,
+}
+
+impl Synthesizer {
+    fn queue(&self, job: &Job, f: impl FnOnce(&Sink)) -> bool {
+        self.output.queue(job.generation, f)
+    }
+
+    fn at_playback(&self, sink: &Sink, utterance_id: &str, event: TTSMessageEvent) {
+        self.output.at_playback(sink, utterance_id, event)
+    }
+
+    /// Speak a span starting at UTF-16 `offset` of the document's text.
+    fn speak_span(&mut self, job: &Job, span: &Span, offset: usize) -> crate::Result<bool> {
+        let voice = job.voices.get(span.lang.as_ref()).clone();
+        let silence = vec![0.0; (voice.sample_rate as f32 * SENTENCE_SILENCE) as usize];
+        for sentence_range in text::sentences(&span.text) {
+            if self.output.cancelled(job.generation) {
+                return Ok(false);
+            }
+            let sentence = &span.text[sentence_range.clone()];
+            let ids = voice.phoneme_ids(&phonemize::phonemize(&voice, sentence)?);
+            let audio = self.model.infer(&voice, ids, job.rate * span.rate)?;
+
+            let pos = |range: &Range<usize>| {
+                let start = text::utf16_offset(&span.text, sentence_range.start + range.start);
+                let end = text::utf16_offset(&span.text, sentence_range.start + range.end);
+                format!("pos:{}-{}", offset + start, offset + end)
+            };
+            let queued = self.queue(job, |sink| {
+                let whole = 0..sentence.len();
+                self.at_playback(
+                    sink,
+                    &job.utterance_id,
+                    event("boundary", Some("sentence"), Some(pos(&whole))),
+                );
+                let words = text::words(sentence);
+                let offsets = text::word_offsets(sentence, &words, audio.len());
+                for (i, word) in words.iter().enumerate() {
+                    let end = offsets.get(i + 1).copied().unwrap_or(audio.len());
+                    self.at_playback(
+                        sink,
+                        &job.utterance_id,
+                        event("boundary", Some("range"), Some(pos(word))),
+                    );
+                    let chunk = audio[offsets[i]..end].to_vec();
+                    sink.append(SamplesBuffer::new(1, voice.sample_rate, chunk));
+                }
+                sink.append(SamplesBuffer::new(1, voice.sample_rate, silence.clone()));
+            });
+            if !queued {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+
+    fn run(&mut self, job: Job) -> crate::Result<()> {
+        let start = event("boundary", Some("start"), None);
+        if !self.queue(&job, |sink| {
+            self.at_playback(sink, &job.utterance_id, start)
+        }) {
+            return Ok(());
+        }
+        let mut offset = 0;
+        for item in &job.items {
+            let queued = match item {
+                Item::Text(span) => {
+                    let queued = self.speak_span(&job, span, offset)?;
+                    offset += span.text.encode_utf16().count();
+                    queued
+                }
+                Item::Mark(name) => self.queue(&job, |sink| {
+                    let mark = event("boundary", Some("mark"), Some(name.clone()));
+                    self.at_playback(sink, &job.utterance_id, mark)
+                }),
+                Item::Break(millis) => self.queue(&job, |sink| Output::silence(sink, *millis)),
+            };
+            if !queued {
+                return Ok(());
+            }
+        }
+        let end = event("end", None, None);
+        self.queue(&job, |sink| self.at_playback(sink, &job.utterance_id, end));
+        Ok(())
+    }
+}
+
+fn usable(voice: &Voice) -> bool {
+    voice.phoneme_type != PhonemeType::Espeak || phonemize::espeak_available()
+}
+
+/// Voices installed as Piper models in a directory.
+pub struct Piper {
+    dir: PathBuf,
+    voice: Mutex<Option<Arc<Voice>>>,
+    rate: Mutex<f32>,
+    model: Model,
+    output: Arc<Output>,
+    next_id: AtomicU64,
+    jobs: Mutex<Option<Sender<Job>>>,
+}
+
+impl Piper {
+    pub fn new(dir: PathBuf, output: Arc<Output>) -> Self {
+        // Show users where models go.
+        let _ = std::fs::create_dir_all(&dir);
+        Self {
+            dir,
+            voice: Mutex::new(None),
+            rate: Mutex::new(1.0),
+            model: Model::default(),
+            output,
+            next_id: AtomicU64::new(0),
+            jobs: Mutex::new(None),
+        }
+    }
+
+    /// The installed voices. Those that need eSpeak NG are disabled without it.
+    pub fn voices(&self) -> Vec<TTSVoice> {
+        voice::list(&self.dir)
+            .into_iter()
+            .map(|voice| TTSVoice {
+                id: format!("{VOICE_PREFIX}{}", voice.key),
+                disabled: !usable(&voice),
+                name: voice.name,
+                lang: voice.lang,
+            })
+            .collect()
+    }
+
+    pub fn set_voice(&self, key: &str) -> crate::Result<()> {
+        let mut current = self.voice.lock().unwrap();
+        if current.as_ref().is_some_and(|voice| voice.key == key) {
+            return Ok(());
+        }
+        let model = self.dir.join(format!("{key}.onnx"));
+        if key.contains(['/', '\\']) || !model.is_file() {
+            return Err(crate::Error::NativeTTSError(format!(
+                "Voice {key} is not installed"
+            )));
+        }
+        let voice = Voice::load(&model)?;
+        if !usable(&voice) {
+            return Err(crate::Error::NativeTTSError(
+                phonemize::ESPEAK_MISSING.into(),
+            ));
+        }
+        *current = Some(Arc::new(voice));
+        Ok(())
+    }
+
+    pub fn set_rate(&self, rate: f32) {
+        *self.rate.lock().unwrap() = rate;
+    }
+
+    fn jobs(&self) -> crate::Result<Sender<Job>> {
+        let mut jobs = self.jobs.lock().unwrap();
+        if let Some(sender) = jobs.as_ref() {
+            return Ok(sender.clone());
+        }
+        let (sender, receiver) = mpsc::channel::<Job>();
+        let mut synthesizer = Synthesizer {
+            model: self.model.clone(),
+            output: self.output.clone(),
+        };
+        std::thread::Builder::new()
+            .name("piper-synthesis".into())
+            .spawn(move || {
+                for job in receiver {
+                    let utterance_id = job.utterance_id.clone();
+                    if let Err(err) = synthesizer.run(job) {
+                        let message = err.to_string();
+                        let error = event("error", Some(&message), None);
+                        synthesizer.output.emit(&utterance_id, error);
+                    }
+                }
+            })?;
+        *jobs = Some(sender.clone());
+        Ok(sender)
+    }
+
+    /// An installed voice for `lang` other than the current one, which
+    /// speaks anything else.
+    fn voice_for(&self, lang: &str, current: &Voice) -> Option<Arc<Voice>> {
+        let primary = |tag: &str| {
+            tag.split(['-', '_'])
+                .next()
+                .unwrap_or_default()
+                .to_lowercase()
+        };
+        if primary(lang) == primary(&current.lang) {
+            return None;
+        }
+        let voices = voice::list(&self.dir);
+        voices
+            .iter()
+            .position(|voice| voice.lang.eq_ignore_ascii_case(lang))
+            .or_else(|| {
+                voices
+                    .iter()
+                    .position(|voice| primary(&voice.lang) == primary(lang))
+            })
+            .map(|i| Arc::new(voices.into_iter().nth(i).unwrap()))
+    }
+
+    fn document_voices(&self, document: &Document) -> crate::Result<Voices> {
+        let voice = self
+            .voice
+            .lock()
+            .unwrap()
+            .clone()
+            .ok_or_else(|| crate::Error::NativeTTSError("No Piper voice selected".into()))?;
+        let mut langs: Vec<&String> = document
+            .items
+            .iter()
+            .filter_map(|item| match item {
+                Item::Text(span) => span.lang.as_ref(),
+                _ => None,
+            })
+            .collect();
+        langs.sort();
+        langs.dedup();
+        let by_lang = langs
+            .into_iter()
+            .filter_map(|lang| Some((lang.clone(), self.voice_for(lang, &voice)?)))
+            .collect();
+        Ok(Voices {
+            default: voice,
+            by_lang,
+        })
+    }
+
+    pub fn speak(&self, document: Document) -> crate::Result<String> {
+        let voices = self.document_voices(&document)?;
+        self.output.open()?;
+        let utterance_id = format!("piper-{}", self.next_id.fetch_add(1, Ordering::SeqCst));
+        let job = Job {
+            utterance_id: utterance_id.clone(),
+            items: document.items,
+            voices,
+            rate: *self.rate.lock().unwrap(),
+            generation: self.output.generation(),
+        };
+        self.jobs()?
+            .send(job)
+            .map_err(|e| error("Piper worker stopped", e))?;
+        Ok(utterance_id)
+    }
+
+    /// Synthesize a document into memory with the current voice and rate,
+    /// reporting the fraction of its sentences done.
+    pub fn render(
+        &self,
+        document: &Document,
+        mut on_progress: impl FnMut(f32),
+    ) -> crate::Result<Pcm> {
+        let voices = self.document_voices(document)?;
+        let rate = *self.rate.lock().unwrap();
+        let total: usize = document
+            .items
+            .iter()
+            .map(|item| match item {
+                Item::Text(span) => text::sentences(&span.text).len(),
+                _ => 0,
+            })
+            .sum();
+        let mut pcm = Pcm {
+            samples: Vec::new(),
+            sample_rate: voices.default.sample_rate,
+        };
+        let mut done = 0;
+        for item in &document.items {
+            match item {
+                Item::Text(span) => {
+                    let voice = voices.get(span.lang.as_ref());
+                    for range in text::sentences(&span.text) {
+                        let sentence = &span.text[range];
+                        let ids = voice.phoneme_ids(&phonemize::phonemize(voice, sentence)?);
+                        let audio = self.model.infer(voice, ids, rate * span.rate)?;
+                        pcm.append(&audio, voice.sample_rate);
+                        pcm.append_silence(SENTENCE_SILENCE);
+                        done += 1;
+                        on_progress(done as f32 / total as f32);
+                    }
+                }
+                Item::Break(millis) => pcm.append_silence(*millis as f32 / 1000.0),
+                Item::Mark(_) => {}
+            }
+        }
+        Ok(pcm)
+    }
+}
@@ -0,0 +1,65 @@
+use std::io::Write;
+use std::process::{Command, Stdio};
+use std::sync::OnceLock;
+
+use super::text;
+use super::voice::{PhonemeType, Voice};
+
+const ESPEAK: &str = "espeak-ng";
+
+/// Why voices phonemized by eSpeak cannot speak.
+pub const ESPEAK_MISSING: &str =
+    "Piper voices need eSpeak NG (espeak-ng) to be installed and on the PATH";
+
+/// Whether the `espeak-ng` program can be run. Checked once, as voices are listed
+/// often.
+pub fn espeak_available() -> bool {
+    static AVAILABLE: OnceLock<bool> = OnceLock::new();
+    *AVAILABLE.get_or_init(|| {
+        Command::new(ESPEAK)
+            .arg("--version")
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()
+            .is_ok_and(|status| status.success())
+    })
+}
+
+/// Phonemes for a sentence in the form the voice was trained on. Piper
+/// voices use eSpeak NG's IPA output with the clause punctuation kept,
+/// since eSpeak itself drops it.
+pub fn phonemize(voice: &Voice, sentence: &str) -> crate::Result<String> {
+    let mut phonemes = String::new();
+    for (clause, punctuation) in text::clauses(sentence) {
+        match voice.phoneme_type {
+            PhonemeType::Text => phonemes.push_str(&clause.to_lowercase()),
+            PhonemeType::Espeak => phonemes.push_str(&espeak(&voice.espeak_voice, clause)?),
+        }
+        if let Some(punctuation) = punctuation {
+            phonemes.push(punctuation);
+        }
+        phonemes.push(' ');
+    }
+    Ok(phonemes.trim_end().to_string())
+}
+
+fn espeak(voice: &str, text: &str) -> crate::Result<String> {
+    let mut child = Command::new(ESPEAK)
+        .args(["-q", "--ipa", "-v", voice, "--stdin"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|e| crate::Error::NativeTTSError(format!("{ESPEAK_MISSING}: {e}")))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(text.as_bytes())?;
+    }
+    let output = child.wait_with_output()?;
+    if !output.status.success() {
+        return Err(crate::Error::NativeTTSError(format!(
+            "espeak-ng has no voice '{voice}'"
+        )));
+    }
+    let phonemes = String::from_utf8_lossy(&output.stdout);
+    Ok(phonemes.split_whitespace().collect::<Vec<_>>().join(" "))
+}
@@ -0,0 +1,128 @@
+//! Splitting text into the units synthesis and boundary events work on.
+//! Offsets are byte ranges into the text being spoken.
+
+use std::ops::Range;
+
+const SENTENCE_END: &[char] = &['.', '!', '?', '।', '॥', '。', '！', '？', '\n'];
+const CLAUSE_END: &[char] = &[',', ';', ':', '，', '；', '：', '、'];
+
+/// Whether `c` ends a sentence or clause without following whitespace.
+fn is_full_width(c: char) -> bool {
+    matches!(c, '。' | '！' | '？' | '，' | '；' | '：' | '、' | '\n')
+}
+
+fn trimmed(text: &str, range: Range<usize>) -> Option<Range<usize>> {
+    let slice = &text[range.clone()];
+    let start = range.start + (slice.len() - slice.trim_start().len());
+    let end = range.end - (slice.len() - slice.trim_end().len());
+    (start < end).then_some(start..end)
+}
+
+/// Sentence ranges, each keeping its closing punctuation.
+pub fn sentences(text: &str) -> Vec<Range<usize>> {
+    let mut sentences = Vec::new();
+    let mut start = 0;
+    let mut chars = text.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        if !SENTENCE_END.contains(&c) {
+            continue;
+        }
+        // Keep runs like `?!` or `...` together.
+        let mut end = i + c.len_utf8();
+        while let Some(&(j, next)) = chars.peek() {
+            if !SENTENCE_END.contains(&next) || next == '\n' {
+                break;
+            }
+            end = j + next.len_utf8();
+            chars.next();
+        }
+        let at_break = match chars.peek() {
+            Some(&(_, next)) => is_full_width(c) || next.is_whitespace(),
+            None => true,
+        };
+        if at_break {
+            sentences.extend(trimmed(text, start..end));
+            start = end;
+        }
+    }
+    sentences.extend(trimmed(text, start..text.len()));
+    sentences
+}
+
+/// Clauses of a sentence with the punctuation that closes them, which
+/// shapes the intonation Piper models produce.
+pub fn clauses(sentence: &str) -> Vec<(&str, Option<char>)> {
+    let mut clauses = Vec::new();
+    let mut start = 0;
+    let mut chars = sentence.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        let at_break = match chars.peek() {
+            Some(&(_, next)) => is_full_width(c) || next.is_whitespace(),
+            None => true,
+        };
+        let punctuation = if !at_break {
+            continue;
+        } else if CLAUSE_END.contains(&c) {
+            ','
+        } else if SENTENCE_END.contains(&c) {
+            match c {
+                '!' | '！' => '!',
+                '?' | '？' => '?',
+                '\n' => continue,
+                _ => '.',
+            }
+        } else {
+            continue;
+        };
+        let clause = sentence[start..i].trim();
+        if !clause.is_empty() {
+            clauses.push((clause, Some(punctuation)));
+        }
+        start = i + c.len_utf8();
+    }
+    let rest = sentence[start..].trim();
+    if !rest.is_empty() {
+        clauses.push((rest, None));
+    }
+    clauses
+}
+
+/// Word ranges within a sentence.
+pub fn words(sentence: &str) -> Vec<Range<usize>> {
+    let mut words = Vec::new();
+    let mut start = None;
+    for (i, c) in sentence.char_indices() {
+        let separator = c.is_whitespace() || CLAUSE_END.contains(&c) || SENTENCE_END.contains(&c);
+        match (separator, start) {
+            (false, None) => start = Some(i),
+            (true, Some(s)) => {
+                words.push(s..i);
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    if let Some(s) = start {
+        words.push(s..sentence.len());
+    }
+    words
+}
+
+/// Where each word starts within `samples` of audio for the sentence,
+/// estimated from the word lengths since Piper reports no alignment.
+pub fn word_offsets(sentence: &str, words: &[Range<usize>], samples: usize) -> Vec<usize> {
+    let weight = |range: &Range<usize>| sentence[range.clone()].chars().count() + 1;
+    let total: usize = words.iter().map(weight).sum();
+    let mut offsets = Vec::with_capacity(words.len());
+    let mut elapsed = 0;
+    for word in words {
+        offsets.push(samples * elapsed / total.max(1));
+        elapsed += weight(word);
+    }
+    offsets
+}
+
+/// UTF-16 offset of byte offset `index`, as the web view counts positions.
+pub fn utf16_offset(text: &str, index: usize) -> usize {
+    text[..index].encode_utf16().count()
+}
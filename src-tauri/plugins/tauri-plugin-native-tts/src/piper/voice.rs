@@ -0,0 +1,201 @@
+//! Piper voice models: a `<name>.onnx` file next to its `<name>.onnx.json`
+//! config, as published in the Piper voice repositories.
+
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use unicode_normalization::UnicodeNormalization;
+
+const PAD: &str = "_";
+const BOS: &str = "^";
+const EOS: &str = "$";
+
+#[derive(Debug, Default, Deserialize)]
+struct AudioConfig {
+    #[serde(default = "default_sample_rate")]
+    sample_rate: u32,
+    #[serde(default)]
+    quality: Option<String>,
+}
+
+fn default_sample_rate() -> u32 {
+    22050
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct EspeakConfig {
+    voice: String,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub struct InferenceConfig {
+    #[serde(default = "default_noise_scale")]
+    pub noise_scale: f32,
+    #[serde(default = "default_length_scale")]
+    pub length_scale: f32,
+    #[serde(default = "default_noise_w")]
+    pub noise_w: f32,
+}
+
+fn default_noise_scale() -> f32 {
+    0.667
+}
+
+fn default_length_scale() -> f32 {
+    1.0
+}
+
+fn default_noise_w() -> f32 {
+    0.8
+}
+
+impl Default for InferenceConfig {
+    fn default() -> Self {
+        Self {
+            noise_scale: default_noise_scale(),
+            length_scale: default_length_scale(),
+            noise_w: default_noise_w(),
+        }
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct LanguageConfig {
+    code: String,
+    #[serde(default)]
+    name_native: Option<String>,
+    #[serde(default)]
+    name_english: Option<String>,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PhonemeType {
+    /// IPA phonemes from eSpeak NG.
+    #[default]
+    Espeak,
+    /// The characters of the text itself.
+    Text,
+}
+
+#[derive(Debug, Deserialize)]
+struct VoiceConfig {
+    #[serde(default)]
+    audio: AudioConfig,
+    #[serde(default)]
+    espeak: EspeakConfig,
+    #[serde(default)]
+    inference: InferenceConfig,
+    #[serde(default)]
+    phoneme_type: PhonemeType,
+    phoneme_id_map: HashMap<String, Vec<i64>>,
+    #[serde(default)]
+    num_speakers: u32,
+    #[serde(default)]
+    language: Option<LanguageConfig>,
+    #[serde(default)]
+    dataset: Option<String>,
+}
+
+#[derive(Debug)]
+pub struct Voice {
+    /// File stem of the model, unique within the voices directory.
+    pub key: String,
+    pub name: String,
+    /// BCP 47 language tag.
+    pub lang: String,
+    pub model: PathBuf,
+    pub sample_rate: u32,
+    pub espeak_voice: String,
+    pub phoneme_type: PhonemeType,
+    pub inference: InferenceConfig,
+    pub multi_speaker: bool,
+    phoneme_ids: HashMap<String, Vec<i64>>,
+}
+
+impl Voice {
+    pub fn load(model: &Path) -> crate::Result<Self> {
+        let mut config_path = model.as_os_str().to_owned();
+        config_path.push(".json");
+        let config = std::fs::read_to_string(&config_path)?;
+        Self::from_config(model, &config)
+    }
+
+    fn from_config(model: &Path, config: &str) -> crate::Result<Self> {
+        let config: VoiceConfig = serde_json::from_str(config).map_err(|e| {
+            crate::Error::NativeTTSError(format!(
+                "Invalid voice config for {}: {e}",
+                model.display()
+            ))
+        })?;
+        let key = model
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .unwrap_or_default()
+            .to_string();
+        let language = config.language.unwrap_or_default();
+        let lang = match language.code.as_str() {
+            "" => config.espeak.voice.clone(),
+            code => code.replace('_', "-"),
+        };
+        let language_name = language.name_native.or(language.name_english);
+        let name = match (language_name, config.dataset) {
+            (Some(language), Some(dataset)) => match config.audio.quality {
+                Some(quality) => format!("{dataset} ({language}, {quality})"),
+                None => format!("{dataset} ({language})"),
+            },
+            _ => key.clone(),
+        };
+        Ok(Self {
+            key,
+            name,
+            lang,
+            model: model.to_path_buf(),
+            sample_rate: config.audio.sample_rate,
+            espeak_voice: config.espeak.voice,
+            phoneme_type: config.phoneme_type,
+            inference: config.inference,
+            multi_speaker: config.num_speakers > 1,
+            phoneme_ids: config.phoneme_id_map,
+        })
+    }
+
+    /// Model input for the phonemes of one sentence, following Piper: the
+    /// phonemes between begin and end markers, each followed by padding.
+    /// Phonemes the model does not know are dropped.
+    pub fn phoneme_ids(&self, phonemes: &str) -> Vec<i64> {
+        let mut ids = Vec::new();
+        let mut push = |phoneme: &str| {
+            if let Some(phoneme_ids) = self.phoneme_ids.get(phoneme) {
+                ids.extend_from_slice(phoneme_ids);
+                true
+            } else {
+                false
+            }
+        };
+        push(BOS);
+        push(PAD);
+        let mut buf = [0u8; 4];
+        for phoneme in phonemes.nfd() {
+            if push(phoneme.encode_utf8(&mut buf)) {
+                push(PAD);
+            }
+        }
+        push(EOS);
+        ids
+    }
+}
+
+/// Installed voices, sorted by key.
+pub fn list(dir: &Path) -> Vec<Voice> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut voices: Vec<Voice> = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "onnx"))
+        .filter_map(|path| Voice::load(&path).ok())
+        .collect();
+    voices.sort_by(|a, b| a.key.cmp(&b.key));
+    voices
+}
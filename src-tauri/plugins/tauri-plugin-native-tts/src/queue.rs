@@ -0,0 +1,587 @@
+//! A playback queue, so reading goes on from one item to the next without a
+//! round trip through the webview, which the system may have suspended.
+//!
+//! The queue speaks through the platform's `speak` and learns that an item
+//! is done from its `end` event. It also keeps the sleep timer and the
+//! resume position of each book, in `$APPDATA/tts-positions.json`.
+
+use std::collections::{HashMap, VecDeque};
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serde_json::{json, Value};
+use tauri::{AppHandle, Runtime};
+
+use crate::models::*;
+use crate::NativeTtsExt;
+
+pub const EVENT_NAME: &str = "queue_events";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Sleep {
+    At(SystemTime),
+    EndOfChapter,
+}
+
+/// What to do after a change to the queue.
+#[derive(Debug, PartialEq)]
+pub enum Step {
+    Speak(QueueItem),
+    /// The sleep timer went off; stop speaking.
+    Sleep,
+    /// Nothing is left to speak.
+    Drained,
+    Ignore,
+}
+
+/// Whether two items are the same block of the book, so that appending it
+/// again joins the one queued. Paragraphs with the same text are different
+/// blocks.
+fn same_block(a: &QueueItem, b: &QueueItem) -> bool {
+    a.id == b.id || (a.position.is_some() && a.position == b.position)
+}
+
+#[derive(Debug, Default)]
+pub struct Queue {
+    book: Option<String>,
+    /// The item speaking, and its utterance.
+    current: Option<(QueueItem, String)>,
+    pending: VecDeque<QueueItem>,
+    /// Chapter of the last item started.
+    chapter: Option<String>,
+    sleep: Option<Sleep>,
+    /// Bumped with every timer, so that an older one going off does nothing.
+    generation: u64,
+}
+
+impl Queue {
+    pub fn enqueue(
+        &mut self,
+        book: Option<String>,
+        items: Vec<QueueItem>,
+        mode: EnqueueMode,
+    ) -> Step {
+        if book.is_some() {
+            self.book = book;
+        }
+        match mode {
+            EnqueueMode::Append => {
+                for item in items {
+                    let mut queued = self
+                        .current
+                        .iter()
+                        .map(|(item, _)| item)
+                        .chain(&self.pending);
+                    if !queued.any(|queued| same_block(queued, &item)) {
+                        self.pending.push_back(item);
+                    }
+                }
+            }
+            EnqueueMode::Next => {
+                self.pending = items.into();
+            }
+            EnqueueMode::Replace => {
+                // A new start is not the end of a chapter.
+                self.current = None;
+                self.chapter = None;
+                self.pending = items.into();
+            }
+        }
+        if self.current.is_some() {
+            return Step::Ignore;
+        }
+        self.advance()
+    }
+
+    fn advance(&mut self) -> Step {
+        let Some(item) = self.pending.pop_front() else {
+            return Step::Drained;
+        };
+        let new_chapter = self.chapter.is_some() && item.chapter != self.chapter;
+        if new_chapter && self.sleep == Some(Sleep::EndOfChapter) {
+            self.sleep = None;
+            self.clear();
+            return Step::Sleep;
+        }
+        Step::Speak(item)
+    }
+
+    /// The utterance speaking `item`, if one is.
+    pub fn speaking(&self, item: &QueueItem) -> Option<String> {
+        match &self.current {
+            Some((current, utterance_id)) if same_block(current, item) => {
+                Some(utterance_id.clone())
+            }
+            _ => None,
+        }
+    }
+
+    pub fn started(&mut self, item: QueueItem, utterance_id: String) {
+        self.chapter = item.chapter.clone();
+        self.current = Some((item, utterance_id));
+    }
+
+    /// Move on after the utterance ended, or stop if it failed.
+    pub fn finished(&mut self, utterance_id: &str, ok: bool) -> Step {
+        match &self.current {
+            Some((_, current)) if current == utterance_id => self.current = None,
+            _ => return Step::Ignore,
+        }
+        if !ok {
+            self.clear();
+            return Step::Drained;
+        }
+        self.advance()
+    }
+
+    /// Forget the items, keeping the sleep timer.
+    pub fn clear(&mut self) {
+        self.current = None;
+        self.pending.clear();
+        self.chapter = None;
+    }
+
+    /// Set the sleep timer, returning its generation.
+    pub fn set_sleep(&mut self, sleep: Option<Sleep>) -> u64 {
+        self.sleep = sleep;
+        self.generation += 1;
+        self.generation
+    }
+
+    /// Whether the timer of `generation` is still set; it goes off if so.
+    pub fn expire(&mut self, generation: u64) -> bool {
+        if generation != self.generation || !matches!(self.sleep, Some(Sleep::At(_))) {
+            return false;
+        }
+        self.sleep = None;
+        self.clear();
+        true
+    }
+
+    pub fn state(&self) -> GetQueueStateResponse {
+        let sleep_at = match self.sleep {
+            Some(Sleep::At(time)) => time
+                .duration_since(UNIX_EPOCH)
+                .ok()
+                .map(|since| since.as_millis() as u64),
+            _ => None,
+        };
+        GetQueueStateResponse {
+            book: self.book.clone(),
+            current: self.current.as_ref().map(|(item, _)| item.id.clone()),
+            utterance_id: self.current.as_ref().map(|(_, id)| id.clone()),
+            pending: self.pending.iter().map(|item| item.id.clone()).collect(),
+            sleep_at,
+            sleep_at_end_of_chapter: self.sleep == Some(Sleep::EndOfChapter),
+        }
+    }
+}
+
+/// The last position spoken in each book.
+struct Positions {
+    path: PathBuf,
+    /// Read from the file on first use.
+    saved: Mutex<Option<HashMap<String, Value>>>,
+}
+
+impl Positions {
+    fn load(&self) -> HashMap<String, Value> {
+        std::fs::read(&self.path)
+            .ok()
+            .and_then(|data| serde_json::from_slice(&data).ok())
+            .unwrap_or_default()
+    }
+
+    fn get(&self, book: &str) -> Option<Value> {
+        let mut saved = self.saved.lock().unwrap();
+        saved.get_or_insert_with(|| self.load()).get(book).cloned()
+    }
+
+    fn set(&self, book: &str, position: Value) -> crate::Result<()> {
+        let mut saved = self.saved.lock().unwrap();
+        let saved = saved.get_or_insert_with(|| self.load());
+        if saved.get(book) == Some(&position) {
+            return Ok(());
+        }
+        saved.insert(book.to_string(), position);
+        if let Some(dir) = self.path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        let tmp = self.path.with_extension("tmp");
+        std::fs::write(&tmp, serde_json::to_vec(saved).unwrap_or_default())?;
+        std::fs::rename(tmp, &self.path)?;
+        Ok(())
+    }
+}
+
+pub struct Player {
+    queue: Mutex<Queue>,
+    positions: Positions,
+}
+
+impl Player {
+    pub fn new(positions: PathBuf) -> Self {
+        Self {
+            queue: Mutex::new(Queue::default()),
+            positions: Positions {
+                path: positions,
+                saved: Mutex::new(None),
+            },
+        }
+    }
+}
+
+/// Carry out `step`, with the queue still locked so that the end of the new
+/// utterance waits until it is recorded.
+fn run<R: Runtime>(
+    app: &AppHandle<R>,
+    queue: &mut Queue,
+    step: Step,
+) -> crate::Result<Option<String>> {
+    let tts = app.native_tts();
+    match step {
+        Step::Speak(item) => {
+            if let (Some(book), Some(position)) = (&queue.book, &item.position) {
+                // Losing a position is no reason to stop reading.
+                let _ = tts.player().positions.set(book, position.clone());
+            }
+            let args = SpeakArgs {
+                text: item.text.clone(),
+                preload: false,
+                ssml: item.ssml,
+                marks: Vec::new(),
+                lang: item.lang.clone(),
+            };
+            let utterance_id = match tts.speak(args) {
+                Ok(response) => response.utterance_id,
+                Err(err) => {
+                    queue.clear();
+                    tts.send_queue_event(json!({ "type": "drained" }));
+                    return Err(err);
+                }
+            };
+            let id = item.id.clone();
+            queue.started(item, utterance_id.clone());
+            tts.send_queue_event(json!({ "type": "start", "id": id, "utteranceId": utterance_id }));
+            Ok(Some(utterance_id))
+        }
+        Step::Sleep => {
+            tts.stop()?;
+            tts.send_queue_event(json!({ "type": "sleep" }));
+            Ok(None)
+        }
+        Step::Drained => {
+            tts.send_queue_event(json!({ "type": "drained" }));
+            Ok(None)
+        }
+        Step::Ignore => Ok(None),
+    }
+}
+
+pub fn enqueue<R: Runtime>(
+    app: &AppHandle<R>,
+    args: EnqueueArgs,
+) -> crate::Result<EnqueueResponse> {
+    let player = app.native_tts().player();
+    let mut queue = player.queue.lock().unwrap();
+    let speaking = queue.current.is_some();
+    // Appending the item that is speaking joins it.
+    let first = args.items.first().cloned();
+    let step = queue.enqueue(args.book, args.items, args.mode);
+    if speaking && args.mode == EnqueueMode::Replace {
+        app.native_tts().stop()?;
+    }
+    let utterance_id = match step {
+        Step::Ignore if args.mode == EnqueueMode::Append => {
+            first.and_then(|item| queue.speaking(&item))
+        }
+        step => run(app, &mut queue, step)?,
+    };
+    Ok(EnqueueResponse { utterance_id })
+}
+
+/// Follow the events of the platform's utterances.
+pub fn on_event<R: Runtime>(app: &AppHandle<R>, utterance_id: &str, code: &str) {
+    if code != "end" && code != "error" {
+        return;
+    }
+    let player = app.native_tts().player();
+    let mut queue = player.queue.lock().unwrap();
+    let step = queue.finished(utterance_id, code == "end");
+    let _ = run(app, &mut queue, step);
+}
+
+pub fn clear<R: Runtime>(app: &AppHandle<R>) {
+    app.native_tts().player().queue.lock().unwrap().clear();
+}
+
+pub fn set_sleep_timer<R: Runtime>(
+    app: &AppHandle<R>,
+    args: SetSleepTimerArgs,
+) -> crate::Result<()> {
+    let delay = args
+        .minutes
+        .filter(|minutes| minutes.is_finite() && *minutes > 0.0)
+        .map(|minutes| Duration::from_secs_f64(minutes * 60.0));
+    let sleep = match delay {
+        _ if args.end_of_chapter => Some(Sleep::EndOfChapter),
+        Some(delay) => Some(Sleep::At(SystemTime::now() + delay)),
+        None => None,
+    };
+    let generation = app
+        .native_tts()
+        .player()
+        .queue
+        .lock()
+        .unwrap()
+        .set_sleep(sleep);
+    if let (Some(Sleep::At(_)), Some(delay)) = (sleep, delay) {
+        let app = app.clone();
+        std::thread::spawn(move || {
+            std::thread::sleep(delay);
+            let player = app.native_tts().player();
+            let mut queue = player.queue.lock().unwrap();
+            if queue.expire(generation) {
+                let _ = run(&app, &mut queue, Step::Sleep);
+            }
+        });
+    }
+    Ok(())
+}
+
+pub fn get_queue_state<R: Runtime>(app: &AppHandle<R>) -> GetQueueStateResponse {
+    app.native_tts().player().queue.lock().unwrap().state()
+}
+
+pub fn get_resume_position<R: Runtime>(
+    app: &AppHandle<R>,
+    args: GetResumePositionArgs,
+) -> GetResumePositionResponse {
+    GetResumePositionResponse {
+        position: app.native_tts().player().positions.get(&args.book),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(id: &str, chapter: &str) -> QueueItem {
+        QueueItem {
+            id: id.to_string(),
+            text: format!("Text of {id}"),
+            ssml: false,
+            lang: None,
+            chapter: Some(chapter.to_string()),
+            position: Some(json!({ "cfi": format!("cfi-{id}") })),
+        }
+    }
+
+    /// Start what `step` says to, as the platform would.
+    fn start(queue: &mut Queue, step: Step) -> Option<String> {
+        match step {
+            Step::Speak(item) => {
+                let id = item.id.clone();
+                queue.started(item, format!("u-{id}"));
+                Some(id)
+            }
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn test_append_starts_when_idle() {
+        let mut queue = Queue::default();
+        let step = queue.enqueue(
+            None,
+            vec![item("a", "1"), item("b", "1")],
+            EnqueueMode::Append,
+        );
+        assert_eq!(start(&mut queue, step), Some("a".into()));
+        let step = queue.enqueue(None, vec![item("c", "1")], EnqueueMode::Append);
+        assert_eq!(step, Step::Ignore);
+        assert_eq!(queue.state().pending, ["b", "c"]);
+    }
+
+    #[test]
+    fn test_append_skips_queued_items() {
+        let mut queue = Queue::default();
+        let step = queue.enqueue(
+            None,
+            vec![item("a", "1"), item("b", "1")],
+            EnqueueMode::Append,
+        );
+        start(&mut queue, step);
+        // The webview numbers the items afresh, but their positions stay.
+        let again = vec![
+            QueueItem {
+                id: "a2".into(),
+                ..item("a", "1")
+            },
+            item("b", "1"),
+            item("c", "1"),
+        ];
+        queue.enqueue(None, again.clone(), EnqueueMode::Append);
+        assert_eq!(queue.state().pending, ["b", "c"]);
+        assert_eq!(queue.speaking(&again[0]).as_deref(), Some("u-a"));
+        assert_eq!(queue.speaking(&again[1]), None);
+    }
+
+    #[test]
+    fn test_append_keeps_repeated_text() {
+        let mut queue = Queue::default();
+        let step = queue.enqueue(None, vec![item("a", "1")], EnqueueMode::Append);
+        start(&mut queue, step);
+        let repeated = QueueItem {
+            text: "Text of a".into(),
+            ..item("b", "1")
+        };
+        queue.enqueue(None, vec![repeated.clone()], EnqueueMode::Append);
+        assert_eq!(queue.state().pending, ["b"]);
+        assert_eq!(queue.speaking(&repeated), None);
+    }
+
+    #[test]
+    fn test_next_keeps_current() {
+        let mut queue = Queue::default();
+        let step = queue.enqueue(
+            None,
+            vec![item("a", "1"), item("b", "1")],
+            EnqueueMode::Append,
+        );
+        start(&mut queue, step);
+        let step = queue.enqueue(None, vec![item("c", "1")], EnqueueMode::Next);
+        assert_eq!(step, Step::Ignore);
+        let state = queue.state();
+        assert_eq!(state.current.as_deref(), Some("a"));
+        assert_eq!(state.pending, ["c"]);
+    }
+
+    #[test]
+    fn test_replace_restarts() {
+        let mut queue = Queue::default();
+        let step = queue.enqueue(
+            None,
+            vec![item("a", "1"), item("b", "1")],
+            EnqueueMode::Append,
+        );
+        start(&mut queue, step);
+        let step = queue.enqueue(None, vec![item("c", "1")], EnqueueMode::Replace);
+        assert_eq!(start(&mut queue, step), Some("c".into()));
+        assert!(queue.state().pending.is_empty());
+    }
+
+    #[test]
+    fn test_finished_advances() {
+        let mut queue = Queue::default();
+        let step = queue.enqueue(
+            None,
+            vec![item("a", "1"), item("b", "1")],
+            EnqueueMode::Append,
+        );
+        start(&mut queue, step);
+        // The end of an utterance the queue did not start changes nothing.
+        assert_eq!(queue.finished("u-x", true), Step::Ignore);
+        let step = queue.finished("u-a", true);
+        assert_eq!(start(&mut queue, step), Some("b".into()));
+        assert_eq!(queue.finished("u-b", true), Step::Drained);
+        assert_eq!(queue.state().current, None);
+    }
+
+    #[test]
+    fn test_error_stops() {
+        let mut queue = Queue::default();
+        let step = queue.enqueue(
+            None,
+            vec![item("a", "1"), item("b", "1")],
+            EnqueueMode::Append,
+        );
+        start(&mut queue, step);
+        assert_eq!(queue.finished("u-a", false), Step::Drained);
+        assert!(queue.state().pending.is_empty());
+    }
+
+    #[test]
+    fn test_sleep_at_end_of_chapter() {
+        let mut queue = Queue::default();
+        let items = vec![item("a", "1"), item("b", "1"), item("c", "2")];
+        let step = queue.enqueue(None, items, EnqueueMode::Append);
+        start(&mut queue, step);
+        queue.set_sleep(Some(Sleep::EndOfChapter));
+        let step = queue.finished("u-a", true);
+        assert_eq!(start(&mut queue, step), Some("b".into()));
+        assert_eq!(queue.finished("u-b", true), Step::Sleep);
+        let state = queue.state();
+        assert!(state.pending.is_empty());
+        assert!(!state.sleep_at_end_of_chapter);
+    }
+
+    #[test]
+    fn test_sleep_when_next_chapter_is_appended() {
+        let mut queue = Queue::default();
+        let step = queue.enqueue(None, vec![item("a", "1")], EnqueueMode::Append);
+        start(&mut queue, step);
+        queue.set_sleep(Some(Sleep::EndOfChapter));
+        assert_eq!(queue.finished("u-a", true), Step::Drained);
+        let step = queue.enqueue(None, vec![item("b", "2")], EnqueueMode::Append);
+        assert_eq!(step, Step::Sleep);
+    }
+
+    #[test]
+    fn test_replace_is_not_end_of_chapter() {
+        let mut queue = Queue::default();
+        let step = queue.enqueue(None, vec![item("a", "1")], EnqueueMode::Append);
+        start(&mut queue, step);
+        queue.set_sleep(Some(Sleep::EndOfChapter));
+        let step = queue.enqueue(None, vec![item("b", "5")], EnqueueMode::Replace);
+        assert_eq!(start(&mut queue, step), Some("b".into()));
+        assert!(queue.state().sleep_at_end_of_chapter);
+    }
+
+    #[test]
+    fn test_timer_generations() {
+        let mut queue = Queue::default();
+        let step = queue.enqueue(
+            None,
+            vec![item("a", "1"), item("b", "1")],
+            EnqueueMode::Append,
+        );
+        start(&mut queue, step);
+        let old = queue.set_sleep(Some(Sleep::At(SystemTime::now())));
+        let new = queue.set_sleep(Some(Sleep::At(SystemTime::now())));
+        assert!(queue.state().sleep_at.is_some());
+        assert!(!queue.expire(old));
+        assert!(queue.expire(new));
+        let state = queue.state();
+        assert_eq!(state.current, None);
+        assert_eq!(state.sleep_at, None);
+        // A timer turned off does not go off.
+        let off = queue.set_sleep(Some(Sleep::At(SystemTime::now())));
+        queue.set_sleep(None);
+        assert!(!queue.expire(off));
+    }
+
+    #[test]
+    fn test_positions() {
+        let dir = std::env::temp_dir().join(format!("native-tts-positions-{}", std::process::id()));
+        let path = dir.join("tts-positions.json");
+        let positions = Positions {
+            path: path.clone(),
+            saved: Mutex::new(None),
+        };
+        assert_eq!(positions.get("book"), None);
+        positions
+            .set("book", json!({ "cfi": "epubcfi(/6/4!/4/2)" }))
+            .unwrap();
+        let reloaded = Positions {
+            path,
+            saved: Mutex::new(None),
+        };
+        assert_eq!(
+            reloaded.get("book"),
+            Some(json!({ "cfi": "epubcfi(/6/4!/4/2)" }))
+        );
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,482 @@
+//! Minimal client for SSIP, the text protocol spoken by Speech Dispatcher.
+//!
+//! Commands are CRLF-terminated lines; every reply is a run of `NNN-data`
+//! lines closed by a `NNN message` line. With notifications enabled, the
+//! server interleaves `7NN` event replies, which a reader thread hands to
+//! the event callback while command replies go back to the caller.
+
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpStream;
+#[cfg(unix)]
+use std::path::PathBuf;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::Mutex;
+
+use crate::{Error, Result};
+
+const CLIENT_NAME: &str = "user:hackxindia26:tts";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Address {
+    #[cfg(unix)]
+    Unix(PathBuf),
+    Inet(String, u16),
+}
+
+impl Address {
+    /// `$SPEECHD_ADDRESS`, or the per-user socket Speech Dispatcher uses by default.
+    pub fn from_env() -> Option<Self> {
+        match std::env::var("SPEECHD_ADDRESS") {
+            Ok(value) if !value.trim().is_empty() => Self::parse(value.trim()),
+            _ => Self::default_socket(),
+        }
+    }
+
+    fn parse(value: &str) -> Option<Self> {
+        let (method, rest) = value.split_once(':').unwrap_or((value, ""));
+        match method {
+            #[cfg(unix)]
+            "unix_socket" if rest.is_empty() => Self::default_socket(),
+            #[cfg(unix)]
+            "unix_socket" => Some(Self::Unix(PathBuf::from(rest))),
+            "inet_socket" => {
+                let (host, port) = rest.rsplit_once(':').unwrap_or((rest, "6560"));
+                let host = if host.is_empty() { "127.0.0.1" } else { host };
+                Some(Self::Inet(host.to_string(), port.parse().ok()?))
+            }
+            _ => None,
+        }
+    }
+
+    #[cfg(unix)]
+    fn default_socket() -> Option<Self> {
+        let runtime_dir = std::env::var_os("XDG_RUNTIME_DIR")?;
+        Some(Self::Unix(
+            PathBuf::from(runtime_dir).join("speech-dispatcher/speechd.sock"),
+        ))
+    }
+
+    #[cfg(not(unix))]
+    fn default_socket() -> Option<Self> {
+        None
+    }
+}
+
+/// Notification about a message, identified by the id `speak` returned.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Event {
+    IndexMark(u64, String),
+    Begin(u64),
+    End(u64),
+    Cancel(u64),
+    Pause(u64),
+    Resume(u64),
+    /// The connection was closed; no further events will arrive.
+    Closed,
+}
+
+impl Event {
+    fn from_reply(reply: &Reply) -> Option<Self> {
+        let id = reply.lines.first()?.parse().ok()?;
+        Some(match reply.code {
+            700 => Self::IndexMark(id, reply.lines.get(2)?.clone()),
+            701 => Self::Begin(id),
+            702 => Self::End(id),
+            703 => Self::Cancel(id),
+            704 => Self::Pause(id),
+            705 => Self::Resume(id),
+            _ => return None,
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Reply {
+    code: u16,
+    /// The `NNN-` data lines.
+    lines: Vec<String>,
+    /// Text of the closing `NNN ` line.
+    message: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Voice {
+    pub name: String,
+    pub language: String,
+    pub variant: String,
+}
+
+struct Connection {
+    writer: Box<dyn Write + Send>,
+    replies: Receiver<Reply>,
+}
+
+impl Connection {
+    fn send(&mut self, line: &str) -> Result<Reply> {
+        self.writer.write_all(line.as_bytes())?;
+        self.writer.write_all(b"\r\n")?;
+        self.writer.flush()?;
+        let reply = self.replies.recv().map_err(|_| {
+            Error::NativeTTSError("Speech Dispatcher closed the connection".to_string())
+        })?;
+        if (200..300).contains(&reply.code) {
+            Ok(reply)
+        } else {
+            Err(Error::NativeTTSError(format!(
+                "Speech Dispatcher rejected `{}`: {} {}",
+                line.split_whitespace().next().unwrap_or_default(),
+                reply.code,
+                reply.message
+            )))
+        }
+    }
+}
+
+pub struct Client {
+    conn: Mutex<Connection>,
+}
+
+impl Client {
+    pub fn connect(address: &Address, on_event: impl Fn(Event) + Send + 'static) -> Result<Self> {
+        match address {
+            #[cfg(unix)]
+            Address::Unix(path) => {
+                let stream = std::os::unix::net::UnixStream::connect(path)?;
+                Self::from_stream(stream.try_clone()?, stream, on_event)
+            }
+            Address::Inet(host, port) => {
+                let stream = TcpStream::connect((host.as_str(), *port))?;
+                Self::from_stream(stream.try_clone()?, stream, on_event)
+            }
+        }
+    }
+
+    /// Start a session over an established connection.
+    pub fn from_stream(
+        reader: impl Read + Send + 'static,
+        writer: impl Write + Send + 'static,
+        on_event: impl Fn(Event) + Send + 'static,
+    ) -> Result<Self> {
+        let (tx, rx) = mpsc::channel();
+        std::thread::Builder::new()
+            .name("ssip-reader".into())
+            .spawn(move || read_replies(BufReader::new(reader), tx, on_event))?;
+        let client = Self {
+            conn: Mutex::new(Connection {
+                writer: Box::new(writer),
+                replies: rx,
+            }),
+        };
+        client.send(&format!("SET SELF CLIENT_NAME {CLIENT_NAME}"))?;
+        client.send("SET SELF NOTIFICATION ALL on")?;
+        Ok(client)
+    }
+
+    fn send(&self, line: &str) -> Result<Reply> {
+        self.conn.lock().unwrap().send(line)
+    }
+
+    /// Queue `text` and return its message id.
+    pub fn speak(&self, text: &str) -> Result<u64> {
+        let mut conn = self.conn.lock().unwrap();
+        conn.send("SPEAK")?;
+        // A lone `.` ends the data, so leading dots are doubled.
+        let mut data = String::new();
+        for line in text.lines() {
+            if line.starts_with('.') {
+                data.push('.');
+            }
+            data.push_str(line);
+            data.push_str("\r\n");
+        }
+        data.push('.');
+        let reply = conn.send(&data)?;
+        reply
+            .lines
+            .first()
+            .and_then(|id| id.parse().ok())
+            .ok_or_else(|| Error::NativeTTSError("Speech Dispatcher sent no message id".into()))
+    }
+
+    pub fn cancel(&self) -> Result<()> {
+        self.send("CANCEL SELF").map(|_| ())
+    }
+
+    pub fn pause(&self) -> Result<()> {
+        self.send("PAUSE SELF").map(|_| ())
+    }
+
+    pub fn resume(&self) -> Result<()> {
+        self.send("RESUME SELF").map(|_| ())
+    }
+
+    /// Speech rate, from -100 (slowest) to 100 (fastest).
+    pub fn set_rate(&self, rate: i32) -> Result<()> {
+        self.send(&format!("SET SELF RATE {}", rate.clamp(-100, 100)))
+            .map(|_| ())
+    }
+
+    /// Voice pitch, from -100 to 100.
+    pub fn set_pitch(&self, pitch: i32) -> Result<()> {
+        self.send(&format!("SET SELF PITCH {}", pitch.clamp(-100, 100)))
+            .map(|_| ())
+    }
+
+    /// Whether `speak` takes SSML rather than plain text.
+    pub fn set_ssml_mode(&self, enabled: bool) -> Result<()> {
+        let mode = if enabled { "on" } else { "off" };
+        self.send(&format!("SET SELF SSML_MODE {mode}")).map(|_| ())
+    }
+
+    pub fn set_output_module(&self, module: &str) -> Result<()> {
+        self.send(&format!("SET SELF OUTPUT_MODULE {module}"))
+            .map(|_| ())
+    }
+
+    pub fn set_voice(&self, voice: &str) -> Result<()> {
+        self.send(&format!("SET SELF SYNTHESIS_VOICE {voice}"))
+            .map(|_| ())
+    }
+
+    pub fn output_module(&self) -> Result<String> {
+        let reply = self.send("GET OUTPUT_MODULE")?;
+        reply
+            .lines
+            .into_iter()
+            .next()
+            .ok_or_else(|| Error::NativeTTSError("Speech Dispatcher sent no output module".into()))
+    }
+
+    pub fn list_output_modules(&self) -> Result<Vec<String>> {
+        Ok(self.send("LIST OUTPUT_MODULES")?.lines)
+    }
+
+    /// Voices of the current output module.
+    pub fn list_voices(&self) -> Result<Vec<Voice>> {
+        let reply = self.send("LIST SYNTHESIS_VOICES")?;
+        Ok(reply
+            .lines
+            .iter()
+            .filter_map(|line| {
+                let mut fields = line.split('\t');
+                let name = fields.next().filter(|n| !n.is_empty())?;
+                Some(Voice {
+                    name: name.to_string(),
+                    language: fields.next().unwrap_or_default().to_string(),
+                    variant: fields.next().unwrap_or("none").to_string(),
+                })
+            })
+            .collect())
+    }
+}
+
+impl Drop for Client {
+    fn drop(&mut self) {
+        if let Ok(mut conn) = self.conn.lock() {
+            let _ = conn.writer.write_all(b"QUIT\r\n");
+            let _ = conn.writer.flush();
+        }
+    }
+}
+
+fn read_replies(reader: impl BufRead, replies: Sender<Reply>, on_event: impl Fn(Event)) {
+    let mut lines = Vec::new();
+    for line in reader.split(b'\n') {
+        let Ok(line) = line else { break };
+        let line = String::from_utf8_lossy(&line);
+        let line = line.trim_end_matches('\r');
+        let (Some(code), Some(separator)) = (
+            line.get(..3).and_then(|code| code.parse::<u16>().ok()),
+            line.as_bytes().get(3).copied(),
+        ) else {
+            continue;
+        };
+        let text = line.get(4..).unwrap_or_default().to_string();
+        if separator == b'-' {
+            lines.push(text);
+            continue;
+        }
+        let reply = Reply {
+            code,
+            lines: std::mem::take(&mut lines),
+            message: text,
+        };
+        if (700..800).contains(&reply.code) {
+            if let Some(event) = Event::from_reply(&reply) {
+                on_event(event);
+            }
+        } else if replies.send(reply).is_err() {
+            break;
+        }
+    }
+    on_event(Event::Closed);
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::os::unix::net::{UnixListener, UnixStream};
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    /// A scripted Speech Dispatcher: answers each command with the reply of
+    /// the first matching prefix, and records what it received.
+    fn fake_server(
+        script: Vec<(&'static str, &'static str)>,
+    ) -> (PathBuf, Arc<Mutex<Vec<String>>>) {
+        let dir = std::env::temp_dir().join(format!(
+            "ssip-test-{}-{:?}",
+            std::process::id(),
+            std::thread::current().id()
+        ));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("speechd.sock");
+        let listener = UnixListener::bind(&path).unwrap();
+        let received = Arc::new(Mutex::new(Vec::new()));
+        let log = received.clone();
+        std::thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut writer = stream.try_clone().unwrap();
+            let mut receiving_data = false;
+            for line in BufReader::new(stream).lines() {
+                let line = line.unwrap();
+                let line = line.trim_end_matches('\r').to_string();
+                log.lock().unwrap().push(line.clone());
+                let key = if receiving_data {
+                    if line != "." {
+                        continue;
+                    }
+                    "."
+                } else {
+                    line.as_str()
+                };
+                if key == "QUIT" {
+                    break;
+                }
+                receiving_data = key == "SPEAK";
+                let reply = script
+                    .iter()
+                    .find(|(prefix, _)| key.starts_with(prefix))
+                    .map(|(_, reply)| *reply)
+                    .unwrap_or("300 ERR UNKNOWN COMMAND\r\n");
+                writer.write_all(reply.as_bytes()).unwrap();
+            }
+        });
+        (path, received)
+    }
+
+    fn recv_events(events: &Arc<Mutex<Vec<Event>>>, count: usize) -> Vec<Event> {
+        for _ in 0..100 {
+            if events.lock().unwrap().len() >= count {
+                break;
+            }
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        events.lock().unwrap().clone()
+    }
+
+    #[test]
+    fn test_parse_address() {
+        assert_eq!(
+            Address::parse("unix_socket:/run/speechd.sock"),
+            Some(Address::Unix(PathBuf::from("/run/speechd.sock")))
+        );
+        assert_eq!(
+            Address::parse("inet_socket:localhost:6561"),
+            Some(Address::Inet("localhost".into(), 6561))
+        );
+        assert_eq!(
+            Address::parse("inet_socket"),
+            Some(Address::Inet("127.0.0.1".into(), 6560))
+        );
+        assert_eq!(Address::parse("carrier_pigeon"), None);
+    }
+
+    #[test]
+    fn test_speak_and_events() {
+        let (path, received) = fake_server(vec![
+            ("SET SELF CLIENT_NAME", "208 OK CLIENT NAME SET\r\n"),
+            ("SET SELF NOTIFICATION", "218 OK NOTIFICATION SET\r\n"),
+            ("SPEAK", "230 OK RECEIVING DATA\r\n"),
+            (
+                ".",
+                "225-42\r\n225 OK MESSAGE QUEUED\r\n\
+                 701-42\r\n701-1\r\n701 BEGIN\r\n\
+                 700-42\r\n700-1\r\n700-m1\r\n700 INDEX MARK\r\n\
+                 702-42\r\n702-1\r\n702 END\r\n",
+            ),
+            ("SET SELF RATE", "203 OK RATE SET\r\n"),
+        ]);
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let sink = events.clone();
+        let client = Client::connect(&Address::Unix(path), move |event| {
+            sink.lock().unwrap().push(event)
+        })
+        .unwrap();
+
+        assert_eq!(client.speak("Hello\n.hidden dot").unwrap(), 42);
+        client.set_rate(250).unwrap();
+        assert_eq!(
+            recv_events(&events, 3),
+            vec![
+                Event::Begin(42),
+                Event::IndexMark(42, "m1".into()),
+                Event::End(42)
+            ]
+        );
+        assert_eq!(
+            received.lock().unwrap()[2..],
+            ["SPEAK", "Hello", "..hidden dot", ".", "SET SELF RATE 100"]
+        );
+
+        drop(client);
+        assert_eq!(recv_events(&events, 4).last(), Some(&Event::Closed));
+    }
+
+    #[test]
+    fn test_voices_and_errors() {
+        let (path, _) = fake_server(vec![
+            ("SET SELF", "200 OK\r\n"),
+            (
+                "LIST OUTPUT_MODULES",
+                "250-espeak-ng\r\n250-piper\r\n250 OK MODULE LIST SENT\r\n",
+            ),
+            (
+                "LIST SYNTHESIS_VOICES",
+                "249-English (America)\ten-US\tnone\r\n249-German\tde\tf1\r\n249 OK VOICE LIST SENT\r\n",
+            ),
+            ("PAUSE SELF", "401 ERR NO SUCH MESSAGE\r\n"),
+        ]);
+        let client = Client::connect(&Address::Unix(path), |_| {}).unwrap();
+        assert_eq!(
+            client.list_output_modules().unwrap(),
+            vec!["espeak-ng", "piper"]
+        );
+        assert_eq!(
+            client.list_voices().unwrap(),
+            vec![
+                Voice {
+                    name: "English (America)".into(),
+                    language: "en-US".into(),
+                    variant: "none".into()
+                },
+                Voice {
+                    name: "German".into(),
+                    language: "de".into(),
+                    variant: "f1".into()
+                },
+            ]
+        );
+        let err = client.pause().unwrap_err().to_string();
+        assert!(err.contains("401"), "{err}");
+    }
+
+    #[test]
+    fn test_connection_closed() {
+        let (ours, theirs) = UnixStream::pair().unwrap();
+        drop(theirs);
+        let result = Client::from_stream(ours.try_clone().unwrap(), ours, |_| {});
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,557 @@
+//! The SSML the reader sends: `<mark>`s for highlighting, `<break>`s,
+//! `<prosody>` rate and pitch, and `xml:lang` switches. Backends consume
+//! the document as a flat list of items.
+
+use std::borrow::Cow;
+
+use crate::models::SpeechMark;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Span {
+    pub text: String,
+    pub lang: Option<String>,
+    /// Rate and pitch multipliers, 1.0 being the voice's default.
+    pub rate: f32,
+    pub pitch: f32,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Item {
+    Text(Span),
+    Mark(String),
+    /// Silence, in milliseconds.
+    Break(u32),
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Document {
+    pub items: Vec<Item>,
+}
+
+#[derive(Clone)]
+struct Context<'a> {
+    name: &'a str,
+    lang: Option<String>,
+    rate: f32,
+    pitch: f32,
+    /// Content that is not spoken, like the text of `<sub>`.
+    silent: bool,
+}
+
+pub(crate) enum Token<'a> {
+    Open {
+        name: &'a str,
+        attrs: Vec<(&'a str, String)>,
+        empty: bool,
+    },
+    Close(&'a str),
+    Text(String),
+}
+
+fn invalid(message: &str) -> crate::Error {
+    crate::Error::NativeTTSError(format!("Invalid SSML: {message}"))
+}
+
+fn unescape(text: &str) -> Cow<'_, str> {
+    if !text.contains('&') {
+        return Cow::Borrowed(text);
+    }
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(amp) = rest.find('&') {
+        out.push_str(&rest[..amp]);
+        rest = &rest[amp..];
+        let Some(semi) = rest.find(';').filter(|&i| i <= 10) else {
+            out.push('&');
+            rest = &rest[1..];
+            continue;
+        };
+        let entity = &rest[1..semi];
+        let decoded = match entity {
+            "amp" => Some('&'),
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            "nbsp" => Some('\u{a0}'),
+            _ => entity
+                .strip_prefix("#x")
+                .or_else(|| entity.strip_prefix("#X"))
+                .and_then(|hex| u32::from_str_radix(hex, 16).ok())
+                .or_else(|| entity.strip_prefix('#').and_then(|dec| dec.parse().ok()))
+                .and_then(char::from_u32),
+        };
+        match decoded {
+            Some(c) => {
+                out.push(c);
+                rest = &rest[semi + 1..];
+            }
+            None => {
+                out.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    Cow::Owned(out)
+}
+
+pub(crate) fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn attributes(mut input: &str) -> crate::Result<Vec<(&str, String)>> {
+    let mut attrs = Vec::new();
+    loop {
+        input = input.trim_start();
+        if input.is_empty() {
+            return Ok(attrs);
+        }
+        let eq = input
+            .find('=')
+            .ok_or_else(|| invalid("attribute without value"))?;
+        let name = input[..eq].trim();
+        let value = input[eq + 1..].trim_start();
+        let quote = value
+            .chars()
+            .next()
+            .filter(|q| *q == '"' || *q == '\'')
+            .ok_or_else(|| invalid("unquoted attribute"))?;
+        let end = value[1..]
+            .find(quote)
+            .ok_or_else(|| invalid("unterminated attribute"))?;
+        attrs.push((name, unescape(&value[1..end + 1]).into_owned()));
+        input = &value[end + 2..];
+    }
+}
+
+pub(crate) fn tokens(mut input: &str) -> crate::Result<Vec<Token<'_>>> {
+    let mut tokens = Vec::new();
+    while !input.is_empty() {
+        let Some(lt) = input.find('<') else {
+            tokens.push(Token::Text(unescape(input).into_owned()));
+            break;
+        };
+        if lt > 0 {
+            tokens.push(Token::Text(unescape(&input[..lt]).into_owned()));
+            input = &input[lt..];
+        }
+        for (open, close) in [("<!--", "-->"), ("<?", "?>"), ("<!", ">")] {
+            if input.starts_with(open) {
+                let end = input
+                    .find(close)
+                    .ok_or_else(|| invalid("unterminated markup"))?;
+                input = &input[end + close.len()..];
+            }
+        }
+        if !input.starts_with('<') {
+            continue;
+        }
+        let gt = input.find('>').ok_or_else(|| invalid("unterminated tag"))?;
+        let tag = &input[1..gt];
+        input = &input[gt + 1..];
+        if let Some(name) = tag.strip_prefix('/') {
+            tokens.push(Token::Close(name.trim()));
+            continue;
+        }
+        let (tag, empty) = match tag.strip_suffix('/') {
+            Some(tag) => (tag, true),
+            None => (tag, false),
+        };
+        let (name, attrs) = tag.split_once(char::is_whitespace).unwrap_or((tag, ""));
+        tokens.push(Token::Open {
+            name,
+            attrs: attributes(attrs)?,
+            empty,
+        });
+    }
+    Ok(tokens)
+}
+
+pub(crate) fn attr<'a>(attrs: &'a [(&str, String)], name: &str) -> Option<&'a str> {
+    attrs
+        .iter()
+        .find(|(key, _)| *key == name)
+        .map(|(_, value)| value.as_str())
+}
+
+/// `"150%"`, `"+20%"`, `"-10%"` or a plain multiplier.
+fn percentage(value: &str) -> Option<f32> {
+    if let Some(percent) = value.strip_suffix('%') {
+        let percent: f32 = percent.trim_start_matches('+').parse().ok()?;
+        return Some(if value.starts_with(['+', '-']) {
+            1.0 + percent / 100.0
+        } else {
+            percent / 100.0
+        });
+    }
+    value.parse().ok()
+}
+
+fn prosody_rate(value: &str) -> Option<f32> {
+    match value {
+        "x-slow" => Some(0.5),
+        "slow" => Some(0.75),
+        "medium" | "default" => Some(1.0),
+        "fast" => Some(1.5),
+        "x-fast" => Some(2.0),
+        _ => percentage(value),
+    }
+}
+
+fn prosody_pitch(value: &str) -> Option<f32> {
+    match value {
+        "x-low" => Some(0.7),
+        "low" => Some(0.85),
+        "medium" | "default" => Some(1.0),
+        "high" => Some(1.15),
+        "x-high" => Some(1.3),
+        _ => {
+            if let Some(semitones) = value.strip_suffix("st") {
+                let semitones: f32 = semitones.trim_start_matches('+').parse().ok()?;
+                Some(2f32.powf(semitones / 12.0))
+            } else if value.ends_with("Hz") {
+                // Absolute pitches depend on the voice; keep its own.
+                Some(1.0)
+            } else {
+                percentage(value)
+            }
+        }
+    }
+}
+
+/// Duration of a `<break>`, in milliseconds.
+fn break_time(attrs: &[(&str, String)]) -> u32 {
+    if let Some(time) = attr(attrs, "time") {
+        let millis = match time.strip_suffix("ms") {
+            Some(ms) => ms.trim().parse::<f32>().ok(),
+            None => time
+                .strip_suffix('s')
+                .and_then(|s| s.trim().parse::<f32>().ok())
+                .map(|s| s * 1000.0),
+        };
+        if let Some(millis) = millis {
+            return millis.clamp(0.0, 10_000.0) as u32;
+        }
+    }
+    match attr(attrs, "strength") {
+        Some("none") => 0,
+        Some("x-weak") => 100,
+        Some("weak") => 250,
+        Some("strong") => 750,
+        Some("x-strong") => 1200,
+        _ => 400,
+    }
+}
+
+impl Document {
+    /// A document speaking plain `text` with `marks` at their UTF-16
+    /// offsets, so that normalizing it keeps them on their words.
+    pub fn with_marks(text: &str, marks: &[SpeechMark]) -> Self {
+        let mut marks = marks.to_vec();
+        marks.sort_by_key(|mark| mark.offset);
+        let mut marks = marks.into_iter().peekable();
+        let mut items = Vec::new();
+        let push_span = |items: &mut Vec<Item>, text: &str| {
+            if !text.is_empty() {
+                items.push(Item::Text(Span {
+                    text: text.to_string(),
+                    lang: None,
+                    rate: 1.0,
+                    pitch: 1.0,
+                }));
+            }
+        };
+        let mut start = 0;
+        let mut offset = 0;
+        for (i, c) in text.char_indices().chain([(text.len(), ' ')]) {
+            let mut marked = false;
+            while let Some(mark) = marks.next_if(|mark| mark.offset <= offset) {
+                if !marked {
+                    push_span(&mut items, &text[start..i]);
+                    start = i;
+                    marked = true;
+                }
+                items.push(Item::Mark(mark.name));
+            }
+            offset += c.len_utf16();
+        }
+        push_span(&mut items, &text[start..]);
+        Self { items }
+    }
+
+    pub fn parse(ssml: &str) -> crate::Result<Self> {
+        let root = Context {
+            name: "",
+            lang: None,
+            rate: 1.0,
+            pitch: 1.0,
+            silent: false,
+        };
+        let mut stack = vec![root];
+        let mut items = Vec::new();
+        for token in tokens(ssml)? {
+            let context = stack.last().unwrap().clone();
+            match token {
+                Token::Text(text) => {
+                    if context.silent {
+                        continue;
+                    }
+                    push_text(&mut items, &context, &text);
+                }
+                Token::Open { name, attrs, empty } => {
+                    let mut inner = Context { name, ..context };
+                    if let Some(lang) = attr(&attrs, "xml:lang") {
+                        inner.lang = Some(lang.to_string());
+                    }
+                    match name {
+                        "mark" => {
+                            if let Some(mark) = attr(&attrs, "name") {
+                                items.push(Item::Mark(mark.to_string()));
+                            }
+                        }
+                        "break" => items.push(Item::Break(break_time(&attrs))),
+                        "prosody" => {
+                            if let Some(rate) = attr(&attrs, "rate").and_then(prosody_rate) {
+                                inner.rate *= rate.clamp(0.1, 10.0);
+                            }
+                            if let Some(pitch) = attr(&attrs, "pitch").and_then(prosody_pitch) {
+                                inner.pitch *= pitch.clamp(0.1, 10.0);
+                            }
+                        }
+                        "sub" => {
+                            if let Some(alias) = attr(&attrs, "alias") {
+                                push_text(&mut items, &inner, alias);
+                            }
+                            inner.silent = true;
+                        }
+                        "desc" => inner.silent = true,
+                        _ => {}
+                    }
+                    if !empty {
+                        stack.push(inner);
+                    }
+                }
+                Token::Close(name) => {
+                    // Tolerate unbalanced markup by closing up to the match.
+                    if let Some(depth) = stack.iter().rposition(|c| c.name == name) {
+                        stack.truncate(depth.max(1));
+                    }
+                }
+            }
+        }
+        if let Some(Item::Text(span)) = items.last_mut() {
+            span.text.truncate(span.text.trim_end().len());
+        }
+        Ok(Self { items })
+    }
+
+    /// The spoken text without markup, and where each mark falls in it,
+    /// counted in UTF-16 units as the mobile engines report positions.
+    pub fn plain_text(&self) -> (String, Vec<SpeechMark>) {
+        let mut text = String::new();
+        let mut offset = 0;
+        let mut marks = Vec::new();
+        for item in &self.items {
+            match item {
+                Item::Text(span) => {
+                    text.push_str(&span.text);
+                    offset += span.text.encode_utf16().count();
+                }
+                Item::Mark(name) => marks.push(SpeechMark {
+                    offset,
+                    name: name.clone(),
+                }),
+                Item::Break(_) => {}
+            }
+        }
+        (text, marks)
+    }
+
+    /// Normalized SSML, keeping only what Speech Dispatcher modules handle.
+    #[cfg(desktop)]
+    pub fn to_ssml(&self) -> String {
+        let mut out = String::from("<speak>");
+        for item in &self.items {
+            match item {
+                Item::Text(span) => {
+                    let mut text = escape(&span.text);
+                    if span.rate != 1.0 || span.pitch != 1.0 {
+                        text = format!(
+                            "<prosody rate=\"{:.0}%\" pitch=\"{:+.0}%\">{text}</prosody>",
+                            span.rate * 100.0,
+                            (span.pitch - 1.0) * 100.0
+                        );
+                    }
+                    if let Some(lang) = &span.lang {
+                        text = format!("<voice xml:lang=\"{}\">{text}</voice>", escape(lang));
+                    }
+                    out.push_str(&text);
+                }
+                Item::Mark(name) => out.push_str(&format!("<mark name=\"{}\"/>", escape(name))),
+                Item::Break(millis) => out.push_str(&format!("<break time=\"{millis}ms\"/>")),
+            }
+        }
+        out.push_str("</speak>");
+        out
+    }
+}
+
+/// Append text with whitespace collapsed, merging it into the previous
+/// span when the settings match.
+fn push_text(items: &mut Vec<Item>, context: &Context, text: &str) {
+    let mut collapsed = String::with_capacity(text.len());
+    for (i, word) in text.split_whitespace().enumerate() {
+        if i > 0 {
+            collapsed.push(' ');
+        }
+        collapsed.push_str(word);
+    }
+    let leading = text.starts_with(char::is_whitespace);
+    let trailing = text.ends_with(char::is_whitespace) && !collapsed.is_empty();
+    let at_start = !items.iter().any(|item| matches!(item, Item::Text(_)));
+    let after_space = items.iter().rev().find_map(|item| match item {
+        Item::Text(span) => Some(span.text.ends_with(' ')),
+        _ => None,
+    });
+    let mut text = String::new();
+    if leading && !at_start && after_space == Some(false) {
+        text.push(' ');
+    }
+    text.push_str(&collapsed);
+    if trailing {
+        text.push(' ');
+    }
+    if text.is_empty() {
+        return;
+    }
+    if let Some(Item::Text(span)) = items.last_mut() {
+        if span.lang == context.lang && span.rate == context.rate && span.pitch == context.pitch {
+            span.text.push_str(&text);
+            return;
+        }
+    }
+    items.push(Item::Text(Span {
+        text,
+        lang: context.lang.clone(),
+        rate: context.rate,
+        pitch: context.pitch,
+    }));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn span(text: &str, lang: Option<&str>, rate: f32) -> Item {
+        Item::Text(Span {
+            text: text.into(),
+            lang: lang.map(Into::into),
+            rate,
+            pitch: 1.0,
+        })
+    }
+
+    #[test]
+    fn test_plain_text_with_marks() {
+        let marks = vec![
+            SpeechMark {
+                offset: 0,
+                name: "0".into(),
+            },
+            SpeechMark {
+                offset: 9,
+                name: "1".into(),
+            },
+            SpeechMark {
+                offset: 11,
+                name: "2".into(),
+            },
+        ];
+        let doc = Document::with_marks("Größe 😀 ok", &marks);
+        assert_eq!(
+            doc.items,
+            vec![
+                Item::Mark("0".into()),
+                span("Größe 😀 ", None, 1.0),
+                Item::Mark("1".into()),
+                span("ok", None, 1.0),
+                Item::Mark("2".into()),
+            ]
+        );
+        assert_eq!(doc.plain_text(), ("Größe 😀 ok".to_string(), marks));
+    }
+
+    #[test]
+    fn test_marks_and_whitespace() {
+        let doc = Document::parse(
+            r#"<speak version="1.0" xml:lang="en">
+                <mark name="0"/>Hello,
+                world. <mark name="1"/>Fish &amp; chips&#33;
+            </speak>"#,
+        )
+        .unwrap();
+        assert_eq!(
+            doc.items,
+            vec![
+                Item::Mark("0".into()),
+                span("Hello, world. ", Some("en"), 1.0),
+                Item::Mark("1".into()),
+                span("Fish & chips!", Some("en"), 1.0),
+            ]
+        );
+        let (text, marks) = doc.plain_text();
+        assert_eq!(text, "Hello, world. Fish & chips!");
+        assert_eq!(
+            marks,
+            vec![
+                SpeechMark {
+                    offset: 0,
+                    name: "0".into()
+                },
+                SpeechMark {
+                    offset: 14,
+                    name: "1".into()
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_prosody_breaks_and_lang() {
+        let doc = Document::parse(
+            r#"<speak><prosody rate="1.5">Fast <prosody rate="-50%">then slow</prosody></prosody>
+               <break time="1.5s"/><lang xml:lang="hi-IN">नमस्ते 😀</lang><break strength="weak"/>
+               <sub alias="World Health Organization">WHO</sub></speak>"#,
+        )
+        .unwrap();
+        assert_eq!(
+            doc.items,
+            vec![
+                span("Fast ", None, 1.5),
+                span("then slow", None, 0.75),
+                span(" ", None, 1.0),
+                Item::Break(1500),
+                span("नमस्ते 😀", Some("hi-IN"), 1.0),
+                Item::Break(250),
+                span(" World Health Organization", None, 1.0),
+            ]
+        );
+        let (text, marks) = Document::parse("<mark name='a'/>😀<mark name='b'/>x")
+            .unwrap()
+            .plain_text();
+        assert_eq!(text, "😀x");
+        assert_eq!(marks[1].offset, 2);
+    }
+
+    #[test]
+    fn test_invalid() {
+        assert!(Document::parse("<speak><mark name=\"0\"").is_err());
+        assert!(Document::parse("<speak><mark name=0/></speak>").is_err());
+        assert_eq!(prosody_pitch("+2st"), Some(2f32.powf(2.0 / 12.0)));
+        assert_eq!(prosody_rate("80%"), Some(0.8));
+    }
+}
@@ -1,3 +1,3 @@
 pub mod eink;
 
-pub use eink::is_eink_device;
+pub use eink::device_info;
@@ -0,0 +1,66 @@
+use super::{pool, store, AnnotatedBook, Annotation, AnnotationEntry, AnnotationFilter, TagCount};
+use tauri::AppHandle;
+
+/// Mirror the booknotes of a book into the store.
+#[tauri::command]
+pub async fn upsert_annotations(
+    app: AppHandle,
+    book: AnnotatedBook,
+    annotations: Vec<Annotation>,
+) -> Result<usize, String> {
+    let pool = pool(&app).await?;
+    store::upsert(&pool, &book, &annotations)
+        .await
+        .map_err(|e| format!("Failed to save annotations: {e}"))
+}
+
+#[tauri::command]
+pub async fn delete_annotations(app: AppHandle, ids: Vec<String>) -> Result<u64, String> {
+    let pool = pool(&app).await?;
+    store::delete(&pool, &ids)
+        .await
+        .map_err(|e| format!("Failed to delete annotations: {e}"))
+}
+
+#[tauri::command]
+pub async fn set_annotation_tags(
+    app: AppHandle,
+    id: String,
+    tags: Vec<String>,
+) -> Result<(), String> {
+    let pool = pool(&app).await?;
+    match store::set_tags(&pool, &id, &tags).await {
+        Ok(true) => Ok(()),
+        Ok(false) => Err(format!("Annotation {id} not found")),
+        Err(e) => Err(format!("Failed to set annotation tags: {e}")),
+    }
+}
+
+/// Query annotations by book, tag, color, type and/or full text.
+#[tauri::command]
+pub async fn query_annotations(
+    app: AppHandle,
+    filter: AnnotationFilter,
+) -> Result<Vec<AnnotationEntry>, String> {
+    let pool = pool(&app).await?;
+    store::query(&pool, &filter)
+        .await
+        .map_err(|e| format!("Failed to query annotations: {e}"))
+}
+
+/// Look up a single annotation, e.g. to follow an `annotation/<id>` deep link.
+#[tauri::command]
+pub async fn get_annotation(app: AppHandle, id: String) -> Result<Option<AnnotationEntry>, String> {
+    let pool = pool(&app).await?;
+    store::get(&pool, &id)
+        .await
+        .map_err(|e| format!("Failed to get annotation: {e}"))
+}
+
+#[tauri::command]
+pub async fn list_annotation_tags(app: AppHandle) -> Result<Vec<TagCount>, String> {
+    let pool = pool(&app).await?;
+    store::tags(&pool)
+        .await
+        .map_err(|e| format!("Failed to list annotation tags: {e}"))
+}
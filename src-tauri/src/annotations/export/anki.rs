@@ -0,0 +1,355 @@
+//! Anki package (`.apkg`): a zip holding a legacy (schema 11) collection
+//! database and an empty media map. Each book gets a subdeck of
+//! `HackXIndia26`; the highlight is on the front of the card and the note,
+//! followed by the book, on the back.
+//!
+//! Deck, note type and note GUIDs are derived from stable ids, so importing
+//! a newer export updates the existing cards instead of duplicating them.
+
+use super::{book_title, by_book, write_file, AnnotationEntry, Result};
+use serde_json::json;
+use sha1::{Digest, Sha1};
+use sqlx::sqlite::{SqliteConnectOptions, SqliteJournalMode};
+use sqlx::{Connection, SqliteConnection};
+use std::collections::HashSet;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use zip::write::SimpleFileOptions;
+
+const FILE_NAME: &str = "HackXIndia26 Highlights.apkg";
+const ROOT_DECK: &str = "HackXIndia26";
+const MODEL_ID: i64 = 1_737_000_000_000;
+const FIELD_SEPARATOR: char = '\u{1f}';
+
+const SCHEMA: &str = "
+CREATE TABLE col (id integer primary key, crt integer not null, mod integer not null,
+    scm integer not null, ver integer not null, dty integer not null, usn integer not null,
+    ls integer not null, conf text not null, models text not null, decks text not null,
+    dconf text not null, tags text not null);
+CREATE TABLE notes (id integer primary key, guid text not null, mid integer not null,
+    mod integer not null, usn integer not null, tags text not null, flds text not null,
+    sfld integer not null, csum integer not null, flags integer not null, data text not null);
+CREATE TABLE cards (id integer primary key, nid integer not null, did integer not null,
+    ord integer not null, mod integer not null, usn integer not null, type integer not null,
+    queue integer not null, due integer not null, ivl integer not null, factor integer not null,
+    reps integer not null, lapses integer not null, left integer not null, odue integer not null,
+    odid integer not null, flags integer not null, data text not null);
+CREATE TABLE revlog (id integer primary key, cid integer not null, usn integer not null,
+    ease integer not null, ivl integer not null, lastIvl integer not null,
+    factor integer not null, time integer not null, type integer not null);
+CREATE TABLE graves (usn integer not null, oid integer not null, type integer not null);
+CREATE INDEX ix_notes_usn ON notes (usn);
+CREATE INDEX ix_cards_usn ON cards (usn);
+CREATE INDEX ix_revlog_usn ON revlog (usn);
+CREATE INDEX ix_cards_nid ON cards (nid);
+CREATE INDEX ix_cards_sched ON cards (did, queue, due);
+CREATE INDEX ix_revlog_cid ON revlog (cid);
+CREATE INDEX ix_notes_csum ON notes (csum);
+";
+
+const CARD_CSS: &str = ".card { font-family: serif; font-size: 22px; text-align: left; \
+     color: black; background-color: white; }\n\
+     .source { margin-top: 1em; font-size: 16px; color: #777; }";
+
+fn sha1(value: &str) -> [u8; 20] {
+    Sha1::digest(value.as_bytes()).into()
+}
+
+/// A positive 48-bit id derived from `value`, in the range of the
+/// millisecond timestamps Anki uses as ids.
+fn stable_id(value: &str) -> i64 {
+    let hash = sha1(value);
+    let mut id = 0i64;
+    for byte in &hash[..6] {
+        id = (id << 8) | i64::from(*byte);
+    }
+    id | (1 << 40)
+}
+
+/// Anki's duplicate check: the first 8 hex digits of the SHA-1 of the sort field.
+fn checksum(sort_field: &str) -> i64 {
+    let hash = sha1(sort_field);
+    i64::from(u32::from_be_bytes([hash[0], hash[1], hash[2], hash[3]]))
+}
+
+fn html(text: &str) -> String {
+    text.trim()
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('\n', "<br>")
+}
+
+/// Anki tags are separated by spaces.
+fn anki_tag(tag: &str) -> String {
+    tag.split_whitespace().collect::<Vec<_>>().join("_")
+}
+
+fn deck(id: i64, name: &str, now: i64) -> serde_json::Value {
+    json!({
+        "id": id, "name": name, "mod": now / 1000, "usn": -1, "desc": "", "dyn": 0,
+        "conf": 1, "collapsed": false, "extendNew": 10, "extendRev": 50,
+        "lrnToday": [0, 0], "revToday": [0, 0], "newToday": [0, 0], "timeToday": [0, 0],
+    })
+}
+
+fn field(name: &str, ord: u32) -> serde_json::Value {
+    json!({
+        "name": name, "ord": ord, "sticky": false, "rtl": false,
+        "font": "Arial", "size": 20, "media": [],
+    })
+}
+
+fn model(deck_id: i64, now: i64) -> serde_json::Value {
+    json!({
+        "id": MODEL_ID, "name": "HackXIndia26 Highlight", "type": 0,
+        "mod": now / 1000, "usn": -1, "sortf": 0, "did": deck_id,
+        "flds": [field("Highlight", 0), field("Back", 1)],
+        "tmpls": [{
+            "name": "Card 1", "ord": 0, "did": null, "bqfmt": "", "bafmt": "",
+            "qfmt": "{{Highlight}}",
+            "afmt": "{{FrontSide}}<hr id=answer>{{Back}}",
+        }],
+        "css": CARD_CSS,
+        "latexPre": "\\documentclass[12pt]{article}\n\\special{papersize=3in,5in}\n\\usepackage[utf8]{inputenc}\n\\usepackage{amssymb,amsmath}\n\\pagestyle{empty}\n\\setlength{\\parindent}{0in}\n\\begin{document}\n",
+        "latexPost": "\\end{document}",
+        "tags": [], "vers": [], "req": [[0, "any", [0]]],
+    })
+}
+
+fn deck_config() -> serde_json::Value {
+    json!({
+        "1": {
+            "id": 1, "name": "Default", "mod": 0, "usn": 0, "dyn": false,
+            "maxTaken": 60, "timer": 0, "autoplay": true, "replayq": true,
+            "new": {
+                "perDay": 20, "delays": [1, 10], "ints": [1, 4, 7], "initialFactor": 2500,
+                "separate": true, "order": 1, "bury": true,
+            },
+            "rev": {
+                "perDay": 100, "ease4": 1.3, "fuzz": 0.05, "ivlFct": 1, "maxIvl": 36500,
+                "minSpace": 1, "bury": true,
+            },
+            "lapse": {
+                "delays": [10], "mult": 0, "minInt": 1, "leechFails": 8, "leechAction": 0,
+            },
+        }
+    })
+}
+
+async fn build_collection(path: &Path, entries: &[AnnotationEntry]) -> Result<()> {
+    let options = SqliteConnectOptions::new()
+        .filename(path)
+        .create_if_missing(true)
+        .journal_mode(SqliteJournalMode::Delete);
+    let mut conn = SqliteConnection::connect_with(&options).await?;
+    sqlx::raw_sql(SCHEMA).execute(&mut conn).await?;
+
+    let now = chrono::Utc::now().timestamp_millis();
+    let root_id = stable_id(ROOT_DECK);
+    let mut decks = serde_json::Map::new();
+    decks.insert("1".into(), deck(1, "Default", now));
+    decks.insert(root_id.to_string(), deck(root_id, ROOT_DECK, now));
+
+    let mut tx = conn.begin().await?;
+    let mut due = 0;
+    let mut deck_names = HashSet::new();
+    for (book, group) in by_book(entries) {
+        let title = book_title(book);
+        let deck_id = stable_id(&book.annotation.book_hash);
+        let mut deck_name = format!("{ROOT_DECK}::{}", title.replace("::", ":"));
+        if !deck_names.insert(deck_name.to_lowercase()) {
+            let hash: String = book.annotation.book_hash.chars().take(8).collect();
+            deck_name = format!("{deck_name} ({hash})");
+            deck_names.insert(deck_name.to_lowercase());
+        }
+        decks.insert(deck_id.to_string(), deck(deck_id, &deck_name, now));
+
+        let mut source = html(title);
+        if let Some(author) = book.book_author.as_deref().filter(|a| !a.is_empty()) {
+            source = format!("{source} — {}", html(author));
+        }
+
+        for entry in group {
+            let annotation = &entry.annotation;
+            let Some(text) = annotation.text.as_deref().filter(|t| !t.trim().is_empty()) else {
+                continue;
+            };
+            due += 1;
+            let front = html(text);
+            let mut back = String::new();
+            if !annotation.note.trim().is_empty() {
+                back.push_str(&html(&annotation.note));
+            }
+            back.push_str(&format!("<div class=\"source\">{source}</div>"));
+            let mut tags: Vec<String> = vec![ROOT_DECK.to_string()];
+            tags.extend(
+                annotation
+                    .tags
+                    .as_deref()
+                    .unwrap_or_default()
+                    .iter()
+                    .map(|tag| anki_tag(tag)),
+            );
+            if let Some(color) = &annotation.color {
+                tags.push(format!("color::{color}"));
+            }
+
+            // Anki ids are millisecond timestamps; keep them unique and stable.
+            let note_id = stable_id(&format!("note:{}", annotation.id));
+            let card_id = stable_id(&format!("card:{}", annotation.id));
+            let modified = annotation.updated_at / 1000;
+            sqlx::query(
+                "INSERT INTO notes (id, guid, mid, mod, usn, tags, flds, sfld, csum, flags, data) \
+                 VALUES (?, ?, ?, ?, -1, ?, ?, ?, ?, 0, '')",
+            )
+            .bind(note_id)
+            .bind(format!("HackXIndia26-{}", annotation.id))
+            .bind(MODEL_ID)
+            .bind(modified)
+            .bind(format!(" {} ", tags.join(" ")))
+            .bind(format!("{front}{FIELD_SEPARATOR}{back}"))
+            .bind(text.trim())
+            .bind(checksum(text.trim()))
+            .execute(&mut *tx)
+            .await?;
+            sqlx::query(
+                "INSERT INTO cards (id, nid, did, ord, mod, usn, type, queue, due, ivl, factor, \
+                 reps, lapses, left, odue, odid, flags, data) \
+                 VALUES (?, ?, ?, 0, ?, -1, 0, 0, ?, 0, 0, 0, 0, 0, 0, 0, 0, '')",
+            )
+            .bind(card_id)
+            .bind(note_id)
+            .bind(deck_id)
+            .bind(modified)
+            .bind(due)
+            .execute(&mut *tx)
+            .await?;
+        }
+    }
+
+    let conf = json!({
+        "activeDecks": [1], "curDeck": 1, "newSpread": 0, "collapseTime": 1200,
+        "timeLim": 0, "estTimes": true, "dueCounts": true, "curModel": null,
+        "nextPos": due + 1, "sortType": "noteFld", "sortBackwards": false, "addToCur": true,
+    });
+    let models = json!({ MODEL_ID.to_string(): model(root_id, now) });
+    sqlx::query(
+        "INSERT INTO col (id, crt, mod, scm, ver, dty, usn, ls, conf, models, decks, dconf, tags) \
+         VALUES (1, ?, ?, ?, 11, 0, 0, 0, ?, ?, ?, ?, '{}')",
+    )
+    .bind(now / 1000)
+    .bind(now)
+    .bind(now)
+    .bind(conf.to_string())
+    .bind(models.to_string())
+    .bind(serde_json::Value::Object(decks).to_string())
+    .bind(deck_config().to_string())
+    .execute(&mut *tx)
+    .await?;
+    tx.commit().await?;
+    conn.close().await?;
+    Ok(())
+}
+
+pub async fn write(dir: &Path, entries: &[AnnotationEntry]) -> Result<Vec<PathBuf>> {
+    let collection = std::env::temp_dir().join(format!(
+        "HackXIndia26-export-{}-{}.anki2",
+        std::process::id(),
+        chrono::Utc::now().timestamp_millis()
+    ));
+    let built = build_collection(&collection, entries).await;
+    let bytes = built.and_then(|_| Ok(std::fs::read(&collection)?));
+    let _ = std::fs::remove_file(&collection);
+    let bytes = bytes?;
+
+    let mut package = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+    let options = SimpleFileOptions::default().compression_method(zip::CompressionMethod::Deflated);
+    package.start_file("collection.anki2", options)?;
+    package.write_all(&bytes)?;
+    package.start_file("media", options)?;
+    package.write_all(b"{}")?;
+    let package = package.finish()?.into_inner();
+
+    Ok(vec![write_file(dir, FILE_NAME, &package)?])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::tests::{highlight, out_dir};
+    use super::*;
+    use sqlx::Row;
+    use std::io::Read;
+
+    #[test]
+    fn escapes_html() {
+        assert_eq!(
+            html(" <b>Tom & Jerry</b>\nagain "),
+            "&lt;b&gt;Tom &amp; Jerry&lt;/b&gt;<br>again"
+        );
+        assert_eq!(anki_tag("to read  later"), "to_read_later");
+    }
+
+    #[test]
+    fn derives_stable_ids() {
+        assert_eq!(stable_id("note:1"), stable_id("note:1"));
+        assert_ne!(stable_id("note:1"), stable_id("note:2"));
+        let id = stable_id(ROOT_DECK);
+        assert!((1 << 40..1 << 48).contains(&id));
+    }
+
+    #[test]
+    fn packages_a_card_per_highlight() {
+        let dir = out_dir("anki");
+        let mut noted = highlight("1", "h1", "<i>Call</i> me Ishmael.");
+        noted.annotation.note = "Opening line".to_string();
+        noted.annotation.tags = Some(vec!["first lines".to_string()]);
+        let mut bookmark = highlight("2", "h1", "");
+        bookmark.annotation.text = None;
+        let entries = [noted, bookmark, highlight("3", "h2", "Second book")];
+        let files = tauri::async_runtime::block_on(write(&dir, &entries)).unwrap();
+
+        let mut package = zip::ZipArchive::new(std::fs::File::open(&files[0]).unwrap()).unwrap();
+        let mut media = String::new();
+        package
+            .by_name("media")
+            .unwrap()
+            .read_to_string(&mut media)
+            .unwrap();
+        assert_eq!(media, "{}");
+        let collection = dir.join("collection.anki2");
+        std::io::copy(
+            &mut package.by_name("collection.anki2").unwrap(),
+            &mut std::fs::File::create(&collection).unwrap(),
+        )
+        .unwrap();
+
+        tauri::async_runtime::block_on(async {
+            let options = SqliteConnectOptions::new().filename(&collection);
+            let mut conn = SqliteConnection::connect_with(&options).await.unwrap();
+            let notes = sqlx::query("SELECT flds, tags FROM notes ORDER BY sfld")
+                .fetch_all(&mut conn)
+                .await
+                .unwrap();
+            assert_eq!(notes.len(), 2);
+            let fields: String = notes[0].get("flds");
+            assert_eq!(
+                fields,
+                format!(
+                    "&lt;i&gt;Call&lt;/i&gt; me Ishmael.{FIELD_SEPARATOR}Opening line\
+                     <div class=\"source\">Book h1 — Jane Doe</div>"
+                )
+            );
+            let tags: String = notes[0].get("tags");
+            assert_eq!(tags, " HackXIndia26 first_lines color::yellow ");
+            let decks: i64 = sqlx::query("SELECT count(DISTINCT did) FROM cards")
+                .fetch_one(&mut conn)
+                .await
+                .unwrap()
+                .get(0);
+            assert_eq!(decks, 2);
+            conn.close().await.unwrap();
+        });
+        let _ = std::fs::remove_dir_all(dir);
+    }
+}
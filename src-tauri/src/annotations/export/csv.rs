@@ -0,0 +1,78 @@
+use super::{iso_timestamp, write_file, AnnotationEntry, Result};
+use std::path::{Path, PathBuf};
+
+const FILE_NAME: &str = "HackXIndia26 Annotations.csv";
+const HEADER: [&str; 11] = [
+    "Book",
+    "Author",
+    "Type",
+    "Highlight",
+    "Note",
+    "Color",
+    "Style",
+    "Tags",
+    "Location",
+    "Created",
+    "Updated",
+];
+
+/// Quote a field if needed, and defuse values a spreadsheet would evaluate.
+fn field(value: &str) -> String {
+    let value = if value.starts_with(['=', '+', '-', '@']) {
+        format!("'{value}")
+    } else {
+        value.to_string()
+    };
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value
+    }
+}
+
+fn push_row(out: &mut String, row: &[&str]) {
+    let row: Vec<String> = row.iter().map(|value| field(value)).collect();
+    out.push_str(&row.join(","));
+    out.push_str("\r\n");
+}
+
+pub fn write(dir: &Path, entries: &[AnnotationEntry]) -> Result<Vec<PathBuf>> {
+    // The BOM makes Excel read the file as UTF-8.
+    let mut out = String::from("\u{feff}");
+    push_row(&mut out, &HEADER);
+    for entry in entries {
+        let annotation = &entry.annotation;
+        let tags = annotation.tags.as_deref().unwrap_or_default().join(", ");
+        push_row(
+            &mut out,
+            &[
+                entry.book_title.as_deref().unwrap_or_default(),
+                entry.book_author.as_deref().unwrap_or_default(),
+                &annotation.kind,
+                annotation.text.as_deref().unwrap_or_default(),
+                &annotation.note,
+                annotation.color.as_deref().unwrap_or_default(),
+                annotation.style.as_deref().unwrap_or_default(),
+                &tags,
+                &annotation.cfi,
+                &iso_timestamp(annotation.created_at),
+                &iso_timestamp(annotation.updated_at),
+            ],
+        );
+    }
+    Ok(vec![write_file(dir, FILE_NAME, out.as_bytes())?])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quotes_and_defuses_fields() {
+        assert_eq!(field("plain"), "plain");
+        assert_eq!(field("a, b"), "\"a, b\"");
+        assert_eq!(field("say \"hi\"\n"), "\"say \"\"hi\"\"\n\"");
+        assert_eq!(field("=SUM(A1)"), "'=SUM(A1)");
+        assert_eq!(field("-1,5"), "\"'-1,5\"");
+    }
+}
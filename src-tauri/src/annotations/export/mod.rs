@@ -0,0 +1,268 @@
+//! Export stored annotations to formats other tools can import.
+//!
+//! Every exporter receives the entries of [`store::query`](super::store::query)
+//! and writes one or more files into a folder chosen by the user.
+
+mod anki;
+mod csv;
+mod obsidian;
+mod readwise;
+
+use super::{pool, store, AnnotationEntry, AnnotationFilter};
+use serde::{ser::Serializer, Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use tauri::AppHandle;
+use tauri_plugin_fs::FsExt;
+
+type Result<T> = std::result::Result<T, Error>;
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error(transparent)]
+    Sql(#[from] sqlx::Error),
+    #[error(transparent)]
+    Zip(#[from] zip::result::ZipError),
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+    #[error("{0}")]
+    Other(String),
+}
+
+impl Serialize for Error {
+    fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(self.to_string().as_ref())
+    }
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    Anki,
+    Csv,
+    Obsidian,
+    Readwise,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportSummary {
+    pub format: ExportFormat,
+    pub files: Vec<String>,
+    pub annotations: usize,
+}
+
+/// Export the annotations matching `filter` (all of them by default) into
+/// `dir`, asking the user for a folder when it is not given. A given `dir`
+/// must be in the filesystem scope of the webview. Resolves to `None` if the
+/// folder dialog is cancelled.
+#[tauri::command]
+pub async fn export_annotations(
+    app: AppHandle,
+    format: ExportFormat,
+    filter: Option<AnnotationFilter>,
+    dir: Option<String>,
+) -> Result<Option<ExportSummary>> {
+    let dir = match dir {
+        Some(dir) => {
+            let dir = PathBuf::from(dir);
+            if !app.fs_scope().is_allowed(&dir) {
+                return Err(Error::Other(
+                    "Permission denied: Path not in filesystem scope".to_string(),
+                ));
+            }
+            dir
+        }
+        None => match pick_folder(&app).await? {
+            Some(dir) => dir,
+            None => return Ok(None),
+        },
+    };
+    std::fs::create_dir_all(&dir)?;
+
+    let pool = pool(&app).await.map_err(Error::Other)?;
+    let entries = store::query(&pool, &filter.unwrap_or_default()).await?;
+    let files = write(format, &dir, &entries).await?;
+    log::info!(
+        "Exported {} annotations as {format:?} to {dir:?}",
+        entries.len()
+    );
+    Ok(Some(ExportSummary {
+        format,
+        files: files
+            .iter()
+            .map(|f| f.to_string_lossy().to_string())
+            .collect(),
+        annotations: entries.len(),
+    }))
+}
+
+/// Write `entries` into `dir` in the given format, returning the files created.
+pub(crate) async fn write(
+    format: ExportFormat,
+    dir: &Path,
+    entries: &[AnnotationEntry],
+) -> Result<Vec<PathBuf>> {
+    match format {
+        ExportFormat::Anki => anki::write(dir, entries).await,
+        ExportFormat::Csv => csv::write(dir, entries),
+        ExportFormat::Obsidian => obsidian::write(dir, entries),
+        ExportFormat::Readwise => readwise::write(dir, entries),
+    }
+}
+
+/// The Obsidian note of every book in `entries`, one after the other.
+pub(crate) fn markdown(entries: &[AnnotationEntry]) -> String {
+    by_book(entries)
+        .into_iter()
+        .map(|(book, group)| obsidian::book_note(book, &group))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(desktop)]
+async fn pick_folder(app: &AppHandle) -> Result<Option<PathBuf>> {
+    use tauri_plugin_dialog::DialogExt;
+
+    let (tx, rx) = futures::channel::oneshot::channel();
+    app.dialog()
+        .file()
+        .set_title("Export Annotations")
+        .pick_folder(move |folder| {
+            let _ = tx.send(folder);
+        });
+    match rx.await {
+        Ok(Some(folder)) => folder
+            .into_path()
+            .map(Some)
+            .map_err(|e| Error::Other(e.to_string())),
+        _ => Ok(None),
+    }
+}
+
+#[cfg(mobile)]
+async fn pick_folder(_app: &AppHandle) -> Result<Option<PathBuf>> {
+    Err(Error::Other(
+        "An export folder must be given on mobile".to_string(),
+    ))
+}
+
+/// Group entries by book, keeping the order in which books first appear.
+fn by_book(entries: &[AnnotationEntry]) -> Vec<(&AnnotationEntry, Vec<&AnnotationEntry>)> {
+    let mut books: Vec<(&AnnotationEntry, Vec<&AnnotationEntry>)> = Vec::new();
+    for entry in entries {
+        match books
+            .iter_mut()
+            .find(|(first, _)| first.annotation.book_hash == entry.annotation.book_hash)
+        {
+            Some((_, group)) => group.push(entry),
+            None => books.push((entry, vec![entry])),
+        }
+    }
+    books
+}
+
+fn book_title(entry: &AnnotationEntry) -> &str {
+    entry
+        .book_title
+        .as_deref()
+        .filter(|t| !t.trim().is_empty())
+        .unwrap_or("Untitled")
+}
+
+/// Make `name` usable as a file name on every platform.
+fn file_stem(name: &str) -> String {
+    let stem: String = name
+        .chars()
+        .map(|c| match c {
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' | '#' | '^' | '[' | ']' => '_',
+            c if c.is_control() => '_',
+            c => c,
+        })
+        .take(120)
+        .collect();
+    let stem = stem.trim().trim_matches('.').trim();
+    if stem.is_empty() {
+        "Untitled".to_string()
+    } else {
+        stem.to_string()
+    }
+}
+
+/// Milliseconds since the epoch as an RFC 3339 UTC timestamp.
+fn iso_timestamp(millis: i64) -> String {
+    chrono::DateTime::from_timestamp_millis(millis)
+        .unwrap_or_default()
+        .to_rfc3339_opts(chrono::SecondsFormat::Secs, true)
+}
+
+fn write_file(dir: &Path, name: &str, contents: &[u8]) -> Result<PathBuf> {
+    let path = dir.join(name);
+    std::fs::write(&path, contents)?;
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::annotations::Annotation;
+
+    /// A highlight of the book `hash`, made on 14 November 2023.
+    pub fn highlight(id: &str, hash: &str, text: &str) -> AnnotationEntry {
+        AnnotationEntry {
+            annotation: Annotation {
+                id: id.to_string(),
+                book_hash: hash.to_string(),
+                kind: "annotation".to_string(),
+                cfi: format!("epubcfi(/6/4!/4/2,/1:0,/1:{})", text.len()),
+                text: Some(text.to_string()),
+                style: Some("highlight".to_string()),
+                color: Some("yellow".to_string()),
+                note: String::new(),
+                tags: Some(Vec::new()),
+                created_at: 1_700_000_000_000,
+                updated_at: 1_700_000_000_000,
+                deleted_at: None,
+            },
+            book_title: Some(format!("Book {hash}")),
+            book_author: Some("Jane Doe".to_string()),
+        }
+    }
+
+    /// An empty folder for the files of one test.
+    pub fn out_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("annotations-{name}-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn groups_entries_by_book_in_order() {
+        let entries = [
+            highlight("1", "b", "one"),
+            highlight("2", "a", "two"),
+            highlight("3", "b", "three"),
+        ];
+        let books: Vec<(&str, Vec<&str>)> = by_book(&entries)
+            .into_iter()
+            .map(|(book, group)| {
+                let ids = group.iter().map(|e| e.annotation.id.as_str()).collect();
+                (book.annotation.book_hash.as_str(), ids)
+            })
+            .collect();
+        assert_eq!(books, [("b", vec!["1", "3"]), ("a", vec!["2"])]);
+    }
+
+    #[test]
+    fn makes_portable_file_stems() {
+        assert_eq!(file_stem("War: and/or Peace?"), "War_ and_or Peace_");
+        assert_eq!(file_stem(" ..Dune.. "), "Dune");
+        assert_eq!(file_stem("..."), "Untitled");
+        assert_eq!(file_stem(&"x".repeat(200)).len(), 120);
+    }
+}
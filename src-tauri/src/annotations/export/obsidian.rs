@@ -0,0 +1,172 @@
+//! One note per book plus an index note linking them, so that every book
+//! shows up in the backlinks of the index and vice versa.
+
+use super::{book_title, by_book, file_stem, iso_timestamp, write_file, AnnotationEntry, Result};
+use std::collections::HashSet;
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+
+const INDEX_NOTE: &str = "HackXIndia26 Annotations";
+
+/// A string as a YAML scalar; JSON strings are valid double-quoted YAML.
+fn yaml(value: &str) -> String {
+    serde_json::to_string(value).unwrap_or_default()
+}
+
+/// Obsidian tags cannot contain whitespace.
+fn tag(value: &str) -> String {
+    value.split_whitespace().collect::<Vec<_>>().join("-")
+}
+
+/// Obsidian block ids only allow letters, digits and dashes.
+fn block_id(id: &str) -> String {
+    id.chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+        .collect()
+}
+
+pub(super) fn book_note(book: &AnnotationEntry, entries: &[&AnnotationEntry]) -> String {
+    let title = book_title(book);
+    let author = book.book_author.as_deref().filter(|a| !a.is_empty());
+    let mut out = String::new();
+
+    let _ = writeln!(out, "---");
+    let _ = writeln!(out, "title: {}", yaml(title));
+    if let Some(author) = author {
+        let _ = writeln!(out, "author: {}", yaml(author));
+    }
+    let _ = writeln!(out, "book_hash: {}", yaml(&book.annotation.book_hash));
+    let _ = writeln!(out, "source: HackXIndia26");
+    let _ = writeln!(out, "tags: [HackXIndia26]");
+    let _ = writeln!(out, "---");
+    let _ = writeln!(out);
+    let _ = writeln!(out, "# {title}");
+    let _ = writeln!(out);
+    if let Some(author) = author {
+        let _ = writeln!(out, "by {author}");
+        let _ = writeln!(out);
+    }
+    let _ = writeln!(out, "Part of [[{INDEX_NOTE}]]");
+
+    for entry in entries {
+        let annotation = &entry.annotation;
+        let _ = writeln!(out);
+        if annotation.kind == "bookmark" {
+            let _ = writeln!(out, "## Bookmark");
+        } else {
+            let _ = writeln!(out, "## Highlight");
+        }
+        let _ = writeln!(out);
+        if let Some(text) = annotation.text.as_deref().filter(|t| !t.trim().is_empty()) {
+            for line in text.trim().lines() {
+                let _ = writeln!(out, "> {line}");
+            }
+            let _ = writeln!(out);
+            let _ = writeln!(out, "^{}", block_id(&annotation.id));
+        }
+        if !annotation.note.trim().is_empty() {
+            let _ = writeln!(out);
+            let _ = writeln!(out, "{}", annotation.note.trim());
+        }
+
+        let tags = annotation.tags.as_deref().unwrap_or_default();
+        let mut details = Vec::new();
+        if !tags.is_empty() {
+            details.push(
+                tags.iter()
+                    .map(|t| format!("#{}", tag(t)))
+                    .collect::<Vec<_>>()
+                    .join(" "),
+            );
+        }
+        if let Some(color) = &annotation.color {
+            details.push(format!("color: {color}"));
+        }
+        details.push(iso_timestamp(annotation.created_at));
+        let _ = writeln!(out);
+        let _ = writeln!(out, "{}", details.join(" · "));
+    }
+    out
+}
+
+pub fn write(dir: &Path, entries: &[AnnotationEntry]) -> Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    let mut stems = HashSet::new();
+    let mut index = format!("# {INDEX_NOTE}\n\n");
+
+    for (book, group) in by_book(entries) {
+        let mut stem = file_stem(book_title(book));
+        if !stems.insert(stem.to_lowercase()) {
+            let hash: String = book.annotation.book_hash.chars().take(8).collect();
+            stem = format!("{stem} ({hash})");
+            stems.insert(stem.to_lowercase());
+        }
+        let note = book_note(book, &group);
+        files.push(write_file(dir, &format!("{stem}.md"), note.as_bytes())?);
+        let _ = writeln!(index, "- [[{stem}]] ({} annotations)", group.len());
+    }
+
+    files.push(write_file(
+        dir,
+        &format!("{INDEX_NOTE}.md"),
+        index.as_bytes(),
+    )?);
+    Ok(files)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::tests::{highlight, out_dir};
+    use super::*;
+
+    #[test]
+    fn writes_a_note_per_book_with_quotes_and_tags() {
+        let mut quoted = highlight("a:1", "h1", "First line\nSecond line");
+        quoted.annotation.note = "Worth rereading".to_string();
+        quoted.annotation.tags = Some(vec!["to read".to_string()]);
+        let mut bookmark = highlight("a:2", "h1", "");
+        bookmark.annotation.kind = "bookmark".to_string();
+        bookmark.annotation.text = None;
+        bookmark.annotation.color = None;
+
+        let note = book_note(&quoted, &[&quoted, &bookmark]);
+        assert!(
+            note.starts_with("---\ntitle: \"Book h1\"\nauthor: \"Jane Doe\"\nbook_hash: \"h1\"\n")
+        );
+        assert!(note.contains(&format!("Part of [[{INDEX_NOTE}]]")));
+        assert!(note.contains("## Highlight\n\n> First line\n> Second line\n\n^a-1\n"));
+        assert!(note.contains("\nWorth rereading\n"));
+        assert!(note.contains("#to-read · color: yellow · 2023-11-14T22:13:20Z"));
+        let (_, bookmark) = note.split_once("## Bookmark").unwrap();
+        assert_eq!(bookmark.trim(), "2023-11-14T22:13:20Z");
+    }
+
+    #[test]
+    fn links_every_book_from_the_index() {
+        let dir = out_dir("obsidian");
+        let mut same_title = highlight("3", "h2-abcdefgh", "three");
+        same_title.book_title = Some("Book h1".to_string());
+        let entries = [
+            highlight("1", "h1", "one"),
+            highlight("2", "h1", "two"),
+            same_title,
+        ];
+        let files = write(&dir, &entries).unwrap();
+        let names: Vec<_> = files
+            .iter()
+            .map(|f| f.file_name().unwrap().to_string_lossy().to_string())
+            .collect();
+        assert_eq!(
+            names,
+            [
+                "Book h1.md",
+                "Book h1 (h2-abcde).md",
+                "HackXIndia26 Annotations.md"
+            ]
+        );
+        let index = std::fs::read_to_string(&files[2]).unwrap();
+        assert!(index.contains("- [[Book h1]] (2 annotations)"));
+        assert!(index.contains("- [[Book h1 (h2-abcde)]] (1 annotations)"));
+        let _ = std::fs::remove_dir_all(dir);
+    }
+}
@@ -0,0 +1,122 @@
+//! The body of a Readwise `POST /api/v2/highlights/` request.
+
+use super::{book_title, by_book, iso_timestamp, write_file, AnnotationEntry, Result};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+
+const FILE_NAME: &str = "readwise-highlights.json";
+/// Readwise rejects longer highlights and notes.
+const MAX_TEXT_CHARS: usize = 8191;
+
+#[derive(Serialize)]
+struct Highlights<'a> {
+    highlights: Vec<Highlight<'a>>,
+}
+
+#[derive(Serialize)]
+struct Highlight<'a> {
+    text: String,
+    title: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    author: Option<&'a str>,
+    source_type: &'static str,
+    category: &'static str,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    note: String,
+    location: usize,
+    location_type: &'static str,
+    highlighted_at: String,
+}
+
+fn truncate(text: &str) -> String {
+    text.chars().take(MAX_TEXT_CHARS).collect()
+}
+
+/// Readwise reads a leading `.tag` token of a note as a tag.
+fn note_with_tags(note: &str, tags: &[String]) -> String {
+    let tags: Vec<String> = tags
+        .iter()
+        .map(|tag| format!(".{}", tag.split_whitespace().collect::<Vec<_>>().join("-")))
+        .collect();
+    match (tags.is_empty(), note.trim().is_empty()) {
+        (true, _) => note.trim().to_string(),
+        (false, true) => tags.join(" "),
+        (false, false) => format!("{}\n{}", tags.join(" "), note.trim()),
+    }
+}
+
+pub fn write(dir: &Path, entries: &[AnnotationEntry]) -> Result<Vec<PathBuf>> {
+    let mut highlights = Vec::new();
+    for (book, group) in by_book(entries) {
+        let highlighted = group.into_iter().filter_map(|entry| {
+            let text = entry.annotation.text.as_deref()?.trim();
+            (!text.is_empty()).then_some((entry, text))
+        });
+        for (location, (entry, text)) in highlighted.enumerate() {
+            let annotation = &entry.annotation;
+            let note = note_with_tags(
+                &annotation.note,
+                annotation.tags.as_deref().unwrap_or_default(),
+            );
+            highlights.push(Highlight {
+                text: truncate(text),
+                title: book_title(book),
+                author: book.book_author.as_deref().filter(|a| !a.is_empty()),
+                source_type: "HackXIndia26",
+                category: "books",
+                note: truncate(&note),
+                location: location + 1,
+                location_type: "order",
+                highlighted_at: iso_timestamp(annotation.created_at),
+            });
+        }
+    }
+    let json = serde_json::to_vec_pretty(&Highlights { highlights })?;
+    Ok(vec![write_file(dir, FILE_NAME, &json)?])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::tests::{highlight, out_dir};
+    use super::*;
+
+    #[test]
+    fn writes_highlights_as_readwise_json() {
+        let dir = out_dir("readwise");
+        let mut tagged = highlight("1", "h1", "  Call me Ishmael.  ");
+        tagged.annotation.note = "Opening".to_string();
+        tagged.annotation.tags = Some(vec!["first lines".to_string(), "classic".to_string()]);
+        let mut bookmark = highlight("2", "h1", "");
+        bookmark.annotation.text = None;
+        let mut long = highlight("3", "h2", &"a".repeat(MAX_TEXT_CHARS + 10));
+        long.book_author = None;
+
+        let files = write(&dir, &[tagged, bookmark, long]).unwrap();
+        let json: serde_json::Value =
+            serde_json::from_slice(&std::fs::read(&files[0]).unwrap()).unwrap();
+        let highlights = json["highlights"].as_array().unwrap();
+        assert_eq!(highlights.len(), 2);
+        assert_eq!(
+            highlights[0],
+            serde_json::json!({
+                "text": "Call me Ishmael.",
+                "title": "Book h1",
+                "author": "Jane Doe",
+                "source_type": "HackXIndia26",
+                "category": "books",
+                "note": ".first-lines .classic\nOpening",
+                "location": 1,
+                "location_type": "order",
+                "highlighted_at": "2023-11-14T22:13:20Z",
+            })
+        );
+        assert_eq!(
+            highlights[1]["text"].as_str().unwrap().len(),
+            MAX_TEXT_CHARS
+        );
+        assert_eq!(highlights[1]["location"], 1);
+        assert!(highlights[1].get("author").is_none());
+        assert!(highlights[1].get("note").is_none());
+        let _ = std::fs::remove_dir_all(dir);
+    }
+}
@@ -0,0 +1,59 @@
+CREATE TABLE annotation_books (
+    hash TEXT PRIMARY KEY,
+    meta_hash TEXT,
+    title TEXT NOT NULL,
+    author TEXT
+);
+
+-- `seq` is the stable rowid the full-text index points at.
+CREATE TABLE annotations (
+    seq INTEGER PRIMARY KEY,
+    id TEXT NOT NULL UNIQUE,
+    book_hash TEXT NOT NULL,
+    type TEXT NOT NULL,
+    cfi TEXT NOT NULL,
+    cfi_start TEXT NOT NULL,
+    cfi_end TEXT NOT NULL,
+    text TEXT,
+    style TEXT,
+    color TEXT,
+    note TEXT NOT NULL DEFAULT '',
+    created_at INTEGER NOT NULL,
+    updated_at INTEGER NOT NULL,
+    deleted_at INTEGER
+);
+
+CREATE INDEX annotations_book ON annotations (book_hash, created_at);
+CREATE INDEX annotations_color ON annotations (color);
+
+CREATE TABLE annotation_tags (
+    annotation_id TEXT NOT NULL REFERENCES annotations (id) ON DELETE CASCADE,
+    tag TEXT NOT NULL,
+    PRIMARY KEY (annotation_id, tag)
+);
+
+CREATE INDEX annotation_tags_tag ON annotation_tags (tag);
+
+-- Trigram tokens make substring search work for CJK text as well.
+CREATE VIRTUAL TABLE annotations_fts USING fts5 (
+    text,
+    note,
+    content = 'annotations',
+    content_rowid = 'seq',
+    tokenize = 'trigram'
+);
+
+CREATE TRIGGER annotations_fts_insert AFTER INSERT ON annotations BEGIN
+    INSERT INTO annotations_fts (rowid, text, note) VALUES (new.seq, new.text, new.note);
+END;
+
+CREATE TRIGGER annotations_fts_delete AFTER DELETE ON annotations BEGIN
+    INSERT INTO annotations_fts (annotations_fts, rowid, text, note)
+    VALUES ('delete', old.seq, old.text, old.note);
+END;
+
+CREATE TRIGGER annotations_fts_update AFTER UPDATE OF text, note ON annotations BEGIN
+    INSERT INTO annotations_fts (annotations_fts, rowid, text, note)
+    VALUES ('delete', old.seq, old.text, old.note);
+    INSERT INTO annotations_fts (rowid, text, note) VALUES (new.seq, new.text, new.note);
+END;
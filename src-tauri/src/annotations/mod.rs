@@ -0,0 +1,142 @@
+//! Library-wide store of highlights, notes and bookmarks.
+//!
+//! The per-book JSON configs remain the source of truth for the reader; every
+//! saved config is mirrored here so that annotations can be queried across
+//! books. The database is opened and migrated by `tauri-plugin-sql`, which is
+//! not exposed to the webview: the frontend only goes through the commands below.
+
+pub mod commands;
+pub mod export;
+mod store;
+
+use crate::portable::PortablePaths;
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use tauri::{AppHandle, Manager};
+use tauri_plugin_sql::{DbInstances, DbPool, Migration, MigrationKind};
+
+/// The database file, next to the settings.
+const DB_FILE: &str = "annotations.db";
+
+/// The connection url of the store, managed as app state.
+pub struct Database {
+    pub url: String,
+}
+
+impl Database {
+    /// The sql plugin resolves relative urls against the app config dir, so portable
+    /// installs get an absolute one.
+    pub fn new(portable: Option<&PortablePaths>) -> Self {
+        let url = match portable {
+            Some(portable) => format!("sqlite:{}", portable.data_dir.join(DB_FILE).display()),
+            None => format!("sqlite:{DB_FILE}"),
+        };
+        Self { url }
+    }
+
+    /// Has the sql plugin open and migrate the store at startup.
+    pub fn preload(&self, config: &mut tauri::Config) {
+        let sql = serde_json::json!({ "preload": [self.url] });
+        config.plugins.0.insert("sql".to_string(), sql);
+    }
+}
+
+pub fn migrations() -> Vec<Migration> {
+    vec![Migration {
+        version: 1,
+        description: "create_annotations",
+        sql: include_str!("migrations/0001_create_annotations.sql"),
+        kind: MigrationKind::Up,
+    }]
+}
+
+async fn pool(app: &AppHandle) -> Result<SqlitePool, String> {
+    let url = &app.state::<Database>().inner().url;
+    let instances = app.state::<DbInstances>();
+    let instances = instances.0.read().await;
+    instances
+        .get(url)
+        .and_then(DbPool::sqlite)
+        .cloned()
+        .ok_or_else(|| format!("Database {url} is not loaded"))
+}
+
+/// Query the store without a running app, e.g. from the command line. The
+/// database is opened read-only and must have been created by the app.
+pub(crate) async fn query_file(
+    config_dir: &std::path::Path,
+    filter: &AnnotationFilter,
+) -> Result<Vec<AnnotationEntry>, sqlx::Error> {
+    let options = sqlx::sqlite::SqliteConnectOptions::new()
+        .filename(config_dir.join(DB_FILE))
+        .read_only(true);
+    let pool = SqlitePool::connect_with(options).await?;
+    let entries = store::query(&pool, filter).await;
+    pool.close().await;
+    entries
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AnnotatedBook {
+    pub hash: String,
+    pub meta_hash: Option<String>,
+    pub title: String,
+    pub author: Option<String>,
+}
+
+/// A `BookNote` of the frontend, plus its tags.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Annotation {
+    pub id: String,
+    #[serde(default)]
+    pub book_hash: String,
+    /// `bookmark`, `annotation` or `excerpt`.
+    #[serde(rename = "type")]
+    pub kind: String,
+    /// Position, or range (`epubcfi(parent,start,end)`) for highlights.
+    pub cfi: String,
+    pub text: Option<String>,
+    pub style: Option<String>,
+    pub color: Option<String>,
+    #[serde(default)]
+    pub note: String,
+    /// Left untouched on upsert when `None`.
+    pub tags: Option<Vec<String>>,
+    pub created_at: i64,
+    pub updated_at: i64,
+    pub deleted_at: Option<i64>,
+}
+
+/// Every field narrows the result; an empty filter returns all live annotations.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AnnotationFilter {
+    pub book_hash: Option<String>,
+    pub tag: Option<String>,
+    pub color: Option<String>,
+    #[serde(rename = "type")]
+    pub kind: Option<String>,
+    /// Full-text search over highlighted text and note bodies; the best
+    /// matches come first.
+    pub query: Option<String>,
+    #[serde(default)]
+    pub include_deleted: bool,
+    pub limit: Option<u32>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AnnotationEntry {
+    #[serde(flatten)]
+    pub annotation: Annotation,
+    pub book_title: Option<String>,
+    pub book_author: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TagCount {
+    pub tag: String,
+    pub count: u32,
+}
@@ -0,0 +1,510 @@
+use super::{AnnotatedBook, Annotation, AnnotationEntry, AnnotationFilter, TagCount};
+use sqlx::sqlite::SqliteRow;
+use sqlx::{QueryBuilder, Row, Sqlite, SqlitePool};
+
+/// Separator of the aggregated tags column; cannot appear in a tag.
+const TAG_SEPARATOR: char = '\u{1f}';
+
+/// Shortest query the trigram index can answer; shorter ones fall back to LIKE.
+const MIN_FTS_QUERY_CHARS: usize = 3;
+
+const SELECT_ANNOTATIONS: &str = "SELECT a.id, a.book_hash, a.type, a.cfi, a.text, a.style, \
+     a.color, a.note, a.created_at, a.updated_at, a.deleted_at, \
+     (SELECT group_concat(t.tag, char(31)) FROM annotation_tags t WHERE t.annotation_id = a.id) AS tags, \
+     b.title AS book_title, b.author AS book_author \
+     FROM annotations a LEFT JOIN annotation_books b ON b.hash = a.book_hash";
+
+/// Split a range CFI `epubcfi(parent,start,end)` into its start and end
+/// positions. Anything else is a collapsed range.
+fn split_cfi_range(cfi: &str) -> (String, String) {
+    let inner = cfi
+        .strip_prefix("epubcfi(")
+        .and_then(|s| s.strip_suffix(')'));
+    if let Some(inner) = inner {
+        let parts: Vec<&str> = inner.split(',').collect();
+        if let [parent, start, end] = parts[..] {
+            return (
+                format!("epubcfi({parent}{start})"),
+                format!("epubcfi({parent}{end})"),
+            );
+        }
+    }
+    (cfi.to_string(), cfi.to_string())
+}
+
+fn normalize_tags(tags: &[String]) -> Vec<String> {
+    let mut tags: Vec<String> = tags
+        .iter()
+        .map(|tag| tag.trim().replace(TAG_SEPARATOR, ""))
+        .filter(|tag| !tag.is_empty())
+        .collect();
+    tags.sort();
+    tags.dedup();
+    tags
+}
+
+fn entry_from_row(row: &SqliteRow) -> Result<AnnotationEntry, sqlx::Error> {
+    let tags: Option<String> = row.try_get("tags")?;
+    let mut tags: Vec<String> = tags
+        .map(|tags| tags.split(TAG_SEPARATOR).map(str::to_string).collect())
+        .unwrap_or_default();
+    tags.sort();
+    Ok(AnnotationEntry {
+        annotation: Annotation {
+            id: row.try_get("id")?,
+            book_hash: row.try_get("book_hash")?,
+            kind: row.try_get("type")?,
+            cfi: row.try_get("cfi")?,
+            text: row.try_get("text")?,
+            style: row.try_get("style")?,
+            color: row.try_get("color")?,
+            note: row.try_get("note")?,
+            tags: Some(tags),
+            created_at: row.try_get("created_at")?,
+            updated_at: row.try_get("updated_at")?,
+            deleted_at: row.try_get("deleted_at")?,
+        },
+        book_title: row.try_get("book_title")?,
+        book_author: row.try_get("book_author")?,
+    })
+}
+
+/// Insert or update the annotations of a book. Rows already stored with a
+/// newer `updated_at` are kept. Returns the number of rows written.
+pub async fn upsert(
+    pool: &SqlitePool,
+    book: &AnnotatedBook,
+    annotations: &[Annotation],
+) -> Result<usize, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+    sqlx::query(
+        "INSERT INTO annotation_books (hash, meta_hash, title, author) VALUES (?, ?, ?, ?) \
+         ON CONFLICT (hash) DO UPDATE SET \
+         meta_hash = excluded.meta_hash, title = excluded.title, author = excluded.author",
+    )
+    .bind(&book.hash)
+    .bind(&book.meta_hash)
+    .bind(&book.title)
+    .bind(&book.author)
+    .execute(&mut *tx)
+    .await?;
+
+    let mut written = 0;
+    for annotation in annotations {
+        let (cfi_start, cfi_end) = split_cfi_range(&annotation.cfi);
+        let result = sqlx::query(
+            "INSERT INTO annotations (id, book_hash, type, cfi, cfi_start, cfi_end, text, style, \
+             color, note, created_at, updated_at, deleted_at) \
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?) \
+             ON CONFLICT (id) DO UPDATE SET \
+             book_hash = excluded.book_hash, type = excluded.type, cfi = excluded.cfi, \
+             cfi_start = excluded.cfi_start, cfi_end = excluded.cfi_end, text = excluded.text, \
+             style = excluded.style, color = excluded.color, note = excluded.note, \
+             updated_at = excluded.updated_at, deleted_at = excluded.deleted_at \
+             WHERE excluded.updated_at >= annotations.updated_at",
+        )
+        .bind(&annotation.id)
+        .bind(&book.hash)
+        .bind(&annotation.kind)
+        .bind(&annotation.cfi)
+        .bind(cfi_start)
+        .bind(cfi_end)
+        .bind(&annotation.text)
+        .bind(&annotation.style)
+        .bind(&annotation.color)
+        .bind(&annotation.note)
+        .bind(annotation.created_at)
+        .bind(annotation.updated_at)
+        .bind(annotation.deleted_at)
+        .execute(&mut *tx)
+        .await?;
+        if result.rows_affected() == 0 {
+            continue;
+        }
+        written += 1;
+        if let Some(tags) = &annotation.tags {
+            replace_tags(&mut tx, &annotation.id, tags).await?;
+        }
+    }
+    tx.commit().await?;
+    Ok(written)
+}
+
+async fn replace_tags(
+    tx: &mut sqlx::Transaction<'_, Sqlite>,
+    id: &str,
+    tags: &[String],
+) -> Result<(), sqlx::Error> {
+    sqlx::query("DELETE FROM annotation_tags WHERE annotation_id = ?")
+        .bind(id)
+        .execute(&mut **tx)
+        .await?;
+    for tag in normalize_tags(tags) {
+        sqlx::query("INSERT INTO annotation_tags (annotation_id, tag) VALUES (?, ?)")
+            .bind(id)
+            .bind(tag)
+            .execute(&mut **tx)
+            .await?;
+    }
+    Ok(())
+}
+
+/// Replace the tags of an annotation. Returns `false` if it does not exist.
+pub async fn set_tags(pool: &SqlitePool, id: &str, tags: &[String]) -> Result<bool, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+    let exists = sqlx::query("SELECT 1 FROM annotations WHERE id = ?")
+        .bind(id)
+        .fetch_optional(&mut *tx)
+        .await?
+        .is_some();
+    if exists {
+        replace_tags(&mut tx, id, tags).await?;
+    }
+    tx.commit().await?;
+    Ok(exists)
+}
+
+/// Permanently remove annotations, along with their tags.
+pub async fn delete(pool: &SqlitePool, ids: &[String]) -> Result<u64, sqlx::Error> {
+    if ids.is_empty() {
+        return Ok(0);
+    }
+    let mut query = QueryBuilder::<Sqlite>::new("DELETE FROM annotations WHERE id IN (");
+    let mut separated = query.separated(", ");
+    for id in ids {
+        separated.push_bind(id);
+    }
+    separated.push_unseparated(")");
+    Ok(query.build().execute(pool).await?.rows_affected())
+}
+
+/// A live annotation by id.
+pub async fn get(pool: &SqlitePool, id: &str) -> Result<Option<AnnotationEntry>, sqlx::Error> {
+    let sql = format!("{SELECT_ANNOTATIONS} WHERE a.id = ? AND a.deleted_at IS NULL");
+    sqlx::query(&sql)
+        .bind(id)
+        .fetch_optional(pool)
+        .await?
+        .as_ref()
+        .map(entry_from_row)
+        .transpose()
+}
+
+pub async fn query(
+    pool: &SqlitePool,
+    filter: &AnnotationFilter,
+) -> Result<Vec<AnnotationEntry>, sqlx::Error> {
+    let mut query = QueryBuilder::<Sqlite>::new(SELECT_ANNOTATIONS);
+    query.push(" WHERE 1 = 1");
+    if !filter.include_deleted {
+        query.push(" AND a.deleted_at IS NULL");
+    }
+    if let Some(book_hash) = &filter.book_hash {
+        query.push(" AND a.book_hash = ").push_bind(book_hash);
+    }
+    if let Some(kind) = &filter.kind {
+        query.push(" AND a.type = ").push_bind(kind);
+    }
+    if let Some(color) = &filter.color {
+        query.push(" AND a.color = ").push_bind(color);
+    }
+    if let Some(tag) = &filter.tag {
+        query
+            .push(" AND a.id IN (SELECT annotation_id FROM annotation_tags WHERE tag = ")
+            .push_bind(tag.trim())
+            .push(")");
+    }
+
+    let text = filter
+        .query
+        .as_deref()
+        .map(str::trim)
+        .filter(|q| !q.is_empty());
+    let mut phrase = None;
+    match text {
+        Some(text) if text.chars().count() >= MIN_FTS_QUERY_CHARS => {
+            // A single quoted phrase, so that user input is never parsed as
+            // FTS5 query syntax.
+            let text = format!("\"{}\"", text.replace('"', "\"\""));
+            query
+                .push(
+                    " AND a.seq IN (SELECT rowid FROM annotations_fts WHERE annotations_fts MATCH ",
+                )
+                .push_bind(text.clone())
+                .push(")");
+            phrase = Some(text);
+        }
+        Some(text) => {
+            let pattern = format!(
+                "%{}%",
+                text.replace('\\', "\\\\")
+                    .replace('%', "\\%")
+                    .replace('_', "\\_")
+            );
+            query
+                .push(" AND (a.note LIKE ")
+                .push_bind(pattern.clone())
+                .push(" ESCAPE '\\' OR a.text LIKE ")
+                .push_bind(pattern)
+                .push(" ESCAPE '\\')");
+        }
+        None => {}
+    }
+
+    if let Some(phrase) = phrase {
+        // Best matches first, by the bm25 rank of FTS5.
+        query
+            .push(" ORDER BY (SELECT rank FROM annotations_fts WHERE annotations_fts MATCH ")
+            .push_bind(phrase)
+            .push(" AND rowid = a.seq), a.updated_at DESC");
+    } else if filter.book_hash.is_some() {
+        query.push(" ORDER BY a.created_at");
+    } else {
+        query.push(" ORDER BY a.updated_at DESC");
+    }
+    if let Some(limit) = filter.limit {
+        query.push(" LIMIT ").push_bind(limit);
+    }
+
+    query
+        .build()
+        .fetch_all(pool)
+        .await?
+        .iter()
+        .map(entry_from_row)
+        .collect()
+}
+
+/// Tags in use by live annotations, most used first.
+pub async fn tags(pool: &SqlitePool) -> Result<Vec<TagCount>, sqlx::Error> {
+    let rows = sqlx::query(
+        "SELECT t.tag, count(*) AS count FROM annotation_tags t \
+         JOIN annotations a ON a.id = t.annotation_id WHERE a.deleted_at IS NULL \
+         GROUP BY t.tag ORDER BY count DESC, t.tag",
+    )
+    .fetch_all(pool)
+    .await?;
+    rows.iter()
+        .map(|row| {
+            Ok(TagCount {
+                tag: row.try_get("tag")?,
+                count: row.try_get("count")?,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    /// A migrated in-memory store. A single connection, since every
+    /// connection to `:memory:` opens a database of its own.
+    async fn store() -> SqlitePool {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        for migration in super::super::migrations() {
+            sqlx::raw_sql(migration.sql).execute(&pool).await.unwrap();
+        }
+        pool
+    }
+
+    fn book(hash: &str) -> AnnotatedBook {
+        AnnotatedBook {
+            hash: hash.to_string(),
+            meta_hash: None,
+            title: format!("Book {hash}"),
+            author: Some("Jane Doe".to_string()),
+        }
+    }
+
+    fn highlight(id: &str, text: &str, updated_at: i64) -> Annotation {
+        Annotation {
+            id: id.to_string(),
+            book_hash: String::new(),
+            kind: "annotation".to_string(),
+            cfi: "epubcfi(/6/4!/4/2,/1:0,/1:5)".to_string(),
+            text: Some(text.to_string()),
+            style: Some("highlight".to_string()),
+            color: Some("yellow".to_string()),
+            note: String::new(),
+            tags: None,
+            created_at: updated_at,
+            updated_at,
+            deleted_at: None,
+        }
+    }
+
+    fn ids(entries: &[AnnotationEntry]) -> Vec<&str> {
+        entries.iter().map(|e| e.annotation.id.as_str()).collect()
+    }
+
+    async fn search(pool: &SqlitePool, text: &str) -> Vec<AnnotationEntry> {
+        let filter = AnnotationFilter {
+            query: Some(text.to_string()),
+            ..Default::default()
+        };
+        query(pool, &filter).await.unwrap()
+    }
+
+    #[test]
+    fn splits_range_cfis() {
+        assert_eq!(
+            split_cfi_range("epubcfi(/6/4!/4/2,/1:0,/1:5)"),
+            (
+                "epubcfi(/6/4!/4/2/1:0)".to_string(),
+                "epubcfi(/6/4!/4/2/1:5)".to_string()
+            )
+        );
+        assert_eq!(
+            split_cfi_range("epubcfi(/6/4!/4/2)"),
+            (
+                "epubcfi(/6/4!/4/2)".to_string(),
+                "epubcfi(/6/4!/4/2)".to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn keeps_the_newer_side_of_an_upsert() {
+        tauri::async_runtime::block_on(async {
+            let pool = store().await;
+            let mut first = highlight("a", "Call me Ishmael.", 2);
+            first.note = "Opening".to_string();
+            first.tags = Some(vec![" whales ".into(), "classic".into(), "whales".into()]);
+            assert_eq!(upsert(&pool, &book("h1"), &[first]).await.unwrap(), 1);
+
+            let mut stale = highlight("a", "Call me Ishmael.", 1);
+            stale.note = "Stale".to_string();
+            stale.tags = Some(Vec::new());
+            assert_eq!(upsert(&pool, &book("h1"), &[stale]).await.unwrap(), 0);
+            let entry = get(&pool, "a").await.unwrap().unwrap();
+            assert_eq!(entry.annotation.note, "Opening");
+            assert_eq!(
+                entry.annotation.tags.unwrap(),
+                ["classic".to_string(), "whales".to_string()]
+            );
+
+            // Without tags, an update leaves them alone.
+            let mut newer = highlight("a", "Call me Ishmael.", 3);
+            newer.note = "First line".to_string();
+            let mut renamed = book("h1");
+            renamed.title = "Moby-Dick".to_string();
+            assert_eq!(upsert(&pool, &renamed, &[newer]).await.unwrap(), 1);
+            let entry = get(&pool, "a").await.unwrap().unwrap();
+            assert_eq!(entry.annotation.note, "First line");
+            assert_eq!(entry.annotation.tags.unwrap().len(), 2);
+            assert_eq!(entry.book_title.as_deref(), Some("Moby-Dick"));
+
+            assert!(set_tags(&pool, "a", &["sea".to_string()]).await.unwrap());
+            assert!(!set_tags(&pool, "missing", &[]).await.unwrap());
+            let tags = tags(&pool).await.unwrap();
+            assert_eq!(tags.len(), 1);
+            assert_eq!((tags[0].tag.as_str(), tags[0].count), ("sea", 1));
+        });
+    }
+
+    #[test]
+    fn queries_a_book_in_reading_order() {
+        tauri::async_runtime::block_on(async {
+            let pool = store().await;
+            let mut second = highlight("b", "second", 20);
+            second.created_at = 2;
+            let mut first = highlight("a", "first", 10);
+            first.created_at = 1;
+            first.color = Some("red".to_string());
+            let mut deleted = highlight("c", "deleted", 30);
+            deleted.deleted_at = Some(30);
+            let mut bookmark = highlight("d", "", 5);
+            bookmark.kind = "bookmark".to_string();
+            bookmark.created_at = 3;
+            upsert(&pool, &book("h1"), &[second, first, deleted, bookmark])
+                .await
+                .unwrap();
+            upsert(&pool, &book("h2"), &[highlight("e", "other", 40)])
+                .await
+                .unwrap();
+
+            let in_book = |hash: &str| AnnotationFilter {
+                book_hash: Some(hash.to_string()),
+                ..Default::default()
+            };
+            let entries = query(&pool, &in_book("h1")).await.unwrap();
+            assert_eq!(ids(&entries), ["a", "b", "d"]);
+            assert_eq!(entries[0].book_title.as_deref(), Some("Book h1"));
+            let all = AnnotationFilter {
+                include_deleted: true,
+                ..in_book("h1")
+            };
+            assert_eq!(
+                ids(&query(&pool, &all).await.unwrap()),
+                ["a", "b", "d", "c"]
+            );
+            let red = AnnotationFilter {
+                color: Some("red".to_string()),
+                ..in_book("h1")
+            };
+            assert_eq!(ids(&query(&pool, &red).await.unwrap()), ["a"]);
+            let bookmarks = AnnotationFilter {
+                kind: Some("bookmark".to_string()),
+                ..Default::default()
+            };
+            assert_eq!(ids(&query(&pool, &bookmarks).await.unwrap()), ["d"]);
+
+            // Across books, the most recently updated come first.
+            let recent = AnnotationFilter {
+                limit: Some(2),
+                ..Default::default()
+            };
+            assert_eq!(ids(&query(&pool, &recent).await.unwrap()), ["e", "b"]);
+            assert!(get(&pool, "c").await.unwrap().is_none());
+            assert_eq!(delete(&pool, &["c".to_string()]).await.unwrap(), 1);
+        });
+    }
+
+    #[test]
+    fn searches_text_and_notes_by_relevance() {
+        tauri::async_runtime::block_on(async {
+            let pool = store().await;
+            let mut noted = highlight("a", "Call me Ishmael.", 3);
+            noted.note = "The whale shows up much later".to_string();
+            upsert(
+                &pool,
+                &book("h1"),
+                &[
+                    noted,
+                    highlight("b", "whale, whale, whale!", 1),
+                    highlight("c", "我们在海上漂流", 2),
+                    highlight("d", "100% \"true\" story", 4),
+                ],
+            )
+            .await
+            .unwrap();
+
+            // The note mentions the whale once, the highlight three times.
+            assert_eq!(ids(&search(&pool, "whale").await), ["b", "a"]);
+            assert_eq!(ids(&search(&pool, "hal").await), ["b", "a"]);
+            assert_eq!(ids(&search(&pool, "在海上").await), ["c"]);
+            // Query syntax is searched for as it is.
+            assert_eq!(ids(&search(&pool, "\"true\" story").await), ["d"]);
+            assert!(search(&pool, "whale OR call").await.is_empty());
+            // Queries too short for the trigram index match substrings too.
+            assert_eq!(ids(&search(&pool, "0%").await), ["d"]);
+            assert_eq!(ids(&search(&pool, "%").await), ["d"]);
+            assert!(search(&pool, "_").await.is_empty());
+            assert_eq!(ids(&search(&pool, "海上").await), ["c"]);
+
+            // Edits and deletions reach the index.
+            let mut edited = highlight("b", "A white sperm", 5);
+            edited.note = String::new();
+            upsert(&pool, &book("h1"), &[edited]).await.unwrap();
+            assert_eq!(ids(&search(&pool, "whale").await), ["a"]);
+            delete(&pool, &["a".to_string()]).await.unwrap();
+            assert!(search(&pool, "whale").await.is_empty());
+        });
+    }
+}
@@ -0,0 +1,437 @@
+//! Just enough EPUB for the command line: reading the title, authors, language
+//! and cover from the package document, and writing plain text as an EPUB.
+
+use super::{Error, Result};
+use std::fs::File;
+use std::io::{Cursor, Read, Seek, Write};
+use std::path::Path;
+use zip::write::SimpleFileOptions;
+use zip::ZipArchive;
+
+/// Paragraphs per chapter when the text has no recognisable headings, as in
+/// `TxtToEpubConverter`.
+const PARAGRAPHS_PER_CHAPTER: usize = 100;
+
+/// Longest line that can still be a chapter heading.
+const MAX_HEADING_CHARS: usize = 60;
+
+const HEADING_KEYWORDS: &[&str] = &[
+    "chapter",
+    "part",
+    "section",
+    "book",
+    "volume",
+    "act",
+    "prologue",
+    "epilogue",
+    "introduction",
+    "foreword",
+    "preface",
+    "afterword",
+    "अध्याय",
+    "भाग",
+];
+
+#[derive(Debug, Default)]
+pub struct Metadata {
+    pub title: Option<String>,
+    pub authors: Vec<String>,
+    pub language: Option<String>,
+    pub cover: Option<Vec<u8>>,
+}
+
+fn read_entry<R: Read + Seek>(archive: &mut ZipArchive<R>, name: &str) -> Result<Vec<u8>> {
+    let mut entry = archive.by_name(name)?;
+    let mut bytes = Vec::with_capacity(entry.size() as usize);
+    entry.read_to_end(&mut bytes)?;
+    Ok(bytes)
+}
+
+/// Resolve `href` against the folder of the package document, undoing the
+/// percent-encoding of spaces and other reserved characters.
+fn resolve_href(opf_path: &str, href: &str) -> String {
+    let mut bytes = Vec::with_capacity(href.len());
+    let mut rest = href.split('#').next().unwrap_or_default().as_bytes();
+    while let [first, tail @ ..] = rest {
+        match (first, tail) {
+            (b'%', [hi, lo, tail @ ..]) => {
+                let hex = std::str::from_utf8(&[*hi, *lo]).ok().map(str::to_string);
+                match hex.and_then(|hex| u8::from_str_radix(&hex, 16).ok()) {
+                    Some(byte) => {
+                        bytes.push(byte);
+                        rest = tail;
+                        continue;
+                    }
+                    None => bytes.push(b'%'),
+                }
+            }
+            _ => bytes.push(*first),
+        }
+        rest = tail;
+    }
+    let href = String::from_utf8_lossy(&bytes);
+    let mut parts = opf_path.split('/').collect::<Vec<_>>();
+    parts.pop();
+    for part in href.split('/') {
+        match part {
+            "." | "" => {}
+            ".." => {
+                parts.pop();
+            }
+            part => parts.push(part),
+        }
+    }
+    parts.join("/")
+}
+
+pub fn read_metadata<R: Read + Seek>(reader: R) -> Result<Metadata> {
+    let mut archive = ZipArchive::new(reader)?;
+    let container = String::from_utf8(read_entry(&mut archive, "META-INF/container.xml")?)
+        .map_err(|err| Error::Other(err.to_string()))?;
+    let container = roxmltree::Document::parse(&container)?;
+    let opf_path = container
+        .descendants()
+        .find(|node| node.has_tag_name("rootfile"))
+        .and_then(|node| node.attribute("full-path"))
+        .ok_or_else(|| Error::Other("No package document in container.xml".to_string()))?
+        .to_string();
+    let opf = String::from_utf8(read_entry(&mut archive, &opf_path)?)
+        .map_err(|err| Error::Other(err.to_string()))?;
+    let opf = roxmltree::Document::parse(&opf)?;
+
+    let texts = |name: &str| {
+        opf.descendants()
+            .filter(|node| node.tag_name().name() == name)
+            .filter_map(|node| node.text())
+            .map(|text| text.trim().to_string())
+            .filter(|text| !text.is_empty())
+            .collect::<Vec<_>>()
+    };
+    let items = opf
+        .descendants()
+        .filter(|node| node.has_tag_name("item"))
+        .collect::<Vec<_>>();
+    // EPUB 3 flags the cover in the manifest, EPUB 2 points to it from a <meta>.
+    let cover_id = opf
+        .descendants()
+        .find(|node| node.has_tag_name("meta") && node.attribute("name") == Some("cover"))
+        .and_then(|node| node.attribute("content"));
+    let cover_href = items
+        .iter()
+        .find(|item| {
+            item.attribute("properties")
+                .is_some_and(|props| props.split_whitespace().any(|p| p == "cover-image"))
+        })
+        .or_else(|| items.iter().find(|item| item.attribute("id") == cover_id))
+        .or_else(|| {
+            items.iter().find(|item| {
+                item.attribute("media-type")
+                    .is_some_and(|t| t.starts_with("image/"))
+                    && item.attribute("id").is_some_and(|id| id.contains("cover"))
+            })
+        })
+        .and_then(|item| item.attribute("href"));
+    let cover = cover_href
+        .map(|href| resolve_href(&opf_path, href))
+        .and_then(|path| read_entry(&mut archive, &path).ok());
+
+    Ok(Metadata {
+        title: texts("title").into_iter().next(),
+        authors: texts("creator"),
+        language: texts("language").into_iter().next(),
+        cover,
+    })
+}
+
+pub fn cover_of_file(path: &Path) -> Result<Vec<u8>> {
+    read_metadata(File::open(path)?)?
+        .cover
+        .ok_or_else(|| Error::Other(format!("{} has no cover", path.display())))
+}
+
+/// `en-US` → `en`, like `getPrimaryLanguage` for the common cases.
+pub fn primary_language(language: &str) -> String {
+    let code = language.split(['-', '_']).next().unwrap_or_default();
+    match code.to_lowercase().as_str() {
+        "" => "en".to_string(),
+        "eng" => "en".to_string(),
+        "hin" => "hi".to_string(),
+        "zho" | "chi" => "zh".to_string(),
+        code => code.to_string(),
+    }
+}
+
+/// Authors joined like `Intl.ListFormat` does in English.
+pub fn format_authors(authors: &[String]) -> String {
+    match authors {
+        [] => String::new(),
+        [author] => author.clone(),
+        [first, second] => format!("{first} and {second}"),
+        [rest @ .., last] => format!("{}, and {last}", rest.join(", ")),
+    }
+}
+
+fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+/// Decode UTF-8 (with or without a BOM) or BOM-marked UTF-16, falling back to a
+/// lossy UTF-8 decode for anything else.
+fn decode(bytes: &[u8]) -> String {
+    let utf16 = |bytes: &[u8], decode: fn([u8; 2]) -> u16| {
+        let units = bytes
+            .chunks_exact(2)
+            .map(|pair| decode([pair[0], pair[1]]))
+            .collect::<Vec<_>>();
+        String::from_utf16_lossy(&units)
+    };
+    match bytes {
+        [0xEF, 0xBB, 0xBF, rest @ ..] => String::from_utf8_lossy(rest).into_owned(),
+        [0xFF, 0xFE, rest @ ..] => utf16(rest, u16::from_le_bytes),
+        [0xFE, 0xFF, rest @ ..] => utf16(rest, u16::from_be_bytes),
+        _ => String::from_utf8_lossy(bytes).into_owned(),
+    }
+}
+
+fn detect_language(text: &str) -> &'static str {
+    let sample = text.chars().filter(|c| c.is_alphabetic()).take(1000);
+    let (mut devanagari, mut cjk, mut total) = (0, 0, 0);
+    for c in sample {
+        total += 1;
+        match c {
+            '\u{0900}'..='\u{097F}' => devanagari += 1,
+            '\u{4E00}'..='\u{9FFF}' | '\u{3040}'..='\u{30FF}' => cjk += 1,
+            _ => {}
+        }
+    }
+    if devanagari * 3 > total {
+        "hi"
+    } else if cjk * 3 > total {
+        "zh"
+    } else {
+        "en"
+    }
+}
+
+fn is_heading(line: &str) -> bool {
+    if line.is_empty() || line.chars().count() > MAX_HEADING_CHARS {
+        return false;
+    }
+    let lower = line.to_lowercase();
+    let keyword = HEADING_KEYWORDS.iter().any(|keyword| {
+        lower.strip_prefix(keyword).is_some_and(|rest| {
+            rest.is_empty() || rest.starts_with(|c: char| c.is_whitespace() || ".:-–—".contains(c))
+        })
+    });
+    // 第十二章, 第3回 and the like.
+    let numbered_cjk = line.starts_with('第')
+        && line
+            .chars()
+            .take(12)
+            .any(|c| "章卷节回讲篇部话".contains(c));
+    keyword || numbered_cjk
+}
+
+struct Chapter {
+    title: String,
+    paragraphs: Vec<String>,
+}
+
+fn split_chapters(text: &str) -> Vec<Chapter> {
+    let mut chapters: Vec<Chapter> = Vec::new();
+    let mut preface = Vec::new();
+    for line in text.lines().map(str::trim).filter(|line| !line.is_empty()) {
+        if is_heading(line) {
+            chapters.push(Chapter {
+                title: line.to_string(),
+                paragraphs: Vec::new(),
+            });
+        } else if let Some(chapter) = chapters.last_mut() {
+            chapter.paragraphs.push(line.to_string());
+        } else {
+            preface.push(line.to_string());
+        }
+    }
+    if chapters.len() < 2 {
+        // Too few headings to trust: number fixed-size chunks instead.
+        let paragraphs = preface
+            .into_iter()
+            .chain(
+                chapters
+                    .into_iter()
+                    .flat_map(|c| std::iter::once(c.title).chain(c.paragraphs)),
+            )
+            .collect::<Vec<_>>();
+        return paragraphs
+            .chunks(PARAGRAPHS_PER_CHAPTER)
+            .enumerate()
+            .map(|(i, chunk)| Chapter {
+                title: (i + 1).to_string(),
+                paragraphs: chunk.to_vec(),
+            })
+            .collect();
+    }
+    if !preface.is_empty() {
+        chapters.insert(
+            0,
+            Chapter {
+                title: String::new(),
+                paragraphs: preface,
+            },
+        );
+    }
+    chapters
+}
+
+fn xhtml(language: &str, title: &str, body: &str) -> String {
+    format!(
+        "<?xml version=\"1.0\" encoding=\"utf-8\"?>\n\
+         <!DOCTYPE html>\n\
+         <html xmlns=\"http://www.w3.org/1999/xhtml\" xmlns:epub=\"http://www.idpf.org/2007/ops\" \
+         lang=\"{language}\" xml:lang=\"{language}\">\n\
+         <head><title>{title}</title></head>\n<body>\n{body}</body>\n</html>\n",
+        title = escape_xml(title),
+    )
+}
+
+/// Convert a plain-text book to EPUB 3, detecting chapter headings by line.
+pub fn convert_txt(bytes: &[u8], title: &str) -> Result<Vec<u8>> {
+    let text = decode(bytes);
+    let chapters = split_chapters(text.trim());
+    if chapters.is_empty() {
+        return Err(Error::Other("The text file is empty".to_string()));
+    }
+    let language = detect_language(&text);
+    let identifier = super::library::partial_md5(&mut Cursor::new(bytes))?;
+
+    let mut book = zip::ZipWriter::new(Cursor::new(Vec::new()));
+    let stored = SimpleFileOptions::default().compression_method(zip::CompressionMethod::Stored);
+    let deflated =
+        SimpleFileOptions::default().compression_method(zip::CompressionMethod::Deflated);
+    // The mimetype must come first and uncompressed.
+    book.start_file("mimetype", stored)?;
+    book.write_all(b"application/epub+zip")?;
+    book.start_file("META-INF/container.xml", deflated)?;
+    book.write_all(
+        b"<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+          <container version=\"1.0\" xmlns=\"urn:oasis:names:tc:opendocument:xmlns:container\">\n\
+          <rootfiles><rootfile full-path=\"OEBPS/content.opf\" \
+          media-type=\"application/oebps-package+xml\"/></rootfiles>\n</container>\n",
+    )?;
+
+    let mut manifest = String::new();
+    let mut spine = String::new();
+    let mut toc = String::new();
+    for (i, chapter) in chapters.iter().enumerate() {
+        let name = format!("chapter{}.xhtml", i + 1);
+        let mut body = String::new();
+        if !chapter.title.is_empty() {
+            body.push_str(&format!("<h2>{}</h2>\n", escape_xml(&chapter.title)));
+            toc.push_str(&format!(
+                "<li><a href=\"{name}\">{}</a></li>\n",
+                escape_xml(&chapter.title)
+            ));
+        }
+        for paragraph in &chapter.paragraphs {
+            body.push_str(&format!("<p>{}</p>\n", escape_xml(paragraph)));
+        }
+        book.start_file(format!("OEBPS/{name}"), deflated)?;
+        book.write_all(xhtml(language, &chapter.title, &body).as_bytes())?;
+        manifest.push_str(&format!(
+            "<item id=\"c{}\" href=\"{name}\" media-type=\"application/xhtml+xml\"/>\n",
+            i + 1
+        ));
+        spine.push_str(&format!("<itemref idref=\"c{}\"/>\n", i + 1));
+    }
+
+    let nav = format!("<nav epub:type=\"toc\"><ol>\n{toc}</ol></nav>\n");
+    book.start_file("OEBPS/nav.xhtml", deflated)?;
+    book.write_all(xhtml(language, title, &nav).as_bytes())?;
+    let modified = chrono::Utc::now().format("%Y-%m-%dT%H:%M:%SZ");
+    book.start_file("OEBPS/content.opf", deflated)?;
+    book.write_all(
+        format!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+             <package xmlns=\"http://www.idpf.org/2007/opf\" version=\"3.0\" \
+             unique-identifier=\"book-id\">\n\
+             <metadata xmlns:dc=\"http://purl.org/dc/elements/1.1/\">\n\
+             <dc:identifier id=\"book-id\">{identifier}</dc:identifier>\n\
+             <dc:title>{title}</dc:title>\n\
+             <dc:language>{language}</dc:language>\n\
+             <meta property=\"dcterms:modified\">{modified}</meta>\n\
+             </metadata>\n\
+             <manifest>\n\
+             <item id=\"nav\" href=\"nav.xhtml\" media-type=\"application/xhtml+xml\" \
+             properties=\"nav\"/>\n{manifest}</manifest>\n\
+             <spine>\n{spine}</spine>\n</package>\n",
+            title = escape_xml(title),
+        )
+        .as_bytes(),
+    )?;
+    Ok(book.finish()?.into_inner())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn converts_text_with_headings() {
+        let text = "A tale\n\nChapter 1\nIt begins.\n\nChapter 2: The End\nIt ends & stops.\n";
+        let epub = convert_txt(text.as_bytes(), "A <Tale>").unwrap();
+        let metadata = read_metadata(Cursor::new(epub.as_slice())).unwrap();
+        assert_eq!(metadata.title.as_deref(), Some("A <Tale>"));
+        assert_eq!(metadata.language.as_deref(), Some("en"));
+        assert!(metadata.cover.is_none());
+
+        let mut archive = ZipArchive::new(Cursor::new(epub.as_slice())).unwrap();
+        assert_eq!(archive.by_index(0).unwrap().name(), "mimetype");
+        let last = String::from_utf8(read_entry(&mut archive, "OEBPS/chapter3.xhtml").unwrap());
+        assert!(last.unwrap().contains("<p>It ends &amp; stops.</p>"));
+    }
+
+    #[test]
+    fn chunks_text_without_headings() {
+        let text = (0..250).map(|i| format!("Line {i}\n")).collect::<String>();
+        let chapters = split_chapters(&text);
+        assert_eq!(chapters.len(), 3);
+        assert_eq!(chapters[2].title, "3");
+        assert_eq!(chapters[2].paragraphs.len(), 50);
+    }
+
+    #[test]
+    fn recognises_headings() {
+        assert!(is_heading("Chapter 12"));
+        assert!(is_heading("PROLOGUE"));
+        assert!(is_heading("अध्याय 3"));
+        assert!(is_heading("第十二章 归来"));
+        assert!(!is_heading("Chapters of my life were long and winding"));
+        assert!(!is_heading("Partly cloudy"));
+    }
+
+    #[test]
+    fn resolves_hrefs_against_the_package() {
+        assert_eq!(
+            resolve_href("OEBPS/content.opf", "images/cover%20art.jpg"),
+            "OEBPS/images/cover art.jpg"
+        );
+        assert_eq!(resolve_href("content.opf", "cover.jpg"), "cover.jpg");
+        assert_eq!(
+            resolve_href("a/b/content.opf", "../img/c.png#x"),
+            "a/img/c.png"
+        );
+    }
+
+    #[test]
+    fn formats_metadata() {
+        let authors = ["A".to_string(), "B".to_string(), "C".to_string()];
+        assert_eq!(format_authors(&authors), "A, B, and C");
+        assert_eq!(format_authors(&authors[..2]), "A and B");
+        assert_eq!(primary_language("en-US"), "en");
+        assert_eq!(primary_language("hin"), "hi");
+    }
+}
@@ -0,0 +1,354 @@
+//! The on-disk library shared with the app: `library.json` plus one folder per
+//! book (named by its partial MD5) holding the book file, `cover.png` and
+//! `config.json`. Layout and hashing follow `appService.importBook`.
+
+use super::{epub, Error, Result};
+use crate::portable::PortablePaths;
+use serde_json::{json, Value};
+use std::fs::{self, File};
+use std::io::{Cursor, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+const DATA_SUBDIR: &str = "HackXIndia26";
+const SETTINGS_FILENAME: &str = "settings.json";
+const LIBRARY_FILENAME: &str = "library.json";
+
+/// File extensions the app opens, with the format recorded in the library.
+const FORMATS: &[(&str, &str)] = &[
+    ("epub", "EPUB"),
+    ("pdf", "PDF"),
+    ("mobi", "MOBI"),
+    ("azw", "AZW"),
+    ("azw3", "AZW3"),
+    ("cbz", "CBZ"),
+    ("fb2", "FB2"),
+    ("fbz", "FBZ"),
+    ("md", "MD"),
+    ("txt", "EPUB"),
+];
+
+/// Where the app keeps its settings, annotations and books, honouring portable
+/// installs and a custom root folder like `nativeAppService` does.
+pub struct Paths {
+    pub config_dir: PathBuf,
+    pub books_dir: PathBuf,
+}
+
+impl Paths {
+    pub fn resolve(identifier: &str, portable: Option<&PortablePaths>) -> Result<Self> {
+        let missing = |what: &str| Error::Other(format!("Cannot locate the {what} directory"));
+        let config_dir = match portable {
+            Some(portable) => portable.data_dir.clone(),
+            None => dirs::config_dir()
+                .ok_or_else(|| missing("config"))?
+                .join(identifier),
+        };
+
+        let custom_root = fs::read_to_string(config_dir.join(SETTINGS_FILENAME))
+            .ok()
+            .and_then(|settings| serde_json::from_str::<Value>(&settings).ok())
+            .and_then(|settings| settings.get("customRootDir")?.as_str().map(PathBuf::from))
+            .filter(|dir| !dir.as_os_str().is_empty());
+        let root = match (custom_root, portable) {
+            (Some(root), _) => root,
+            (None, Some(portable)) => portable.executable_dir.clone(),
+            (None, None) => dirs::data_dir()
+                .ok_or_else(|| missing("data"))?
+                .join(identifier),
+        };
+        Ok(Self {
+            config_dir,
+            books_dir: root.join(DATA_SUBDIR).join("Books"),
+        })
+    }
+}
+
+/// The partial MD5 the app identifies books by: 1 KiB slices at offsets 0 and
+/// 1 KiB × 4^i for i in 0..=10, stopping at the end of the file.
+pub fn partial_md5<R: Read + Seek>(reader: &mut R) -> Result<String> {
+    use md5::{Digest, Md5};
+
+    let size = reader.seek(SeekFrom::End(0))?;
+    let mut hasher = Md5::new();
+    let mut buffer = Vec::with_capacity(1024);
+    for i in -1..=10 {
+        let start = if i < 0 {
+            0
+        } else {
+            (1024u64 << (2 * i)).min(size)
+        };
+        if start >= size {
+            break;
+        }
+        reader.seek(SeekFrom::Start(start))?;
+        buffer.clear();
+        reader.by_ref().take(1024).read_to_end(&mut buffer)?;
+        hasher.update(&buffer);
+    }
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+/// Port of `makeSafeFilename`.
+fn safe_filename(name: &str) -> String {
+    let mut safe: String = name
+        .chars()
+        .map(|c| match c {
+            '<' | '>' | ':' | '%' | '"' | '/' | '\\' | '|' | '?' | '*' => '_',
+            c if (c as u32) < 0x20 => '_',
+            c => c,
+        })
+        .collect();
+    let lower = safe.to_lowercase();
+    let numbered = |prefix: &str| {
+        lower.len() == 4 && lower.starts_with(prefix) && matches!(lower.as_bytes()[3], b'1'..=b'9')
+    };
+    if ["con", "prn", "aux", "nul"].contains(&lower.as_str()) || numbered("com") || numbered("lpt")
+    {
+        safe.push('_');
+    }
+    while safe.len() > 250 {
+        safe.pop();
+    }
+    safe.trim().to_string()
+}
+
+/// The file name without its extension, used as title when a book has none.
+pub fn file_title(path: &Path) -> String {
+    path.file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default()
+}
+
+fn now() -> i64 {
+    chrono::Utc::now().timestamp_millis()
+}
+
+fn str_field<'a>(book: &'a Value, name: &str) -> &'a str {
+    book.get(name).and_then(Value::as_str).unwrap_or("")
+}
+
+pub struct Library {
+    books_dir: PathBuf,
+    books: Vec<Value>,
+}
+
+impl Library {
+    /// Books are kept as raw JSON so that fields only the app knows survive a save.
+    pub fn load(paths: &Paths) -> Result<Self> {
+        let file = paths.books_dir.join(LIBRARY_FILENAME);
+        let backup = paths.books_dir.join(format!("{LIBRARY_FILENAME}.bak"));
+        let books = match fs::read_to_string(&file) {
+            Ok(text) => serde_json::from_str(&text).or_else(|err| {
+                let text = fs::read_to_string(&backup).map_err(|_| err)?;
+                Ok::<_, Error>(serde_json::from_str(&text)?)
+            })?,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+            Err(err) => return Err(err.into()),
+        };
+        Ok(Self {
+            books_dir: paths.books_dir.clone(),
+            books,
+        })
+    }
+
+    /// Same strategy as `safeSaveJSON`: the backup first, then the main file.
+    pub fn save(&self) -> Result<()> {
+        fs::create_dir_all(&self.books_dir)?;
+        let text = serde_json::to_string_pretty(&self.books)?;
+        fs::write(
+            self.books_dir.join(format!("{LIBRARY_FILENAME}.bak")),
+            &text,
+        )?;
+        fs::write(self.books_dir.join(LIBRARY_FILENAME), &text)?;
+        Ok(())
+    }
+
+    /// Books that have not been deleted.
+    pub fn books(&self) -> impl Iterator<Item = &Value> {
+        self.books.iter().filter(|book| book["deletedAt"].is_null())
+    }
+
+    /// Import every supported book under `dir`, returning how many were added
+    /// or refreshed. Files that fail are reported and skipped.
+    pub fn import_dir(&mut self, dir: &Path) -> Result<usize> {
+        if !dir.is_dir() {
+            return Err(Error::Other(format!(
+                "{} is not a directory",
+                dir.display()
+            )));
+        }
+        let mut imported = 0;
+        for entry in WalkDir::new(dir).into_iter().filter_map(|entry| entry.ok()) {
+            let path = entry.path();
+            let extension = path
+                .extension()
+                .map(|ext| ext.to_string_lossy().to_lowercase())
+                .unwrap_or_default();
+            if !FORMATS.iter().any(|(ext, _)| *ext == extension) {
+                continue;
+            }
+            match self.import_file(path, &extension) {
+                Ok(title) => {
+                    println!("{title}");
+                    imported += 1;
+                }
+                Err(err) => eprintln!("Skipped {}: {err}", path.display()),
+            }
+        }
+        Ok(imported)
+    }
+
+    /// Plain text is converted to EPUB first, as the app does. Titles and
+    /// authors are only read from EPUB; other formats are named after the file.
+    fn import_file(&mut self, path: &Path, extension: &str) -> Result<String> {
+        let converted = match extension {
+            "txt" => Some(epub::convert_txt(&fs::read(path)?, &file_title(path))?),
+            _ => None,
+        };
+        let (extension, format) = match FORMATS.iter().find(|(ext, _)| *ext == extension) {
+            Some(("txt", _)) | None => ("epub", "EPUB"),
+            Some((ext, format)) => (*ext, *format),
+        };
+        let (hash, metadata) = match &converted {
+            Some(bytes) => {
+                let mut reader = Cursor::new(bytes.as_slice());
+                (partial_md5(&mut reader)?, epub::read_metadata(reader)?)
+            }
+            None => {
+                let mut file = File::open(path)?;
+                let hash = partial_md5(&mut file)?;
+                let metadata = match format {
+                    "EPUB" => epub::read_metadata(file)?,
+                    _ => epub::Metadata::default(),
+                };
+                (hash, metadata)
+            }
+        };
+
+        let title = metadata
+            .title
+            .filter(|title| !title.trim().is_empty())
+            .unwrap_or_else(|| file_title(path));
+        let now = now();
+        let book = json!({
+            "hash": hash,
+            "format": format,
+            "title": title,
+            "sourceTitle": title,
+            "primaryLanguage": epub::primary_language(metadata.language.as_deref().unwrap_or("")),
+            "author": epub::format_authors(&metadata.authors),
+            "createdAt": now,
+            "uploadedAt": null,
+            "deletedAt": null,
+            "downloadedAt": now,
+            "updatedAt": now,
+        });
+
+        let dir = self.books_dir.join(&hash);
+        fs::create_dir_all(&dir)?;
+        let book_file = dir.join(format!("{}.{extension}", safe_filename(&title)));
+        if !book_file.exists() {
+            match &converted {
+                Some(bytes) => fs::write(&book_file, bytes)?,
+                None => {
+                    fs::copy(path, &book_file)?;
+                }
+            }
+        }
+        let cover_file = dir.join("cover.png");
+        if let (Some(cover), false) = (&metadata.cover, cover_file.exists()) {
+            fs::write(&cover_file, cover)?;
+        }
+
+        match self.books.iter_mut().find(|b| str_field(b, "hash") == hash) {
+            // Re-importing restores a deleted book, like the app does.
+            Some(existing) => {
+                existing["deletedAt"] = Value::Null;
+                existing["createdAt"] = now.into();
+                existing["updatedAt"] = now.into();
+                existing["downloadedAt"] = now.into();
+            }
+            None => {
+                let config_file = dir.join("config.json");
+                if !config_file.exists() {
+                    fs::write(&config_file, r#"{"updatedAt":0}"#)?;
+                }
+                self.books.insert(0, book);
+            }
+        }
+        Ok(title)
+    }
+
+    /// Find a book by file path, hash, title or hash prefix.
+    pub fn resolve_hash(&self, query: &str) -> Result<String> {
+        let path = Path::new(query);
+        if path.is_file() {
+            return partial_md5(&mut File::open(path)?);
+        }
+        Ok(str_field(self.find(query)?, "hash").to_string())
+    }
+
+    fn find(&self, query: &str) -> Result<&Value> {
+        let lower = query.to_lowercase();
+        let exact = self.books().find(|book| {
+            str_field(book, "hash") == query || str_field(book, "title").to_lowercase() == lower
+        });
+        if let Some(book) = exact {
+            return Ok(book);
+        }
+        let prefixed = self
+            .books()
+            .filter(|book| query.len() >= 4 && str_field(book, "hash").starts_with(query))
+            .collect::<Vec<_>>();
+        match prefixed[..] {
+            [book] => Ok(book),
+            [] => Err(Error::Other(format!("No book matches {query}"))),
+            _ => Err(Error::Other(format!(
+                "{query} matches {} books, give more of the hash",
+                prefixed.len()
+            ))),
+        }
+    }
+
+    /// The cover saved at import, or the one inside the book file.
+    pub fn cover(&self, query: &str) -> Result<Vec<u8>> {
+        let book = self.find(query)?;
+        let dir = self.books_dir.join(str_field(book, "hash"));
+        match fs::read(dir.join("cover.png")) {
+            Ok(cover) => Ok(cover),
+            Err(_) if str_field(book, "format") == "EPUB" => {
+                let title = match str_field(book, "sourceTitle") {
+                    "" => str_field(book, "title"),
+                    title => title,
+                };
+                epub::cover_of_file(&dir.join(format!("{}.epub", safe_filename(title))))
+            }
+            Err(_) => Err(Error::Other(format!("{query} has no cover"))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hashes_like_the_app() {
+        // Small files are hashed whole: the slices at 0 and 1 KiB cover them.
+        let data = (0..2048u32).map(|i| i as u8).collect::<Vec<_>>();
+        let mut expected = md5::Md5::default();
+        md5::Digest::update(&mut expected, &data);
+        let expected = format!("{:x}", md5::Digest::finalize(expected));
+        assert_eq!(partial_md5(&mut Cursor::new(&data)).unwrap(), expected);
+    }
+
+    #[test]
+    fn makes_safe_filenames() {
+        assert_eq!(safe_filename("A/B: C?"), "A_B_ C_");
+        assert_eq!(safe_filename("con"), "con_");
+        assert_eq!(safe_filename("COM1"), "COM1_");
+        assert_eq!(safe_filename("console"), "console");
+        assert_eq!(safe_filename(&"é".repeat(200)).len(), 250);
+    }
+}
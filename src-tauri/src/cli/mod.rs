@@ -0,0 +1,359 @@
+//! Headless subcommands, so that libraries can be prepared from scripts.
+//!
+//! `run` looks at the process arguments before Tauri is started. Anything that
+//! is not one of the subcommands below (no arguments, books to open, deep
+//! links) falls through to the app as before.
+
+mod epub;
+pub(crate) mod library;
+
+use crate::annotations::{self, export::ExportFormat, AnnotationFilter};
+use crate::portable::{self, PortablePaths};
+use library::{Library, Paths};
+use std::path::{Path, PathBuf};
+
+type Result<T> = std::result::Result<T, Error>;
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+    #[error(transparent)]
+    Zip(#[from] zip::result::ZipError),
+    #[error(transparent)]
+    Xml(#[from] roxmltree::Error),
+    #[error(transparent)]
+    Image(#[from] image::ImageError),
+    #[error(transparent)]
+    Sql(#[from] sqlx::Error),
+    #[error(transparent)]
+    Export(#[from] annotations::export::Error),
+    #[error("{0}")]
+    Other(String),
+}
+
+const USAGE: &str = "\
+Usage: hackxindia26 <command> [options]
+
+Commands:
+  import <dir>                       Import every book found under <dir>
+  list [--json]                      List the books in the library
+  export-annotations <book> [--format md|csv|obsidian|readwise|anki] [-o <path>]
+                                     Export the annotations of a book
+  convert <txt> --to epub [-o <path>]
+                                     Convert a plain-text book to EPUB
+  cover <book> -o <image>            Save the cover of a book
+  help                               Show this message
+
+<book> is a library book, given by hash, hash prefix or title, or a book file.
+Add --portable to use the library kept next to the executable.
+Run without a command to start the app.";
+
+#[derive(Debug, PartialEq)]
+enum Command {
+    Import {
+        dir: PathBuf,
+    },
+    List {
+        json: bool,
+    },
+    ExportAnnotations {
+        book: String,
+        format: String,
+        output: Option<PathBuf>,
+    },
+    Convert {
+        input: PathBuf,
+        output: Option<PathBuf>,
+    },
+    Cover {
+        book: String,
+        output: PathBuf,
+    },
+    Help,
+}
+
+/// The arguments after the subcommand.
+struct Options<'a> {
+    positionals: Vec<&'a str>,
+    flags: Vec<(&'a str, &'a str)>,
+}
+
+impl<'a> Options<'a> {
+    fn parse(args: &'a [String]) -> std::result::Result<Self, String> {
+        let mut positionals = Vec::new();
+        let mut flags = Vec::new();
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--json" => flags.push(("--json", "")),
+                portable::FLAG => {}
+                flag @ ("--format" | "--to" | "-o" | "--output") => {
+                    let value = args.next().ok_or(format!("{flag} needs a value"))?;
+                    let flag = if flag == "--output" { "-o" } else { flag };
+                    flags.push((flag, value.as_str()));
+                }
+                flag if flag.starts_with('-') && flag.len() > 1 => {
+                    return Err(format!("Unknown option {flag}"));
+                }
+                positional => positionals.push(positional),
+            }
+        }
+        Ok(Self { positionals, flags })
+    }
+
+    fn flag(&self, name: &str) -> Option<&'a str> {
+        self.flags
+            .iter()
+            .find(|(flag, _)| *flag == name)
+            .map(|(_, value)| *value)
+    }
+}
+
+/// `None` when the arguments are not a subcommand and belong to the app.
+fn parse(args: &[String]) -> Option<std::result::Result<Command, String>> {
+    let name = args.get(1)?.as_str();
+    let known = [
+        "import",
+        "list",
+        "export-annotations",
+        "convert",
+        "cover",
+        "help",
+        "--help",
+    ];
+    if !known.contains(&name) {
+        return None;
+    }
+    let parsed = Options::parse(&args[2..]).and_then(|options| {
+        let single = |what: &str| match options.positionals[..] {
+            [value] => Ok(value.to_string()),
+            _ => Err(format!("{name} takes exactly one {what}")),
+        };
+        match name {
+            "import" => Ok(Command::Import {
+                dir: single("directory")?.into(),
+            }),
+            "list" if options.positionals.is_empty() => Ok(Command::List {
+                json: options.flag("--json").is_some(),
+            }),
+            "list" => Err("list takes no arguments".to_string()),
+            "export-annotations" => Ok(Command::ExportAnnotations {
+                book: single("book")?,
+                format: options.flag("--format").unwrap_or("md").to_lowercase(),
+                output: options.flag("-o").map(PathBuf::from),
+            }),
+            "convert" => match options.flag("--to").unwrap_or("epub") {
+                "epub" => Ok(Command::Convert {
+                    input: single("text file")?.into(),
+                    output: options.flag("-o").map(PathBuf::from),
+                }),
+                to => Err(format!("Cannot convert to {to}, only to epub")),
+            },
+            "cover" => Ok(Command::Cover {
+                book: single("book")?,
+                output: options
+                    .flag("-o")
+                    .ok_or("cover needs an output file: -o <image>")?
+                    .into(),
+            }),
+            _ => Ok(Command::Help),
+        }
+    });
+    Some(parsed)
+}
+
+/// Run a subcommand given on the command line, returning the process exit
+/// code, or `None` when the app should start normally.
+pub fn run(identifier: &str, portable: Option<&PortablePaths>) -> Option<i32> {
+    let args = std::env::args().collect::<Vec<_>>();
+    let parsed = parse(&args)?;
+    #[cfg(target_os = "windows")]
+    crate::windows::attach_console();
+    let command = match parsed {
+        Ok(command) => command,
+        Err(err) => {
+            eprintln!("{err}\n\n{USAGE}");
+            return Some(2);
+        }
+    };
+    let result = Paths::resolve(identifier, portable).and_then(|paths| execute(&paths, command));
+    match result {
+        Ok(()) => Some(0),
+        Err(err) => {
+            eprintln!("Error: {err}");
+            Some(1)
+        }
+    }
+}
+
+fn execute(paths: &Paths, command: Command) -> Result<()> {
+    match command {
+        Command::Help => println!("{USAGE}"),
+        Command::Import { dir } => {
+            let mut library = Library::load(paths)?;
+            let imported = library.import_dir(&dir)?;
+            library.save()?;
+            println!(
+                "Imported {imported} book(s) into {}",
+                paths.books_dir.display()
+            );
+        }
+        Command::List { json } => {
+            let library = Library::load(paths)?;
+            let books = library.books().collect::<Vec<_>>();
+            if json {
+                println!("{}", serde_json::to_string_pretty(&books)?);
+                return Ok(());
+            }
+            for book in books {
+                let field = |name: &str| book.get(name).and_then(|v| v.as_str()).unwrap_or("");
+                let author = match field("author") {
+                    "" => String::new(),
+                    author => format!(" — {author}"),
+                };
+                println!(
+                    "{}  {:<4}  {}{author}",
+                    field("hash"),
+                    field("format"),
+                    field("title")
+                );
+            }
+        }
+        Command::ExportAnnotations {
+            book,
+            format,
+            output,
+        } => {
+            let library = Library::load(paths)?;
+            let hash = library.resolve_hash(&book)?;
+            let filter = AnnotationFilter {
+                book_hash: Some(hash),
+                ..Default::default()
+            };
+            let entries = tauri::async_runtime::block_on(annotations::query_file(
+                &paths.config_dir,
+                &filter,
+            ))?;
+            if entries.is_empty() {
+                eprintln!("No annotations found for {book}");
+                return Ok(());
+            }
+            if format == "md" || format == "markdown" {
+                let markdown = annotations::export::markdown(&entries);
+                match output {
+                    Some(output) => std::fs::write(output, markdown)?,
+                    None => print!("{markdown}"),
+                }
+                return Ok(());
+            }
+            let format: ExportFormat = serde_json::from_value(format.clone().into())
+                .map_err(|_| Error::Other(format!("Unknown export format {format}")))?;
+            let dir = output.unwrap_or_else(|| PathBuf::from("."));
+            std::fs::create_dir_all(&dir)?;
+            let files =
+                tauri::async_runtime::block_on(annotations::export::write(format, &dir, &entries))?;
+            for file in files {
+                println!("{}", file.display());
+            }
+        }
+        Command::Convert { input, output } => {
+            let output = output.unwrap_or_else(|| input.with_extension("epub"));
+            let book = epub::convert_txt(&std::fs::read(&input)?, &library::file_title(&input))?;
+            std::fs::write(&output, book)?;
+            println!("{}", output.display());
+        }
+        Command::Cover { book, output } => {
+            let cover = match Path::new(&book).is_file() {
+                true => epub::cover_of_file(Path::new(&book))?,
+                false => Library::load(paths)?.cover(&book)?,
+            };
+            write_image(&cover, &output)?;
+        }
+    }
+    Ok(())
+}
+
+/// Save `bytes` to `output`, re-encoding when the extension asks for another format.
+fn write_image(bytes: &[u8], output: &Path) -> Result<()> {
+    let wanted = image::ImageFormat::from_path(output).ok();
+    match (wanted, image::guess_format(bytes).ok()) {
+        (Some(wanted), Some(actual)) if wanted != actual => {
+            image::load_from_memory_with_format(bytes, actual)?.save_with_format(output, wanted)?
+        }
+        _ => std::fs::write(output, bytes)?,
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(line: &str) -> Vec<String> {
+        std::iter::once("hackxindia26")
+            .chain(line.split_whitespace())
+            .map(str::to_string)
+            .collect()
+    }
+
+    #[test]
+    fn leaves_app_arguments_alone() {
+        assert!(parse(&args("")).is_none());
+        assert!(parse(&args("/books/a.epub")).is_none());
+        assert!(parse(&args("HackXIndia26://open?book=abc")).is_none());
+    }
+
+    #[test]
+    fn parses_subcommands() {
+        assert_eq!(
+            parse(&args("import /srv/books")),
+            Some(Ok(Command::Import {
+                dir: "/srv/books".into()
+            }))
+        );
+        assert_eq!(
+            parse(&args("list --json")),
+            Some(Ok(Command::List { json: true }))
+        );
+        assert_eq!(
+            parse(&args("export-annotations abc --format MD -o notes.md")),
+            Some(Ok(Command::ExportAnnotations {
+                book: "abc".to_string(),
+                format: "md".to_string(),
+                output: Some("notes.md".into()),
+            }))
+        );
+        assert_eq!(
+            parse(&args("convert story.txt --to epub")),
+            Some(Ok(Command::Convert {
+                input: "story.txt".into(),
+                output: None
+            }))
+        );
+        assert_eq!(
+            parse(&args("cover abc --output out.png")),
+            Some(Ok(Command::Cover {
+                book: "abc".to_string(),
+                output: "out.png".into()
+            }))
+        );
+    }
+
+    #[test]
+    fn rejects_bad_usage() {
+        assert!(matches!(parse(&args("import")), Some(Err(_))));
+        assert!(matches!(parse(&args("cover abc")), Some(Err(_))));
+        assert!(matches!(
+            parse(&args("convert a.txt --to pdf")),
+            Some(Err(_))
+        ));
+        assert!(matches!(parse(&args("list --verbose")), Some(Err(_))));
+        assert!(matches!(
+            parse(&args("export-annotations abc -o")),
+            Some(Err(_))
+        ));
+    }
+}
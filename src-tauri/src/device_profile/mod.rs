@@ -0,0 +1,344 @@
+//! Display profiles for e-ink and regular devices.
+//!
+//! The built-in registry in `profiles.json` maps manufacturer, model and system
+//! property rules to a profile; the first profile with a matching rule wins. Users can
+//! add profiles of their own in `device-profiles.json` in the app config dir, which
+//! take precedence over built-in ones with the same id, and can pick a profile by hand
+//! when detection gets their device wrong.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tauri::State;
+
+const BUILTIN_PROFILES: &str = include_str!("profiles.json");
+/// Extra profiles supplied by the user.
+const USER_PROFILES_FILE: &str = "device-profiles.json";
+/// The profile picked by the user, if any.
+const OVERRIDE_FILE: &str = "device-profile.json";
+const DEFAULT_PROFILE: &str = "default";
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Display {
+    Lcd,
+    Grayscale,
+    /// Color filter e-ink such as Kaleido.
+    Color,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum PageTurnAnimation {
+    Enabled,
+    Disabled,
+}
+
+/// A rule matches when every field it sets matches.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+struct Rule {
+    /// Substrings of the manufacturer or brand, any of which matches.
+    #[serde(default)]
+    manufacturer: Vec<String>,
+    /// Substrings of the model or device name, any of which matches.
+    #[serde(default)]
+    model: Vec<String>,
+    /// A system property that must be set,
+    property: Option<String>,
+    /// to one of these values, or to anything when empty.
+    #[serde(default)]
+    values: Vec<String>,
+}
+
+impl Rule {
+    fn matches(&self, device: &DeviceInfo) -> bool {
+        let any = |patterns: &[String], fields: [&str; 2]| {
+            patterns.iter().any(|pattern| {
+                let pattern = pattern.to_lowercase();
+                fields.iter().any(|field| field.contains(&pattern))
+            })
+        };
+        let property = self.property.as_ref().map(|name| {
+            device.properties.get(name).is_some_and(|value| {
+                self.values.is_empty() || self.values.iter().any(|v| v.eq_ignore_ascii_case(value))
+            })
+        });
+        let checks = [
+            (!self.manufacturer.is_empty())
+                .then(|| any(&self.manufacturer, [&device.manufacturer, &device.brand])),
+            (!self.model.is_empty()).then(|| any(&self.model, [&device.model, &device.device])),
+            property,
+        ];
+        checks.iter().any(Option::is_some) && checks.iter().flatten().all(|matched| *matched)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeviceProfile {
+    pub id: String,
+    pub name: String,
+    pub display: Display,
+    pub page_turn_animation: PageTurnAnimation,
+    /// CSS `contrast()` factor for content, 1 for none.
+    pub contrast_boost: f32,
+    #[serde(default, skip_serializing)]
+    rules: Vec<Rule>,
+}
+
+impl DeviceProfile {
+    pub fn is_eink(&self) -> bool {
+        self.display != Display::Lcd
+    }
+}
+
+#[derive(Deserialize)]
+struct ProfileFile {
+    profiles: Vec<DeviceProfile>,
+}
+
+/// What detection goes by, lowercased.
+#[derive(Debug, Default)]
+pub struct DeviceInfo {
+    pub manufacturer: String,
+    pub brand: String,
+    pub model: String,
+    pub device: String,
+    /// The system properties the rules ask about, when set.
+    pub properties: HashMap<String, String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Registry {
+    profiles: Vec<DeviceProfile>,
+}
+
+impl Registry {
+    pub fn builtin() -> Self {
+        let file: ProfileFile =
+            serde_json::from_str(BUILTIN_PROFILES).expect("built-in device profiles are valid");
+        Self {
+            profiles: file.profiles,
+        }
+    }
+
+    /// The built-in profiles, preceded by the user's own from `path`.
+    pub fn load(path: &Path) -> Self {
+        let mut registry = Self::builtin();
+        let user = match std::fs::read_to_string(path) {
+            Ok(json) => match serde_json::from_str::<ProfileFile>(&json) {
+                Ok(file) => file.profiles,
+                Err(e) => {
+                    log::error!("Ignoring invalid {}: {e}", path.display());
+                    Vec::new()
+                }
+            },
+            Err(_) => Vec::new(),
+        };
+        registry
+            .profiles
+            .retain(|builtin| !user.iter().any(|profile| profile.id == builtin.id));
+        registry.profiles.splice(0..0, user);
+        registry
+    }
+
+    /// The system properties any rule looks at.
+    pub fn properties(&self) -> Vec<&str> {
+        let mut names = self
+            .profiles
+            .iter()
+            .flat_map(|profile| &profile.rules)
+            .filter_map(|rule| rule.property.as_deref())
+            .collect::<Vec<_>>();
+        names.sort_unstable();
+        names.dedup();
+        names
+    }
+
+    pub fn get(&self, id: &str) -> Option<&DeviceProfile> {
+        self.profiles.iter().find(|profile| profile.id == id)
+    }
+
+    pub fn detect(&self, device: &DeviceInfo) -> DeviceProfile {
+        self.profiles
+            .iter()
+            .find(|profile| profile.rules.iter().any(|rule| rule.matches(device)))
+            .or_else(|| self.get(DEFAULT_PROFILE))
+            .cloned()
+            .unwrap_or_else(|| Self::builtin().get(DEFAULT_PROFILE).cloned().unwrap())
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Override {
+    profile: Option<String>,
+}
+
+/// The registry and the profile in effect, managed as app state.
+pub struct DeviceProfiles {
+    registry: Registry,
+    detected: DeviceProfile,
+    overridden: Mutex<Option<String>>,
+    override_file: PathBuf,
+}
+
+impl DeviceProfiles {
+    /// Reads the user's profiles and choice from `config_dir`.
+    pub fn load(config_dir: &Path) -> Self {
+        let registry = Registry::load(&config_dir.join(USER_PROFILES_FILE));
+        let detected = registry.detect(&device_info(&registry.properties()));
+        let override_file = config_dir.join(OVERRIDE_FILE);
+        let overridden = std::fs::read_to_string(&override_file)
+            .ok()
+            .and_then(|json| serde_json::from_str::<Override>(&json).ok())
+            .and_then(|settings| settings.profile)
+            .filter(|id| registry.get(id).is_some());
+        log::info!("Detected device profile: {}", detected.id);
+        Self {
+            registry,
+            detected,
+            overridden: Mutex::new(overridden),
+            override_file,
+        }
+    }
+
+    pub fn active(&self) -> DeviceProfile {
+        let overridden = self.overridden.lock().unwrap();
+        overridden
+            .as_deref()
+            .and_then(|id| self.registry.get(id))
+            .unwrap_or(&self.detected)
+            .clone()
+    }
+
+    fn set_override(&self, id: Option<String>) -> Result<(), String> {
+        if let Some(id) = &id {
+            if self.registry.get(id).is_none() {
+                return Err(format!("Unknown device profile {id:?}"));
+            }
+        }
+        let json = serde_json::to_string_pretty(&Override {
+            profile: id.clone(),
+        })
+        .map_err(|e| e.to_string())?;
+        if let Some(dir) = self.override_file.parent() {
+            std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+        }
+        std::fs::write(&self.override_file, json)
+            .map_err(|e| format!("Failed to save the device profile: {e}"))?;
+        *self.overridden.lock().unwrap() = id;
+        Ok(())
+    }
+
+    fn state(&self) -> DeviceProfileState {
+        DeviceProfileState {
+            active: self.active(),
+            detected: self.detected.clone(),
+            overridden: self.overridden.lock().unwrap().is_some(),
+            profiles: self.registry.profiles.clone(),
+        }
+    }
+}
+
+#[cfg(target_os = "android")]
+fn device_info(properties: &[&str]) -> DeviceInfo {
+    crate::android::device_info(properties)
+}
+
+/// Detection relies on Android system properties; elsewhere the profile has to be
+/// picked by hand.
+#[cfg(not(target_os = "android"))]
+fn device_info(_properties: &[&str]) -> DeviceInfo {
+    DeviceInfo::default()
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeviceProfileState {
+    pub active: DeviceProfile,
+    pub detected: DeviceProfile,
+    /// Whether `active` was picked by the user rather than detected.
+    pub overridden: bool,
+    /// Every profile the user can pick from.
+    pub profiles: Vec<DeviceProfile>,
+}
+
+#[tauri::command]
+pub fn get_device_profile(profiles: State<'_, DeviceProfiles>) -> DeviceProfileState {
+    profiles.state()
+}
+
+/// Uses the profile `id` from now on, or the detected one again when `id` is `null`.
+#[tauri::command]
+pub fn set_device_profile(
+    profiles: State<'_, DeviceProfiles>,
+    id: Option<String>,
+) -> Result<DeviceProfileState, String> {
+    profiles.set_override(id)?;
+    Ok(profiles.state())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn device(manufacturer: &str, model: &str) -> DeviceInfo {
+        DeviceInfo {
+            manufacturer: manufacturer.to_string(),
+            brand: manufacturer.to_string(),
+            model: model.to_string(),
+            device: model.to_string(),
+            properties: HashMap::new(),
+        }
+    }
+
+    #[test]
+    fn detects_devices_from_the_builtin_registry() {
+        let registry = Registry::builtin();
+        let detect = |info: DeviceInfo| registry.detect(&info).id;
+        assert_eq!(detect(device("onyx", "nova air c")), "eink-color");
+        assert_eq!(detect(device("onyx", "note air2 plus")), "boox");
+        assert_eq!(detect(device("hisense", "a7cc")), "eink-color");
+        assert_eq!(detect(device("hisense", "a9")), "eink-phone");
+        assert_eq!(detect(device("hisense", "infinity h50")), "default");
+        assert_eq!(detect(device("xiaomi", "inkpalm 5")), "eink-phone");
+        assert_eq!(detect(device("rakuten kobo", "clara hd")), "eink-reader");
+        assert_eq!(detect(device("samsung", "sm-s918b")), "default");
+        assert!(!registry.get(DEFAULT_PROFILE).unwrap().is_eink());
+
+        let mut generic = device("unknown", "reader");
+        generic
+            .properties
+            .insert("ro.eink.support".to_string(), "TRUE".to_string());
+        assert_eq!(detect(generic), "eink");
+        assert_eq!(
+            registry.properties(),
+            ["ro.eink.support", "ro.onyx.devicename"]
+        );
+    }
+
+    #[test]
+    fn user_profiles_come_first() {
+        let path =
+            std::env::temp_dir().join(format!("device-profiles-{}.json", std::process::id()));
+        let json = r#"{"profiles": [{
+            "id": "boox", "name": "My BOOX", "display": "color",
+            "pageTurnAnimation": "enabled", "contrastBoost": 1.5,
+            "rules": [{ "manufacturer": ["onyx"] }]
+        }]}"#;
+        std::fs::write(&path, json).unwrap();
+        let registry = Registry::load(&path);
+        std::fs::remove_file(&path).unwrap();
+
+        let profile = registry.detect(&device("onyx", "nova air c"));
+        assert_eq!(profile.name, "My BOOX");
+        assert_eq!(profile.display, Display::Color);
+        assert_eq!(profile.contrast_boost, 1.5);
+        assert_eq!(
+            registry.profiles.iter().filter(|p| p.id == "boox").count(),
+            1
+        );
+    }
+}
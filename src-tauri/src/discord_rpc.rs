@@ -1,64 +1,277 @@
+//! Discord Rich Presence for the book being read.
+//!
+//! Talking to Discord blocks, sometimes for seconds when it is not running, so the IPC
+//! client lives on its own thread. Commands only queue a message for it; the worker
+//! rate-limits updates, reconnects with backoff and clears the presence when the
+//! reader goes idle.
+
 #[cfg(any(target_os = "macos", target_os = "windows", target_os = "linux"))]
 use discord_rich_presence::{activity, DiscordIpc, DiscordIpcClient};
 use serde::Deserialize;
-use std::sync::{Arc, Mutex};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::time::{Duration, Instant};
 use tauri::State;
 
 const DISCORD_APP_ID: &str = "1462683110612144348";
-const MAX_TITLE_LENGTH: usize = 128;
-const MAX_AUTHOR_LENGTH: usize = 128;
+/// Discord's limit for the text fields of an activity, in bytes.
+const MAX_TEXT_LENGTH: usize = 128;
+const BOOK_ICON: &str = "book_icon";
+
+/// Presence is cleared when no update has come in for this long, e.g. when the reader
+/// has walked away from an open book.
+const IDLE_TIMEOUT: Duration = Duration::from_secs(10 * 60);
+/// Discord accepts five activity updates per 20 seconds; page turns come faster.
+const MIN_UPDATE_INTERVAL: Duration = Duration::from_secs(4);
+const MIN_RECONNECT_DELAY: Duration = Duration::from_secs(5);
+const MAX_RECONNECT_DELAY: Duration = Duration::from_secs(5 * 60);
+/// A restarted Discord forgets the presence, and a dead connection only shows when
+/// written to, so the presence is sent again this often even while no update comes in.
+const REFRESH_INTERVAL: Duration = Duration::from_secs(60);
+
+enum Command {
+    Update(Box<BookPresenceData>),
+    Clear,
+}
 
-#[derive(Debug)]
+/// Handle to the worker thread, managed as app state.
 pub struct DiscordRpcClient {
-    client: Option<DiscordIpcClient>,
-    current_book_hash: Option<String>,
+    commands: Sender<Command>,
 }
 
 impl DiscordRpcClient {
-    pub fn new() -> Self {
-        DiscordRpcClient {
+    pub fn spawn() -> Self {
+        let (sender, receiver) = mpsc::channel();
+        let spawned = std::thread::Builder::new()
+            .name("discord-rpc".to_string())
+            .spawn(move || Worker::default().run(receiver));
+        if let Err(e) = spawned {
+            log::error!("Failed to start the Discord worker: {e}");
+        }
+        Self { commands: sender }
+    }
+
+    fn send(&self, command: Command) {
+        if self.commands.send(command).is_err() {
+            log::debug!("Discord worker is not running");
+        }
+    }
+}
+
+/// Owns the IPC connection; only ever touched from the worker thread.
+struct Worker {
+    client: Option<DiscordIpcClient>,
+    /// The presence last asked for, shown again after reconnecting or when the reader
+    /// comes back from idle.
+    presence: Option<BookPresenceData>,
+    last_update: Instant,
+    idle: bool,
+    /// When the presence is next due to be sent: soon after an update, or to refresh it.
+    flush_at: Option<Instant>,
+    last_sent: Option<Instant>,
+    next_connect: Instant,
+    reconnect_delay: Duration,
+}
+
+impl Default for Worker {
+    fn default() -> Self {
+        Self {
             client: None,
-            current_book_hash: None,
+            presence: None,
+            last_update: Instant::now(),
+            idle: false,
+            flush_at: None,
+            last_sent: None,
+            next_connect: Instant::now(),
+            reconnect_delay: MIN_RECONNECT_DELAY,
         }
     }
+}
 
-    fn ensure_connected(&mut self) -> Result<(), String> {
-        if self.client.is_some() {
-            return Ok(());
+impl Worker {
+    fn run(mut self, commands: Receiver<Command>) {
+        loop {
+            let received = match self.deadline() {
+                Some(deadline) => {
+                    commands.recv_timeout(deadline.saturating_duration_since(Instant::now()))
+                }
+                None => commands.recv().map_err(|_| RecvTimeoutError::Disconnected),
+            };
+            match received {
+                Ok(Command::Update(presence)) => self.update(*presence),
+                Ok(Command::Clear) => {
+                    self.presence = None;
+                    self.flush_at = None;
+                    self.clear();
+                }
+                Err(RecvTimeoutError::Timeout) => {}
+                Err(RecvTimeoutError::Disconnected) => break,
+            }
+            self.tick();
+        }
+        self.disconnect();
+    }
+
+    /// Keeps the session of the book already shown, so that the elapsed time only
+    /// starts over when another book is opened or the reader comes back from idle.
+    fn update(&mut self, mut presence: BookPresenceData) {
+        let now = Instant::now();
+        if let Some(shown) = &self.presence {
+            if shown.book_hash == presence.book_hash && !self.idle {
+                presence.session_start = shown.session_start.min(presence.session_start);
+            }
+        }
+        self.presence = Some(presence);
+        self.last_update = now;
+        self.idle = false;
+        let earliest = self
+            .last_sent
+            .map_or(now, |sent| sent + MIN_UPDATE_INTERVAL)
+            .max(now);
+        self.flush_at = Some(self.flush_at.map_or(earliest, |at| at.min(earliest)));
+    }
+
+    /// When the worker next has something to do without a new command.
+    fn deadline(&self) -> Option<Instant> {
+        if self.presence.is_none() || self.idle {
+            return None;
         }
+        let idle_at = self.last_update + IDLE_TIMEOUT;
+        let flush_at = match self.client {
+            Some(_) => self.flush_at,
+            None => self.flush_at.map(|at| at.max(self.next_connect)),
+        };
+        Some(flush_at.map_or(idle_at, |at| at.min(idle_at)))
+    }
 
+    fn tick(&mut self) {
+        if self.presence.is_none() || self.idle {
+            return;
+        }
+        let now = Instant::now();
+        if now >= self.last_update + IDLE_TIMEOUT {
+            log::info!("Reader is idle, clearing Discord presence");
+            self.idle = true;
+            self.flush_at = None;
+            self.clear();
+            return;
+        }
+        if self.flush_at.is_some_and(|at| now >= at) && self.connect() {
+            self.show();
+        }
+    }
+
+    /// Connects unless a recent attempt failed, backing off while Discord is away.
+    fn connect(&mut self) -> bool {
+        if self.client.is_some() {
+            return true;
+        }
+        if Instant::now() < self.next_connect {
+            return false;
+        }
         let mut client = DiscordIpcClient::new(DISCORD_APP_ID);
         match client.connect() {
             Ok(_) => {
                 log::info!("Successfully connected to Discord");
                 self.client = Some(client);
-                Ok(())
+                self.reconnect_delay = MIN_RECONNECT_DELAY;
+                true
             }
             Err(e) => {
-                log::warn!("Failed to connect to Discord: {}", e);
-                Err(format!("Failed to connect to Discord: {}", e))
+                log::debug!("Discord not available: {e}");
+                self.next_connect = Instant::now() + self.reconnect_delay;
+                self.reconnect_delay = (self.reconnect_delay * 2).min(MAX_RECONNECT_DELAY);
+                false
             }
         }
     }
 
+    /// Drops the connection; the presence is sent again once it is back.
     fn disconnect(&mut self) {
         if let Some(mut client) = self.client.take() {
             let _ = client.close();
             log::debug!("Disconnected from Discord");
         }
-        self.current_book_hash = None;
+        self.next_connect = Instant::now() + self.reconnect_delay;
+        if self.presence.is_some() && !self.idle {
+            self.flush_at = Some(Instant::now());
+        }
     }
 
-    fn truncate_string(s: &str, max_len: usize) -> String {
-        if s.len() <= max_len {
-            s.to_string()
-        } else {
-            format!("{}...", &s[..max_len - 3])
+    fn show(&mut self) {
+        let (Some(presence), Some(client)) = (&self.presence, self.client.as_mut()) else {
+            return;
+        };
+        let text = PresenceText::new(presence);
+        let mut activity_builder = activity::Activity::new()
+            .details(&text.details)
+            .timestamps(activity::Timestamps::new().start(presence.session_start / 1000))
+            .assets(
+                activity::Assets::new()
+                    .large_image(&text.large_image)
+                    .large_text(&text.large_text),
+            )
+            .buttons(vec![activity::Button::new(
+                "Read on HackXIndia26",
+                "https://web.HackXIndia26.com",
+            )]);
+        if let Some(state) = &text.state {
+            activity_builder = activity_builder.state(state);
         }
+        match client.set_activity(activity_builder) {
+            Ok(_) => {
+                log::debug!("Successfully updated Discord presence");
+                let now = Instant::now();
+                self.flush_at = Some(now + REFRESH_INTERVAL);
+                self.last_sent = Some(now);
+            }
+            Err(e) => {
+                log::warn!("Failed to update Discord activity: {e}");
+                self.disconnect();
+            }
+        }
+    }
+
+    fn clear(&mut self) {
+        let Some(client) = self.client.as_mut() else {
+            return;
+        };
+        match client.clear_activity() {
+            Ok(_) => log::info!("Successfully cleared Discord presence"),
+            Err(e) => {
+                log::warn!("Failed to clear Discord activity: {e}");
+                self.disconnect();
+            }
+        }
+    }
+}
+
+/// Shortens `s` to at most `max_len` bytes without splitting a character.
+fn truncate(s: &str, max_len: usize) -> String {
+    const ELLIPSIS: &str = "...";
+    if s.len() <= max_len {
+        return s.to_string();
+    }
+    let mut end = max_len - ELLIPSIS.len();
+    while !s.is_char_boundary(end) {
+        end -= 1;
     }
+    format!("{}{ELLIPSIS}", s[..end].trim_end())
+}
+
+/// How much of the book is shown to other Discord users.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum PresencePrivacy {
+    /// Title, author, chapter and cover.
+    ShowTitle,
+    /// Only the genre, if known, and the progress.
+    ShowGenre,
+    /// Only "Reading a book", for callers that don't pick a level.
+    #[default]
+    Hidden,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct BookPresenceData {
     book_hash: String,
@@ -66,107 +279,92 @@ pub struct BookPresenceData {
     author: Option<String>,
     cover_url: Option<String>,
     session_start: i64,
+    genre: Option<String>,
+    chapter: Option<String>,
+    /// Percentage read, from 0 to 100.
+    progress: Option<f64>,
+    page: Option<u32>,
+    total_pages: Option<u32>,
+    #[serde(default)]
+    privacy: PresencePrivacy,
 }
 
-#[cfg(any(target_os = "macos", target_os = "windows", target_os = "linux"))]
-#[tauri::command]
-pub async fn update_book_presence(
-    state: State<'_, Arc<Mutex<DiscordRpcClient>>>,
-    presence: BookPresenceData,
-) -> Result<(), String> {
-    let mut client = state
-        .lock()
-        .map_err(|e| format!("Mutex lock error: {}", e))?;
-
-    if let Err(e) = client.ensure_connected() {
-        log::debug!("Discord not available: {}", e);
-        return Ok(());
-    }
-
-    let BookPresenceData {
-        book_hash,
-        title,
-        author,
-        cover_url,
-        session_start,
-    } = presence;
-
-    // Truncate title and author to avoid Discord API limits
-    let truncated_title = DiscordRpcClient::truncate_string(&title, MAX_TITLE_LENGTH);
-    let state_text = if let Some(ref author_name) = author {
-        let truncated_author = DiscordRpcClient::truncate_string(author_name, MAX_AUTHOR_LENGTH);
-        format!("by {}", truncated_author)
-    } else {
-        String::new()
-    };
-
-    let mut activity_builder = activity::Activity::new().details(&truncated_title);
-
-    if !state_text.is_empty() {
-        activity_builder = activity_builder.state(&state_text);
-    }
-
-    activity_builder =
-        activity_builder.timestamps(activity::Timestamps::new().start(session_start / 1000));
-
-    let large_image = cover_url
-        .as_deref()
-        .filter(|url| url.starts_with("https://"))
-        .unwrap_or("book_icon");
-    let assets_builder = activity::Assets::new()
-        .large_image(large_image)
-        .large_text(&truncated_title);
-
-    activity_builder = activity_builder.assets(assets_builder);
-
-    let button = activity::Button::new("Read on HackXIndia26", "https://web.HackXIndia26.com");
-    activity_builder = activity_builder.buttons(vec![button]);
+/// The text of an activity, following the privacy level.
+#[derive(Debug, PartialEq)]
+struct PresenceText {
+    details: String,
+    state: Option<String>,
+    large_image: String,
+    large_text: String,
+}
 
-    if let Some(ref mut discord_client) = client.client {
-        match discord_client.set_activity(activity_builder) {
-            Ok(_) => {
-                log::info!("Successfully updated Discord presence");
-                client.current_book_hash = Some(book_hash);
-                Ok(())
+impl PresenceText {
+    fn new(presence: &BookPresenceData) -> Self {
+        let progress = match (presence.page, presence.total_pages, presence.progress) {
+            (Some(page), Some(total), _) if total > 0 => Some(format!("Page {page} of {total}")),
+            (_, _, Some(progress)) => Some(format!("{:.0}%", progress.clamp(0.0, 100.0))),
+            _ => None,
+        };
+        let generic = |details: String, state: Option<String>| PresenceText {
+            large_text: details.clone(),
+            details,
+            state,
+            large_image: BOOK_ICON.to_string(),
+        };
+        match presence.privacy {
+            PresencePrivacy::Hidden => generic("Reading a book".to_string(), None),
+            PresencePrivacy::ShowGenre => {
+                let details = match presence.genre.as_deref().map(str::trim) {
+                    Some(genre) if !genre.is_empty() => format!("Reading {genre}"),
+                    _ => "Reading a book".to_string(),
+                };
+                generic(truncate(&details, MAX_TEXT_LENGTH), progress)
             }
-            Err(e) => {
-                log::error!("Failed to update Discord activity: {}", e);
-                client.disconnect();
-                Err(format!("Failed to update Discord activity: {}", e))
+            PresencePrivacy::ShowTitle => {
+                let title = truncate(&presence.title, MAX_TEXT_LENGTH);
+                let author = presence.author.as_deref();
+                let reading = [presence.chapter.clone(), progress]
+                    .into_iter()
+                    .flatten()
+                    .filter(|part| !part.trim().is_empty())
+                    .collect::<Vec<_>>();
+                let state = match reading.is_empty() {
+                    true => author.map(|author| truncate(&format!("by {author}"), MAX_TEXT_LENGTH)),
+                    false => Some(truncate(&reading.join(" · "), MAX_TEXT_LENGTH)),
+                };
+                PresenceText {
+                    large_image: presence
+                        .cover_url
+                        .clone()
+                        .filter(|url| url.starts_with("https://"))
+                        .unwrap_or_else(|| BOOK_ICON.to_string()),
+                    large_text: match author {
+                        Some(author) => truncate(&format!("{title} by {author}"), MAX_TEXT_LENGTH),
+                        None => title.clone(),
+                    },
+                    details: title,
+                    state,
+                }
             }
         }
-    } else {
-        Err("Discord client not initialized".to_string())
     }
 }
 
 #[cfg(any(target_os = "macos", target_os = "windows", target_os = "linux"))]
 #[tauri::command]
-pub async fn clear_book_presence(
-    state: State<'_, Arc<Mutex<DiscordRpcClient>>>,
+pub async fn update_book_presence(
+    state: State<'_, DiscordRpcClient>,
+    presence: BookPresenceData,
 ) -> Result<(), String> {
-    let mut client = state
-        .lock()
-        .map_err(|e| format!("Mutex lock error: {}", e))?;
+    state.send(Command::Update(Box::new(presence)));
+    Ok(())
+}
 
-    if let Some(ref mut discord_client) = client.client {
-        match discord_client.clear_activity() {
-            Ok(_) => {
-                log::info!("Successfully cleared Discord presence");
-                client.current_book_hash = None;
-                Ok(())
-            }
-            Err(e) => {
-                log::error!("Failed to clear Discord activity: {}", e);
-                client.disconnect();
-                Ok(())
-            }
-        }
-    } else {
-        log::debug!("No Discord client to clear");
-        client.current_book_hash = None;
-        Ok(())
-    }
+#[cfg(any(target_os = "macos", target_os = "windows", target_os = "linux"))]
+#[tauri::command]
+pub async fn clear_book_presence(state: State<'_, DiscordRpcClient>) -> Result<(), String> {
+    state.send(Command::Clear);
+    Ok(())
 }
 
 #[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "linux")))]
@@ -180,3 +378,103 @@ pub async fn update_book_presence(_presence: BookPresenceData) -> Result<(), Str
 pub async fn clear_book_presence() -> Result<(), String> {
     Ok(()) // No-op on non-desktop platforms
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn presence(privacy: PresencePrivacy) -> BookPresenceData {
+        BookPresenceData {
+            book_hash: "0123456789abcdef0123456789abcdef".to_string(),
+            title: "Dune".to_string(),
+            author: Some("Frank Herbert".to_string()),
+            cover_url: Some("https://example.com/cover.png".to_string()),
+            session_start: 0,
+            genre: Some("Science Fiction".to_string()),
+            chapter: Some("Book I: Dune".to_string()),
+            progress: Some(42.4),
+            page: None,
+            total_pages: None,
+            privacy,
+        }
+    }
+
+    #[test]
+    fn shows_as_much_as_the_privacy_level_allows() {
+        let text = PresenceText::new(&presence(PresencePrivacy::ShowTitle));
+        assert_eq!(text.details, "Dune");
+        assert_eq!(text.state.as_deref(), Some("Book I: Dune · 42%"));
+        assert_eq!(text.large_image, "https://example.com/cover.png");
+        assert_eq!(text.large_text, "Dune by Frank Herbert");
+
+        let text = PresenceText::new(&presence(PresencePrivacy::ShowGenre));
+        assert_eq!(text.details, "Reading Science Fiction");
+        assert_eq!(text.state.as_deref(), Some("42%"));
+        assert_eq!(text.large_image, BOOK_ICON);
+
+        let text = PresenceText::new(&presence(PresencePrivacy::Hidden));
+        assert_eq!(text.details, "Reading a book");
+        assert_eq!(text.state, None);
+        assert_eq!(text.large_image, BOOK_ICON);
+    }
+
+    #[test]
+    fn keeps_the_session_until_the_book_changes() {
+        let mut worker = Worker::default();
+        let update = |worker: &mut Worker, hash: &str, session_start| {
+            let mut data = presence(PresencePrivacy::ShowTitle);
+            data.book_hash = hash.to_string();
+            data.session_start = session_start;
+            worker.update(data);
+            worker.presence.as_ref().unwrap().session_start
+        };
+        assert_eq!(update(&mut worker, "a", 1_000), 1_000);
+        assert_eq!(update(&mut worker, "a", 5_000), 1_000);
+        assert_eq!(update(&mut worker, "b", 9_000), 9_000);
+        worker.idle = true;
+        assert_eq!(update(&mut worker, "b", 20_000), 20_000);
+    }
+
+    #[test]
+    fn refreshes_without_holding_up_updates() {
+        let mut worker = Worker {
+            flush_at: Some(Instant::now() + REFRESH_INTERVAL),
+            ..Worker::default()
+        };
+        worker.update(presence(PresencePrivacy::ShowTitle));
+        assert!(worker.flush_at.unwrap() <= Instant::now());
+
+        worker.last_sent = Some(Instant::now());
+        worker.flush_at = Some(Instant::now() + REFRESH_INTERVAL);
+        worker.update(presence(PresencePrivacy::ShowTitle));
+        let flush_at = worker.flush_at.unwrap();
+        assert!(flush_at > Instant::now() && flush_at <= Instant::now() + MIN_UPDATE_INTERVAL);
+    }
+
+    #[test]
+    fn truncates_on_character_boundaries() {
+        assert_eq!(truncate("Dune", 10), "Dune");
+        let hindi = "गोदान ".repeat(20);
+        let short = truncate(&hindi, MAX_TEXT_LENGTH);
+        assert!(short.len() <= MAX_TEXT_LENGTH && short.ends_with("..."));
+        assert!(hindi.starts_with(short.trim_end_matches("...")));
+        let chinese = truncate(&"红楼梦".repeat(30), MAX_TEXT_LENGTH);
+        assert!(chinese.len() <= MAX_TEXT_LENGTH && chinese.starts_with("红楼梦"));
+    }
+
+    #[test]
+    fn prefers_pages_and_falls_back_to_the_author() {
+        let mut data = presence(PresencePrivacy::ShowTitle);
+        data.chapter = None;
+        data.page = Some(12);
+        data.total_pages = Some(300);
+        assert_eq!(
+            PresenceText::new(&data).state.as_deref(),
+            Some("Page 12 of 300")
+        );
+        data.page = None;
+        data.progress = None;
+        let text = PresenceText::new(&data);
+        assert_eq!(text.state.as_deref(), Some("by Frank Herbert"));
+    }
+}
@@ -0,0 +1,102 @@
+//! Environment variables the webview may read.
+//!
+//! Only the names listed under `plugins.environment.allow` in `tauri.conf.json` are
+//! handed out, so tokens and other secrets in the user's environment stay in the
+//! process. What the app itself needs is exposed through typed helpers instead.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::path::PathBuf;
+use tauri::{Config, State};
+
+/// The key of the allowlist under `plugins` in `tauri.conf.json`.
+const CONFIG_KEY: &str = "environment";
+
+#[derive(Debug, Default, Deserialize)]
+struct EnvironmentConfig {
+    #[serde(default)]
+    allow: BTreeSet<String>,
+}
+
+/// The allowlist, read once from the app config.
+#[derive(Debug, Default)]
+pub struct Environment {
+    allow: BTreeSet<String>,
+}
+
+impl Environment {
+    pub fn from_config(config: &Config) -> Self {
+        let allow = match config.plugins.0.get(CONFIG_KEY) {
+            Some(value) => match EnvironmentConfig::deserialize(value) {
+                Ok(config) => config.allow,
+                Err(e) => {
+                    log::error!("Invalid plugins.{CONFIG_KEY} config: {e}");
+                    BTreeSet::new()
+                }
+            },
+            None => BTreeSet::new(),
+        };
+        Self { allow }
+    }
+
+    /// The value of `name`, or an error if it is not allowlisted.
+    pub fn get(&self, name: &str) -> Result<Option<String>, String> {
+        if !self.allow.contains(name) {
+            return Err(format!("Environment variable {name} is not allowed"));
+        }
+        Ok(std::env::var(name).ok())
+    }
+}
+
+/// Where the running AppImage is, on Linux.
+pub fn app_image() -> Option<PathBuf> {
+    std::env::var_os("APPIMAGE")
+        .filter(|path| !path.is_empty())
+        .map(PathBuf::from)
+}
+
+pub fn executable_dir() -> Option<PathBuf> {
+    std::env::current_exe()
+        .ok()
+        .and_then(|path| path.parent().map(|p| p.to_path_buf()))
+}
+
+/// The environment facts the app itself relies on.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AppEnvironment {
+    pub app_image: Option<PathBuf>,
+    pub executable_dir: Option<PathBuf>,
+}
+
+#[tauri::command]
+pub fn get_environment_variable(
+    environment: State<'_, Environment>,
+    name: String,
+) -> Result<Option<String>, String> {
+    environment.get(&name)
+}
+
+#[tauri::command]
+pub fn get_app_environment() -> AppEnvironment {
+    AppEnvironment {
+        app_image: app_image(),
+        executable_dir: executable_dir(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hands_out_allowlisted_variables_only() {
+        let config = serde_json::json!({ "allow": ["PATH"] });
+        let environment = Environment {
+            allow: EnvironmentConfig::deserialize(&config).unwrap().allow,
+        };
+        assert_eq!(environment.get("PATH"), Ok(std::env::var("PATH").ok()));
+        assert!(environment.get("HOME").is_err());
+        assert!(Environment::default().get("PATH").is_err());
+    }
+}
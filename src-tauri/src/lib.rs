@@ -19,21 +19,33 @@ use tauri::TitleBarStyle;
 use std::path::PathBuf;
 use tauri::{AppHandle, Manager};
 use tauri_plugin_fs::FsExt;
+use tauri_plugin_log::{Target, TargetKind};
 
 #[cfg(desktop)]
-use tauri::{Listener, Url};
+use tauri::Url;
+mod annotations;
+#[cfg(desktop)]
+mod cli;
+mod deep_link;
+mod device_profile;
 mod dir_scanner;
 #[cfg(any(target_os = "macos", target_os = "windows", target_os = "linux"))]
 mod discord_rpc;
+mod environment;
 #[cfg(target_os = "macos")]
 mod macos;
+#[cfg(desktop)]
+mod oauth;
+mod portable;
+mod startup;
 mod transfer_file;
-use tauri::{command, Emitter, WebviewUrl, WebviewWindowBuilder, Window};
+#[cfg(desktop)]
+mod window_manager;
+use tauri::{Emitter, WebviewUrl, WebviewWindowBuilder};
 #[cfg(target_os = "android")]
 use tauri_plugin_native_bridge::register_select_directory_callback;
 #[cfg(target_os = "android")]
 use tauri_plugin_native_bridge::{NativeBridgeExt, OpenExternalUrlRequest};
-use tauri_plugin_oauth::start;
 #[cfg(not(target_os = "android"))]
 use tauri_plugin_opener::OpenerExt;
 use transfer_file::{download_file, upload_file};
@@ -84,6 +96,9 @@ fn get_files_from_argv(argv: Vec<String>) -> Vec<PathBuf> {
         }
         // handle `file://` path urls and skip other urls
         if let Ok(url) = Url::parse(maybe_file) {
+            if deep_link::is_deep_link(&url) {
+                continue;
+            }
             if let Ok(path) = url.to_file_path() {
                 files.push(path);
             } else {
@@ -96,46 +111,9 @@ fn get_files_from_argv(argv: Vec<String>) -> Vec<PathBuf> {
     files
 }
 
-#[cfg(desktop)]
-fn set_window_open_with_files(app: &AppHandle, files: Vec<PathBuf>) {
-    let files = files
-        .into_iter()
-        .map(|f| {
-            let file = f
-                .to_string_lossy()
-                .replace("\\", "\\\\")
-                .replace("\"", "\\\"");
-            format!("\"{file}\"",)
-        })
-        .collect::<Vec<_>>()
-        .join(",");
-    let window = app.get_webview_window("main").unwrap();
-    let script = format!("window.OPEN_WITH_FILES = [{files}];");
-    if let Err(e) = window.eval(&script) {
-        eprintln!("Failed to set open files variable: {e}");
-    }
-}
-
-#[command]
-async fn start_server(window: Window) -> Result<u16, String> {
-    start(move |url| {
-        // Because of the unprotected localhost port, you must verify the URL here.
-        // Preferebly send back only the token, or nothing at all if you can handle everything else in Rust.
-        let _ = window.emit("redirect_uri", url);
-    })
-    .map_err(|err| err.to_string())
-}
-
-#[tauri::command]
-fn get_environment_variable(name: &str) -> String {
-    std::env::var(String::from(name)).unwrap_or(String::from(""))
-}
-
 #[tauri::command]
 fn get_executable_dir() -> String {
-    std::env::current_exe()
-        .ok()
-        .and_then(|path| path.parent().map(|p| p.to_path_buf()))
+    environment::executable_dir()
         .map(|p| p.to_string_lossy().to_string())
         .unwrap_or_default()
 }
@@ -147,24 +125,171 @@ struct SingleInstancePayload {
     cwd: String,
 }
 
+/// A window with the app's chrome, used for the main window and the reader windows.
+fn window_builder<'a>(
+    app: &'a AppHandle,
+    label: &str,
+    url: WebviewUrl,
+) -> WebviewWindowBuilder<'a, tauri::Wry, AppHandle> {
+    let init_script = r#"
+            window.addEventListener('DOMContentLoaded', function() {
+                document.documentElement.classList.add('edge-to-edge');
+                const isTauriLocal = window.location.protocol === 'tauri:' ||
+                                    window.location.protocol === 'about:' ||
+                                    window.location.hostname === 'tauri.localhost';
+                const needsSafeArea = !isTauriLocal;
+                if (needsSafeArea && !document.getElementById('safe-area-style')) {
+                    const style = document.createElement('style');
+                    style.id = 'safe-area-style';
+                    style.textContent = `
+                        body {
+                            padding-top: env(safe-area-inset-top) !important;
+                            padding-bottom: env(safe-area-inset-bottom) !important;
+                            padding-left: env(safe-area-inset-left) !important;
+                            padding-right: env(safe-area-inset-right) !important;
+                        }
+                    `;
+                    document.head.appendChild(style);
+                }
+            });
+        "#;
+
+    let is_eink = app
+        .state::<device_profile::DeviceProfiles>()
+        .active()
+        .is_eink();
+    let app_handle = app.clone();
+    let win_builder = WebviewWindowBuilder::new(app, label, url)
+        .background_throttling(BackgroundThrottlingPolicy::Disabled)
+        .background_color(if is_eink {
+            tauri::window::Color(255, 255, 255, 255)
+        } else {
+            tauri::window::Color(50, 49, 48, 255)
+        })
+        .initialization_script(init_script)
+        .on_navigation(move |url| {
+            if url.scheme() == "alipays" || url.scheme() == "alipay" {
+                let url_str = url.as_str().to_string();
+                #[cfg(target_os = "android")]
+                {
+                    let handle = app_handle.clone();
+                    tauri::async_runtime::spawn(async move {
+                        match handle
+                            .native_bridge()
+                            .open_external_url(OpenExternalUrlRequest { url: url_str })
+                        {
+                            Ok(result) => println!("Result: {:?}", result),
+                            Err(e) => eprintln!("Error: {:?}", e),
+                        }
+                    });
+                }
+                #[cfg(not(target_os = "android"))]
+                {
+                    let _ = app_handle.opener().open_url(url_str, None::<&str>);
+                }
+                return false;
+            }
+            true
+        });
+
+    #[cfg(desktop)]
+    let win_builder = win_builder.inner_size(800.0, 600.0).resizable(true);
+
+    #[cfg(target_os = "macos")]
+    let win_builder = win_builder
+        .decorations(true)
+        .title_bar_style(TitleBarStyle::Overlay)
+        .title("");
+
+    #[cfg(all(not(target_os = "macos"), desktop))]
+    let win_builder = {
+        let mut builder = win_builder
+            .decorations(false)
+            .visible(false)
+            .shadow(true)
+            .title("HackXIndia26");
+
+        #[cfg(target_os = "windows")]
+        {
+            builder = builder.transparent(false);
+        }
+        #[cfg(target_os = "linux")]
+        {
+            builder = builder
+                .transparent(true)
+                .background_color(tauri::window::Color(0, 0, 0, 0));
+        }
+
+        builder
+    };
+
+    win_builder
+}
+
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
+    let mut context = tauri::generate_context!();
+
+    let portable = portable::PortablePaths::detect(&std::env::args().collect::<Vec<_>>());
+    if let Some(portable) = &portable {
+        if let Err(e) = portable.prepare() {
+            eprintln!("Failed to prepare the portable folder: {e}");
+        }
+    }
+    let database = annotations::Database::new(portable.as_ref());
+    database.preload(context.config_mut());
+    let native_tts = match &portable {
+        Some(portable) => tauri_plugin_native_tts::init_with_dirs(portable.tts_dirs()),
+        None => tauri_plugin_native_tts::init(),
+    };
+
+    #[cfg(desktop)]
+    if let Some(code) = cli::run(&context.config().identifier, portable.as_ref()) {
+        std::process::exit(code);
+    }
+
+    let log = tauri_plugin_log::Builder::new().level(log::LevelFilter::Info);
+    let log = match &portable {
+        Some(portable) => log.targets([
+            Target::new(TargetKind::Stdout),
+            Target::new(TargetKind::Folder {
+                path: portable.log_dir.clone(),
+                file_name: None,
+            }),
+        ]),
+        None => log,
+    };
+
     let builder = tauri::Builder::default()
-        .plugin(
-            tauri_plugin_log::Builder::new()
-                .level(log::LevelFilter::Info)
-                .build(),
-        )
+        .plugin(log.build())
         .plugin(tauri_plugin_websocket::init())
         .plugin(tauri_plugin_process::init())
-        .plugin(tauri_plugin_oauth::init())
         .invoke_handler(tauri::generate_handler![
-            start_server,
             download_file,
             upload_file,
-            get_environment_variable,
+            environment::get_environment_variable,
+            environment::get_app_environment,
             get_executable_dir,
             dir_scanner::read_dir,
+            annotations::commands::upsert_annotations,
+            annotations::commands::delete_annotations,
+            annotations::commands::set_annotation_tags,
+            annotations::commands::query_annotations,
+            annotations::commands::list_annotation_tags,
+            annotations::commands::get_annotation,
+            annotations::export::export_annotations,
+            startup::get_startup_state,
+            startup::frontend_ready,
+            device_profile::get_device_profile,
+            device_profile::set_device_profile,
+            #[cfg(desktop)]
+            oauth::authorize_oauth,
+            #[cfg(desktop)]
+            window_manager::open_reader_window,
+            #[cfg(desktop)]
+            window_manager::focus_book_window,
+            #[cfg(desktop)]
+            window_manager::set_window_books,
             #[cfg(target_os = "macos")]
             macos::safari_auth::auth_with_safari,
             #[cfg(target_os = "macos")]
@@ -185,20 +310,42 @@ pub fn run() {
         .plugin(tauri_plugin_dialog::init())
         .plugin(tauri_plugin_sharekit::init())
         .plugin(tauri_plugin_native_bridge::init())
-        .plugin(tauri_plugin_native_tts::init());
+        .plugin(native_tts)
+        .plugin(
+            tauri_plugin_sql::Builder::new()
+                .add_migrations(&database.url, annotations::migrations())
+                .build(),
+        );
 
     #[cfg(desktop)]
     let builder = builder.plugin(tauri_plugin_single_instance::init(|app, argv, cwd| {
-        let _ = app
-            .get_webview_window("main")
-            .expect("no main window")
-            .set_focus();
-        let files = get_files_from_argv(argv.clone());
-        if !files.is_empty() {
-            allow_file_in_scopes(app, files.clone());
-        }
-        app.emit("single-instance", SingleInstancePayload { args: argv, cwd })
-            .unwrap();
+        // Hashing the files reads them, which must not hold up the main thread
+        let app = app.clone();
+        tauri::async_runtime::spawn_blocking(move || {
+            let files = get_files_from_argv(argv.clone());
+            // Books that are open already only need their window brought to the front
+            let focused = files
+                .iter()
+                .filter(|file| {
+                    let hash = std::fs::File::open(file)
+                        .ok()
+                        .and_then(|mut file| cli::library::partial_md5(&mut file).ok());
+                    hash.is_some_and(|hash| window_manager::focus_book(&app, &hash).is_some())
+                })
+                .count();
+            if !files.is_empty() && focused == files.len() {
+                return;
+            }
+            window_manager::focus_main(&app);
+            if !files.is_empty() {
+                allow_file_in_scopes(&app, files.clone());
+            }
+            deep_link::route_args(&app, &argv);
+            let payload = SingleInstancePayload { args: argv, cwd };
+            if let Err(e) = app.emit("single-instance", payload) {
+                log::error!("Failed to emit single instance: {e}");
+            }
+        });
     }));
 
     let builder = builder.plugin(tauri_plugin_deep_link::init());
@@ -207,7 +354,15 @@ pub fn run() {
     let builder = builder.plugin(tauri_plugin_updater::Builder::new().build());
 
     #[cfg(desktop)]
-    let builder = builder.plugin(tauri_plugin_window_state::Builder::default().build());
+    let window_state = match &portable {
+        Some(portable) => tauri_plugin_window_state::Builder::default()
+            .with_filename(portable.window_state_file().to_string_lossy()),
+        None => tauri_plugin_window_state::Builder::default(),
+    };
+    #[cfg(desktop)]
+    let builder = builder
+        .plugin(window_state.build())
+        .on_window_event(window_manager::on_window_event);
 
     #[cfg(target_os = "macos")]
     let builder = builder.plugin(macos::traffic_light::init());
@@ -222,30 +377,33 @@ pub fn run() {
     let builder = builder.plugin(tauri_plugin_haptics::init());
 
     builder
-        .setup(|#[allow(unused_variables)] app| {
+        .setup(move |#[allow(unused_variables)] app| {
+            // Detect the display before building the window, which is tinted for e-ink
+            let config_dir = match &portable {
+                Some(portable) => portable.data_dir.clone(),
+                None => app.path().app_config_dir()?,
+            };
+            app.manage(device_profile::DeviceProfiles::load(&config_dir));
+            app.manage(database);
+            app.manage(startup::Startup::new(portable.clone()));
+            app.manage(environment::Environment::from_config(app.config()));
+            #[cfg(desktop)]
+            app.manage(window_manager::WindowManager::default());
+
             #[cfg(any(target_os = "macos", target_os = "windows", target_os = "linux"))]
-            {
-                use std::sync::{Arc, Mutex};
-                let discord_client = Arc::new(Mutex::new(discord_rpc::DiscordRpcClient::new()));
-                app.manage(discord_client);
-            }
+            app.manage(discord_rpc::DiscordRpcClient::spawn());
 
             #[cfg(desktop)]
             {
                 let files = get_files_from_argv(std::env::args().collect());
                 if !files.is_empty() {
-                    let app_handle = app.handle().clone();
-                    allow_file_in_scopes(&app_handle, files.clone());
-                    app.listen("window-ready", move |_| {
-                        println!("Window is ready, proceeding to handle files.");
-                        set_window_open_with_files(&app_handle, files.clone());
-                    });
+                    allow_file_in_scopes(app.handle(), files.clone());
+                    startup::open_files(app.handle(), files);
                 }
             }
 
-            #[cfg(desktop)]
-            {
-                allow_dir_in_scopes(app.handle(), &PathBuf::from(get_executable_dir()));
+            if let Some(portable) = &portable {
+                allow_dir_in_scopes(app.handle(), &portable.data_dir);
             }
 
             #[cfg(target_os = "android")]
@@ -254,31 +412,7 @@ pub fn run() {
             });
 
             #[cfg(desktop)]
-            {
-                app.handle().plugin(tauri_plugin_cli::init())?;
-
-                let app_handle = app.handle().clone();
-                app.listen("window-ready", move |_| {
-                    let webview = app_handle.get_webview_window("main").unwrap();
-                    webview
-                        .eval("window.__HackXIndia26_CLI_ACCESS = true;")
-                        .expect("Failed to set cli access config");
-
-                    #[cfg(target_os = "linux")]
-                    {
-                        let is_appimage = std::env::var("APPIMAGE").is_ok()
-                            || std::env::current_exe()
-                                .map(|path| path.to_string_lossy().contains("/tmp/.mount_"))
-                                .unwrap_or(false);
-
-                        let script =
-                            format!("window.__HackXIndia26_UPDATER_DISABLED = {};", !is_appimage);
-                        webview
-                            .eval(&script)
-                            .expect("Failed to set updater disabled config");
-                    }
-                });
-            }
+            app.handle().plugin(tauri_plugin_cli::init())?;
 
             #[cfg(any(target_os = "windows", target_os = "linux"))]
             {
@@ -286,122 +420,20 @@ pub fn run() {
                 let _ = app.deep_link().register_all();
             }
 
-            // Check for e-ink device on Android before building the window
-            #[cfg(target_os = "android")]
-            let is_eink = android::is_eink_device();
-            #[cfg(not(target_os = "android"))]
-            let is_eink = false;
-
-            let eink_script = if is_eink {
-                "window.__HackXIndia26_IS_EINK = true;"
-            } else {
-                ""
-            };
-
-            let init_script = format!(
-                r#"
-                    {eink_script}
-                    window.addEventListener('DOMContentLoaded', function() {{
-                        document.documentElement.classList.add('edge-to-edge');
-                        const isTauriLocal = window.location.protocol === 'tauri:' ||
-                                            window.location.protocol === 'about:' ||
-                                            window.location.hostname === 'tauri.localhost';
-                        const needsSafeArea = !isTauriLocal;
-                        if (needsSafeArea && !document.getElementById('safe-area-style')) {{
-                            const style = document.createElement('style');
-                            style.id = 'safe-area-style';
-                            style.textContent = `
-                                body {{
-                                    padding-top: env(safe-area-inset-top) !important;
-                                    padding-bottom: env(safe-area-inset-bottom) !important;
-                                    padding-left: env(safe-area-inset-left) !important;
-                                    padding-right: env(safe-area-inset-right) !important;
-                                }}
-                            `;
-                            document.head.appendChild(style);
-                        }}
-                    }});
-                "#,
-                eink_script = eink_script
-            );
-
-            let app_handle = app.handle().clone();
-            let win_builder = WebviewWindowBuilder::new(app, "main", WebviewUrl::default())
-                .background_throttling(BackgroundThrottlingPolicy::Disabled)
-                .background_color(if is_eink {
-                    tauri::window::Color(255, 255, 255, 255)
-                } else {
-                    tauri::window::Color(50, 49, 48, 255)
-                })
-                .initialization_script(&init_script)
-                .on_navigation(move |url| {
-                    if url.scheme() == "alipays" || url.scheme() == "alipay" {
-                        let url_str = url.as_str().to_string();
-                        #[cfg(target_os = "android")]
-                        {
-                            let handle = app_handle.clone();
-                            tauri::async_runtime::spawn(async move {
-                                match handle
-                                    .native_bridge()
-                                    .open_external_url(OpenExternalUrlRequest { url: url_str })
-                                {
-                                    Ok(result) => println!("Result: {:?}", result),
-                                    Err(e) => eprintln!("Error: {:?}", e),
-                                }
-                            });
-                        }
-                        #[cfg(not(target_os = "android"))]
-                        {
-                            let _ = app_handle.opener().open_url(url_str, None::<&str>);
-                        }
-                        return false;
-                    }
-                    true
-                });
+            deep_link::init(app.handle());
 
-            #[cfg(desktop)]
-            let win_builder = win_builder.inner_size(800.0, 600.0).resizable(true);
-
-            #[cfg(target_os = "macos")]
-            let win_builder = win_builder
-                .decorations(true)
-                .title_bar_style(TitleBarStyle::Overlay)
-                .title("");
-
-            #[cfg(all(not(target_os = "macos"), desktop))]
-            let win_builder = {
-                let mut builder = win_builder
-                    .decorations(false)
-                    .visible(false)
-                    .shadow(true)
-                    .title("HackXIndia26");
-
-                #[cfg(target_os = "windows")]
-                {
-                    builder = builder.transparent(false);
-                }
-                #[cfg(target_os = "linux")]
-                {
-                    builder = builder
-                        .transparent(true)
-                        .background_color(tauri::window::Color(0, 0, 0, 0));
-                }
-
-                builder
-            };
-
-            win_builder.build().unwrap();
+            window_builder(app.handle(), "main", WebviewUrl::default())
+                .build()
+                .unwrap();
             // let win = win_builder.build().unwrap();
             // win.open_devtools();
 
             #[cfg(target_os = "macos")]
             macos::menu::setup_macos_menu(app.handle())?;
 
-            app.handle().emit("window-ready", ()).unwrap();
-
             Ok(())
         })
-        .build(tauri::generate_context!())
+        .build(context)
         .expect("error while running tauri application")
         .run(
             #[allow(unused_variables)]
@@ -413,12 +445,8 @@ pub fn run() {
                         .filter_map(|url| url.to_file_path().ok())
                         .collect::<Vec<_>>();
 
-                    let app_handler_clone = app_handle.clone();
                     allow_file_in_scopes(app_handle, files.clone());
-                    app_handle.listen("window-ready", move |_| {
-                        println!("Window is ready, proceeding to handle files.");
-                        set_window_open_with_files(&app_handler_clone, files.clone());
-                    });
+                    startup::open_files(app_handle, files);
                 }
             },
         );
@@ -1,17 +1,10 @@
-use crate::allow_file_in_scopes;
+use crate::{allow_file_in_scopes, startup};
 use std::path::PathBuf;
 use tauri::menu::MenuEvent;
 use tauri::menu::{MenuItemBuilder, SubmenuBuilder, HELP_SUBMENU_ID};
 use tauri::AppHandle;
-use tauri::Emitter;
 use tauri_plugin_opener::OpenerExt;
 
-#[derive(Clone, serde::Serialize)]
-#[allow(dead_code)]
-struct OpenFilesPayload {
-    files: Vec<String>,
-}
-
 pub fn setup_macos_menu(app: &AppHandle) -> tauri::Result<()> {
     let global_menu = app.menu().unwrap();
 
@@ -78,11 +71,9 @@ fn handle_open_file(app: &AppHandle) {
         )
         .pick_file(move |file_path| {
             if let Some(path) = file_path {
-                let payload = OpenFilesPayload {
-                    files: vec![path.to_string()],
-                };
-                allow_file_in_scopes(&app_handle, vec![PathBuf::from(path.to_string())]);
-                let _ = app_handle.emit("open-files", payload);
+                let files = vec![PathBuf::from(path.to_string())];
+                allow_file_in_scopes(&app_handle, files.clone());
+                startup::open_files(&app_handle, files);
             }
         });
 }
@@ -0,0 +1,192 @@
+//! The one-shot HTTP listener the browser is redirected to.
+//!
+//! It binds the loopback IP literal rather than `localhost` (RFC 8252, section 8.3)
+//! on a port picked by the OS, and closes as soon as a redirect carrying the expected
+//! `state` has been answered, or when the flow times out.
+
+use super::Error;
+use std::collections::HashMap;
+use std::time::Duration;
+use tauri::Url;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::task::JoinSet;
+
+const CALLBACK_PATH: &str = "/callback";
+
+/// Browsers send the redirect as a bare GET; anything longer is not one.
+const MAX_REQUEST_LEN: usize = 16 * 1024;
+
+/// So that connections that never send a request do not pile up.
+const READ_TIMEOUT: Duration = Duration::from_secs(10);
+
+pub struct Listener {
+    inner: TcpListener,
+    port: u16,
+}
+
+impl Listener {
+    pub async fn bind() -> std::io::Result<Self> {
+        let inner = TcpListener::bind(("127.0.0.1", 0)).await?;
+        let port = inner.local_addr()?.port();
+        Ok(Self { inner, port })
+    }
+
+    pub fn redirect_uri(&self) -> String {
+        format!("http://127.0.0.1:{}{CALLBACK_PATH}", self.port)
+    }
+
+    /// Answers requests until the redirect for `state` arrives, resolving to its code.
+    /// Other paths and redirects with another `state`, which any local process could
+    /// send, get an error page and do not end the flow. Each connection is served on
+    /// its own, so one the browser opened ahead of time does not hold up the redirect.
+    pub async fn accept(self, state: &str, timeout: Duration) -> Result<String, Error> {
+        let serve = async {
+            let mut connections = JoinSet::new();
+            loop {
+                tokio::select! {
+                    accepted = self.inner.accept() => {
+                        let (stream, _) = accepted?;
+                        connections.spawn(serve(stream, state.to_string()));
+                    }
+                    Some(served) = connections.join_next() => {
+                        if let Ok(Some(result)) = served {
+                            return result;
+                        }
+                    }
+                }
+            }
+        };
+        tokio::time::timeout(timeout, serve)
+            .await
+            .unwrap_or(Err(Error::Timeout))
+    }
+}
+
+/// Answers one connection, resolving to the outcome of the flow when it was the
+/// redirect for `state`.
+async fn serve(mut stream: TcpStream, state: String) -> Option<Result<String, Error>> {
+    let request = tokio::time::timeout(READ_TIMEOUT, read_request(&mut stream));
+    let Ok(Some(url)) = request.await else {
+        respond(&mut stream, "400 Bad Request", &failure_page("Bad request")).await;
+        return None;
+    };
+    if url.path() != CALLBACK_PATH {
+        respond(&mut stream, "404 Not Found", &failure_page("Not found")).await;
+        return None;
+    }
+    match callback(&url, &state) {
+        Ok(code) => {
+            respond(&mut stream, "200 OK", &success_page()).await;
+            Some(Ok(code))
+        }
+        Err(Error::StateMismatch) => {
+            let page = failure_page(&Error::StateMismatch.to_string());
+            respond(&mut stream, "400 Bad Request", &page).await;
+            None
+        }
+        Err(e) => {
+            respond(&mut stream, "200 OK", &failure_page(&e.to_string())).await;
+            Some(Err(e))
+        }
+    }
+}
+
+/// The code from a redirect, once its `state` has been checked.
+fn callback(url: &Url, state: &str) -> Result<String, Error> {
+    let params = url.query_pairs().collect::<HashMap<_, _>>();
+    if params.get("state").map(|value| value.as_ref()) != Some(state) {
+        return Err(Error::StateMismatch);
+    }
+    if let Some(error) = params.get("error") {
+        let message = match params.get("error_description") {
+            Some(description) => format!("{error}: {description}"),
+            None => error.to_string(),
+        };
+        return Err(Error::Denied(message));
+    }
+    match params.get("code") {
+        Some(code) if !code.is_empty() => Ok(code.to_string()),
+        _ => Err(Error::MissingCode),
+    }
+}
+
+/// Reads the request line of a GET request and returns its target, resolved
+/// against the listener's origin.
+async fn read_request(stream: &mut TcpStream) -> Option<Url> {
+    let mut buf = Vec::new();
+    let mut chunk = [0; 1024];
+    while !buf.windows(4).any(|w| w == b"\r\n\r\n") {
+        let read = stream.read(&mut chunk).await.ok()?;
+        if read == 0 || buf.len() + read > MAX_REQUEST_LEN {
+            return None;
+        }
+        buf.extend_from_slice(&chunk[..read]);
+    }
+    let head = std::str::from_utf8(&buf).ok()?;
+    let mut request_line = head.lines().next()?.split(' ');
+    let (Some("GET"), Some(target)) = (request_line.next(), request_line.next()) else {
+        return None;
+    };
+    if !target.starts_with('/') {
+        return None;
+    }
+    Url::parse(&format!("http://127.0.0.1{target}")).ok()
+}
+
+async fn respond(stream: &mut TcpStream, status: &str, page: &str) {
+    let response = format!(
+        "HTTP/1.1 {status}\r\n\
+         Content-Type: text/html; charset=utf-8\r\n\
+         Content-Security-Policy: default-src 'none'; style-src 'unsafe-inline'\r\n\
+         Cache-Control: no-store\r\n\
+         Referrer-Policy: no-referrer\r\n\
+         Content-Length: {}\r\n\
+         Connection: close\r\n\r\n{page}",
+        page.len()
+    );
+    if let Err(e) = stream.write_all(response.as_bytes()).await {
+        log::warn!("Failed to answer the authorization redirect: {e}");
+    }
+    let _ = stream.shutdown().await;
+}
+
+const PAGE_STYLE: &str = "body{font-family:system-ui,sans-serif;display:flex;\
+align-items:center;justify-content:center;min-height:90vh;margin:0;text-align:center;\
+color:#333;background:#fafafa}h1{font-weight:600}@media(prefers-color-scheme:dark)\
+{body{color:#ddd;background:#1e1e1e}}";
+
+fn page(title: &str, body: &str) -> String {
+    format!(
+        "<!doctype html><html><head><meta charset=\"utf-8\"><title>{title}</title>\
+         <style>{PAGE_STYLE}</style></head><body><main>{body}</main></body></html>"
+    )
+}
+
+fn success_page() -> String {
+    page(
+        "Signed in",
+        "<h1>You are signed in</h1><p>You can close this tab and return to HackXIndia26.</p>",
+    )
+}
+
+fn failure_page(message: &str) -> String {
+    let body = format!(
+        "<h1>Sign-in failed</h1><p>{}</p><p>Close this tab and try again from HackXIndia26.</p>",
+        escape(message)
+    );
+    page("Sign-in failed", &body)
+}
+
+fn escape(text: &str) -> String {
+    text.chars()
+        .map(|c| match c {
+            '&' => "&amp;".to_string(),
+            '<' => "&lt;".to_string(),
+            '>' => "&gt;".to_string(),
+            '"' => "&quot;".to_string(),
+            '\'' => "&#39;".to_string(),
+            c => c.to_string(),
+        })
+        .collect()
+}
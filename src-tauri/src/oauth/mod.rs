@@ -0,0 +1,416 @@
+//! OAuth 2.0 sign-in for native apps, following RFC 8252.
+//!
+//! The authorization request is opened in the system browser and the provider
+//! redirects back to a one-shot listener on the loopback interface. The code never
+//! reaches the webview: it is checked against the `state` nonce and exchanged here,
+//! with a PKCE verifier (RFC 7636), and only the resulting tokens are returned.
+
+mod loopback;
+
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::time::Duration;
+use tauri::{AppHandle, Url};
+use tauri_plugin_opener::OpenerExt;
+
+/// How long to wait for the browser to come back when the request does not say.
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(5 * 60);
+
+/// Parameters of the authorization request that callers cannot override.
+const RESERVED_PARAMS: [&str; 7] = [
+    "response_type",
+    "client_id",
+    "redirect_uri",
+    "scope",
+    "state",
+    "code_challenge",
+    "code_challenge_method",
+];
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("{0} must be an https URL")]
+    InvalidEndpoint(&'static str),
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error("Timed out waiting for the authorization response")]
+    Timeout,
+    #[error("Authorization response does not match the request")]
+    StateMismatch,
+    #[error("Authorization failed: {0}")]
+    Denied(String),
+    #[error("Authorization response has no code")]
+    MissingCode,
+    #[error(transparent)]
+    Http(#[from] reqwest::Error),
+    #[error("Token endpoint answered {status}: {body}")]
+    TokenEndpoint { status: u16, body: String },
+}
+
+/// What the frontend asks for; the client must be registered as a public client
+/// with a loopback redirect URI on any port.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OAuthRequest {
+    pub authorization_endpoint: String,
+    pub token_endpoint: String,
+    pub client_id: String,
+    #[serde(default)]
+    pub scopes: Vec<String>,
+    /// Extra authorization parameters such as `prompt` or `audience`.
+    #[serde(default)]
+    pub params: BTreeMap<String, String>,
+    pub timeout_secs: Option<u64>,
+}
+
+/// The token response, without anything else the provider may have sent along.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all(serialize = "camelCase"))]
+pub struct Tokens {
+    pub access_token: String,
+    pub token_type: String,
+    pub expires_in: Option<u64>,
+    pub refresh_token: Option<String>,
+    pub id_token: Option<String>,
+    pub scope: Option<String>,
+}
+
+/// A PKCE verifier and its S256 challenge.
+struct Pkce {
+    verifier: String,
+    challenge: String,
+}
+
+impl Pkce {
+    fn new() -> Self {
+        let verifier = random_token(32);
+        Self {
+            challenge: challenge(&verifier),
+            verifier,
+        }
+    }
+}
+
+fn challenge(verifier: &str) -> String {
+    URL_SAFE_NO_PAD.encode(Sha256::digest(verifier.as_bytes()))
+}
+
+/// `bytes` random bytes, base64url encoded, so only unreserved characters are used.
+fn random_token(bytes: usize) -> String {
+    let mut buf = vec![0; bytes];
+    rand::thread_rng().fill_bytes(&mut buf);
+    URL_SAFE_NO_PAD.encode(buf)
+}
+
+/// Endpoints must use https; plain http is only accepted on the loopback interface,
+/// for development servers and tests.
+fn endpoint(value: &str, name: &'static str) -> Result<Url, Error> {
+    let url = Url::parse(value).map_err(|_| Error::InvalidEndpoint(name))?;
+    let loopback = matches!(url.host_str(), Some("127.0.0.1" | "[::1]" | "localhost"));
+    match url.scheme() {
+        "https" => Ok(url),
+        "http" if loopback => Ok(url),
+        _ => Err(Error::InvalidEndpoint(name)),
+    }
+}
+
+/// An authorization in progress: the listener is bound and `url` is ready to be
+/// opened in the browser.
+pub struct Authorization {
+    pub url: Url,
+    token_endpoint: Url,
+    client_id: String,
+    redirect_uri: String,
+    state: String,
+    pkce: Pkce,
+    timeout: Duration,
+    listener: loopback::Listener,
+}
+
+impl Authorization {
+    pub async fn start(request: OAuthRequest) -> Result<Self, Error> {
+        let mut url = endpoint(&request.authorization_endpoint, "Authorization endpoint")?;
+        let token_endpoint = endpoint(&request.token_endpoint, "Token endpoint")?;
+        let listener = loopback::Listener::bind().await?;
+        let redirect_uri = listener.redirect_uri();
+        let state = random_token(16);
+        let pkce = Pkce::new();
+        {
+            let mut query = url.query_pairs_mut();
+            for (name, value) in &request.params {
+                if !RESERVED_PARAMS.contains(&name.as_str()) {
+                    query.append_pair(name, value);
+                }
+            }
+            query
+                .append_pair("response_type", "code")
+                .append_pair("client_id", &request.client_id)
+                .append_pair("redirect_uri", &redirect_uri)
+                .append_pair("state", &state)
+                .append_pair("code_challenge", &pkce.challenge)
+                .append_pair("code_challenge_method", "S256");
+            if !request.scopes.is_empty() {
+                query.append_pair("scope", &request.scopes.join(" "));
+            }
+        }
+        Ok(Self {
+            url,
+            token_endpoint,
+            client_id: request.client_id,
+            redirect_uri,
+            state,
+            pkce,
+            timeout: request
+                .timeout_secs
+                .map(Duration::from_secs)
+                .unwrap_or(DEFAULT_TIMEOUT),
+            listener,
+        })
+    }
+
+    /// Waits for the redirect, then exchanges the code for tokens.
+    pub async fn finish(self, client: &reqwest::Client) -> Result<Tokens, Error> {
+        let code = self.listener.accept(&self.state, self.timeout).await?;
+        let form = [
+            ("grant_type", "authorization_code"),
+            ("code", code.as_str()),
+            ("redirect_uri", self.redirect_uri.as_str()),
+            ("client_id", self.client_id.as_str()),
+            ("code_verifier", self.pkce.verifier.as_str()),
+        ];
+        let response = client
+            .post(self.token_endpoint)
+            .header(reqwest::header::ACCEPT, "application/json")
+            .form(&form)
+            .send()
+            .await?;
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            return Err(Error::TokenEndpoint {
+                status: status.as_u16(),
+                body: body.chars().take(200).collect(),
+            });
+        }
+        Ok(response.json().await?)
+    }
+}
+
+/// Signs in with the system browser and resolves to the tokens.
+#[tauri::command]
+pub async fn authorize_oauth(app: AppHandle, request: OAuthRequest) -> Result<Tokens, String> {
+    let authorization = Authorization::start(request)
+        .await
+        .map_err(|e| e.to_string())?;
+    app.opener()
+        .open_url(authorization.url.as_str(), None::<&str>)
+        .map_err(|e| format!("Failed to open the browser: {e}"))?;
+    authorization
+        .finish(&reqwest::Client::new())
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use std::sync::{Arc, Mutex};
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::{TcpListener, TcpStream};
+
+    /// Reads one HTTP request, returning its head and body.
+    async fn read_request(stream: &mut TcpStream) -> (String, String) {
+        let mut buf = Vec::new();
+        let mut chunk = [0; 1024];
+        let head_end = loop {
+            let read = stream.read(&mut chunk).await.unwrap();
+            assert_ne!(read, 0, "connection closed before the request ended");
+            buf.extend_from_slice(&chunk[..read]);
+            if let Some(end) = buf.windows(4).position(|w| w == b"\r\n\r\n") {
+                break end + 4;
+            }
+        };
+        let head = String::from_utf8_lossy(&buf[..head_end]).into_owned();
+        let length = head
+            .lines()
+            .find_map(|line| {
+                let (name, value) = line.split_once(':')?;
+                name.eq_ignore_ascii_case("content-length")
+                    .then(|| value.trim().parse::<usize>().unwrap())
+            })
+            .unwrap_or(0);
+        while buf.len() < head_end + length {
+            let read = stream.read(&mut chunk).await.unwrap();
+            buf.extend_from_slice(&chunk[..read]);
+        }
+        let body = String::from_utf8_lossy(&buf[head_end..]).into_owned();
+        (head, body)
+    }
+
+    /// Plays the browser following the redirect to `url`.
+    async fn get(url: &str) -> String {
+        let url = Url::parse(url).unwrap();
+        let mut stream = TcpStream::connect(("127.0.0.1", url.port().unwrap()))
+            .await
+            .unwrap();
+        let target = match url.query() {
+            Some(query) => format!("{}?{query}", url.path()),
+            None => url.path().to_string(),
+        };
+        let request = format!("GET {target} HTTP/1.1\r\nHost: 127.0.0.1\r\n\r\n");
+        stream.write_all(request.as_bytes()).await.unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.unwrap();
+        response
+    }
+
+    fn query(url: &Url) -> HashMap<String, String> {
+        url.query_pairs().into_owned().collect()
+    }
+
+    /// A token endpoint that accepts `code` only with the verifier matching the
+    /// challenge it is given.
+    async fn mock_token_server(code: &'static str) -> (String, Arc<Mutex<String>>) {
+        let server = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/token", server.local_addr().unwrap());
+        let challenge = Arc::new(Mutex::new(String::new()));
+        let expected = challenge.clone();
+        tokio::spawn(async move {
+            let (mut stream, _) = server.accept().await.unwrap();
+            let (head, body) = read_request(&mut stream).await;
+            let form = query(&Url::parse(&format!("http://127.0.0.1/?{body}")).unwrap());
+            let valid = head.starts_with("POST /token ")
+                && form["grant_type"] == "authorization_code"
+                && form["code"] == code
+                && form["client_id"] == "reader"
+                && super::challenge(&form["code_verifier"]) == *expected.lock().unwrap();
+            let (status, body) = match valid {
+                true => (
+                    "200 OK",
+                    r#"{"access_token":"at","token_type":"Bearer","expires_in":3600,"refresh_token":"rt","extra":"x"}"#,
+                ),
+                false => ("400 Bad Request", r#"{"error":"invalid_grant"}"#),
+            };
+            let response = format!(
+                "HTTP/1.1 {status}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                body.len()
+            );
+            stream.write_all(response.as_bytes()).await.unwrap();
+        });
+        (url, challenge)
+    }
+
+    fn request(token_endpoint: &str) -> OAuthRequest {
+        OAuthRequest {
+            authorization_endpoint: "https://auth.example.com/authorize".to_string(),
+            token_endpoint: token_endpoint.to_string(),
+            client_id: "reader".to_string(),
+            scopes: vec!["openid".to_string(), "profile".to_string()],
+            params: BTreeMap::from([("state".to_string(), "forced".to_string())]),
+            timeout_secs: Some(10),
+        }
+    }
+
+    #[test]
+    fn derives_the_s256_challenge() {
+        // RFC 7636, appendix B
+        assert_eq!(
+            challenge("dBjftJeZ4CVP-mB92K27uhbUJU1p1r_wW1gFWFOEjXk"),
+            "E9Melhoa2OwvFrEMTJguCHaoeK1t8URWbuGJSstw-cM"
+        );
+        let pkce = Pkce::new();
+        assert_eq!(pkce.verifier.len(), 43);
+        assert_eq!(pkce.challenge, challenge(&pkce.verifier));
+    }
+
+    #[test]
+    fn requires_https_endpoints() {
+        assert!(endpoint("https://auth.example.com/token", "Token endpoint").is_ok());
+        assert!(endpoint("http://127.0.0.1:8080/token", "Token endpoint").is_ok());
+        assert!(endpoint("http://auth.example.com/token", "Token endpoint").is_err());
+        assert!(endpoint("javascript:alert(1)", "Token endpoint").is_err());
+    }
+
+    #[test]
+    fn completes_the_flow_against_a_mock_server() {
+        tauri::async_runtime::block_on(async {
+            let (token_endpoint, challenge) = mock_token_server("the-code").await;
+            let authorization = Authorization::start(request(&token_endpoint))
+                .await
+                .unwrap();
+            let params = query(&authorization.url);
+            assert_eq!(params["response_type"], "code");
+            assert_eq!(params["code_challenge_method"], "S256");
+            assert_eq!(params["scope"], "openid profile");
+            assert_ne!(params["state"], "forced");
+            *challenge.lock().unwrap() = params["code_challenge"].clone();
+
+            let redirect = params["redirect_uri"].clone();
+            assert!(redirect.starts_with("http://127.0.0.1:"));
+            let state = params["state"].clone();
+            let browser = tokio::spawn(async move {
+                // Browsers open connections ahead of time that may never send anything
+                let port = Url::parse(&redirect).unwrap().port().unwrap();
+                let _idle = TcpStream::connect(("127.0.0.1", port)).await.unwrap();
+                // Stray requests and forged redirects do not end the flow
+                assert!(get(&redirect.replace("/callback", "/favicon.ico"))
+                    .await
+                    .starts_with("HTTP/1.1 404"));
+                let forged = get(&format!("{redirect}?code=evil&state=guess")).await;
+                assert!(forged.starts_with("HTTP/1.1 400"));
+                get(&format!("{redirect}?code=the-code&state={state}")).await
+            });
+
+            let tokens = authorization.finish(&reqwest::Client::new()).await.unwrap();
+            assert_eq!(
+                tokens,
+                Tokens {
+                    access_token: "at".to_string(),
+                    token_type: "Bearer".to_string(),
+                    expires_in: Some(3600),
+                    refresh_token: Some("rt".to_string()),
+                    id_token: None,
+                    scope: None,
+                }
+            );
+            let page = browser.await.unwrap();
+            assert!(page.starts_with("HTTP/1.1 200"));
+            assert!(page.contains("You are signed in"));
+        });
+    }
+
+    #[test]
+    fn reports_denied_authorization() {
+        tauri::async_runtime::block_on(async {
+            let authorization = Authorization::start(request("http://127.0.0.1:9/token"))
+                .await
+                .unwrap();
+            let params = query(&authorization.url);
+            let callback = format!(
+                "{}?error=access_denied&error_description=%3Cb%3Enope%3C%2Fb%3E&state={}",
+                params["redirect_uri"], params["state"]
+            );
+            let browser = tokio::spawn(async move { get(&callback).await });
+            let result = authorization.finish(&reqwest::Client::new()).await;
+            assert!(matches!(result, Err(Error::Denied(message)) if message.contains("nope")));
+            let page = browser.await.unwrap();
+            assert!(page.contains("&lt;b&gt;nope"));
+        });
+    }
+
+    #[test]
+    fn times_out() {
+        tauri::async_runtime::block_on(async {
+            let mut request = request("http://127.0.0.1:9/token");
+            request.timeout_secs = Some(0);
+            let authorization = Authorization::start(request).await.unwrap();
+            let result = authorization.finish(&reqwest::Client::new()).await;
+            assert!(matches!(result, Err(Error::Timeout)));
+        });
+    }
+}
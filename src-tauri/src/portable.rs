@@ -0,0 +1,149 @@
+//! Portable mode: settings, books, cache and logs live in a `HackXIndia26` folder
+//! next to the executable instead of the OS profile, so a library can be carried
+//! around on a USB stick.
+//!
+//! It is turned on by starting the app with `--portable`, by a `portable` marker file
+//! next to the executable, or by a `settings.json` there, which is where portable
+//! installs kept their settings before the folder existed. Portable release builds
+//! are always portable: they are built with `NEXT_PUBLIC_PORTABLE_APP` set.
+
+use serde::Serialize;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+pub const FLAG: &str = "--portable";
+pub const MARKER_FILE: &str = "portable";
+
+/// Named after the data folder the frontend keeps under its root, so that books
+/// end up in the same place as with the old layout.
+const FOLDER: &str = "HackXIndia26";
+
+const LEGACY_SETTINGS: [&str; 2] = ["settings.json", "settings.json.bak"];
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PortablePaths {
+    pub executable_dir: PathBuf,
+    /// Stands in for `$APPDATA` and `$APPCONFIG`.
+    pub data_dir: PathBuf,
+    /// Stands in for `$APPCACHE`.
+    pub cache_dir: PathBuf,
+    /// Stands in for `$APPLOG`.
+    pub log_dir: PathBuf,
+}
+
+impl PortablePaths {
+    fn new(executable_dir: &Path) -> Self {
+        let data_dir = executable_dir.join(FOLDER);
+        Self {
+            executable_dir: executable_dir.to_path_buf(),
+            cache_dir: data_dir.join("Cache"),
+            log_dir: data_dir.join("Logs"),
+            data_dir,
+        }
+    }
+
+    /// `None` unless portable mode is on for this run.
+    pub fn detect(args: &[String]) -> Option<Self> {
+        let executable_dir = crate::environment::executable_dir()?;
+        if option_env!("NEXT_PUBLIC_PORTABLE_APP").is_some_and(|flag| !flag.is_empty()) {
+            return Some(Self::new(&executable_dir));
+        }
+        Self::detect_in(&executable_dir, args)
+    }
+
+    fn detect_in(executable_dir: &Path, args: &[String]) -> Option<Self> {
+        let enabled = args.iter().skip(1).any(|arg| arg == FLAG)
+            || executable_dir.join(MARKER_FILE).is_file()
+            || executable_dir.join(LEGACY_SETTINGS[0]).is_file();
+        enabled.then(|| Self::new(executable_dir))
+    }
+
+    /// Where the native TTS plugin keeps voices, lexicons, reading positions and caches.
+    pub fn tts_dirs(&self) -> tauri_plugin_native_tts::Dirs {
+        tauri_plugin_native_tts::Dirs {
+            data_dir: self.data_dir.clone(),
+            cache_dir: self.cache_dir.clone(),
+        }
+    }
+
+    /// Where window sizes and positions are remembered.
+    #[cfg(desktop)]
+    pub fn window_state_file(&self) -> PathBuf {
+        self.data_dir
+            .join(tauri_plugin_window_state::DEFAULT_FILENAME)
+    }
+
+    /// Creates the folders and moves settings left next to the executable by older
+    /// portable installs into the data folder, leaving a marker in their place.
+    pub fn prepare(&self) -> io::Result<()> {
+        for dir in [&self.data_dir, &self.cache_dir, &self.log_dir] {
+            fs::create_dir_all(dir)?;
+        }
+        let legacy = self.executable_dir.join(LEGACY_SETTINGS[0]);
+        if !legacy.is_file() {
+            return Ok(());
+        }
+        for name in LEGACY_SETTINGS {
+            let (from, to) = (self.executable_dir.join(name), self.data_dir.join(name));
+            if from.is_file() && !to.exists() {
+                fs::rename(from, to)?;
+            }
+        }
+        let marker = self.executable_dir.join(MARKER_FILE);
+        if !marker.exists() {
+            fs::write(marker, "")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn moves_legacy_settings_into_the_portable_folder() {
+        let exec_dir = std::env::temp_dir().join(format!("portable-test-{}", std::process::id()));
+        fs::create_dir_all(&exec_dir).unwrap();
+        let args = vec!["hackxindia26".to_string()];
+        assert_eq!(PortablePaths::detect_in(&exec_dir, &args), None);
+        let flagged = [args.clone(), vec![FLAG.to_string()]].concat();
+        assert!(PortablePaths::detect_in(&exec_dir, &flagged).is_some());
+
+        fs::write(exec_dir.join("settings.json"), "{}").unwrap();
+        let portable = PortablePaths::detect_in(&exec_dir, &args).unwrap();
+        assert_eq!(portable.data_dir, exec_dir.join("HackXIndia26"));
+        portable.prepare().unwrap();
+        assert!(portable.data_dir.join("settings.json").is_file());
+        assert!(portable.log_dir.is_dir() && portable.cache_dir.is_dir());
+        assert!(!exec_dir.join("settings.json").exists());
+        assert_eq!(PortablePaths::detect_in(&exec_dir, &args), Some(portable));
+
+        fs::remove_dir_all(exec_dir).unwrap();
+    }
+
+    #[cfg(desktop)]
+    #[test]
+    fn keeps_every_path_next_to_the_executable() {
+        let exec_dir = std::env::temp_dir().join(format!("portable-paths-{}", std::process::id()));
+        let portable = PortablePaths::new(&exec_dir);
+        let database = crate::annotations::Database::new(Some(&portable));
+        let cli = crate::cli::library::Paths::resolve("com.example.app", Some(&portable)).unwrap();
+        let tts = portable.tts_dirs();
+        let paths = [
+            PathBuf::from(database.url.strip_prefix("sqlite:").unwrap()),
+            cli.config_dir,
+            cli.books_dir,
+            tts.data_dir,
+            tts.cache_dir,
+            portable.window_state_file(),
+            portable.cache_dir.clone(),
+            portable.log_dir.clone(),
+        ];
+        for path in paths {
+            assert!(path.starts_with(&exec_dir), "{} is outside", path.display());
+        }
+    }
+}
@@ -0,0 +1,131 @@
+//! What the frontend needs to know when it starts: books the app was asked to open,
+//! deep links and a few platform flags. The webview fetches it with `get_startup_state`
+//! rather than having it evaluated into the page, so paths are always passed as data.
+
+use crate::deep_link::{DeepLink, Router};
+use crate::device_profile::{DeviceProfile, DeviceProfiles};
+use crate::portable::PortablePaths;
+use serde::Serialize;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter, Manager, Runtime, State};
+
+/// Emitted with an [`OpenFilesPayload`] for books opened after the frontend has started.
+pub const OPEN_FILES_EVENT: &str = "open-files";
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StartupState {
+    /// Books to open, handed out only once.
+    pub files: Vec<String>,
+    /// Deep links received so far, handed out only once.
+    pub deep_links: Vec<DeepLink>,
+    pub is_eink: bool,
+    /// The display profile in effect, detected or picked by the user.
+    pub device_profile: DeviceProfile,
+    pub has_updater: bool,
+    /// Whether the `cli` plugin is available to read command-line arguments.
+    pub cli_access: bool,
+    /// Where data, cache and logs go instead of the OS profile, in portable mode.
+    pub portable: Option<PortablePaths>,
+}
+
+#[derive(Clone, Serialize)]
+pub struct OpenFilesPayload {
+    pub files: Vec<String>,
+}
+
+#[derive(Default)]
+struct Pending {
+    ready: bool,
+    files: Vec<String>,
+}
+
+pub struct Startup {
+    portable: Option<PortablePaths>,
+    pending: Mutex<Pending>,
+}
+
+impl Startup {
+    pub fn new(portable: Option<PortablePaths>) -> Self {
+        Self {
+            portable,
+            pending: Mutex::default(),
+        }
+    }
+}
+
+/// The updater can only replace AppImages on Linux; distro packages update themselves.
+fn has_updater() -> bool {
+    if !cfg!(target_os = "linux") {
+        return true;
+    }
+    crate::environment::app_image().is_some()
+        || std::env::current_exe()
+            .map(|path| path.to_string_lossy().contains("/tmp/.mount_"))
+            .unwrap_or(false)
+}
+
+/// Hands books to the frontend: kept for `get_startup_state` and `frontend_ready` until
+/// the frontend listens, emitted as [`OPEN_FILES_EVENT`] afterwards. Callers allow the
+/// files in the scopes.
+pub fn open_files<R: Runtime>(app: &AppHandle<R>, files: Vec<PathBuf>) {
+    if files.is_empty() {
+        return;
+    }
+    let files = files
+        .iter()
+        .map(|file| file.to_string_lossy().into_owned())
+        .collect::<Vec<_>>();
+    let startup = app.state::<Startup>();
+    let mut pending = startup.pending.lock().unwrap();
+    if !pending.ready {
+        pending.files.extend(files);
+        return;
+    }
+    drop(pending);
+    if let Err(e) = app.emit(OPEN_FILES_EVENT, OpenFilesPayload { files }) {
+        log::error!("Failed to emit open files: {e}");
+    }
+}
+
+/// Every window may call this; pending books and deep links go to the first caller.
+/// Anything arriving later is kept until `frontend_ready`.
+#[tauri::command]
+pub fn get_startup_state(
+    startup: State<'_, Startup>,
+    router: State<'_, Router>,
+    profiles: State<'_, DeviceProfiles>,
+) -> StartupState {
+    let device_profile = profiles.active();
+    let files = {
+        let mut pending = startup.pending.lock().unwrap();
+        std::mem::take(&mut pending.files)
+    };
+    StartupState {
+        files,
+        deep_links: router.take(),
+        is_eink: device_profile.is_eink(),
+        device_profile,
+        has_updater: has_updater(),
+        cli_access: cfg!(desktop),
+        portable: startup.portable.clone(),
+    }
+}
+
+/// Called once the frontend listens for [`OPEN_FILES_EVENT`] and deep links: emits what
+/// arrived since `get_startup_state`, and everything after it directly.
+#[tauri::command]
+pub fn frontend_ready<R: Runtime>(app: AppHandle<R>, startup: State<'_, Startup>) {
+    let files = {
+        let mut pending = startup.pending.lock().unwrap();
+        pending.ready = true;
+        std::mem::take(&mut pending.files)
+    };
+    if !files.is_empty() {
+        if let Err(e) = app.emit(OPEN_FILES_EVENT, OpenFilesPayload { files }) {
+            log::error!("Failed to emit open files: {e}");
+        }
+    }
+    crate::deep_link::flush(&app);
+}
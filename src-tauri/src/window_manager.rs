@@ -0,0 +1,162 @@
+//! Reader windows, one per book, labelled `reader-<hash>`.
+//!
+//! Every window reports the books it shows, so opening a book that is already open
+//! focuses its window instead of loading it again. Labels stay the same for a book,
+//! which lets the window-state plugin restore each book's window geometry.
+
+use crate::deep_link::is_book_hash;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager, Runtime, State, WebviewUrl, WebviewWindow, Window, WindowEvent};
+use tauri_plugin_window_state::{AppHandleExt, StateFlags};
+
+pub const READER_LABEL_PREFIX: &str = "reader-";
+
+/// Joins book hashes in the reader's `ids` query parameter: a `+`, percent-encoded
+/// since a bare one reads as a space.
+const BOOK_IDS_SEPARATOR: &str = "%2B";
+
+/// The books shown in each window, by label.
+#[derive(Default)]
+pub struct WindowManager(Mutex<HashMap<String, Vec<String>>>);
+
+impl WindowManager {
+    fn set_books(&self, label: &str, books: Vec<String>) {
+        let mut windows = self.0.lock().unwrap();
+        if books.is_empty() {
+            windows.remove(label);
+        } else {
+            windows.insert(label.to_string(), books);
+        }
+    }
+
+    /// The window showing `hash`, preferring reader windows over the library.
+    fn find(&self, hash: &str) -> Option<String> {
+        let windows = self.0.lock().unwrap();
+        windows
+            .iter()
+            .filter(|(_, books)| books.iter().any(|book| book == hash))
+            .map(|(label, _)| label)
+            .min_by_key(|label| (!label.starts_with(READER_LABEL_PREFIX), label.as_str()))
+            .cloned()
+    }
+}
+
+pub fn reader_label(hash: &str) -> String {
+    format!("{READER_LABEL_PREFIX}{hash}")
+}
+
+fn focus<R: Runtime>(window: &WebviewWindow<R>) {
+    let _ = window.unminimize();
+    let _ = window.show();
+    let _ = window.set_focus();
+}
+
+/// Focuses the window showing `hash`, if any.
+pub fn focus_book<R: Runtime>(app: &AppHandle<R>, hash: &str) -> Option<WebviewWindow<R>> {
+    let label = app.state::<WindowManager>().find(hash)?;
+    let window = app.get_webview_window(&label)?;
+    focus(&window);
+    Some(window)
+}
+
+/// Focuses the main window, or any other one left open once it has been closed, and
+/// opens a new main window when there is none at all.
+pub fn focus_main(app: &AppHandle) -> Option<WebviewWindow> {
+    let window = app
+        .get_webview_window("main")
+        .or_else(|| {
+            app.webview_windows()
+                .into_values()
+                .min_by(|a, b| a.label().cmp(b.label()))
+        })
+        .or_else(|| {
+            crate::window_builder(app, "main", WebviewUrl::default())
+                .build()
+                .map_err(|e| log::error!("Failed to open main window: {e}"))
+                .ok()
+        })?;
+    focus(&window);
+    Some(window)
+}
+
+/// Opens `ids` in a new reader window, or focuses the window that already shows the
+/// first of them.
+pub fn open_reader(app: &AppHandle, ids: &[String]) -> Result<WebviewWindow, String> {
+    let Some(first) = ids.first() else {
+        return Err("No book to open".to_string());
+    };
+    if let Some(id) = ids.iter().find(|id| !is_book_hash(id)) {
+        return Err(format!("Invalid book hash {id:?}"));
+    }
+    if let Some(window) = focus_book(app, first) {
+        return Ok(window);
+    }
+    let label = reader_label(first);
+    if let Some(window) = app.get_webview_window(&label) {
+        focus(&window);
+        return Ok(window);
+    }
+    let url = format!("reader?ids={}", ids.join(BOOK_IDS_SEPARATOR));
+    let window = crate::window_builder(app, &label, WebviewUrl::App(url.into()))
+        .center()
+        .build()
+        .map_err(|e| format!("Failed to open reader window: {e}"))?;
+    app.state::<WindowManager>().set_books(&label, ids.to_vec());
+    Ok(window)
+}
+
+/// Forgets closed windows and saves the geometry of reader windows right away, rather
+/// than only when the app exits.
+pub fn on_window_event(window: &Window, event: &WindowEvent) {
+    if let WindowEvent::Destroyed = event {
+        let app = window.app_handle();
+        app.state::<WindowManager>()
+            .set_books(window.label(), Vec::new());
+        if window.label().starts_with(READER_LABEL_PREFIX) {
+            if let Err(e) = app.save_window_state(StateFlags::all()) {
+                log::error!("Failed to save window state: {e}");
+            }
+        }
+    }
+}
+
+/// Opens books in their own window; resolves to the label of the window.
+#[tauri::command]
+pub async fn open_reader_window(app: AppHandle, ids: Vec<String>) -> Result<String, String> {
+    open_reader(&app, &ids).map(|window| window.label().to_string())
+}
+
+/// Focuses the window showing a book; resolves to its label, or `null` if none does.
+#[tauri::command]
+pub fn focus_book_window(app: AppHandle, hash: String) -> Option<String> {
+    focus_book(&app, &hash).map(|window| window.label().to_string())
+}
+
+/// Called by a window whenever the books it shows change.
+#[tauri::command]
+pub fn set_window_books(window: Window, manager: State<'_, WindowManager>, ids: Vec<String>) {
+    let ids = ids.into_iter().filter(|id| is_book_hash(id)).collect();
+    manager.set_books(window.label(), ids);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const A: &str = "0123456789abcdef0123456789abcdef";
+    const B: &str = "fedcba9876543210fedcba9876543210";
+
+    #[test]
+    fn finds_the_window_showing_a_book() {
+        let manager = WindowManager::default();
+        manager.set_books("main", vec![A.to_string()]);
+        assert_eq!(manager.find(A).as_deref(), Some("main"));
+        manager.set_books(&reader_label(A), vec![A.to_string(), B.to_string()]);
+        assert_eq!(manager.find(A), Some(reader_label(A)));
+        assert_eq!(manager.find(B), Some(reader_label(A)));
+        manager.set_books(&reader_label(A), Vec::new());
+        assert_eq!(manager.find(A).as_deref(), Some("main"));
+        assert_eq!(manager.find(B), None);
+    }
+}
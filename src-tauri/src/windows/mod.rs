@@ -1 +1,10 @@
+use windows_sys::Win32::System::Console::{AttachConsole, ATTACH_PARENT_PROCESS};
 
+/// Release builds use the GUI subsystem and start without a console, so subcommands
+/// attach to the console of the shell they were run from before printing anything.
+pub fn attach_console() {
+    // Fails when there is no parent console, e.g. when started from Explorer.
+    unsafe {
+        AttachConsole(ATTACH_PARENT_PROCESS);
+    }
+}
@@ -13,6 +13,7 @@ import { useEnv } from '@/context/EnvContext';
 import { useThemeStore } from '@/store/themeStore';
 import { useSettingsStore } from '@/store/settingsStore';
 import { useTranslation } from '@/hooks/useTranslation';
+import { DiscordPresenceType } from '@/types/settings';
 import { tauriHandleSetAlwaysOnTop, tauriHandleToggleFullScreen } from '@/utils/window';
 
 import { setMigrateDataDirDialogVisible } from '@/app/library/components/MigrateDataWindow';
@@ -45,6 +46,8 @@ const SettingsMenuV2: React.FC<SettingsMenuProps> = ({ setIsDropdownOpen }) => {
     settings.autoImportBooksOnOpen,
   );
 
+  const [discordPresence, setDiscordPresence] = useState(settings.discordPresence);
+
   const [alwaysInForeground, setAlwaysInForeground] = useState(settings.alwaysInForeground);
   const [savedBookCoverForLockScreen, setSavedBookCoverForLockScreen] = useState(
     settings.savedBookCoverForLockScreen || '',
@@ -113,6 +116,18 @@ const SettingsMenuV2: React.FC<SettingsMenuProps> = ({ setIsDropdownOpen }) => {
     setIsOpenLastBooks(newValue);
   };
 
+  const handleSetDiscordPresence = (value: DiscordPresenceType) => {
+    saveSysSettings(envConfig, 'discordPresence', value);
+    setDiscordPresence(value);
+  };
+
+  const discordPresenceOptions: { value: DiscordPresenceType; label: string }[] = [
+    { value: 'showTitle', label: _('Show Book Title') },
+    { value: 'showGenre', label: _('Show Genre Only') },
+    { value: 'hidden', label: _('Show "Reading a book"') },
+    { value: 'off', label: _('Off') },
+  ];
+
   const handleSetRootDir = () => {
     setMigrateDataDirDialogVisible(true);
     setIsDropdownOpen?.(false);
@@ -206,6 +221,23 @@ const SettingsMenuV2: React.FC<SettingsMenuProps> = ({ setIsDropdownOpen }) => {
       {appService?.hasWindow && (
         <MenuItem label={_('Always on Top')} toggled={isAlwaysOnTop} onClick={toggleAlwaysOnTop} />
       )}
+      {appService?.isDesktopApp && (
+        <MenuItem
+          label={_('Show on Discord')}
+          tooltip={_("Display what I'm reading on Discord")}
+        >
+          <ul className='flex flex-col'>
+            {discordPresenceOptions.map(({ value, label }) => (
+              <MenuItem
+                key={value}
+                label={label}
+                toggled={discordPresence === value}
+                onClick={() => handleSetDiscordPresence(value)}
+              />
+            ))}
+          </ul>
+        </MenuItem>
+      )}
       {appService?.isMobileApp && (
         <MenuItem
           label={_('Always Show Status Bar')}
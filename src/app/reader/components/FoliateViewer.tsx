@@ -15,6 +15,7 @@ import { usePagination } from '../hooks/usePagination';
 import { useFoliateEvents } from '../hooks/useFoliateEvents';
 import { useProgressSync } from '../hooks/useProgressSync';
 import { useProgressAutoSave } from '../hooks/useProgressAutoSave';
+import { useDiscordPresence } from '../hooks/useDiscordPresence';
 import { useBackgroundTexture } from '@/hooks/useBackgroundTexture';
 import { useAutoFocus } from '@/hooks/useAutoFocus';
 import { useTranslation } from '@/hooks/useTranslation';
@@ -50,6 +51,7 @@ import { isTauriAppPlatform } from '@/services/environment';
 import { TransformContext } from '@/services/transformers/types';
 import { transformContent } from '@/services/transformService';
 import { lockScreenOrientation } from '@/utils/bridge';
+import { getContentFilter } from '@/utils/deviceProfile';
 import { useTextTranslation } from '../hooks/useTextTranslation';
 import { useBookCoverAutoSave } from '../hooks/useAutoSaveBookCover';
 import { manageSyntaxHighlighting } from '@/utils/highlightjs';
@@ -104,6 +106,7 @@ const FoliateViewer: React.FC<{
   useUICSS(bookKey);
   useProgressSync(bookKey);
   useProgressAutoSave(bookKey);
+  useDiscordPresence(bookKey);
   useBookCoverAutoSave(bookKey);
   useTextTranslation(bookKey, viewRef.current);
 
@@ -354,6 +357,7 @@ const FoliateViewer: React.FC<{
         } else {
           view.renderer.removeAttribute('eink');
         }
+        view.style.filter = getContentFilter(appService.deviceProfile, eink);
         applyEinkMode(eink);
       }
       if (bookDoc?.rendition?.layout === 'pre-paginated') {
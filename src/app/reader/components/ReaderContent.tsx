@@ -19,7 +19,7 @@ import { isTauriAppPlatform } from '@/services/environment';
 import { uniqueId } from '@/utils/misc';
 import { throttle } from '@/utils/throttle';
 import { eventDispatcher } from '@/utils/event';
-import { navigateToLibrary } from '@/utils/nav';
+import { navigateToLibrary, setWindowBooks } from '@/utils/nav';
 import { BOOK_IDS_SEPARATOR } from '@/services/constants';
 import { BookDetailModal } from '@/components/metadata';
 
@@ -122,6 +122,15 @@ const ReaderContent: React.FC<{ ids?: string; settings: SystemSettings }> = ({ i
     // eslint-disable-next-line react-hooks/exhaustive-deps
   }, [bookKeys]);
 
+  // Lets the backend focus this window when one of its books is opened again
+  useEffect(() => {
+    if (!appService?.hasWindow) return;
+    setWindowBooks([...new Set(bookKeys.map((key) => key.split('-')[0]!))]);
+    return () => {
+      setWindowBooks([]);
+    };
+  }, [appService, bookKeys]);
+
   const saveBookConfig = async (bookKey: string) => {
     const config = getConfig(bookKey);
     const { book } = getBookData(bookKey) || {};
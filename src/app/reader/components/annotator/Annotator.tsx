@@ -289,7 +289,8 @@ const Annotator: React.FC<{ bookKey: string }> = ({ bookKey }) => {
 
   const onDrawAnnotation = (event: Event) => {
     const viewSettings = getViewSettings(bookKey)!;
-    const isEink = viewSettings.isEink;
+    // Color e-ink panels can show the highlight colors
+    const isEink = viewSettings.isEink && appService?.deviceProfile?.display !== 'color';
     const detail = (event as CustomEvent).detail;
     const { draw, annotation, doc, range } = detail;
     const { style, color } = annotation as BookNote;
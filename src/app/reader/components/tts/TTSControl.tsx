@@ -18,7 +18,7 @@ import { Insets } from '@/types/misc';
 import { Overlay } from '@/components/Overlay';
 import { fetchImageAsBase64 } from '@/utils/image';
 import { invokeUseBackgroundAudio } from '@/utils/bridge';
-import { isCfiInLocation } from '@/utils/cfi';
+import { getLaterCfi, isCfiInLocation } from '@/utils/cfi';
 import { getLocale } from '@/utils/misc';
 import Popup from '@/components/Popup';
 import TTSPanel from './TTSPanel';
@@ -55,6 +55,8 @@ const TTSControl: React.FC<TTSControlProps> = ({ bookKey, gridInsets }) => {
   const [timeoutOption, setTimeoutOption] = useState(0);
   const [timeoutTimestamp, setTimeoutTimestamp] = useState(0);
   const [timeoutFunc, setTimeoutFunc] = useState<ReturnType<typeof setTimeout> | null>(null);
+  // For the controllers created after the timer was set
+  const timeoutOptionRef = useRef(0);
 
   const hoverTimeoutRef = useRef<ReturnType<typeof setTimeout> | null>(null);
   const [showIndicatorWithinTimeout, setShowIndicatorWithinTimeout] = useState(true);
@@ -252,13 +254,20 @@ const TTSControl: React.FC<TTSControlProps> = ({ bookKey, gridInsets }) => {
       }
     };
 
+    const handleSleep = () => {
+      handleSelectTimeout(bookKey, 0);
+      handleStop(bookKey);
+    };
+
     ttsController.addEventListener('tts-need-auth', handleNeedAuth);
     ttsController.addEventListener('tts-speak-mark', handleSpeakMark);
     ttsController.addEventListener('tts-highlight-mark', handleHighlightMark);
+    ttsController.addEventListener('tts-sleep', handleSleep);
     return () => {
       ttsController.removeEventListener('tts-need-auth', handleNeedAuth);
       ttsController.removeEventListener('tts-speak-mark', handleSpeakMark);
       ttsController.removeEventListener('tts-highlight-mark', handleHighlightMark);
+      ttsController.removeEventListener('tts-sleep', handleSleep);
     };
     // eslint-disable-next-line react-hooks/exhaustive-deps
   }, [ttsController, bookKey]);
@@ -327,21 +336,19 @@ const TTSControl: React.FC<TTSControlProps> = ({ bookKey, gridInsets }) => {
       return;
     }
 
-    let ttsFromRange = range;
-    if (!ttsFromRange && viewSettings.ttsLocation) {
-      const { location } = progress;
-      const ttsCfi = viewSettings.ttsLocation;
-      if (isCfiInLocation(ttsCfi, location)) {
+    const getResumeRange = async (ttsController: TTSController) => {
+      // The native queue may have read on while the reader was in the background.
+      const nativeCfi = await ttsController.getResumeCFI().catch(() => undefined);
+      const ttsCfi = getLaterCfi(viewSettings.ttsLocation, nativeCfi);
+      if (ttsCfi && isCfiInLocation(ttsCfi, progress.location)) {
         const { index, anchor } = view.resolveCFI(ttsCfi);
         const { doc } = view.renderer.getContents().find((x) => x.index === index) || {};
         if (doc) {
-          ttsFromRange = anchor(doc);
+          return anchor(doc);
         }
       }
-    }
-    if (!ttsFromRange) {
-      ttsFromRange = progress.range;
-    }
+      return progress.range;
+    };
 
     const primaryLang = bookData.book.primaryLanguage;
 
@@ -364,6 +371,7 @@ const TTSControl: React.FC<TTSControlProps> = ({ bookKey, gridInsets }) => {
         setShowIndicator(true);
       }
       const ttsController = new TTSController(appService, view, !!user?.id, preprocessSSMLForTTS);
+      ttsController.bookHash = bookKey.split('-')[0]!;
       ttsControllerRef.current = ttsController;
       setTtsController(ttsController);
 
@@ -371,6 +379,10 @@ const TTSControl: React.FC<TTSControlProps> = ({ bookKey, gridInsets }) => {
       await ttsController.initViewTTS(
         getTTSHighlightOptions(viewSettings.ttsHighlightOptions, viewSettings.isEink),
       );
+      if (timeoutOptionRef.current === -1) {
+        await ttsController.setSleepTimer('chapter');
+      }
+      const ttsFromRange = range || (await getResumeRange(ttsController));
       const ssml = view.tts?.from(ttsFromRange);
       if (ssml) {
         const lang = parseSSMLLang(ssml, primaryLang) || 'en';
@@ -529,11 +541,15 @@ const TTSControl: React.FC<TTSControlProps> = ({ bookKey, gridInsets }) => {
     return '';
   };
 
+  // A value of -1 stops at the end of the chapter
   const handleSelectTimeout = (bookKey: string, value: number) => {
     setTimeoutOption(value);
+    timeoutOptionRef.current = value;
     if (timeoutFunc) {
       clearTimeout(timeoutFunc);
     }
+    // The native player keeps time even while the reader is in the background.
+    ttsControllerRef.current?.setSleepTimer(value === -1 ? 'chapter' : value);
     if (value > 0) {
       setTimeoutFunc(
         setTimeout(() => {
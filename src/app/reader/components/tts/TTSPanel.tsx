@@ -35,6 +35,10 @@ const getTTSTimeoutOptions = (_: TranslationFunc) => {
       label: _('No Timeout'),
       value: 0,
     },
+    {
+      label: _('End of Chapter'),
+      value: -1,
+    },
     {
       label: _('{{value}} minute', { value: 1 }),
       value: 60,
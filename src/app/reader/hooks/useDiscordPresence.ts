@@ -0,0 +1,57 @@
+import { useEffect, useRef } from 'react';
+import { useEnv } from '@/context/EnvContext';
+import { useBookDataStore } from '@/store/bookDataStore';
+import { useReaderStore } from '@/store/readerStore';
+import { useSettingsStore } from '@/store/settingsStore';
+import { clearBookPresence, updateBookPresence } from '@/utils/discord';
+
+const getGenre = (subject: unknown) => {
+  const first = Array.isArray(subject) ? subject[0] : subject;
+  return typeof first === 'string' ? first : undefined;
+};
+
+// Shows the book on Discord as the reader moves through it; the presence clears itself
+// once the updates stop.
+export const useDiscordPresence = (bookKey: string) => {
+  const { appService } = useEnv();
+  const { settings } = useSettingsStore();
+  const { getBookData } = useBookDataStore();
+  const { getProgress } = useReaderStore();
+  const progress = getProgress(bookKey);
+  const sessionStart = useRef(Date.now());
+  const mode = settings.discordPresence;
+
+  useEffect(() => {
+    if (!appService?.isDesktopApp || !progress) return;
+    if (mode === 'off') {
+      clearBookPresence().catch((e) => console.warn('Failed to clear Discord presence:', e));
+      return;
+    }
+    const bookData = getBookData(bookKey);
+    const book = bookData?.book;
+    if (!book) return;
+    const pageInfo = bookData.isFixedLayout ? progress.section : progress.pageinfo;
+    const hasPages = bookData.isFixedLayout && pageInfo && pageInfo.total > 0;
+    updateBookPresence({
+      bookHash: book.hash,
+      title: book.title,
+      author: book.author || undefined,
+      coverUrl: book.coverImageUrl || undefined,
+      sessionStart: sessionStart.current,
+      genre: getGenre(bookData.bookDoc?.metadata.subject),
+      chapter: progress.sectionLabel || undefined,
+      progress: pageInfo?.total ? ((pageInfo.current + 1) / pageInfo.total) * 100 : undefined,
+      page: hasPages ? pageInfo.current + 1 : undefined,
+      totalPages: hasPages ? pageInfo.total : undefined,
+      privacy: mode,
+    }).catch((e) => console.warn('Failed to update Discord presence:', e));
+    // eslint-disable-next-line react-hooks/exhaustive-deps
+  }, [progress, mode, bookKey]);
+
+  useEffect(() => {
+    return () => {
+      if (appService?.isDesktopApp) clearBookPresence().catch(() => {});
+    };
+    // eslint-disable-next-line react-hooks/exhaustive-deps
+  }, []);
+};
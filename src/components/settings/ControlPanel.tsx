@@ -9,6 +9,12 @@ import { useResetViewSettings } from '@/hooks/useResetSettings';
 import { useEinkMode } from '@/hooks/useEinkMode';
 import { getStyles } from '@/utils/style';
 import { getMaxInlineSize } from '@/utils/config';
+import {
+  DeviceProfileState,
+  getContentFilter,
+  getDeviceProfile,
+  setDeviceProfile,
+} from '@/utils/deviceProfile';
 import { saveSysSettings, saveViewSettings } from '@/helpers/settings';
 import { SettingsPanelPanelProp } from './SettingsDialog';
 import { annotationToolQuickActions } from '@/app/reader/components/annotator/AnnotationTools';
@@ -45,6 +51,7 @@ const ControlPanel: React.FC<SettingsPanelPanelProp> = ({ bookKey, onRegisterRes
   const [isEink, setIsEink] = useState(viewSettings.isEink);
   const [autoScreenBrightness, setAutoScreenBrightness] = useState(settings.autoScreenBrightness);
   const [allowScript, setAllowScript] = useState(viewSettings.allowScript);
+  const [deviceProfiles, setDeviceProfiles] = useState<DeviceProfileState | null>(null);
 
   const resetToDefaults = useResetViewSettings();
 
@@ -143,10 +150,19 @@ const ControlPanel: React.FC<SettingsPanelPanelProp> = ({ bookKey, onRegisterRes
     } else {
       getView(bookKey)?.renderer.removeAttribute('eink');
     }
+    const view = getView(bookKey);
+    if (view) view.style.filter = getContentFilter(appService?.deviceProfile ?? null, isEink);
     applyEinkMode(isEink);
     // eslint-disable-next-line react-hooks/exhaustive-deps
   }, [isEink]);
 
+  useEffect(() => {
+    if (!appService?.isAndroidApp) return;
+    getDeviceProfile()
+      .then(setDeviceProfiles)
+      .catch((error) => console.error('Failed to get device profile:', error));
+  }, [appService]);
+
   useEffect(() => {
     if (autoScreenBrightness === settings.autoScreenBrightness) return;
     saveSysSettings(envConfig, 'autoScreenBrightness', autoScreenBrightness);
@@ -191,6 +207,37 @@ const ControlPanel: React.FC<SettingsPanelPanelProp> = ({ bookKey, onRegisterRes
     ];
   };
 
+  const getDeviceProfileOptions = () => {
+    if (!deviceProfiles) return [];
+    return [
+      {
+        value: '',
+        label: _('Detected ({{name}})', { name: deviceProfiles.detected.name }),
+      },
+      ...deviceProfiles.profiles.map((profile) => ({
+        value: profile.id,
+        label: profile.name,
+      })),
+    ];
+  };
+
+  const handleSelectDeviceProfile = async (event: React.ChangeEvent<HTMLSelectElement>) => {
+    try {
+      const state = await setDeviceProfile(event.target.value || null);
+      setDeviceProfiles(state);
+      if (appService) {
+        appService.deviceProfile = state.active;
+      }
+      // Picking a profile for another kind of display switches e-ink mode along with it
+      const eink = state.active.display !== 'lcd';
+      setIsEink(eink);
+      const view = getView(bookKey);
+      if (view) view.style.filter = getContentFilter(state.active, eink);
+    } catch (error) {
+      console.error('Failed to set device profile:', error);
+    }
+  };
+
   const handleSelectAnnotationQuickAction = (event: React.ChangeEvent<HTMLSelectElement>) => {
     const action = event.target.value as typeof annotationQuickAction;
     setAnnotationQuickAction(action);
@@ -369,6 +416,16 @@ const ControlPanel: React.FC<SettingsPanelPanelProp> = ({ bookKey, onRegisterRes
                   />
                 </div>
               )}
+              {deviceProfiles && (
+                <div className='config-item'>
+                  <span className=''>{_('Device Profile')}</span>
+                  <Select
+                    value={deviceProfiles.overridden ? deviceProfiles.active.id : ''}
+                    onChange={handleSelectDeviceProfile}
+                    options={getDeviceProfileOptions()}
+                  />
+                </div>
+              )}
               {appService?.isMobileApp && (
                 <div className='config-item'>
                   <span className=''>{_('Auto Screen Brightness')}</span>
@@ -33,9 +33,13 @@ import NumberInput from './NumberInput';
 import FontDropdown from './FontDropDown';
 import CustomFonts from './CustomFonts';
 
-const genCJKFontsList = (sysFonts: string[]) => {
+const CJK_SCRIPTS = ['Hani', 'Hira', 'Kana', 'Hang'];
+
+const genCJKFontsList = (sysFonts: string[], coveredFonts: string[] = []) => {
   return Array.from(new Set([...sysFonts, ...CJK_SERIF_FONTS, ...CJK_SANS_SERIF_FONTS]))
-    .filter((font) => CJK_FONTS_PATTENS.test(font) || isCJKStr(font))
+    .filter(
+      (font) => CJK_FONTS_PATTENS.test(font) || isCJKStr(font) || coveredFonts.includes(font),
+    )
     .filter((font) => !CJK_EXCLUDE_PATTENS.test(font))
     .sort((a, b) => a.localeCompare(b));
 };
@@ -129,6 +133,8 @@ const FontPanel: React.FC<SettingsPanelPanelProp> = ({ bookKey, onRegisterReset
       break;
   }
   const [sysFonts, setSysFonts] = useState<string[]>(defaultSysFonts);
+  const [sysCJKFonts, setSysCJKFonts] = useState<string[]>([]);
+  const [sysMonospaceFonts, setSysMonospaceFonts] = useState<string[]>([]);
   const [defaultFont, setDefaultFont] = useState(viewSettings.defaultFont);
   const [defaultFontSize, setDefaultFontSize] = useState(viewSettings.defaultFontSize);
   const [minFontSize, setMinFontSize] = useState(viewSettings.minimumFontSize);
@@ -175,10 +181,10 @@ const FontPanel: React.FC<SettingsPanelPanelProp> = ({ bookKey, onRegisterReset
 
   useEffect(() => {
     setCJKFonts((prev) => {
-      const newFonts = genCJKFontsList([...customFonts, ...sysFonts]);
+      const newFonts = genCJKFontsList([...customFonts, ...sysFonts], sysCJKFonts);
       return prev.length !== newFonts.length ? newFonts : prev;
     });
-  }, [customFonts, sysFonts]);
+  }, [customFonts, sysFonts, sysCJKFonts]);
 
   useEffect(() => {
     setCustomFonts(getFontFamilies());
@@ -212,6 +218,22 @@ const FontPanel: React.FC<SettingsPanelPanelProp> = ({ bookKey, onRegisterReset
           }
         });
         setSysFonts([...new Set(processedFonts)].sort((a, b) => a.localeCompare(b)));
+
+        // Match faces by either name since processedFonts mixes family and font names
+        const faces = res.faces ?? [];
+        const namesOf = (matches: typeof faces) => [
+          ...new Set(
+            processedFonts.filter((font) =>
+              matches.some((face) => face.name === font || face.family === font),
+            ),
+          ),
+        ];
+        setSysCJKFonts(
+          namesOf(faces.filter((face) => face.scripts.some((s) => CJK_SCRIPTS.includes(s)))),
+        );
+        setSysMonospaceFonts(
+          namesOf(faces.filter((face) => face.monospace)).sort((a, b) => a.localeCompare(b)),
+        );
       });
     }
   }, [appService]);
@@ -403,7 +425,7 @@ const FontPanel: React.FC<SettingsPanelPanelProp> = ({ bookKey, onRegisterReset
               family='monospace'
               label={_('Monospace Font')}
               options={[...customFonts, ...MONOSPACE_FONTS]}
-              moreOptions={sysFonts}
+              moreOptions={sysMonospaceFonts.length > 0 ? sysMonospaceFonts : sysFonts}
               selected={monospaceFont}
               onSelect={setMonospaceFont}
             />
@@ -0,0 +1,9 @@
+/** Emitted by the Rust router for every validated `hackxindia26://` link. */
+export const DEEP_LINK_EVENT = 'deep-link';
+
+export type DeepLink =
+  | { route: 'open'; book: string; cfi: string | null }
+  | { route: 'import'; url: string }
+  | { route: 'annotation'; id: string };
+
+export const isDeepLinkUrl = (url: string) => /^hackxindia26:/i.test(url);
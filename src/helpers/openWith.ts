@@ -1,6 +1,7 @@
-import { isWebAppPlatform, hasCli } from '@/services/environment';
+import { isWebAppPlatform } from '@/services/environment';
 import { AppService } from '@/types/system';
 import { getCurrent } from '@tauri-apps/plugin-deep-link';
+import { getStartupState, takeStartupFiles } from './startup';
 
 declare global {
   interface Window {
@@ -64,7 +65,10 @@ export const parseOpenWithFiles = async (appService: AppService | null) => {
   if (isWebAppPlatform()) return [];
 
   let files = parseWindowOpenWithFiles();
-  if ((!files || files.length === 0) && hasCli()) {
+  if (!files || files.length === 0) {
+    files = await takeStartupFiles();
+  }
+  if (files.length === 0 && (await getStartupState()).cliAccess) {
     files = await parseCLIOpenWithFiles();
   }
   if (!files || files.length === 0) {
@@ -0,0 +1,42 @@
+import { invoke } from '@tauri-apps/api/core';
+import { DeviceProfile } from '@/utils/deviceProfile';
+import { DeepLink } from './deepLink';
+
+export interface PortablePaths {
+  executableDir: string;
+  dataDir: string;
+  cacheDir: string;
+  logDir: string;
+}
+
+export interface StartupState {
+  files: string[];
+  deepLinks: DeepLink[];
+  isEink: boolean;
+  deviceProfile: DeviceProfile;
+  hasUpdater: boolean;
+  cliAccess: boolean;
+  // Set when the app runs in portable mode and keeps everything next to the executable.
+  portable: PortablePaths | null;
+}
+
+let startupState: Promise<StartupState> | null = null;
+
+/**
+ * Fetched from the backend once per page. The pending files and deep links are only
+ * handed to the first window that asks, and are emptied here by whoever takes them.
+ */
+export const getStartupState = () => {
+  if (!startupState) {
+    startupState = invoke<StartupState>('get_startup_state');
+  }
+  return startupState;
+};
+
+export const takeStartupFiles = async () => (await getStartupState()).files.splice(0);
+
+export const takeStartupDeepLinks = async () => (await getStartupState()).deepLinks.splice(0);
+
+// Books and deep links arriving after the startup state are held back until the window
+// listens for them, and are emitted once this is called.
+export const frontendReady = () => invoke<void>('frontend_ready');
@@ -3,11 +3,22 @@ import { useRouter } from 'next/navigation';
 import { useEnv } from '@/context/EnvContext';
 import { useLibraryStore } from '@/store/libraryStore';
 import { useSettingsStore } from '@/store/settingsStore';
+import { useReaderStore } from '@/store/readerStore';
 import { addPluginListener, PluginListener } from '@tauri-apps/api/core';
 import { onOpenUrl } from '@tauri-apps/plugin-deep-link';
 import { getCurrentWindow, getAllWindows } from '@tauri-apps/api/window';
+import { emitTo } from '@tauri-apps/api/event';
 import { isTauriAppPlatform } from '@/services/environment';
-import { navigateToLibrary, showLibraryWindow } from '@/utils/nav';
+import {
+  focusBookWindow,
+  navigateToLibrary,
+  navigateToReader,
+  showLibraryWindow,
+  showReaderWindow,
+} from '@/utils/nav';
+import { getAnnotation } from '@/utils/annotations';
+import { DEEP_LINK_EVENT, DeepLink, isDeepLinkUrl } from '@/helpers/deepLink';
+import { frontendReady, takeStartupDeepLinks } from '@/helpers/startup';
 
 interface SingleInstancePayload {
   args: string[];
@@ -25,7 +36,7 @@ interface SharedIntentPayload {
 export function useOpenWithBooks() {
   const router = useRouter();
   const { appService } = useEnv();
-  const { setCheckOpenWithBooks } = useLibraryStore();
+  const { setCheckOpenWithBooks, setLibrary } = useLibraryStore();
   const listenedOpenWithBooks = useRef(false);
 
   const isFirstWindow = async () => {
@@ -46,7 +57,8 @@ export function useOpenWithBooks() {
           url = decodeURI(url.replace('file://', ''));
         }
       }
-      if (!/^(https?:|data:|blob:)/i.test(url)) {
+      // hackxindia26:// links are validated and routed by the backend
+      if (!/^(https?:|data:|blob:)/i.test(url) && !isDeepLinkUrl(url)) {
         filePaths.push(url);
       }
     }
@@ -64,6 +76,83 @@ export function useOpenWithBooks() {
     }
   };
 
+  const loadLibrary = async () => {
+    const { library } = useLibraryStore.getState();
+    return library.length > 0 ? library : await appService!.loadLibraryBooks();
+  };
+
+  const openBooks = (bookIds: string[]) => {
+    const settings = useSettingsStore.getState().settings;
+    if (appService?.hasWindow && settings.openBookInNewWindow) {
+      showReaderWindow(appService, bookIds);
+    } else {
+      navigateToReader(router, bookIds);
+    }
+  };
+
+  const openBookAt = async (hash: string, cfi: string | null) => {
+    const views = useReaderStore.getState().getViewsById(hash);
+    if (views.length > 0) {
+      if (cfi) views[0]?.goTo(cfi);
+      return;
+    }
+    if (appService?.hasWindow) {
+      // Hand the link over to the window that already shows the book
+      const label = await focusBookWindow(hash);
+      if (label) {
+        if (cfi) await emitTo(label, DEEP_LINK_EVENT, { route: 'open', book: hash, cfi });
+        return;
+      }
+    }
+    const book = (await loadLibrary()).find((b) => b.hash === hash && !b.deletedAt);
+    if (!book) {
+      console.warn('Deep link to a book not in the library:', hash);
+      return;
+    }
+    if (cfi) {
+      const settings = useSettingsStore.getState().settings;
+      const config = await appService!.loadBookConfig(book, settings);
+      await appService!.saveBookConfig(book, { ...config, location: cfi }, settings);
+    }
+    openBooks([book.hash]);
+  };
+
+  const handleDeepLink = async (link: DeepLink) => {
+    console.log('Handle deep link:', link);
+    switch (link.route) {
+      case 'open':
+        await openBookAt(link.book, link.cfi);
+        break;
+      case 'import': {
+        const library = await loadLibrary();
+        const book = await appService!.importBook(link.url, library);
+        if (book) {
+          setLibrary(library);
+          await appService!.saveLibraryBooks(library);
+          openBooks([book.hash]);
+        }
+        break;
+      }
+      case 'annotation': {
+        const annotation = await getAnnotation(link.id);
+        if (annotation) {
+          await openBookAt(annotation.bookHash, annotation.cfi);
+        } else {
+          console.warn('Deep link to an unknown annotation:', link.id);
+        }
+        break;
+      }
+    }
+  };
+
+  const handleDeepLinks = async (links: DeepLink[]) => {
+    for (const link of links) {
+      await handleDeepLink(link).catch((error) => {
+        console.error('Failed to handle deep link:', link, error);
+      });
+    }
+  };
+
   const initializeListeners = async () => {
     return await addPluginListener<SharedIntentPayload>(
       'native-bridge',
@@ -93,7 +182,7 @@ export function useOpenWithBooks() {
       },
     );
 
-    // macOS in-app open-files event
+    // Books opened while the app is running (macOS open-file events, File > Open)
     const unlistenOpenFiles = getCurrentWindow().listen<OpenFilesPayload>(
       'open-files',
       ({ payload }) => {
@@ -113,6 +202,17 @@ export function useOpenWithBooks() {
       unlistenSharedIntent = initializeListeners();
     }
 
+    // hackxindia26:// links routed by the backend
+    const unlistenDeepLinks = getCurrentWindow().listen<DeepLink>(
+      DEEP_LINK_EVENT,
+      ({ payload }) => {
+        handleDeepLinks([payload]);
+      },
+    );
+    unlistenDeepLinks.then(() => takeStartupDeepLinks()).then(handleDeepLinks);
+    // The backend holds books and links back until every listener above is in place
+    Promise.all([unlistenDeeplink, unlistenOpenFiles, unlistenDeepLinks]).then(frontendReady);
+
     // iOS Open with URL event
     const listenOpenWithFiles = async () => {
       return await onOpenUrl((urls) => {
@@ -123,6 +223,7 @@ export function useOpenWithBooks() {
     return () => {
       unlistenDeeplink.then((f) => f());
       unlistenOpenFiles.then((f) => f());
+      unlistenDeepLinks.then((f) => f());
       unlistenOpenUrl.then((f) => f());
       unlistenSharedIntent?.then((f) => f.unregister());
     };
@@ -81,6 +81,7 @@ import { CustomTextureInfo } from '@/styles/textures';
 import { CustomFont, CustomFontInfo } from '@/styles/fonts';
 import { parseFontInfo } from '@/utils/font';
 import { svg2png } from '@/utils/svg';
+import { DeviceProfile } from '@/utils/deviceProfile';
 
 export abstract class BaseAppService implements AppService {
   osPlatform: OsPlatform = getOSPlatform();
@@ -96,6 +97,7 @@ export abstract class BaseAppService implements AppService {
   isPortableApp = false;
   isDesktopApp = false;
   isEink = false;
+  deviceProfile: DeviceProfile | null = null;
   hasTrafficLight = false;
   hasWindow = false;
   hasWindowBar = false;
@@ -215,6 +217,9 @@ export abstract class BaseAppService implements AppService {
       ...DEFAULT_BOOK_LANGUAGE,
       ...(this.isMobile ? DEFAULT_MOBILE_VIEW_SETTINGS : {}),
       ...(this.isEink ? DEFAULT_EINK_VIEW_SETTINGS : {}),
+      ...(this.isEink && this.deviceProfile
+        ? { animated: this.deviceProfile.pageTurnAnimation === 'enabled' }
+        : {}),
       ...(isCJKEnv() ? DEFAULT_CJK_VIEW_SETTINGS : {}),
       ...DEFAULT_VIEW_CONFIG,
       ...DEFAULT_TTS_CONFIG,
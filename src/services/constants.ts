@@ -69,6 +69,7 @@ export const DEFAULT_SYSTEM_SETTINGS: Partial<SystemSettings> = {
   openLastBooks: false,
   lastOpenBooks: [],
   autoImportBooksOnOpen: false,
+  discordPresence: 'off',
   telemetryEnabled: true,
   libraryViewMode: 'grid',
   librarySortBy: 'updated',
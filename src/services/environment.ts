@@ -1,15 +1,8 @@
 import { AppService } from '@/types/system';
 import { HackXIndia26_NODE_BASE_URL, HackXIndia26_WEB_BASE_URL } from './constants';
 
-declare global {
-  interface Window {
-    __HackXIndia26_CLI_ACCESS?: boolean;
-  }
-}
-
 export const isTauriAppPlatform = () => process.env['NEXT_PUBLIC_APP_PLATFORM'] === 'tauri';
 export const isWebAppPlatform = () => process.env['NEXT_PUBLIC_APP_PLATFORM'] === 'web';
-export const hasCli = () => window.__HackXIndia26_CLI_ACCESS === true;
 export const isPWA = () => window.matchMedia('(display-mode: standalone)').matches;
 export const getBaseUrl = () => process.env['NEXT_PUBLIC_API_BASE_URL'] ?? HackXIndia26_WEB_BASE_URL;
 export const getNodeBaseUrl = () =>
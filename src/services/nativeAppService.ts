@@ -40,6 +40,7 @@ import { getDirPath, getFilename } from '@/utils/path';
 import { NativeFile, RemoteFile } from '@/utils/file';
 import { copyURIToPath } from '@/utils/bridge';
 import { copyFiles } from '@/utils/files';
+import { getStartupState, PortablePaths } from '@/helpers/startup';
 
 import { BaseAppService } from './appService';
 import {
@@ -47,35 +48,34 @@ import {
   LOCAL_BOOKS_SUBDIR,
   LOCAL_FONTS_SUBDIR,
   LOCAL_IMAGES_SUBDIR,
-  SETTINGS_FILENAME,
 } from './constants';
 
-declare global {
-  interface Window {
-    __HackXIndia26_UPDATER_DISABLED?: boolean;
-    __HackXIndia26_IS_EINK?: boolean;
-  }
-}
-
 const OS_TYPE = osType();
 
+// Portable builds are flagged at build time as well; should the backend not have been
+// built with the flag, the portable folder is laid out as in portable.rs.
+const getBuildPortablePaths = async (): Promise<PortablePaths> => {
+  const executableDir = await invoke<string>('get_executable_dir');
+  const dataDir = `${executableDir}/HackXIndia26`;
+  return { executableDir, dataDir, cacheDir: `${dataDir}/Cache`, logDir: `${dataDir}/Logs` };
+};
+
 // Helper function to create a path resolver based on custom root directory and portable mode
 // 0. If no custom root dir and not portable mode, use default Tauri BaseDirectory
 // 1. If custom root dir is set, use it as base dir (baseDir = 0)
-// 2. If portable mode is detected (Settings.json in executable dir), use executable dir as base dir (baseDir = 0)
-// 3. If both custom root dir and portable mode are set, use custom root dir as base dir (baseDir = 0)
+// 2. In portable mode (see portable.rs), settings, cache and logs go to the portable folder
+//    and the executable dir is the root for data unless a custom root dir is set
 // Path Resolver Usage:
 //  - appService.resolvePath and use returned baseDir + fp, when baseDir is 0, fp will be absolute path
 //  - fileSystem.getPrefix and use prefix + path
 const getPathResolver = ({
   customRootDir,
-  isPortable,
-  execDir,
+  portable,
 }: {
   customRootDir?: string;
-  isPortable?: boolean;
-  execDir?: string;
+  portable?: PortablePaths | null;
 } = {}) => {
+  customRootDir ||= portable?.executableDir;
   const customBaseDir = customRootDir ? 0 : undefined;
   const isCustomBaseDir = Boolean(customRootDir);
   const getCustomBasePrefixSync = isCustomBaseDir
@@ -98,19 +98,27 @@ const getPathResolver = ({
     switch (base) {
       case 'Settings':
         return {
-          baseDir: isPortable ? 0 : BaseDirectory.AppConfig,
-          basePrefix: isPortable && execDir ? async () => execDir : appConfigDir,
-          fp: isPortable && execDir ? `${execDir}${path ? `/${path}` : ''}` : path,
+          baseDir: portable ? 0 : BaseDirectory.AppConfig,
+          basePrefix: portable ? async () => portable.dataDir : appConfigDir,
+          fp: portable ? `${portable.dataDir}${path ? `/${path}` : ''}` : path,
           base,
         };
       case 'Cache':
         return {
-          baseDir: BaseDirectory.AppCache,
-          basePrefix: appCacheDir,
-          fp: path,
+          baseDir: portable ? 0 : BaseDirectory.AppCache,
+          basePrefix: portable ? async () => portable.cacheDir : appCacheDir,
+          fp: portable ? `${portable.cacheDir}${path ? `/${path}` : ''}` : path,
           base,
         };
       case 'Log':
+        if (portable) {
+          return {
+            baseDir: 0,
+            basePrefix: async () => portable.logDir,
+            fp: `${portable.logDir}${path ? `/${path}` : ''}`,
+            base,
+          };
+        }
         return {
           baseDir: isCustomBaseDir ? 0 : BaseDirectory.AppLog,
           basePrefix: customBasePrefix ?? appLogDir,
@@ -399,7 +407,6 @@ export class NativeAppService extends BaseAppService {
   override isLinuxApp = OS_TYPE === 'linux';
   override isMobileApp = ['android', 'ios'].includes(OS_TYPE);
   override isDesktopApp = ['macos', 'windows', 'linux'].includes(OS_TYPE);
-  override isEink = Boolean(window.__HackXIndia26_IS_EINK);
   override hasTrafficLight = OS_TYPE === 'macos';
   override hasWindow = !(OS_TYPE === 'ios' || OS_TYPE === 'android');
   override hasWindowBar = !(OS_TYPE === 'ios' || OS_TYPE === 'android');
@@ -407,14 +414,12 @@ export class NativeAppService extends BaseAppService {
   override hasRoundedWindow = OS_TYPE === 'linux';
   override hasSafeAreaInset = OS_TYPE === 'ios' || OS_TYPE === 'android';
   override hasHaptics = OS_TYPE === 'ios' || OS_TYPE === 'android';
-  override hasUpdater =
-    OS_TYPE !== 'ios' &&
-    !process.env['NEXT_PUBLIC_DISABLE_UPDATER'] &&
-    !window.__HackXIndia26_UPDATER_DISABLED;
+  override hasUpdater = OS_TYPE !== 'ios' && !process.env['NEXT_PUBLIC_DISABLE_UPDATER'];
   // orientation lock is not supported on iPad
   override hasOrientationLock =
     (OS_TYPE === 'ios' && getOSPlatform() === 'ios') || OS_TYPE === 'android';
-  override hasScreenBrightness = OS_TYPE === 'ios' || OS_TYPE === 'android';
+  override hasScreenBrightness =
+    OS_TYPE === 'ios' || OS_TYPE === 'android' || OS_TYPE === 'linux';
   override hasIAP = OS_TYPE === 'ios' || (OS_TYPE === 'android' && DIST_CHANNEL === 'playstore');
   // CustomizeRootDir has a blocker on macOS App Store builds due to Security Scoped Resource restrictions.
   // See: https://github.com/tauri-apps/tauri/issues/3716
@@ -422,28 +427,26 @@ export class NativeAppService extends BaseAppService {
   override canReadExternalDir = DIST_CHANNEL !== 'appstore' && DIST_CHANNEL !== 'playstore';
   override distChannel = DIST_CHANNEL;
 
-  private execDir?: string = undefined;
+  private portable: PortablePaths | null = null;
 
   override async init() {
-    const execDir = await invoke<string>('get_executable_dir');
-    this.execDir = execDir;
-    if (
-      process.env['NEXT_PUBLIC_PORTABLE_APP'] ||
-      (await this.fs.exists(`${execDir}/${SETTINGS_FILENAME}`, 'None'))
-    ) {
+    const startup = await getStartupState();
+    this.isEink = startup.isEink;
+    this.deviceProfile = startup.deviceProfile;
+    this.hasUpdater = this.hasUpdater && startup.hasUpdater;
+    const portable =
+      startup.portable ??
+      (process.env['NEXT_PUBLIC_PORTABLE_APP'] ? await getBuildPortablePaths() : null);
+    if (portable) {
       this.isPortableApp = true;
-      this.fs.resolvePath = getPathResolver({
-        customRootDir: execDir,
-        isPortable: this.isPortableApp,
-        execDir,
-      });
+      this.portable = portable;
+      this.fs.resolvePath = getPathResolver({ portable: this.portable });
     }
     const settings = await this.loadSettings();
     if (settings.customRootDir) {
       this.fs.resolvePath = getPathResolver({
         customRootDir: settings.customRootDir,
-        isPortable: this.isPortableApp,
-        execDir,
+        portable: this.portable,
       });
     }
     await this.prepareBooksDir();
@@ -481,11 +484,7 @@ export class NativeAppService extends BaseAppService {
   }
 
   async setCustomRootDir(customRootDir: string) {
-    this.fs.resolvePath = getPathResolver({
-      customRootDir,
-      isPortable: this.isPortableApp,
-      execDir: this.execDir,
-    });
+    this.fs.resolvePath = getPathResolver({ customRootDir, portable: this.portable });
     await this.prepareBooksDir();
   }
 
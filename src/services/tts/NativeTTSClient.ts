@@ -4,7 +4,14 @@ import { getUserLocale } from '@/utils/misc';
 import { parseSSMLMarks } from '@/utils/ssml';
 import { stubTranslation as _ } from '@/utils/misc';
 import { TTSClient, TTSMessageEvent } from './TTSClient';
-import { TTSGranularity, TTSMark, TTSVoice, TTSVoicesGroup } from './types';
+import {
+  TTSGranularity,
+  TTSMark,
+  TTSQueue,
+  TTSSleepTimer,
+  TTSVoice,
+  TTSVoicesGroup,
+} from './types';
 import { TTSUtils } from './TTSUtils';
 import { TTSController } from './TTSController';
 
@@ -12,6 +19,20 @@ type TTSEventPayload = {
   utteranceId: string;
 } & TTSMessageEvent;
 
+type QueueEventPayload =
+  | { type: 'start'; id: string; utteranceId: string }
+  | { type: 'drained' }
+  | { type: 'sleep' };
+
+type QueueItem = {
+  id: string;
+  text: string;
+  ssml: boolean;
+  lang?: string;
+  chapter?: string;
+  position?: { cfi: string };
+};
+
 const TTSEngines = {
   default: 'System TTS',
   msctts: 'Msc TTS',
@@ -38,6 +59,7 @@ const TTSEngines = {
 export class NativeTTSClient implements TTSClient {
   name = 'native-tts';
   initialized = false;
+  speaksAhead = true;
   controller?: TTSController;
 
   #voices: TTSVoice[] = [];
@@ -56,6 +78,16 @@ export class NativeTTSClient implements TTSClient {
       finished: boolean;
     }
   >();
+  // Events that arrive before `speak` has returned their utterance id
+  #earlyEvents = new Map<string, TTSMessageEvent[]>();
+
+  #queueListener: PluginListener | null = null;
+  #queueCounter = 0;
+  // Positions of the items in the plugin's queue by id, and the utterances
+  // of those it has started by itself. Paragraphs can repeat, so blocks are
+  // told apart by where they start rather than by their text.
+  #queuedCfis = new Map<string, string>();
+  #startedCfis = new Map<string, string>();
 
   constructor(controller?: TTSController) {
     this.controller = controller;
@@ -70,10 +102,17 @@ export class NativeTTSClient implements TTSClient {
         (event) => {
           const { utteranceId, code, message, mark } = event;
 
+          const ttsEvent: TTSMessageEvent = { code, message, mark };
           const utteranceData = this.#activeUtterances.get(utteranceId);
-          if (!utteranceData) return;
+          if (!utteranceData) {
+            const events = this.#earlyEvents.get(utteranceId) ?? [];
+            this.#earlyEvents.set(utteranceId, [...events, ttsEvent]);
+            if (this.#earlyEvents.size > 16) {
+              this.#earlyEvents.delete(this.#earlyEvents.keys().next().value!);
+            }
+            return;
+          }
 
-          const ttsEvent: TTSMessageEvent = { code, message, mark };
           utteranceData.eventQueue.push(ttsEvent);
           if (code === 'end' || code === 'error') {
             utteranceData.finished = true;
@@ -89,6 +128,71 @@ export class NativeTTSClient implements TTSClient {
     } catch (error) {
       console.error('Failed to setup TTS event listener:', error);
     }
+    try {
+      if (this.#queueListener) return;
+      this.#queueListener = await addPluginListener<QueueEventPayload>(
+        'native-tts',
+        'queue_events',
+        (event) => {
+          if (event.type === 'start') {
+            const cfi = this.#queuedCfis.get(event.id);
+            if (cfi === undefined) return;
+            this.#queuedCfis.delete(event.id);
+            this.#startedCfis.set(cfi, event.utteranceId);
+            if (this.#startedCfis.size > 8) {
+              this.#startedCfis.delete(this.#startedCfis.keys().next().value!);
+            }
+          } else if (event.type === 'sleep') {
+            this.controller?.dispatchSleep();
+          }
+        },
+      );
+    } catch (error) {
+      console.error('Failed to setup TTS queue listener:', error);
+    }
+  }
+
+  // Queue the block with the ones after it in the same language, so the plugin
+  // reads on without waiting for the webview. Returns the utterance of the block.
+  async #enqueue(text: string, lang: string, queue: TTSQueue) {
+    const started = queue.cfi ? this.#startedCfis.get(queue.cfi) : undefined;
+    if (queue.cfi) this.#startedCfis.delete(queue.cfi);
+    const chapter = String(queue.section);
+    const blocks = [{ ssml: text, cfi: queue.cfi }];
+    for (const block of queue.upcoming) {
+      const { marks } = parseSSMLMarks(block.ssml, this.#primaryLang);
+      if (!marks.length || marks.some((mark) => mark.language !== marks[0]!.language)) break;
+      const voiceId = await this.getVoiceIdFromLang(marks[0]!.language);
+      if (voiceId !== this.#currentVoiceId) break;
+      blocks.push(block);
+    }
+    const items: QueueItem[] = blocks.map(({ ssml, cfi }) => {
+      const id = `${++this.#queueCounter}`;
+      if (cfi) {
+        this.#queuedCfis.set(id, cfi);
+        if (this.#queuedCfis.size > 32) {
+          this.#queuedCfis.delete(this.#queuedCfis.keys().next().value!);
+        }
+      }
+      return { id, text: ssml, ssml: true, lang, chapter, position: cfi ? { cfi } : undefined };
+    });
+    if (started) {
+      if (items.length > 1) {
+        await invoke('plugin:native-tts|enqueue', {
+          payload: { book: queue.book || undefined, items: items.slice(1), mode: 'append' },
+        });
+      }
+      return started;
+    }
+    // Reading on appends, which joins the block if the plugin is already speaking it.
+    const result = await invoke<{ utteranceId: string | null }>('plugin:native-tts|enqueue', {
+      payload: {
+        book: queue.book || undefined,
+        items,
+        mode: queue.continued ? 'append' : 'replace',
+      },
+    });
+    return result.utteranceId;
   }
 
   async init(): Promise<boolean> {
@@ -105,21 +209,48 @@ export class NativeTTSClient implements TTSClient {
       yield { code: 'end', message: 'Dummy preload finished' } as TTSMessageEvent;
       return;
     }
-    const { language: voiceLang } = mark;
-    const voiceId = await this.getVoiceIdFromLang(voiceLang);
-    this.#currentVoiceId = voiceId;
-    this.#speakingLang = voiceLang;
-    await this.setVoice(voiceId);
+    for await (const event of this.speakText(mark.text, false, mark.language, signal)) {
+      event.mark = mark.name;
+      yield event;
+    }
+  }
+
+  private async *speakText(
+    text: string,
+    ssml: boolean,
+    lang: string,
+    signal: AbortSignal,
+    queue?: TTSQueue,
+  ) {
+    const voiceId = await this.getVoiceIdFromLang(lang);
+    // Reading on keeps the voice the queue is speaking with.
+    if (!queue?.continued || voiceId !== this.#currentVoiceId) {
+      this.#currentVoiceId = voiceId;
+      await this.setVoice(voiceId);
+    }
+    this.#speakingLang = lang;
     try {
-      const result = await invoke<{ utteranceId: string }>('plugin:native-tts|speak', {
-        payload: { text: mark.text, preload },
-      });
+      // The voice's locale tells the plugin how to read dates and numbers.
+      const voiceLang = this.#voices.find((v) => v.id === voiceId)?.lang ?? lang;
+      const utteranceId = queue
+        ? await this.#enqueue(text, voiceLang, queue)
+        : (
+            await invoke<{ utteranceId: string }>('plugin:native-tts|speak', {
+              payload: { text, ssml, lang: voiceLang },
+            })
+          ).utteranceId;
+      // The sleep timer went off instead.
+      if (!utteranceId) {
+        yield { code: 'error', message: 'Stopped by the sleep timer' } as TTSMessageEvent;
+        return;
+      }
 
-      const utteranceId = result.utteranceId;
+      const earlyEvents = this.#earlyEvents.get(utteranceId) ?? [];
+      this.#earlyEvents.delete(utteranceId);
       this.#activeUtterances.set(utteranceId, {
-        eventQueue: [],
+        eventQueue: earlyEvents,
         resolver: null,
-        finished: false,
+        finished: earlyEvents.some((e) => e.code === 'end' || e.code === 'error'),
       });
 
       const abortHandler = () => {
@@ -141,7 +272,6 @@ export class NativeTTSClient implements TTSClient {
 
           if (utteranceData.eventQueue.length > 0) {
             const event = utteranceData.eventQueue.shift()!;
-            event.mark = mark.name;
             yield event;
 
             if (event.code === 'end' || event.code === 'error') {
@@ -177,9 +307,37 @@ export class NativeTTSClient implements TTSClient {
     }
   }
 
-  async *speak(ssml: string, signal: AbortSignal, preload: boolean = false) {
+  async *speak(ssml: string, signal: AbortSignal, preload: boolean = false, queue?: TTSQueue) {
     const { marks } = parseSSMLMarks(ssml, this.#primaryLang);
 
+    // The plugin fetches Edge speech ahead into its cache.
+    if (preload && this.#currentVoiceId.startsWith('edgetts_')) {
+      const voiceLang = this.#voices.find((v) => v.id === this.#currentVoiceId)?.lang;
+      await invoke('plugin:native-tts|speak', {
+        payload: { text: ssml, ssml: true, preload: true, lang: voiceLang },
+      }).catch((error) => console.warn('Failed to preload speech:', error));
+    }
+
+    // A single-language document is spoken as one utterance, and the plugin
+    // reports each mark as speech reaches it.
+    const languages = new Set(marks.map((mark) => mark.language));
+    if (!preload && languages.size === 1) {
+      const marksByName = new Map(marks.map((mark) => [mark.name, mark]));
+      const lang = marks[0]!.language;
+      for await (const ev of this.speakText(ssml, true, lang, signal, queue)) {
+        if (signal.aborted) {
+          yield { code: 'error', message: 'Aborted' } as TTSMessageEvent;
+          return;
+        }
+        const mark = ev.code === 'boundary' && ev.mark ? marksByName.get(ev.mark) : undefined;
+        if (mark) {
+          this.controller?.dispatchSpeakMark(mark);
+        }
+        yield ev;
+      }
+      return;
+    }
+
     for (const mark of marks) {
       this.controller?.dispatchSpeakMark(mark);
       for await (const ev of this.speakMark(mark, preload, signal)) {
@@ -215,6 +373,22 @@ export class NativeTTSClient implements TTSClient {
   async stop() {
     await invoke('plugin:native-tts|stop');
     this.#activeUtterances.clear();
+    this.#startedCfis.clear();
+  }
+
+  async setSleepTimer(timer: TTSSleepTimer) {
+    await invoke('plugin:native-tts|set_sleep_timer', {
+      payload:
+        timer === 'chapter' ? { endOfChapter: true } : { minutes: timer > 0 ? timer / 60 : null },
+    });
+  }
+
+  async getResumeCFI(book: string) {
+    const { position } = await invoke<{ position: { cfi?: string } | null }>(
+      'plugin:native-tts|get_resume_position',
+      { payload: { book } },
+    );
+    return position?.cfi;
   }
 
   async setRate(rate: number) {
@@ -311,6 +485,10 @@ export class NativeTTSClient implements TTSClient {
       this.#eventListener.unregister();
       this.#eventListener = null;
     }
+    if (this.#queueListener) {
+      this.#queueListener.unregister();
+      this.#queueListener = null;
+    }
     await this.stop();
   }
 }
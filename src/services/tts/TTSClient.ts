@@ -1,4 +1,4 @@
-import { TTSGranularity, TTSVoice, TTSVoicesGroup } from './types';
+import { TTSGranularity, TTSQueue, TTSSleepTimer, TTSVoice, TTSVoicesGroup } from './types';
 
 type TTSMessageCode = 'boundary' | 'error' | 'end';
 
@@ -11,9 +11,16 @@ export interface TTSMessageEvent {
 export interface TTSClient {
   name: string;
   initialized: boolean;
+  // Whether the client goes on to the upcoming blocks by itself
+  speaksAhead?: boolean;
   init(): Promise<boolean>;
   shutdown(): Promise<void>;
-  speak(ssml: string, signal: AbortSignal, preload?: boolean): AsyncIterable<TTSMessageEvent>;
+  speak(
+    ssml: string,
+    signal: AbortSignal,
+    preload?: boolean,
+    queue?: TTSQueue,
+  ): AsyncIterable<TTSMessageEvent>;
   pause(): Promise<boolean>;
   resume(): Promise<boolean>;
   stop(): Promise<void>;
@@ -26,4 +33,5 @@ export interface TTSClient {
   getGranularities(): TTSGranularity[];
   getVoiceId(): string;
   getSpeakingLang(): string;
+  setSleepTimer?(timer: TTSSleepTimer): Promise<void>;
 }
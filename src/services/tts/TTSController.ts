@@ -2,7 +2,14 @@ import { FoliateView } from '@/types/view';
 import { AppService } from '@/types/system';
 import { filterSSMLWithLang, parseSSMLMarks } from '@/utils/ssml';
 import { Overlayer } from 'foliate-js/overlayer.js';
-import { TTSGranularity, TTSHighlightOptions, TTSMark, TTSVoice } from './types';
+import {
+  TTSGranularity,
+  TTSHighlightOptions,
+  TTSMark,
+  TTSQueueBlock,
+  TTSSleepTimer,
+  TTSVoice,
+} from './types';
 import { createRejectFilter } from '@/utils/node';
 import { WebSpeechClient } from './WebSpeechClient';
 import { NativeTTSClient } from './NativeTTSClient';
@@ -26,10 +33,16 @@ export class TTSController extends EventTarget {
   appService: AppService | null = null;
   view: FoliateView;
   isAuthenticated: boolean = false;
+  bookHash: string = '';
   preprocessCallback?: (ssml: string) => Promise<string>;
   #nossmlCnt: number = 0;
   #currentSpeakAbortController: AbortController | null = null;
   #currentSpeakPromise: Promise<void> | null = null;
+  // Set when reading moves on by itself, which a client that speaks ahead
+  // has already done
+  #continued = false;
+  #sleepAtSectionEnd = false;
+  #silentHighlight = false;
 
   state: TTSState = 'stopped';
   ttsLang: string = '';
@@ -54,8 +67,8 @@ export class TTSController extends EventTarget {
     super();
     this.ttsWebClient = new WebSpeechClient(this);
     this.ttsEdgeClient = new EdgeTTSClient(this);
-    // TODO: implement native TTS client for iOS and PC
-    if (appService?.isAndroidApp) {
+    // TODO: implement native TTS client for iOS
+    if (appService?.isAndroidApp || appService?.isDesktopApp) {
       this.ttsNativeClient = new NativeTTSClient(this);
     }
     this.ttsClient = this.ttsWebClient;
@@ -67,7 +80,8 @@ export class TTSController extends EventTarget {
 
   async init() {
     const availableClients = [];
-    if (await this.ttsEdgeClient.init()) {
+    // The desktop native client speaks Edge voices itself, with a disk cache.
+    if (!this.appService?.isDesktopApp && (await this.ttsEdgeClient.init())) {
       availableClients.push(this.ttsEdgeClient);
     }
     if (this.ttsNativeClient && (await this.ttsNativeClient.init())) {
@@ -93,6 +107,7 @@ export class TTSController extends EventTarget {
 
   #getHighlighter() {
     return (range: Range) => {
+      if (this.#silentHighlight) return;
       const { overlayer } = this.view.renderer.getContents()[0] as { overlayer: Overlayer };
       const { style, color } = this.options;
       overlayer?.remove(HIGHLIGHT_KEY);
@@ -133,16 +148,37 @@ export class TTSController extends EventTarget {
 
   async preloadNextSSML(count: number = 4) {
     const tts = this.view.tts;
-    if (!tts) return;
+    const blocks: TTSQueueBlock[] = [];
+    if (!tts) return blocks;
     let preloaded = 0;
     for (let i = 0; i < count; i++) {
-      const ssml = await this.#preprocessSSML(tts.next());
+      const next = tts.next();
+      const cfi = this.#getBlockCFI(next);
+      const ssml = await this.#preprocessSSML(next);
       this.preloadSSML(ssml, new AbortController().signal);
-      if (ssml) preloaded++;
+      if (ssml) {
+        preloaded++;
+        blocks.push({ ssml, cfi });
+      }
     }
     for (let i = 0; i < preloaded; i++) {
       tts.prev();
     }
+    return blocks;
+  }
+
+  #getSectionIndex() {
+    return this.view.renderer.getContents()[0]?.index ?? 0;
+  }
+
+  // Where the block of the current TTS position starts, without highlighting it
+  #getBlockCFI(ssml?: string) {
+    const name = ssml ? parseSSMLMarks(ssml).marks[0]?.name : undefined;
+    if (!name) return;
+    this.#silentHighlight = true;
+    const range = this.view.tts?.setMark(name);
+    this.#silentHighlight = false;
+    return range ? this.view.getCFI(this.#getSectionIndex(), range) : undefined;
   }
 
   async #preprocessSSML(ssml?: string) {
@@ -167,8 +203,14 @@ export class TTSController extends EventTarget {
     return ssml;
   }
 
-  async #speak(ssml: string | undefined | Promise<string>, oneTime = false) {
+  async #speak(
+    ssml: string | undefined | Promise<string>,
+    oneTime = false,
+    upcoming?: Promise<TTSQueueBlock[]>,
+  ) {
     await this.stop();
+    const continued = this.#continued;
+    this.#continued = false;
     this.#currentSpeakAbortController = new AbortController();
     const { signal } = this.#currentSpeakAbortController;
 
@@ -187,7 +229,12 @@ export class TTSController extends EventTarget {
           // FIXME: in case we are at the end of the book, need a better way to handle this
           if (this.#nossmlCnt < 10 && this.state === 'playing' && !oneTime) {
             resolve();
+            if (this.#sleepAtSectionEnd) {
+              this.dispatchSleep();
+              return;
+            }
             await this.view.next();
+            this.#continued = true;
             await this.forward();
           }
           console.log('no SSML, skipping for', this.#nossmlCnt);
@@ -199,12 +246,20 @@ export class TTSController extends EventTarget {
         const { plainText, marks } = parseSSMLMarks(ssml);
         if ((!plainText || marks.length === 0) && !oneTime) {
           resolve();
+          this.#continued = continued;
           return await this.forward();
-        } else {
-          this.dispatchSpeakMark(marks[0]);
         }
+        const range = this.dispatchSpeakMark(marks[0]);
+        const section = this.#getSectionIndex();
         await this.preloadSSML(ssml, signal);
-        const iter = await this.ttsClient.speak(ssml, signal);
+        const queue = {
+          book: this.bookHash,
+          section,
+          cfi: range ? this.view.getCFI(section, range) : undefined,
+          upcoming: (await upcoming) ?? [],
+          continued,
+        };
+        const iter = await this.ttsClient.speak(ssml, signal, false, queue);
         let lastCode;
         for await (const { code } of iter) {
           if (signal.aborted) {
@@ -216,6 +271,7 @@ export class TTSController extends EventTarget {
 
         if (lastCode === 'end' && this.state === 'playing' && !oneTime) {
           resolve();
+          this.#continued = true;
           await this.forward();
         }
         resolve();
@@ -238,8 +294,7 @@ export class TTSController extends EventTarget {
 
   async speak(ssml: string | Promise<string>, oneTime = false) {
     await this.initViewTTS();
-    this.#speak(ssml, oneTime).catch((e) => this.error(e));
-    this.preloadNextSSML();
+    this.#speak(ssml, oneTime, this.preloadNextSSML()).catch((e) => this.error(e));
     this.dispatchSpeakMark();
   }
 
@@ -257,8 +312,7 @@ export class TTSController extends EventTarget {
     if (this.state.includes('paused')) {
       this.resume();
     }
-    this.#speak(ssml);
-    this.preloadNextSSML();
+    this.#speak(ssml, false, this.preloadNextSSML());
   }
 
   async pause() {
@@ -278,7 +332,9 @@ export class TTSController extends EventTarget {
     if (this.#currentSpeakAbortController) {
       this.#currentSpeakAbortController.abort();
     }
-    await this.ttsClient.stop().catch((e) => this.error(e));
+    if (!this.#continued || !this.ttsClient.speaksAhead) {
+      await this.ttsClient.stop().catch((e) => this.error(e));
+    }
 
     if (this.#currentSpeakPromise) {
       const timeout = new Promise((_, reject) =>
@@ -313,18 +369,29 @@ export class TTSController extends EventTarget {
     if (this.state === 'playing') {
       await this.stop();
       if (byMark) this.#speak(this.view.tts?.nextMark());
-      else {
-        this.#speak(this.view.tts?.next());
-        this.preloadNextSSML();
-      }
+      else this.#speak(this.view.tts?.next(), false, this.preloadNextSSML());
     } else {
       await this.stop();
+      this.#continued = false;
       this.state = 'forward-paused';
       if (byMark) this.view.tts?.nextMark(true);
       else this.view.tts?.next(true);
     }
   }
 
+  async setSleepTimer(timer: TTSSleepTimer) {
+    this.#sleepAtSectionEnd = timer === 'chapter';
+    if (this.ttsNativeClient?.initialized) {
+      await this.ttsNativeClient.setSleepTimer?.(timer).catch((e) => this.error(e));
+    }
+  }
+
+  async getResumeCFI() {
+    if (this.ttsNativeClient instanceof NativeTTSClient && this.ttsNativeClient.initialized) {
+      return await this.ttsNativeClient.getResumeCFI(this.bookHash);
+    }
+  }
+
   async setLang(lang: string) {
     this.ttsLang = lang;
     this.setPrimaryLang(lang);
@@ -394,9 +461,15 @@ export class TTSController extends EventTarget {
     if (mark) {
       const range = this.view.tts?.setMark(mark.name);
       this.dispatchEvent(new CustomEvent('tts-highlight-mark', { detail: range }));
+      return range;
     }
   }
 
+  dispatchSleep() {
+    this.#sleepAtSectionEnd = false;
+    this.dispatchEvent(new CustomEvent('tts-sleep'));
+  }
+
   error(e: unknown) {
     console.error(e);
     this.state = 'stopped';
@@ -25,3 +25,22 @@ export type TTSMark = {
   text: string;
   language: string;
 };
+
+export type TTSQueueBlock = {
+  ssml: string;
+  // Where the block starts
+  cfi?: string;
+};
+
+// The blocks around the one being spoken, for clients that read ahead by themselves
+export type TTSQueue = {
+  book: string;
+  section: number;
+  cfi?: string;
+  upcoming: TTSQueueBlock[];
+  // Whether reading reached the block by itself rather than by navigation
+  continued: boolean;
+};
+
+// Seconds, or the end of the chapter; 0 turns the timer off
+export type TTSSleepTimer = number | 'chapter';
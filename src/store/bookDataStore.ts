@@ -1,8 +1,9 @@
 import { create } from 'zustand';
 import { SystemSettings } from '@/types/settings';
 import { Book, BookConfig, BookNote } from '@/types/book';
-import { EnvConfigType } from '@/services/environment';
+import { EnvConfigType, isTauriAppPlatform } from '@/services/environment';
 import { BookDoc } from '@/libs/document';
+import { upsertAnnotations } from '@/utils/annotations';
 import { useLibraryStore } from './libraryStore';
 
 interface BookData {
@@ -90,6 +91,11 @@ export const useBookDataStore = create<BookDataState>((set, get) => ({
     config.updatedAt = Date.now();
     await appService.saveBookConfig(book, config, settings);
     await appService.saveLibraryBooks(library);
+    if (isTauriAppPlatform() && config.booknotes) {
+      upsertAnnotations(book, config.booknotes).catch((e) =>
+        console.warn('Failed to mirror annotations:', e),
+      );
+    }
   },
   updateBooknotes: (key: string, booknotes: BookNote[]) => {
     let updatedConfig: BookConfig | undefined;
@@ -3,17 +3,12 @@ import { AppService } from '@/types/system';
 import { getThemeCode, ThemeCode } from '@/utils/style';
 import { getSystemColorScheme } from '@/utils/bridge';
 import { getCurrentWindow } from '@tauri-apps/api/window';
+import { listen } from '@tauri-apps/api/event';
 import { CustomTheme, Palette, ThemeMode } from '@/styles/themes';
 import { EnvConfigType, isWebAppPlatform } from '@/services/environment';
 import { SystemSettings } from '@/types/settings';
 import { Insets } from '@/types/misc';
 
-declare global {
-  interface Window {
-    __HackXIndia26_IS_EINK?: boolean;
-  }
-}
-
 interface ThemeState {
   themeMode: ThemeMode;
   themeColor: string;
@@ -54,8 +49,7 @@ const getInitialThemeMode = (): ThemeMode => {
 
 const getInitialThemeColor = (): string => {
   if (typeof window !== 'undefined' && localStorage) {
-    const defaultColor = window.__HackXIndia26_IS_EINK ? 'contrast' : 'default';
-    return localStorage.getItem('themeColor') || defaultColor;
+    return localStorage.getItem('themeColor') || 'default';
   }
   return 'default';
 };
@@ -166,10 +160,16 @@ export const loadDataTheme = () => {
 export const initSystemThemeListener = (appService: AppService) => {
   if (typeof window === 'undefined' || !appService) return;
 
+  // E-ink screens start out with the high-contrast palette
+  if (appService.isEink && !localStorage.getItem('themeColor')) {
+    useThemeStore.getState().setThemeColor('contrast');
+  }
+
   const mediaQuery = window.matchMedia('(prefers-color-scheme: dark)');
   const updateColorTheme = async () => {
     let systemIsDarkMode;
-    if (appService.isIOSApp) {
+    // WebKitGTK does not follow the desktop's dark style, so ask the portal on Linux
+    if (appService.isIOSApp || appService.isLinuxApp) {
       const res = await getSystemColorScheme();
       systemIsDarkMode = res.colorScheme === 'dark';
     } else {
@@ -189,5 +189,8 @@ export const initSystemThemeListener = (appService: AppService) => {
   mediaQuery?.addEventListener('change', updateColorTheme);
   document.addEventListener('visibilitychange', updateColorTheme);
   window.addEventListener('resize', updateWindowTheme);
+  if (appService.isLinuxApp) {
+    listen('system-color-scheme-changed', updateColorTheme);
+  }
   updateColorTheme();
 };
@@ -15,6 +15,8 @@ export type LibrarySortByType =
   | 'format'
   | 'published';
 export type LibraryCoverFitType = 'crop' | 'fit';
+// What Discord shows of the book being read, if anything
+export type DiscordPresenceType = 'off' | 'showTitle' | 'showGenre' | 'hidden';
 
 export type KOSyncChecksumMethod = 'binary' | 'filename';
 export type KOSyncStrategy = 'prompt' | 'silent' | 'send' | 'receive';
@@ -64,6 +66,7 @@ export interface SystemSettings {
   openLastBooks: boolean;
   lastOpenBooks: string[];
   autoImportBooksOnOpen: boolean;
+  discordPresence: DiscordPresenceType;
   savedBookCoverForLockScreen: string;
   savedBookCoverForLockScreenPath: string;
   telemetryEnabled: boolean;
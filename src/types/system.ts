@@ -4,6 +4,7 @@ import { BookMetadata } from '@/libs/document';
 import { ProgressHandler } from '@/utils/transfer';
 import { CustomFont, CustomFontInfo } from '@/styles/fonts';
 import { CustomTextureInfo } from '@/styles/textures';
+import { DeviceProfile } from '@/utils/deviceProfile';
 
 export type AppPlatform = 'web' | 'tauri';
 export type OsPlatform = 'android' | 'ios' | 'macos' | 'windows' | 'linux' | 'unknown';
@@ -86,6 +87,7 @@ export interface AppService {
   isPortableApp: boolean;
   isDesktopApp: boolean;
   isEink: boolean;
+  deviceProfile: DeviceProfile | null;
   canCustomizeRootDir: boolean;
   canReadExternalDir: boolean;
   distChannel: DistChannel;
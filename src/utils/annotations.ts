@@ -0,0 +1,80 @@
+import { invoke } from '@tauri-apps/api/core';
+import { Book, BookNote, BookNoteType, HighlightColor } from '@/types/book';
+
+export interface StoredAnnotation extends BookNote {
+  bookHash: string;
+  tags: string[];
+  bookTitle?: string;
+  bookAuthor?: string;
+}
+
+export interface AnnotationFilter {
+  bookHash?: string;
+  tag?: string;
+  color?: HighlightColor;
+  type?: BookNoteType;
+  query?: string;
+  includeDeleted?: boolean;
+  limit?: number;
+}
+
+export interface AnnotationTagCount {
+  tag: string;
+  count: number;
+}
+
+export async function upsertAnnotations(book: Book, booknotes: BookNote[]): Promise<number> {
+  return await invoke<number>('upsert_annotations', {
+    book: {
+      hash: book.hash,
+      metaHash: book.metaHash,
+      title: book.title,
+      author: book.author || null,
+    },
+    annotations: booknotes.map((note) => ({ ...note, bookHash: book.hash })),
+  });
+}
+
+export async function deleteAnnotations(ids: string[]): Promise<number> {
+  return await invoke<number>('delete_annotations', { ids });
+}
+
+export async function setAnnotationTags(id: string, tags: string[]): Promise<void> {
+  await invoke('set_annotation_tags', { id, tags });
+}
+
+export async function queryAnnotations(filter: AnnotationFilter): Promise<StoredAnnotation[]> {
+  return await invoke<StoredAnnotation[]>('query_annotations', { filter });
+}
+
+export async function getAnnotation(id: string): Promise<StoredAnnotation | null> {
+  return await invoke<StoredAnnotation | null>('get_annotation', { id });
+}
+
+export async function listAnnotationTags(): Promise<AnnotationTagCount[]> {
+  return await invoke<AnnotationTagCount[]>('list_annotation_tags');
+}
+
+export type AnnotationExportFormat = 'anki' | 'csv' | 'obsidian' | 'readwise';
+
+export interface AnnotationExportSummary {
+  format: AnnotationExportFormat;
+  files: string[];
+  annotations: number;
+}
+
+/**
+ * Export annotations into `dir`, which must be in the filesystem scope, or into a folder
+ * picked by the user when it is omitted. Resolves to null if the folder dialog is cancelled.
+ */
+export async function exportAnnotations(
+  format: AnnotationExportFormat,
+  filter?: AnnotationFilter,
+  dir?: string,
+): Promise<AnnotationExportSummary | null> {
+  return await invoke<AnnotationExportSummary | null>('export_annotations', {
+    format,
+    filter: filter ?? null,
+    dir: dir ?? null,
+  });
+}
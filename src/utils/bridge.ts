@@ -38,11 +38,32 @@ export interface GetStatusBarHeightResponse {
   error?: string;
 }
 
+export interface SystemFontInfo {
+  name: string;
+  family: string;
+  style: string;
+  weight: number;
+  stretch: number; // CSS font-stretch percentage
+  italic: boolean;
+  monospace: boolean;
+  scripts: string[]; // ISO 15924 codes, e.g. 'Latn', 'Deva', 'Hani'
+  path: string;
+}
+
 export interface GetSystemFontsListResponse {
   fonts: Record<string, string>; // { fontName: fontFamily }
+  faces?: SystemFontInfo[];
   error?: string;
 }
 
+export interface LoadFontFileRequest {
+  path: string;
+}
+
+export interface LoadFontFileResponse {
+  faces: SystemFontInfo[];
+}
+
 export interface InterceptKeysRequest {
   volumeKeys?: boolean;
   backKey?: boolean;
@@ -146,6 +167,13 @@ export async function getSysFontsList(): Promise<GetSystemFontsListResponse> {
   return result;
 }
 
+export async function loadFontFile(request: LoadFontFileRequest): Promise<LoadFontFileResponse> {
+  const result = await invoke<LoadFontFileResponse>('plugin:native-bridge|load_font_file', {
+    payload: request,
+  });
+  return result;
+}
+
 export async function interceptKeys(request: InterceptKeysRequest): Promise<void> {
   await invoke('plugin:native-bridge|intercept_keys', {
     payload: request,
@@ -8,3 +8,8 @@ export function isCfiInLocation(cfi: string, location: string | null | undefined
 
   return CFI.compare(cfi, start) >= 0 && CFI.compare(cfi, end) <= 0;
 }
+
+export function getLaterCfi(a?: string, b?: string): string | undefined {
+  if (!a || !b) return a || b;
+  return CFI.compare(a, b) >= 0 ? a : b;
+}
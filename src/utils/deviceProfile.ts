@@ -0,0 +1,39 @@
+import { invoke } from '@tauri-apps/api/core';
+
+export type DeviceDisplay = 'lcd' | 'grayscale' | 'color';
+
+// What the app knows about the screen it runs on, detected from the device-profile
+// registry or picked by the user.
+export interface DeviceProfile {
+  id: string;
+  name: string;
+  // Color e-ink keeps highlight colors in e-ink mode
+  display: DeviceDisplay;
+  pageTurnAnimation: 'enabled' | 'disabled';
+  // CSS contrast() factor for book content in e-ink mode, 1 for none
+  contrastBoost: number;
+}
+
+export interface DeviceProfileState {
+  active: DeviceProfile;
+  detected: DeviceProfile;
+  // Whether the active profile was picked by the user rather than detected
+  overridden: boolean;
+  profiles: DeviceProfile[];
+}
+
+export async function getDeviceProfile(): Promise<DeviceProfileState> {
+  return await invoke<DeviceProfileState>('get_device_profile');
+}
+
+// Pass null to go back to the detected profile. The window background follows on the
+// next launch.
+export async function setDeviceProfile(id: string | null): Promise<DeviceProfileState> {
+  return await invoke<DeviceProfileState>('set_device_profile', { id });
+}
+
+// The CSS filter for book content, empty unless the profile boosts contrast in e-ink mode.
+export const getContentFilter = (profile: DeviceProfile | null, isEink: boolean) => {
+  const contrast = isEink ? (profile?.contrastBoost ?? 1) : 1;
+  return contrast === 1 ? '' : `contrast(${contrast})`;
+};
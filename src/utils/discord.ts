@@ -0,0 +1,31 @@
+import { invoke } from '@tauri-apps/api/core';
+import { DiscordPresenceType } from '@/types/settings';
+
+// How much of the book other Discord users can see: everything, only the genre and
+// progress, or just "Reading a book".
+export type DiscordPresencePrivacy = Exclude<DiscordPresenceType, 'off'>;
+
+export interface BookPresence {
+  bookHash: string;
+  title: string;
+  author?: string;
+  coverUrl?: string;
+  sessionStart: number;
+  genre?: string;
+  chapter?: string;
+  // Percentage read, from 0 to 100
+  progress?: number;
+  page?: number;
+  totalPages?: number;
+  privacy?: DiscordPresencePrivacy;
+}
+
+// Call again as the reader moves through the book: the presence is cleared after ten
+// minutes without an update.
+export async function updateBookPresence(presence: BookPresence): Promise<void> {
+  await invoke('update_book_presence', { presence });
+}
+
+export async function clearBookPresence(): Promise<void> {
+  await invoke('clear_book_presence');
+}
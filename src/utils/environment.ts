@@ -0,0 +1,16 @@
+import { invoke } from '@tauri-apps/api/core';
+
+export interface AppEnvironment {
+  appImage: string | null;
+  executableDir: string | null;
+}
+
+export async function getAppEnvironment(): Promise<AppEnvironment> {
+  return await invoke<AppEnvironment>('get_app_environment');
+}
+
+// Only variables allowlisted under `plugins.environment` in tauri.conf.json can be read;
+// others are rejected.
+export async function getEnvironmentVariable(name: string): Promise<string | null> {
+  return await invoke<string | null>('get_environment_variable', { name });
+}
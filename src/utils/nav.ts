@@ -1,4 +1,5 @@
 import { useRouter, redirect } from 'next/navigation';
+import { invoke } from '@tauri-apps/api/core';
 import { getCurrentWindow } from '@tauri-apps/api/window';
 import { WebviewWindow } from '@tauri-apps/api/webviewWindow';
 import { isPWA, isWebAppPlatform } from '@/services/environment';
@@ -34,12 +35,23 @@ const createReaderWindow = (appService: AppService, url: string) => {
   });
 };
 
-export const showReaderWindow = (appService: AppService, bookIds: string[]) => {
-  const ids = bookIds.join(BOOK_IDS_SEPARATOR);
-  const params = new URLSearchParams('');
-  params.set('ids', ids);
-  const url = `/reader?${params.toString()}`;
-  createReaderWindow(appService, url);
+// Reader windows are managed by the backend, which reuses the window of a book already open
+export const showReaderWindow = async (_appService: AppService, bookIds: string[]) => {
+  try {
+    await invoke<string>('open_reader_window', { ids: bookIds });
+  } catch (error) {
+    console.error('error opening reader window', error);
+  }
+};
+
+/** Brings the window showing a book to the front; resolves to its label, or null. */
+export const focusBookWindow = async (hash: string) => {
+  return await invoke<string | null>('focus_book_window', { hash });
+};
+
+/** Tells the backend which books the current window shows. */
+export const setWindowBooks = async (bookIds: string[]) => {
+  await invoke('set_window_books', { ids: bookIds });
 };
 
 export const showLibraryWindow = (appService: AppService, filenames: string[]) => {
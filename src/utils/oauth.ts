@@ -0,0 +1,25 @@
+import { invoke } from '@tauri-apps/api/core';
+
+export interface OAuthRequest {
+  authorizationEndpoint: string;
+  tokenEndpoint: string;
+  clientId: string;
+  scopes?: string[];
+  params?: Record<string, string>;
+  timeoutSecs?: number;
+}
+
+export interface OAuthTokens {
+  accessToken: string;
+  tokenType: string;
+  expiresIn?: number;
+  refreshToken?: string;
+  idToken?: string;
+  scope?: string;
+}
+
+// Signs in with the system browser; the redirect is received and the code exchanged
+// in Rust, so only the tokens reach the webview. Desktop only.
+export async function authorizeOAuth(request: OAuthRequest): Promise<OAuthTokens> {
+  return await invoke<OAuthTokens>('authorize_oauth', { request });
+}